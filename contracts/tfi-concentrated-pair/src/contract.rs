@@ -0,0 +1,1083 @@
+use crate::error::ContractError;
+use crate::math::{
+    amount_before_commission, amounts_for_liquidity, fee_growth, fees_earned,
+    liquidity_for_amounts, spot_return, sqrt_price_at_tick, swap_step, swap_step_exact_out,
+    tick_at_sqrt_price, wrapping_add, wrapping_sub, MAX_TICK, MIN_TICK,
+};
+use crate::state::{
+    Config, PoolState, Position, TickInfo, CONFIG, LAST_POSITION_ID, OWNER_POSITIONS, POOL,
+    POSITIONS, TICKS,
+};
+
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Order,
+    Response, StdResult, Storage, Uint128, Uint256,
+};
+
+use cw2::set_contract_version;
+use cw20::Cw20ReceiveMsg;
+use cw_storage_plus::Bound;
+use std::convert::TryInto;
+use tfi::asset::{Asset, AssetInfo, PairInfo, CONCENTRATED_PAIR_TYPE};
+use tfi::concentrated::{
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, PositionResponse,
+    PositionsResponse, QueryMsg,
+};
+use tfi::pair::{MigrateMsg, ReverseSimulationResponse, SimulationResponse};
+use tfi_pair::contract::{
+    assert_deadline, assert_max_spread, assert_min_return, assert_not_paused, assert_whitelisted,
+    collect_deposits, query_pair_info, return_msg, save_commission_tiers, swap_idx,
+    trader_commission, update_commission, update_pause, validate_group, Operation,
+};
+use tfi_pair::math::decimal256;
+use tfi_pair::state::PAIR_INFO;
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:tfi-concentrated-pair";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// Maximum number of initialized ticks a single swap can cross, so swaps through many sparse
+/// positions cannot run out of gas
+pub const MAX_TICKS_CROSSED: usize = 50;
+
+/// Instantiates pool with `tfi::pair::InstantiateMsg`, so it can be created by tfi factory. No
+/// liquidity token is created, and `amp`, `weights` and `token_code_id` are ignored.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    mut deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let asset_infos: [AssetInfo; 2] = msg
+        .asset_infos
+        .clone()
+        .try_into()
+        .map_err(|infos: Vec<_>| tfi_pair::error::ContractError::InvalidAssetCount(infos.len()))?;
+    if !(Decimal::zero()..=Decimal::one()).contains(&msg.commission) {
+        return Err(tfi_pair::error::ContractError::InvalidCommission(msg.commission).into());
+    }
+    if !msg.protocol_fee_share.is_zero() {
+        return Err(ContractError::ProtocolFeeNotSupported {});
+    }
+    let initial_price = msg
+        .initial_price
+        .ok_or(ContractError::MissingInitialPrice {})?;
+    let tick_spacing = msg.tick_spacing.unwrap_or(1);
+    if tick_spacing == 0 {
+        return Err(ContractError::InvalidTickSpacing {});
+    }
+
+    let sqrt_price = decimal256(initial_price).sqrt();
+    let tick = tick_at_sqrt_price(sqrt_price).map_err(|_| ContractError::InvalidPrice {})?;
+
+    save_commission_tiers(deps.branch(), msg.commission_group, msg.commission_tiers)?;
+    // liquidity is kept in positions, so there is no liquidity token
+    let mut pair_info = PairInfo::new(msg.asset_infos, env.contract.address, Addr::unchecked(""))
+        .with_commission(msg.commission)
+        .with_pair_type(CONCENTRATED_PAIR_TYPE);
+    if let Some(whitelist_group) = msg.whitelist_group {
+        pair_info =
+            pair_info.with_whitelist_group(validate_group(deps.as_ref(), &whitelist_group)?);
+    }
+    if let Some(admin) = msg.admin {
+        pair_info = pair_info.with_admin(deps.api.addr_validate(&admin)?);
+    }
+    PAIR_INFO.save(deps.storage, &pair_info)?;
+
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            asset_infos,
+            tick_spacing,
+        },
+    )?;
+    POOL.save(
+        deps.storage,
+        &PoolState {
+            sqrt_price,
+            tick,
+            liquidity: Uint128::zero(),
+            fee_growth_global: [Uint256::zero(); 2],
+        },
+    )?;
+    LAST_POSITION_ID.save(deps.storage, &0)?;
+
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::CreatePosition {
+            lower_tick,
+            upper_tick,
+            assets,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Deposits])?;
+            create_position(deps, env, info, lower_tick, upper_tick, assets)
+        }
+        ExecuteMsg::IncreaseLiquidity {
+            position_id,
+            assets,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Deposits])?;
+            increase_liquidity(deps, env, info, position_id, assets)
+        }
+        ExecuteMsg::DecreaseLiquidity {
+            position_id,
+            liquidity,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Withdrawals])?;
+            decrease_liquidity(deps, info, position_id, liquidity)
+        }
+        ExecuteMsg::CollectFees { position_id } => {
+            assert_not_paused(deps.storage, &[Operation::Withdrawals])?;
+            collect_fees(deps, info, position_id)
+        }
+        ExecuteMsg::TransferPosition {
+            position_id,
+            recipient,
+        } => {
+            let recipient = deps.api.addr_validate(&recipient)?;
+            transfer_position(deps, info, position_id, recipient)
+        }
+        ExecuteMsg::Swap {
+            offer_asset,
+            ask_asset_info,
+            belief_price,
+            max_spread,
+            to,
+            min_return,
+            deadline,
+            to_msg,
+        } => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Swaps])?;
+            if !offer_asset.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+            offer_asset.assert_sent_native_token_balance(&info)?;
+
+            let to_addr = to.map(|to| deps.api.addr_validate(&to)).transpose()?;
+            swap(
+                deps,
+                info.sender,
+                offer_asset,
+                ask_asset_info,
+                belief_price,
+                max_spread,
+                min_return,
+                to_addr,
+                to_msg,
+            )
+        }
+        ExecuteMsg::UpdatePause {
+            swaps,
+            deposits,
+            withdrawals,
+        } => Ok(update_pause(deps, info, swaps, deposits, withdrawals)?),
+        ExecuteMsg::UpdateCommission { commission } => {
+            Ok(update_commission(deps, info, commission)?)
+        }
+    }
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    match from_binary(&cw20_msg.msg)? {
+        Cw20HookMsg::Swap {
+            ask_asset_info,
+            belief_price,
+            max_spread,
+            to,
+            min_return,
+            deadline,
+            to_msg,
+        } => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Swaps])?;
+
+            // only asset contract can execute this message
+            let config = CONFIG.load(deps.storage)?;
+            let authorized = config.asset_infos.iter().any(
+                |asset_info| matches!(asset_info, AssetInfo::Token(addr) if addr == &info.sender),
+            );
+            if !authorized {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = to.map(|to| deps.api.addr_validate(&to)).transpose()?;
+            let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+            let offer_asset = Asset {
+                info: AssetInfo::Token(info.sender),
+                amount: cw20_msg.amount,
+            };
+
+            swap(
+                deps,
+                sender,
+                offer_asset,
+                ask_asset_info,
+                belief_price,
+                max_spread,
+                min_return,
+                to_addr,
+                to_msg,
+            )
+        }
+    }
+}
+
+/// Opens new position of the sender, see `deposit` for how `assets` are collected
+pub fn create_position(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    lower_tick: i32,
+    upper_tick: i32,
+    assets: Vec<Asset>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let pair_info = PAIR_INFO.load(deps.storage)?;
+    assert_whitelisted(deps.as_ref(), &pair_info, &[&info.sender])?;
+    let spacing = config.tick_spacing as i32;
+    if lower_tick >= upper_tick
+        || lower_tick < MIN_TICK
+        || upper_tick > MAX_TICK
+        || lower_tick % spacing != 0
+        || upper_tick % spacing != 0
+    {
+        return Err(ContractError::InvalidTickRange {
+            lower_tick,
+            upper_tick,
+        });
+    }
+
+    let position_id = LAST_POSITION_ID.update(deps.storage, |id| -> StdResult<_> { Ok(id + 1) })?;
+    let position = Position::new(info.sender.clone(), lower_tick, upper_tick);
+    OWNER_POSITIONS.save(deps.storage, (&info.sender, position_id), &Empty {})?;
+
+    let res = deposit(deps, &env, &info, &config, position_id, position, assets)?;
+    Ok(res.add_attribute("action", "create_position"))
+}
+
+pub fn increase_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    position_id: u64,
+    assets: Vec<Asset>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let position = POSITIONS.load(deps.storage, position_id)?;
+    if position.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let pair_info = PAIR_INFO.load(deps.storage)?;
+    assert_whitelisted(deps.as_ref(), &pair_info, &[&info.sender])?;
+
+    let res = deposit(deps, &env, &info, &config, position_id, position, assets)?;
+    Ok(res.add_attribute("action", "increase_liquidity"))
+}
+
+/// Adds to the position the greatest liquidity `assets` are enough for at current price. Only the
+/// amounts required for this liquidity are collected - cw20 tokens with `TransferFrom`, and
+/// excess of native tokens sent with the message is refunded.
+fn deposit(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    config: &Config,
+    position_id: u64,
+    mut position: Position,
+    assets: Vec<Asset>,
+) -> Result<Response, ContractError> {
+    if assets.len() != 2 {
+        return Err(tfi_pair::error::ContractError::InvalidAssetCount(assets.len()).into());
+    }
+    for asset in assets.iter() {
+        asset.assert_sent_native_token_balance(info)?;
+    }
+    let requested = [
+        requested_amount(&assets, &config.asset_infos[0])?,
+        requested_amount(&assets, &config.asset_infos[1])?,
+    ];
+
+    let mut pool = POOL.load(deps.storage)?;
+    let (sqrt_price_lower, sqrt_price_upper) = (
+        sqrt_price_at_tick(position.lower_tick)?,
+        sqrt_price_at_tick(position.upper_tick)?,
+    );
+    let liquidity = liquidity_for_amounts(
+        pool.sqrt_price,
+        sqrt_price_lower,
+        sqrt_price_upper,
+        requested,
+    )?;
+    if liquidity.is_zero() {
+        return Err(ContractError::ZeroLiquidity {});
+    }
+    let used = amounts_for_liquidity(
+        pool.sqrt_price,
+        sqrt_price_lower,
+        sqrt_price_upper,
+        liquidity,
+        true,
+    )?;
+
+    let new_liquidity = position.liquidity.checked_add(liquidity)?;
+    update_position(deps.storage, &mut pool, &mut position, new_liquidity)?;
+    POOL.save(deps.storage, &pool)?;
+    POSITIONS.save(deps.storage, position_id, &position)?;
+
+    let used_assets = [
+        Asset {
+            info: config.asset_infos[0].clone(),
+            amount: used[0],
+        },
+        Asset {
+            info: config.asset_infos[1].clone(),
+            amount: used[1],
+        },
+    ];
+    Ok(Response::new()
+        .add_attribute("position_id", position_id.to_string())
+        .add_attribute("liquidity", liquidity.to_string())
+        .add_attribute(
+            "assets",
+            format!(
+                "{}{}, {}{}",
+                used[0], config.asset_infos[0], used[1], config.asset_infos[1]
+            ),
+        )
+        .add_messages(collect_deposits(
+            env,
+            &info.sender,
+            &used_assets,
+            &requested,
+            &used,
+        )?))
+}
+
+fn requested_amount(assets: &[Asset], asset_info: &AssetInfo) -> Result<Uint128, ContractError> {
+    assets
+        .iter()
+        .find(|asset| asset.info.equal(asset_info))
+        .map(|asset| asset.amount)
+        .ok_or_else(|| ContractError::AssetMismatch(asset_info.to_string()))
+}
+
+/// Removes `liquidity` from the position, and pays out its assets at current price together with
+/// all fees earned by the position. Position without liquidity left is closed.
+pub fn decrease_liquidity(
+    deps: DepsMut,
+    info: MessageInfo,
+    position_id: u64,
+    liquidity: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut position = POSITIONS.load(deps.storage, position_id)?;
+    if position.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let pair_info = PAIR_INFO.load(deps.storage)?;
+    assert_whitelisted(deps.as_ref(), &pair_info, &[&info.sender])?;
+    if liquidity.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    if liquidity > position.liquidity {
+        return Err(ContractError::InsufficientPositionLiquidity {
+            liquidity: position.liquidity,
+            requested: liquidity,
+        });
+    }
+
+    let mut pool = POOL.load(deps.storage)?;
+    let withdrawn = amounts_for_liquidity(
+        pool.sqrt_price,
+        sqrt_price_at_tick(position.lower_tick)?,
+        sqrt_price_at_tick(position.upper_tick)?,
+        liquidity,
+        false,
+    )?;
+
+    let new_liquidity = position.liquidity - liquidity;
+    update_position(deps.storage, &mut pool, &mut position, new_liquidity)?;
+    POOL.save(deps.storage, &pool)?;
+
+    let fees = std::mem::take(&mut position.fees_owed);
+    if position.liquidity.is_zero() {
+        POSITIONS.remove(deps.storage, position_id);
+        OWNER_POSITIONS.remove(deps.storage, (&position.owner, position_id));
+    } else {
+        POSITIONS.save(deps.storage, position_id, &position)?;
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "decrease_liquidity")
+        .add_attribute("position_id", position_id.to_string())
+        .add_attribute("liquidity", liquidity.to_string())
+        .add_attribute(
+            "withdrawn_assets",
+            format!(
+                "{}{}, {}{}",
+                withdrawn[0], config.asset_infos[0], withdrawn[1], config.asset_infos[1]
+            ),
+        )
+        .add_attribute(
+            "fees",
+            format!(
+                "{}{}, {}{}",
+                fees[0], config.asset_infos[0], fees[1], config.asset_infos[1]
+            ),
+        );
+    for (asset_info, amount) in config.asset_infos.iter().zip(0..2) {
+        let amount = withdrawn[amount].checked_add(fees[amount])?;
+        if !amount.is_zero() {
+            let payout = Asset {
+                info: asset_info.clone(),
+                amount,
+            };
+            res = res.add_message(payout.into_msg(position.owner.clone())?);
+        }
+    }
+    Ok(res)
+}
+
+/// Pays out fees earned by the position, its liquidity is left untouched
+pub fn collect_fees(
+    deps: DepsMut,
+    info: MessageInfo,
+    position_id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut position = POSITIONS.load(deps.storage, position_id)?;
+    if position.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let pair_info = PAIR_INFO.load(deps.storage)?;
+    assert_whitelisted(deps.as_ref(), &pair_info, &[&info.sender])?;
+
+    let mut pool = POOL.load(deps.storage)?;
+    let liquidity = position.liquidity;
+    update_position(deps.storage, &mut pool, &mut position, liquidity)?;
+    let fees = std::mem::take(&mut position.fees_owed);
+    POSITIONS.save(deps.storage, position_id, &position)?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "collect_fees")
+        .add_attribute("position_id", position_id.to_string())
+        .add_attribute(
+            "fees",
+            format!(
+                "{}{}, {}{}",
+                fees[0], config.asset_infos[0], fees[1], config.asset_infos[1]
+            ),
+        );
+    for (asset_info, amount) in config.asset_infos.iter().zip(fees) {
+        if !amount.is_zero() {
+            let payout = Asset {
+                info: asset_info.clone(),
+                amount,
+            };
+            res = res.add_message(payout.into_msg(position.owner.clone())?);
+        }
+    }
+    Ok(res)
+}
+
+pub fn transfer_position(
+    deps: DepsMut,
+    info: MessageInfo,
+    position_id: u64,
+    recipient: Addr,
+) -> Result<Response, ContractError> {
+    let mut position = POSITIONS.load(deps.storage, position_id)?;
+    if position.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let pair_info = PAIR_INFO.load(deps.storage)?;
+    assert_whitelisted(deps.as_ref(), &pair_info, &[&info.sender, &recipient])?;
+
+    OWNER_POSITIONS.remove(deps.storage, (&position.owner, position_id));
+    OWNER_POSITIONS.save(deps.storage, (&recipient, position_id), &Empty {})?;
+    position.owner = recipient.clone();
+    POSITIONS.save(deps.storage, position_id, &position)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "transfer_position")
+        .add_attribute("position_id", position_id.to_string())
+        .add_attribute("recipient", recipient))
+}
+
+/// Sets position liquidity to `liquidity`, accruing fees it earned so far. Ticks bounding the
+/// position are initialized on their first use, and removed when no position uses them anymore.
+fn update_position(
+    storage: &mut dyn Storage,
+    pool: &mut PoolState,
+    position: &mut Position,
+    liquidity: Uint128,
+) -> StdResult<()> {
+    let mut lower = TICKS
+        .may_load(storage, position.lower_tick)?
+        .unwrap_or_else(|| TickInfo::new(position.lower_tick, pool));
+    let mut upper = TICKS
+        .may_load(storage, position.upper_tick)?
+        .unwrap_or_else(|| TickInfo::new(position.upper_tick, pool));
+    let inside = fee_growth_inside(pool, position, &lower, &upper);
+    accrue_fees(position, inside)?;
+
+    let old_liquidity = position.liquidity;
+    lower.liquidity_lower = lower
+        .liquidity_lower
+        .checked_sub(old_liquidity)?
+        .checked_add(liquidity)?;
+    upper.liquidity_upper = upper
+        .liquidity_upper
+        .checked_sub(old_liquidity)?
+        .checked_add(liquidity)?;
+    if (position.lower_tick..position.upper_tick).contains(&pool.tick) {
+        pool.liquidity = pool
+            .liquidity
+            .checked_sub(old_liquidity)?
+            .checked_add(liquidity)?;
+    }
+    position.liquidity = liquidity;
+
+    for (tick, info) in [(position.lower_tick, lower), (position.upper_tick, upper)] {
+        if info.is_empty() {
+            TICKS.remove(storage, tick);
+        } else {
+            TICKS.save(storage, tick, &info)?;
+        }
+    }
+    Ok(())
+}
+
+/// Fee growth within position range - global fee growth minus growth below its lower tick and
+/// above its upper tick
+fn fee_growth_inside(
+    pool: &PoolState,
+    position: &Position,
+    lower: &TickInfo,
+    upper: &TickInfo,
+) -> [Uint256; 2] {
+    let mut inside = [Uint256::zero(); 2];
+    for (i, global) in pool.fee_growth_global.iter().copied().enumerate() {
+        let below = if pool.tick >= position.lower_tick {
+            lower.fee_growth_outside[i]
+        } else {
+            wrapping_sub(global, lower.fee_growth_outside[i])
+        };
+        let above = if pool.tick < position.upper_tick {
+            upper.fee_growth_outside[i]
+        } else {
+            wrapping_sub(global, upper.fee_growth_outside[i])
+        };
+        inside[i] = wrapping_sub(wrapping_sub(global, below), above);
+    }
+    inside
+}
+
+/// Adds fees earned since the last accrual to `position.fees_owed`
+fn accrue_fees(position: &mut Position, fee_growth_inside: [Uint256; 2]) -> StdResult<()> {
+    for (i, inside) in fee_growth_inside.iter().copied().enumerate() {
+        let growth = wrapping_sub(inside, position.fee_growth_inside_last[i]);
+        position.fees_owed[i] =
+            position.fees_owed[i].checked_add(fees_earned(growth, position.liquidity)?)?;
+        position.fee_growth_inside_last[i] = inside;
+    }
+    Ok(())
+}
+
+/// Result of swap computation, not applied to the state yet
+pub struct SwapResult {
+    /// Pool state after the swap
+    pub pool: PoolState,
+    /// Ticks crossed by the swap, with their updated state
+    pub crossed_ticks: Vec<(i32, TickInfo)>,
+    pub return_amount: Uint128,
+    pub spread_amount: Uint128,
+    pub commission_amount: Uint128,
+}
+
+/// Closest initialized tick the price reaches moving from `tick` - down to the tick itself, or up
+/// to the next one
+fn next_initialized_tick(
+    storage: &dyn Storage,
+    tick: i32,
+    price_down: bool,
+) -> StdResult<Option<(i32, TickInfo)>> {
+    if price_down {
+        TICKS
+            .range(
+                storage,
+                None,
+                Some(Bound::inclusive(tick)),
+                Order::Descending,
+            )
+            .next()
+    } else {
+        TICKS
+            .range(
+                storage,
+                Some(Bound::exclusive(tick)),
+                None,
+                Order::Ascending,
+            )
+            .next()
+    }
+    .transpose()
+}
+
+/// Swaps `offer_amount` of asset with `offer_idx` along the curve. Price moves within range of
+/// constant liquidity until the next initialized tick, where liquidity of positions starting or
+/// ending at it is added or removed. Commission is deducted from every step return, and is
+/// distributed to liquidity the step used. Swap crossing more than `MAX_TICKS_CROSSED`
+/// initialized ticks fails.
+pub fn compute_swap(
+    storage: &dyn Storage,
+    offer_idx: usize,
+    offer_amount: Uint128,
+    commission: Decimal,
+) -> Result<SwapResult, ContractError> {
+    if offer_amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let mut pool = POOL.load(storage)?;
+    let ask_idx = 1 - offer_idx;
+    // offering the first asset makes it cheaper
+    let price_down = offer_idx == 0;
+    let commission = decimal256(commission);
+    let spot_return = spot_return(offer_amount, pool.sqrt_price, price_down)?;
+
+    let mut remaining = Uint256::from(offer_amount);
+    let mut return_amount = Uint256::zero();
+    let mut commission_amount = Uint256::zero();
+    let mut crossed_ticks = vec![];
+    while !remaining.is_zero() {
+        let next_tick = next_initialized_tick(storage, pool.tick, price_down)?;
+        let sqrt_price_target = match &next_tick {
+            Some((tick, _)) => sqrt_price_at_tick(*tick)?,
+            None if price_down => sqrt_price_at_tick(MIN_TICK)?,
+            None => sqrt_price_at_tick(MAX_TICK)?,
+        };
+
+        let (sqrt_price, amount_in, amount_out) = swap_step(
+            pool.sqrt_price,
+            sqrt_price_target,
+            pool.liquidity,
+            remaining,
+            price_down,
+        )?;
+        let step_commission = amount_out * commission;
+        if !pool.liquidity.is_zero() {
+            pool.fee_growth_global[ask_idx] = wrapping_add(
+                pool.fee_growth_global[ask_idx],
+                fee_growth(step_commission, pool.liquidity),
+            );
+        }
+        remaining = remaining.checked_sub(amount_in)?;
+        return_amount = return_amount.checked_add(amount_out)?;
+        commission_amount = commission_amount.checked_add(step_commission)?;
+        pool.sqrt_price = sqrt_price;
+
+        match next_tick {
+            Some((tick, mut info)) if sqrt_price == sqrt_price_target => {
+                if crossed_ticks.len() == MAX_TICKS_CROSSED {
+                    return Err(ContractError::TooManyTicksCrossed(MAX_TICKS_CROSSED));
+                }
+                for (outside, global) in info
+                    .fee_growth_outside
+                    .iter_mut()
+                    .zip(pool.fee_growth_global)
+                {
+                    *outside = wrapping_sub(global, *outside);
+                }
+                // moving up, positions starting at the tick are entered and those ending at it
+                // are left - and the other way around when moving down
+                let (entered, left) = if price_down {
+                    (info.liquidity_upper, info.liquidity_lower)
+                } else {
+                    (info.liquidity_lower, info.liquidity_upper)
+                };
+                pool.liquidity = pool.liquidity.checked_add(entered)?.checked_sub(left)?;
+                pool.tick = if price_down { tick - 1 } else { tick };
+                crossed_ticks.push((tick, info));
+            }
+            _ if !remaining.is_zero() => return Err(ContractError::InsufficientLiquidity {}),
+            _ => pool.tick = tick_at_sqrt_price(sqrt_price)?,
+        }
+    }
+
+    let spread_amount = spot_return.saturating_sub(return_amount);
+    let return_amount = return_amount.checked_sub(commission_amount)?;
+
+    Ok(SwapResult {
+        pool,
+        crossed_ticks,
+        return_amount: return_amount.try_into()?,
+        spread_amount: spread_amount.try_into()?,
+        commission_amount: commission_amount.try_into()?,
+    })
+}
+
+/// Reverse of `compute_swap` - finds amount of asset with `offer_idx` to offer, so `ask_amount`
+/// is returned after commission. Walks the same ticks the swap would cross, accumulating offer
+/// required for every step. Returns offer, spread and commission amounts.
+pub fn compute_offer_amount(
+    storage: &dyn Storage,
+    offer_idx: usize,
+    ask_amount: Uint128,
+    commission: Decimal,
+) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+    if ask_amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let mut pool = POOL.load(storage)?;
+    let start_sqrt_price = pool.sqrt_price;
+    let price_down = offer_idx == 0;
+    let commission = decimal256(commission);
+
+    let mut remaining = amount_before_commission(ask_amount, commission)?;
+    let mut offer_amount = Uint256::zero();
+    let mut return_amount = Uint256::zero();
+    let mut commission_amount = Uint256::zero();
+    let mut ticks_crossed = 0;
+    while !remaining.is_zero() {
+        let next_tick = next_initialized_tick(storage, pool.tick, price_down)?;
+        let sqrt_price_target = match &next_tick {
+            Some((tick, _)) => sqrt_price_at_tick(*tick)?,
+            None if price_down => sqrt_price_at_tick(MIN_TICK)?,
+            None => sqrt_price_at_tick(MAX_TICK)?,
+        };
+
+        let (sqrt_price, amount_in, amount_out) = swap_step_exact_out(
+            pool.sqrt_price,
+            sqrt_price_target,
+            pool.liquidity,
+            remaining,
+            price_down,
+        )?;
+        remaining = remaining.checked_sub(amount_out)?;
+        offer_amount = offer_amount.checked_add(amount_in)?;
+        return_amount = return_amount.checked_add(amount_out)?;
+        commission_amount = commission_amount.checked_add(amount_out * commission)?;
+        pool.sqrt_price = sqrt_price;
+
+        match next_tick {
+            Some((tick, info)) if sqrt_price == sqrt_price_target => {
+                if ticks_crossed == MAX_TICKS_CROSSED {
+                    return Err(ContractError::TooManyTicksCrossed(MAX_TICKS_CROSSED));
+                }
+                let (entered, left) = if price_down {
+                    (info.liquidity_upper, info.liquidity_lower)
+                } else {
+                    (info.liquidity_lower, info.liquidity_upper)
+                };
+                pool.liquidity = pool.liquidity.checked_add(entered)?.checked_sub(left)?;
+                pool.tick = if price_down { tick - 1 } else { tick };
+                ticks_crossed += 1;
+            }
+            _ if !remaining.is_zero() => return Err(ContractError::InsufficientLiquidity {}),
+            _ => pool.tick = tick_at_sqrt_price(sqrt_price)?,
+        }
+    }
+
+    let offer_amount: Uint128 = offer_amount.try_into()?;
+    let spread_amount =
+        spot_return(offer_amount, start_sqrt_price, price_down)?.saturating_sub(return_amount);
+
+    Ok((
+        offer_amount,
+        spread_amount.try_into()?,
+        commission_amount.try_into()?,
+    ))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn swap(
+    deps: DepsMut,
+    sender: Addr,
+    offer_asset: Asset,
+    ask_asset_info: Option<AssetInfo>,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    min_return: Option<Uint128>,
+    to: Option<Addr>,
+    to_msg: Option<Binary>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let pair_info = PAIR_INFO.load(deps.storage)?;
+    let receiver = to.unwrap_or_else(|| sender.clone());
+    assert_whitelisted(deps.as_ref(), &pair_info, &[&sender, &receiver])?;
+    let (offer_idx, ask_idx) = swap_idx(
+        &config.asset_infos,
+        &offer_asset.info,
+        ask_asset_info.as_ref(),
+    )?;
+    let ask_info = config.asset_infos[ask_idx].clone();
+
+    let offer_amount = offer_asset.amount;
+    let commission = trader_commission(deps.as_ref(), &pair_info, Some(&sender))?;
+    let SwapResult {
+        pool,
+        crossed_ticks,
+        return_amount,
+        spread_amount,
+        commission_amount,
+    } = compute_swap(deps.storage, offer_idx, offer_amount, commission)?;
+
+    // check max spread limit if exist
+    assert_max_spread(
+        belief_price,
+        max_spread,
+        offer_amount,
+        return_amount + commission_amount,
+        spread_amount,
+    )?;
+    assert_min_return(return_amount, min_return)?;
+
+    POOL.save(deps.storage, &pool)?;
+    for (tick, info) in crossed_ticks {
+        TICKS.save(deps.storage, tick, &info)?;
+    }
+
+    let return_msg = return_msg(
+        Asset {
+            info: ask_info.clone(),
+            amount: return_amount,
+        },
+        receiver,
+        to_msg,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "swap")
+        .add_attribute("offer_asset", offer_asset.info.to_string())
+        .add_attribute("ask_asset", ask_info.to_string())
+        .add_attribute("offer_amount", offer_amount.to_string())
+        .add_attribute("return_amount", return_amount.to_string())
+        .add_attribute("spread_amount", spread_amount.to_string())
+        .add_attribute("commission_amount", commission_amount.to_string())
+        .add_message(return_msg))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::Pair {} => Ok(to_binary(&query_pair_info(deps)?)?),
+        QueryMsg::Config {} => Ok(to_binary(&query_config(deps)?)?),
+        QueryMsg::Pool {} => Ok(to_binary(&query_pool(deps)?)?),
+        QueryMsg::Simulation {
+            offer_asset,
+            ask_asset_info,
+            trader,
+        } => Ok(to_binary(&query_simulation(
+            deps,
+            offer_asset,
+            ask_asset_info,
+            trader,
+        )?)?),
+        QueryMsg::ReverseSimulation {
+            ask_asset,
+            offer_asset_info,
+            trader,
+        } => Ok(to_binary(&query_reverse_simulation(
+            deps,
+            ask_asset,
+            offer_asset_info,
+            trader,
+        )?)?),
+        QueryMsg::Position { position_id } => Ok(to_binary(&query_position(deps, position_id)?)?),
+        QueryMsg::Positions {
+            owner,
+            start_after,
+            limit,
+        } => {
+            let owner = deps.api.addr_validate(&owner)?;
+            Ok(to_binary(&query_positions(
+                deps,
+                owner,
+                start_after,
+                limit,
+            )?)?)
+        }
+    }
+}
+
+pub fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let pair_info = PAIR_INFO.load(deps.storage)?;
+    Ok(ConfigResponse {
+        asset_infos: config.asset_infos.to_vec(),
+        commission: pair_info.commission,
+        tick_spacing: config.tick_spacing,
+    })
+}
+
+pub fn query_pool(deps: Deps) -> Result<PoolResponse, ContractError> {
+    let pool = POOL.load(deps.storage)?;
+    Ok(PoolResponse {
+        price: pool.sqrt_price * pool.sqrt_price,
+        sqrt_price: pool.sqrt_price,
+        tick: pool.tick,
+        liquidity: pool.liquidity,
+    })
+}
+
+pub fn query_simulation(
+    deps: Deps,
+    offer_asset: Asset,
+    ask_asset_info: Option<AssetInfo>,
+    trader: Option<String>,
+) -> Result<SimulationResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let pair_info = PAIR_INFO.load(deps.storage)?;
+    let trader = trader
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let commission = trader_commission(deps, &pair_info, trader.as_ref())?;
+    let (offer_idx, _) = swap_idx(
+        &config.asset_infos,
+        &offer_asset.info,
+        ask_asset_info.as_ref(),
+    )?;
+
+    let res = compute_swap(deps.storage, offer_idx, offer_asset.amount, commission)?;
+    Ok(SimulationResponse {
+        return_amount: res.return_amount,
+        spread_amount: res.spread_amount,
+        commission_amount: res.commission_amount,
+    })
+}
+
+pub fn query_reverse_simulation(
+    deps: Deps,
+    ask_asset: Asset,
+    offer_asset_info: Option<AssetInfo>,
+    trader: Option<String>,
+) -> Result<ReverseSimulationResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let pair_info = PAIR_INFO.load(deps.storage)?;
+    let trader = trader
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let commission = trader_commission(deps, &pair_info, trader.as_ref())?;
+    let (_, offer_idx) = swap_idx(
+        &config.asset_infos,
+        &ask_asset.info,
+        offer_asset_info.as_ref(),
+    )?;
+
+    let (offer_amount, spread_amount, commission_amount) =
+        compute_offer_amount(deps.storage, offer_idx, ask_asset.amount, commission)?;
+    Ok(ReverseSimulationResponse {
+        offer_amount,
+        spread_amount,
+        commission_amount,
+    })
+}
+
+pub fn query_position(deps: Deps, position_id: u64) -> Result<PositionResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let pool = POOL.load(deps.storage)?;
+    let mut position = POSITIONS.load(deps.storage, position_id)?;
+
+    // open positions always have liquidity, so their ticks are initialized
+    let lower = TICKS.load(deps.storage, position.lower_tick)?;
+    let upper = TICKS.load(deps.storage, position.upper_tick)?;
+    let inside = fee_growth_inside(&pool, &position, &lower, &upper);
+    accrue_fees(&mut position, inside)?;
+
+    let amounts = amounts_for_liquidity(
+        pool.sqrt_price,
+        sqrt_price_at_tick(position.lower_tick)?,
+        sqrt_price_at_tick(position.upper_tick)?,
+        position.liquidity,
+        false,
+    )?;
+    let assets_of = |amounts: [Uint128; 2]| -> Vec<Asset> {
+        config
+            .asset_infos
+            .iter()
+            .zip(amounts)
+            .map(|(info, amount)| Asset {
+                info: info.clone(),
+                amount,
+            })
+            .collect()
+    };
+
+    Ok(PositionResponse {
+        position_id,
+        owner: position.owner,
+        lower_tick: position.lower_tick,
+        upper_tick: position.upper_tick,
+        liquidity: position.liquidity,
+        assets: assets_of(amounts),
+        fees: assets_of(position.fees_owed),
+    })
+}
+
+pub fn query_positions(
+    deps: Deps,
+    owner: Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<PositionsResponse, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let positions = OWNER_POSITIONS
+        .prefix(&owner)
+        .keys(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|position_id| query_position(deps, position_id?))
+        .collect::<Result<_, _>>()?;
+
+    Ok(PositionsResponse { positions })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    if let Some(admin) = msg.admin {
+        let admin = deps.api.addr_validate(&admin)?;
+        PAIR_INFO.update(deps.storage, |pair_info| -> StdResult<_> {
+            Ok(pair_info.with_admin(admin))
+        })?;
+    }
+
+    Ok(Response::default())
+}
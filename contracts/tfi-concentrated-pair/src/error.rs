@@ -0,0 +1,56 @@
+use cosmwasm_std::{ConversionOverflowError, OverflowError, StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    OverflowError(#[from] OverflowError),
+
+    #[error("{0}")]
+    ConversionOverflowError(#[from] ConversionOverflowError),
+
+    #[error("{0}")]
+    Pair(#[from] tfi_pair::error::ContractError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Asset mismatch: {0}")]
+    AssetMismatch(String),
+
+    #[error("Invalid zero amount")]
+    InvalidZeroAmount {},
+
+    #[error("Initial price is required by concentrated pair")]
+    MissingInitialPrice {},
+
+    #[error("Concentrated pair does not take protocol fee")]
+    ProtocolFeeNotSupported {},
+
+    #[error("Price out of range supported by ticks")]
+    InvalidPrice {},
+
+    #[error("Tick spacing has to be positive")]
+    InvalidTickSpacing {},
+
+    #[error("Invalid position range {lower_tick}..{upper_tick}, ticks have to be ordered multiples of tick spacing within supported range")]
+    InvalidTickRange { lower_tick: i32, upper_tick: i32 },
+
+    #[error("Deposit too small, no liquidity would be added")]
+    ZeroLiquidity {},
+
+    #[error("Position has only {liquidity} liquidity, {requested} requested")]
+    InsufficientPositionLiquidity {
+        liquidity: Uint128,
+        requested: Uint128,
+    },
+
+    #[error("Not enough liquidity to fill the swap")]
+    InsufficientLiquidity {},
+
+    #[error("Swap would cross more than {0} initialized ticks")]
+    TooManyTicksCrossed(usize),
+}
@@ -0,0 +1,7 @@
+pub mod contract;
+pub mod error;
+pub mod math;
+pub mod state;
+
+#[cfg(test)]
+mod multitest;
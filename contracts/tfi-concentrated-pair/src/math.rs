@@ -0,0 +1,334 @@
+use cosmwasm_std::{Decimal256, StdError, StdResult, Uint128, Uint256, Uint512};
+use std::convert::TryInto;
+
+/// Lowest tick of position range, price at it is about 2.06e-9
+pub const MIN_TICK: i32 = -200_000;
+/// Highest tick of position range, price at it is about 4.85e8
+pub const MAX_TICK: i32 = 200_000;
+/// Square root of 1.0001 - every tick changes the price by 0.01%
+const SQRT_TICK_RATIO: Decimal256 = Decimal256::raw(1_000_049_998_750_062_496);
+/// Precision of fee growth per unit of liquidity
+const FEE_GROWTH_SCALE: u128 = 1_000_000_000_000_000_000_000_000_000_000_000_000;
+
+/// Square root of price at `tick`, which is `1.0001^tick`
+pub fn sqrt_price_at_tick(tick: i32) -> StdResult<Decimal256> {
+    let sqrt_price = SQRT_TICK_RATIO.checked_pow(tick.unsigned_abs())?;
+    if tick < 0 {
+        Ok(Decimal256::one() / sqrt_price)
+    } else {
+        Ok(sqrt_price)
+    }
+}
+
+/// Greatest tick with square root of price not above `sqrt_price`
+pub fn tick_at_sqrt_price(sqrt_price: Decimal256) -> StdResult<i32> {
+    if sqrt_price < sqrt_price_at_tick(MIN_TICK)? || sqrt_price > sqrt_price_at_tick(MAX_TICK)? {
+        return Err(StdError::generic_err("Price out of supported range"));
+    }
+
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if sqrt_price_at_tick(mid)? <= sqrt_price {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(low)
+}
+
+/// Amount of the first asset for `liquidity` between two square roots of price
+pub fn amount0_delta(
+    sqrt_price_a: Decimal256,
+    sqrt_price_b: Decimal256,
+    liquidity: Uint128,
+    round_up: bool,
+) -> StdResult<Uint256> {
+    let (a, b) = sorted(sqrt_price_a, sqrt_price_b);
+    // liquidity * (b - a) / (a * b)
+    let numerator = Uint256::from(liquidity).full_mul(b - a) * Uint512::from(scale());
+    div(numerator, a.full_mul(b), round_up)
+}
+
+/// Amount of the second asset for `liquidity` between two square roots of price
+pub fn amount1_delta(
+    sqrt_price_a: Decimal256,
+    sqrt_price_b: Decimal256,
+    liquidity: Uint128,
+    round_up: bool,
+) -> StdResult<Uint256> {
+    let (a, b) = sorted(sqrt_price_a, sqrt_price_b);
+    // liquidity * (b - a)
+    div(
+        Uint256::from(liquidity).full_mul(b - a),
+        Uint512::from(scale()),
+        round_up,
+    )
+}
+
+/// Amounts of both assets for `liquidity` of position between `sqrt_price_lower` and
+/// `sqrt_price_upper`, at current `sqrt_price`
+pub fn amounts_for_liquidity(
+    sqrt_price: Decimal256,
+    sqrt_price_lower: Decimal256,
+    sqrt_price_upper: Decimal256,
+    liquidity: Uint128,
+    round_up: bool,
+) -> StdResult<[Uint128; 2]> {
+    let (amount0, amount1) = if sqrt_price <= sqrt_price_lower {
+        (
+            amount0_delta(sqrt_price_lower, sqrt_price_upper, liquidity, round_up)?,
+            Uint256::zero(),
+        )
+    } else if sqrt_price < sqrt_price_upper {
+        (
+            amount0_delta(sqrt_price, sqrt_price_upper, liquidity, round_up)?,
+            amount1_delta(sqrt_price_lower, sqrt_price, liquidity, round_up)?,
+        )
+    } else {
+        (
+            Uint256::zero(),
+            amount1_delta(sqrt_price_lower, sqrt_price_upper, liquidity, round_up)?,
+        )
+    };
+    Ok([amount0.try_into()?, amount1.try_into()?])
+}
+
+/// Greatest liquidity of position between `sqrt_price_lower` and `sqrt_price_upper`, which can
+/// be provided with `amounts` at current `sqrt_price`
+pub fn liquidity_for_amounts(
+    sqrt_price: Decimal256,
+    sqrt_price_lower: Decimal256,
+    sqrt_price_upper: Decimal256,
+    amounts: [Uint128; 2],
+) -> StdResult<Uint128> {
+    let liquidity = if sqrt_price <= sqrt_price_lower {
+        liquidity_for_amount0(sqrt_price_lower, sqrt_price_upper, amounts[0])?
+    } else if sqrt_price < sqrt_price_upper {
+        std::cmp::min(
+            liquidity_for_amount0(sqrt_price, sqrt_price_upper, amounts[0])?,
+            liquidity_for_amount1(sqrt_price_lower, sqrt_price, amounts[1])?,
+        )
+    } else {
+        liquidity_for_amount1(sqrt_price_lower, sqrt_price_upper, amounts[1])?
+    };
+    Ok(liquidity.try_into()?)
+}
+
+fn liquidity_for_amount0(
+    sqrt_price_a: Decimal256,
+    sqrt_price_b: Decimal256,
+    amount0: Uint128,
+) -> StdResult<Uint256> {
+    let (a, b) = sorted(sqrt_price_a, sqrt_price_b);
+    // amount0 * a * b / (b - a)
+    let numerator = Uint256::from(amount0).full_mul(a) * Uint512::from(b);
+    div(numerator, (b - a).full_mul(scale()), false)
+}
+
+fn liquidity_for_amount1(
+    sqrt_price_a: Decimal256,
+    sqrt_price_b: Decimal256,
+    amount1: Uint128,
+) -> StdResult<Uint256> {
+    let (a, b) = sorted(sqrt_price_a, sqrt_price_b);
+    // amount1 / (b - a)
+    div(
+        Uint256::from(amount1).full_mul(scale()),
+        Uint512::from(b - a),
+        false,
+    )
+}
+
+/// Swaps within range of constant `liquidity`, moving the price from `sqrt_price` towards
+/// `sqrt_price_target` until `amount_remaining` of offer is used. Offering the first asset moves
+/// the price down, offering the second one moves it up. Returns square root of price after the
+/// step, offer amount used and amount returned before commission. Amounts are rounded in pool
+/// favour.
+pub fn swap_step(
+    sqrt_price: Decimal256,
+    sqrt_price_target: Decimal256,
+    liquidity: Uint128,
+    amount_remaining: Uint256,
+    price_down: bool,
+) -> StdResult<(Decimal256, Uint256, Uint256)> {
+    let amount_to_target = if price_down {
+        amount0_delta(sqrt_price_target, sqrt_price, liquidity, true)?
+    } else {
+        amount1_delta(sqrt_price, sqrt_price_target, liquidity, true)?
+    };
+
+    let (new_sqrt_price, amount_in) = if amount_remaining >= amount_to_target {
+        (sqrt_price_target, amount_to_target)
+    } else {
+        (
+            next_sqrt_price(sqrt_price, liquidity, amount_remaining, price_down)?,
+            amount_remaining,
+        )
+    };
+
+    let amount_out = if price_down {
+        amount1_delta(new_sqrt_price, sqrt_price, liquidity, false)?
+    } else {
+        amount0_delta(sqrt_price, new_sqrt_price, liquidity, false)?
+    };
+
+    Ok((new_sqrt_price, amount_in, amount_out))
+}
+
+/// Reverse of `swap_step` - swaps within range of constant `liquidity`, moving the price from
+/// `sqrt_price` towards `sqrt_price_target` until `amount_remaining` is returned before
+/// commission. Returns square root of price after the step, offer amount required and amount
+/// returned. Amounts are rounded in pool favour.
+pub fn swap_step_exact_out(
+    sqrt_price: Decimal256,
+    sqrt_price_target: Decimal256,
+    liquidity: Uint128,
+    amount_remaining: Uint256,
+    price_down: bool,
+) -> StdResult<(Decimal256, Uint256, Uint256)> {
+    let amount_to_target = if price_down {
+        amount1_delta(sqrt_price_target, sqrt_price, liquidity, false)?
+    } else {
+        amount0_delta(sqrt_price, sqrt_price_target, liquidity, false)?
+    };
+
+    let (new_sqrt_price, amount_out) = if amount_remaining >= amount_to_target {
+        (sqrt_price_target, amount_to_target)
+    } else {
+        (
+            prev_sqrt_price(sqrt_price, liquidity, amount_remaining, price_down)?,
+            amount_remaining,
+        )
+    };
+
+    let amount_in = if price_down {
+        amount0_delta(new_sqrt_price, sqrt_price, liquidity, true)?
+    } else {
+        amount1_delta(sqrt_price, new_sqrt_price, liquidity, true)?
+    };
+
+    Ok((new_sqrt_price, amount_in, amount_out))
+}
+
+/// Square root of price after `amount_in` is offered to `liquidity`. It is rounded so the price
+/// moves less, which keeps the return lower.
+fn next_sqrt_price(
+    sqrt_price: Decimal256,
+    liquidity: Uint128,
+    amount_in: Uint256,
+    price_down: bool,
+) -> StdResult<Decimal256> {
+    let (p, liquidity, scale) = (sqrt_price.atomics(), Uint256::from(liquidity), scale());
+    if price_down {
+        // liquidity * p / (liquidity + amount_in * p)
+        let numerator = liquidity.full_mul(p) * Uint512::from(scale);
+        let denominator = liquidity.full_mul(scale) + amount_in.full_mul(p);
+        Ok(Decimal256::new(div(numerator, denominator, true)?))
+    } else {
+        // p + amount_in / liquidity
+        let delta = div(amount_in.full_mul(scale), Uint512::from(liquidity), false)?;
+        Ok(Decimal256::new(p.checked_add(delta)?))
+    }
+}
+
+/// Square root of price after `amount_out` is returned by `liquidity`. It is rounded so the price
+/// moves more, which keeps the offer higher. `amount_out` has to be less than `liquidity` holds.
+fn prev_sqrt_price(
+    sqrt_price: Decimal256,
+    liquidity: Uint128,
+    amount_out: Uint256,
+    price_down: bool,
+) -> StdResult<Decimal256> {
+    let (p, liquidity, scale) = (sqrt_price.atomics(), Uint256::from(liquidity), scale());
+    if price_down {
+        // p - amount_out / liquidity
+        let delta = div(amount_out.full_mul(scale), Uint512::from(liquidity), true)?;
+        Ok(Decimal256::new(p.checked_sub(delta)?))
+    } else {
+        // liquidity * p / (liquidity - amount_out * p)
+        let numerator = liquidity.full_mul(p) * Uint512::from(scale);
+        let denominator = liquidity
+            .full_mul(scale)
+            .checked_sub(amount_out.full_mul(p))?;
+        Ok(Decimal256::new(div(numerator, denominator, true)?))
+    }
+}
+
+/// Amount which leaves `amount` after `commission` is deducted from it, rounded up
+pub fn amount_before_commission(amount: Uint128, commission: Decimal256) -> StdResult<Uint256> {
+    let numerator = Uint256::from(amount).full_mul(scale());
+    div(
+        numerator,
+        Uint512::from((Decimal256::one() - commission).atomics()),
+        true,
+    )
+}
+
+/// Amount returned for `offer_amount` at `sqrt_price`, if the price didn't move during the swap
+pub fn spot_return(
+    offer_amount: Uint128,
+    sqrt_price: Decimal256,
+    price_down: bool,
+) -> StdResult<Uint256> {
+    let (p, scale) = (Uint512::from(sqrt_price.atomics()), Uint512::from(scale()));
+    let offer_amount = Uint512::from(Uint256::from(offer_amount));
+    if price_down {
+        div(offer_amount * p * p, scale * scale, false)
+    } else {
+        div(offer_amount * scale * scale, p * p, false)
+    }
+}
+
+/// Fee growth per unit of `liquidity`, when it earns `fee`
+pub fn fee_growth(fee: Uint256, liquidity: Uint128) -> Uint256 {
+    fee.multiply_ratio(FEE_GROWTH_SCALE, liquidity)
+}
+
+/// Fees earned by `liquidity` during `fee_growth`
+pub fn fees_earned(fee_growth: Uint256, liquidity: Uint128) -> StdResult<Uint128> {
+    let earned: Uint256 =
+        (fee_growth.full_mul(liquidity) / Uint512::from(FEE_GROWTH_SCALE)).try_into()?;
+    Ok(earned.try_into()?)
+}
+
+/// Fee growth accumulators only ever increase, and only their differences are meaningful, so they
+/// are allowed to overflow - as long as their differences are computed with wrapping as well
+pub fn wrapping_add(a: Uint256, b: Uint256) -> Uint256 {
+    match a.checked_add(b) {
+        Ok(sum) => sum,
+        Err(_) => b - (Uint256::MAX - a) - Uint256::from(1u8),
+    }
+}
+
+/// See `wrapping_add`
+pub fn wrapping_sub(a: Uint256, b: Uint256) -> Uint256 {
+    if a >= b {
+        a - b
+    } else {
+        Uint256::MAX - (b - a) + Uint256::from(1u8)
+    }
+}
+
+fn scale() -> Uint256 {
+    Decimal256::one().atomics()
+}
+
+fn sorted(a: Decimal256, b: Decimal256) -> (Uint256, Uint256) {
+    if a <= b {
+        (a.atomics(), b.atomics())
+    } else {
+        (b.atomics(), a.atomics())
+    }
+}
+
+fn div(numerator: Uint512, denominator: Uint512, round_up: bool) -> StdResult<Uint256> {
+    let quotient = numerator.checked_div(denominator)?;
+    let quotient = if round_up && !(numerator % denominator).is_zero() {
+        quotient + Uint512::from(1u8)
+    } else {
+        quotient
+    };
+    Ok(quotient.try_into()?)
+}
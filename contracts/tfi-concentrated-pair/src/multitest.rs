@@ -0,0 +1,1044 @@
+use anyhow::{anyhow, Result};
+use cosmwasm_std::{
+    coin, coins, to_binary, Addr, BankMsg, ContractInfoResponse, Decimal, Decimal256, Empty,
+    Uint128, WasmQuery,
+};
+use cw20::Cw20Coin;
+use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper, Executor};
+use derivative::Derivative;
+use std::str::FromStr;
+use tg4::Member;
+
+use crate::contract::MAX_TICKS_CROSSED;
+use crate::error::ContractError;
+use tfi::asset::{Asset, AssetInfo, PairInfo, CONCENTRATED_PAIR_TYPE};
+use tfi::concentrated::{
+    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, PositionResponse, PositionsResponse,
+    QueryMsg,
+};
+use tfi::pair::{MigrateMsg, ReverseSimulationResponse, SimulationResponse};
+use tfi_pair::contract::Operation;
+
+const TEST_RESERVE: &str = "reserve";
+const DENOM: &str = "usdc";
+
+fn mock_app() -> App {
+    // Instantiates custom account (TEST_RESERVE) with arbitrary amount
+    // of tokens to fill any custom account later needed in tests
+    AppBuilder::new_custom().build(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &Addr::unchecked(TEST_RESERVE),
+                coins(1_000_000, DENOM),
+            )
+            .unwrap();
+    })
+}
+
+fn contract_pair() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    )
+    .with_migrate(crate::contract::migrate);
+    Box::new(contract)
+}
+
+fn contract_group() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        tg4_group::contract::execute,
+        tg4_group::contract::instantiate,
+        tg4_group::contract::query,
+    );
+    Box::new(contract)
+}
+
+fn contract_cw20() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    Box::new(contract)
+}
+
+/// Helper struct providing unified environment for tfi-concentrated-pair testing
+///
+/// It assumes actors:
+/// * usdc: native token, the first pool asset
+/// * cash: cw20 token, the second pool asset
+/// * pair: tfi-concentrated-pair contract between usdc and cash
+/// * admin: pair admin
+/// * actors: number of accounts with initial usdc and cash
+#[derive(Derivative)]
+#[derivative(Debug)]
+struct Suite {
+    /// Multitest app
+    #[derivative(Debug = "ignore")]
+    app: App,
+    /// Pair admin
+    admin: Addr,
+    /// Cash cw20 contract address
+    cash: Addr,
+    /// Pair contract address
+    pair: Addr,
+    /// Actors addresses
+    actors: Vec<Addr>,
+}
+
+impl Suite {
+    /// Returns usdc asset info
+    fn usdc(&self) -> AssetInfo {
+        AssetInfo::Native(DENOM.to_owned())
+    }
+
+    /// Returns cash asset info
+    fn cash(&self) -> AssetInfo {
+        AssetInfo::Token(self.cash.clone())
+    }
+
+    /// Increases allowance for cash, and opens position with at most `usdc` and `cash` deposited
+    fn create_position(
+        &mut self,
+        owner: &Addr,
+        lower_tick: i32,
+        upper_tick: i32,
+        usdc: u128,
+        cash: u128,
+    ) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                owner.clone(),
+                self.cash.clone(),
+                &cw20_base::msg::ExecuteMsg::IncreaseAllowance {
+                    spender: self.pair.to_string(),
+                    amount: Uint128::new(cash),
+                    expires: None,
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        self.app
+            .execute_contract(
+                owner.clone(),
+                self.pair.clone(),
+                &ExecuteMsg::CreatePosition {
+                    lower_tick,
+                    upper_tick,
+                    assets: vec![
+                        Asset {
+                            info: self.usdc(),
+                            amount: Uint128::new(usdc),
+                        },
+                        Asset {
+                            info: self.cash(),
+                            amount: Uint128::new(cash),
+                        },
+                    ],
+                    deadline: None,
+                },
+                &coins(usdc, DENOM),
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    fn decrease_liquidity(
+        &mut self,
+        owner: &Addr,
+        position_id: u64,
+        liquidity: u128,
+    ) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                owner.clone(),
+                self.pair.clone(),
+                &ExecuteMsg::DecreaseLiquidity {
+                    position_id,
+                    liquidity: Uint128::new(liquidity),
+                    deadline: None,
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    fn collect_fees(&mut self, owner: &Addr, position_id: u64) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                owner.clone(),
+                self.pair.clone(),
+                &ExecuteMsg::CollectFees { position_id },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    fn transfer_position(
+        &mut self,
+        owner: &Addr,
+        position_id: u64,
+        recipient: &Addr,
+    ) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                owner.clone(),
+                self.pair.clone(),
+                &ExecuteMsg::TransferPosition {
+                    position_id,
+                    recipient: recipient.to_string(),
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    /// Swaps usdc for cash with `ExecuteMsg::Swap`
+    fn swap_usdc(&mut self, trader: &Addr, usdc: u128) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                trader.clone(),
+                self.pair.clone(),
+                &ExecuteMsg::Swap {
+                    offer_asset: Asset {
+                        info: self.usdc(),
+                        amount: Uint128::new(usdc),
+                    },
+                    ask_asset_info: None,
+                    belief_price: None,
+                    max_spread: None,
+                    to: None,
+                    min_return: None,
+                    deadline: None,
+                    to_msg: None,
+                },
+                &coins(usdc, DENOM),
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    /// Swaps cash for usdc with cw20 `Send` and `Cw20HookMsg::Swap`
+    fn swap_cash(&mut self, trader: &Addr, cash: u128) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                trader.clone(),
+                self.cash.clone(),
+                &cw20_base::msg::ExecuteMsg::Send {
+                    contract: self.pair.to_string(),
+                    amount: Uint128::new(cash),
+                    msg: to_binary(&Cw20HookMsg::Swap {
+                        ask_asset_info: None,
+                        belief_price: None,
+                        max_spread: None,
+                        to: None,
+                        min_return: None,
+                        deadline: None,
+                        to_msg: None,
+                    })
+                    .unwrap(),
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    /// Pauses or unpauses given operations with `ExecuteMsg::UpdatePause`
+    fn update_pause(
+        &mut self,
+        sender: &Addr,
+        swaps: Option<bool>,
+        deposits: Option<bool>,
+        withdrawals: Option<bool>,
+    ) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                sender.clone(),
+                self.pair.clone(),
+                &ExecuteMsg::UpdatePause {
+                    swaps,
+                    deposits,
+                    withdrawals,
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    /// Migrates pair to the same code with `MigrateMsg`, as its wasm admin
+    fn migrate(&mut self, admin: Option<&Addr>) -> Result<&mut Self> {
+        let contract_info: ContractInfoResponse = self
+            .app
+            .wrap()
+            .query(
+                &WasmQuery::ContractInfo {
+                    contract_addr: self.pair.to_string(),
+                }
+                .into(),
+            )
+            .map_err(|err| anyhow!(err))?;
+        self.app
+            .migrate_contract(
+                self.admin.clone(),
+                self.pair.clone(),
+                &MigrateMsg {
+                    admin: admin.map(Addr::to_string),
+                },
+                contract_info.code_id,
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    fn pair_info(&self) -> Result<PairInfo> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.pair.clone(), &QueryMsg::Pair {})
+            .map_err(|err| anyhow!(err))
+    }
+
+    fn pool(&self) -> Result<PoolResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.pair.clone(), &QueryMsg::Pool {})
+            .map_err(|err| anyhow!(err))
+    }
+
+    fn simulate_swap(&self, offer: u128, asset: AssetInfo) -> Result<SimulationResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                self.pair.clone(),
+                &QueryMsg::Simulation {
+                    offer_asset: Asset {
+                        info: asset,
+                        amount: Uint128::new(offer),
+                    },
+                    ask_asset_info: None,
+                    trader: None,
+                },
+            )
+            .map_err(|err| anyhow!(err))
+    }
+
+    fn reverse_simulate_swap(
+        &self,
+        ask: u128,
+        asset: AssetInfo,
+    ) -> Result<ReverseSimulationResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                self.pair.clone(),
+                &QueryMsg::ReverseSimulation {
+                    ask_asset: Asset {
+                        info: asset,
+                        amount: Uint128::new(ask),
+                    },
+                    offer_asset_info: None,
+                    trader: None,
+                },
+            )
+            .map_err(|err| anyhow!(err))
+    }
+
+    fn position(&self, position_id: u64) -> Result<PositionResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.pair.clone(), &QueryMsg::Position { position_id })
+            .map_err(|err| anyhow!(err))
+    }
+
+    /// Returns ids of positions owned by `owner`
+    fn positions(
+        &self,
+        owner: &Addr,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> Result<Vec<u64>> {
+        let resp: PositionsResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                self.pair.clone(),
+                &QueryMsg::Positions {
+                    owner: owner.to_string(),
+                    start_after,
+                    limit,
+                },
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(resp
+            .positions
+            .into_iter()
+            .map(|position| position.position_id)
+            .collect())
+    }
+
+    /// Returns usdc and cash amounts of `assets`, as reported in position query
+    fn amounts(&self, assets: &[Asset]) -> (u128, u128) {
+        assert_eq!(assets[0].info, self.usdc());
+        assert_eq!(assets[1].info, self.cash());
+        (assets[0].amount.u128(), assets[1].amount.u128())
+    }
+
+    /// Asserts if balances on account are as expected
+    #[track_caller]
+    fn assert_balances(&self, addr: &Addr, usdc: u128, cash: u128) -> &Self {
+        let usdc_balance = self.app.wrap().query_balance(addr, DENOM).unwrap();
+        assert_eq!(usdc_balance, coin(usdc, DENOM), "Usdc balance mismatch");
+
+        let balance: cw20::BalanceResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                self.cash.clone(),
+                &cw20_base::msg::QueryMsg::Balance {
+                    address: addr.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(balance.balance.u128(), cash, "Cash balance mismatch");
+
+        self
+    }
+}
+
+/// Builder helping construction of `Suite` helper
+#[derive(Debug)]
+struct SuiteConfig {
+    /// Initial actors: address, usdc, cash
+    actors: Vec<(String, u128, u128)>,
+    /// Initial price of usdc in cash
+    initial_price: Decimal,
+    tick_spacing: Option<u32>,
+    /// Whitelist group members, pair is not whitelisted if not given
+    whitelist: Option<Vec<String>>,
+}
+
+impl SuiteConfig {
+    fn new() -> Self {
+        Self {
+            actors: vec![],
+            initial_price: Decimal::one(),
+            tick_spacing: None,
+            whitelist: None,
+        }
+    }
+
+    fn with_actor(mut self, addr: &str, usdc: u128, cash: u128) -> Self {
+        self.actors.push((addr.to_owned(), usdc, cash));
+        self
+    }
+
+    fn with_tick_spacing(mut self, tick_spacing: u32) -> Self {
+        self.tick_spacing = Some(tick_spacing);
+        self
+    }
+
+    fn with_whitelist(mut self, members: &[&str]) -> Self {
+        self.whitelist = Some(members.iter().map(|member| member.to_string()).collect());
+        self
+    }
+
+    fn init(self) -> Result<Suite> {
+        let mut app = mock_app();
+
+        let cw20_id = app.store_code(contract_cw20());
+        let pair_id = app.store_code(contract_pair());
+
+        let admin = Addr::unchecked("admin");
+
+        let mut actors = vec![];
+        let mut initial_balances = vec![];
+        for (addr, usdc, cash) in self.actors {
+            app.execute(
+                Addr::unchecked(TEST_RESERVE),
+                BankMsg::Send {
+                    to_address: addr.clone(),
+                    amount: coins(usdc, DENOM),
+                }
+                .into(),
+            )?;
+            initial_balances.push(Cw20Coin {
+                address: addr.clone(),
+                amount: Uint128::new(cash),
+            });
+            actors.push(Addr::unchecked(addr));
+        }
+
+        let cash = app
+            .instantiate_contract(
+                cw20_id,
+                admin.clone(),
+                &cw20_base::msg::InstantiateMsg {
+                    name: "Cash Money".to_owned(),
+                    symbol: "cash".to_owned(),
+                    decimals: 6,
+                    initial_balances,
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                "Cash",
+                None,
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        // no liquidity token is created, so its code id is not used
+        let instantiate_msg = InstantiateMsg::new(
+            [
+                AssetInfo::Native(DENOM.to_owned()),
+                AssetInfo::Token(cash.clone()),
+            ],
+            0,
+        )
+        .with_initial_price(self.initial_price)
+        .with_admin(admin.clone());
+
+        let instantiate_msg = if let Some(tick_spacing) = self.tick_spacing {
+            instantiate_msg.with_tick_spacing(tick_spacing)
+        } else {
+            instantiate_msg
+        };
+
+        let instantiate_msg = if let Some(members) = self.whitelist {
+            let group_id = app.store_code(contract_group());
+            let members = members
+                .into_iter()
+                .map(|addr| Member {
+                    addr,
+                    points: 1,
+                    start_height: None,
+                })
+                .collect();
+            let whitelist = app
+                .instantiate_contract(
+                    group_id,
+                    admin.clone(),
+                    &tg4_group::msg::InstantiateMsg {
+                        admin: Some(admin.to_string()),
+                        members,
+                    },
+                    &[],
+                    "Whitelist",
+                    None,
+                )
+                .map_err(|err| anyhow!(err))?;
+            instantiate_msg.with_whitelist_group(whitelist)
+        } else {
+            instantiate_msg
+        };
+
+        let pair = app
+            .instantiate_contract(
+                pair_id,
+                admin.clone(),
+                &instantiate_msg,
+                &[],
+                "Pair",
+                Some(admin.to_string()),
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(Suite {
+            app,
+            admin,
+            cash,
+            pair,
+            actors,
+        })
+    }
+}
+
+#[test]
+fn invalid_position_range() {
+    let mut suite = SuiteConfig::new()
+        .with_actor("liquidity-provider", 10000, 10000)
+        .with_tick_spacing(10)
+        .init()
+        .unwrap();
+
+    let lp = suite.actors[0].clone();
+
+    for (lower_tick, upper_tick) in [(100, -100), (100, 100), (-105, 100), (-300_000, 100)] {
+        let err = suite
+            .create_position(&lp, lower_tick, upper_tick, 1000, 1000)
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::InvalidTickRange {
+                lower_tick,
+                upper_tick
+            }
+            .to_string()
+        );
+    }
+
+    suite.create_position(&lp, -100, 100, 1000, 1000).unwrap();
+}
+
+#[test]
+// Only deposit matching the current price is collected, excess of usdc sent is refunded
+fn create_position() {
+    let mut suite = SuiteConfig::new()
+        .with_actor("liquidity-provider", 20000, 20000)
+        .init()
+        .unwrap();
+
+    let (lp, pair) = (suite.actors[0].clone(), suite.pair.clone());
+
+    // price 1 is right in the middle of range 0.905..1.105, so deposits of both assets are
+    // almost equal
+    suite
+        .create_position(&lp, -1000, 1000, 20000, 10000)
+        .unwrap();
+    suite
+        .assert_balances(&lp, 10000, 10000)
+        .assert_balances(&pair, 10000, 10000);
+
+    let position = suite.position(1).unwrap();
+    assert_eq!(position.owner, lp);
+    assert_eq!((position.lower_tick, position.upper_tick), (-1000, 1000));
+    assert_eq!(position.liquidity, Uint128::new(205051));
+    // withdrawal is rounded down
+    assert_eq!(suite.amounts(&position.assets), (9999, 9999));
+    assert_eq!(suite.amounts(&position.fees), (0, 0));
+
+    let pool = suite.pool().unwrap();
+    assert_eq!(pool.tick, 0);
+    assert_eq!(pool.liquidity, position.liquidity);
+
+    // range above the current price is provided in usdc only
+    suite.create_position(&lp, 1000, 2000, 5000, 5000).unwrap();
+    suite.assert_balances(&lp, 5000, 10000);
+    let position = suite.position(2).unwrap();
+    assert_eq!(suite.amounts(&position.assets).1, 0);
+
+    // and it doesn't add to liquidity at the current price
+    assert_eq!(suite.pool().unwrap().liquidity, Uint128::new(205051));
+}
+
+#[test]
+// Swaps in both directions return as much as simulated, and move the price
+fn swap() {
+    let mut suite = SuiteConfig::new()
+        .with_actor("liquidity-provider", 10000, 10000)
+        .with_actor("trader", 1000, 1000)
+        .init()
+        .unwrap();
+
+    let (lp, trader, pair) = (
+        suite.actors[0].clone(),
+        suite.actors[1].clone(),
+        suite.pair.clone(),
+    );
+    suite
+        .create_position(&lp, -1000, 1000, 10000, 10000)
+        .unwrap();
+
+    // sqrt price moves from 1 to 205051 / (205051 + 1000) = 0.995147, returning
+    // 205051 * (1 - 0.995147) = 995.1 cash, commission is 0.3% of it
+    let simulation = suite.simulate_swap(1000, suite.usdc()).unwrap();
+    assert_eq!(
+        simulation,
+        SimulationResponse {
+            return_amount: Uint128::new(993),
+            spread_amount: Uint128::new(5),
+            commission_amount: Uint128::new(2),
+        }
+    );
+
+    suite.swap_usdc(&trader, 1000).unwrap();
+    suite
+        .assert_balances(&trader, 0, 1993)
+        .assert_balances(&pair, 11000, 9007);
+
+    let pool = suite.pool().unwrap();
+    assert_eq!(pool.tick, -98);
+    assert_eq!(
+        pool.price,
+        Decimal256::from_str("0.990317218394570843").unwrap()
+    );
+
+    let simulation = suite.simulate_swap(1000, suite.cash()).unwrap();
+    assert_eq!(
+        simulation,
+        SimulationResponse {
+            return_amount: Uint128::new(1001),
+            spread_amount: Uint128::new(5),
+            commission_amount: Uint128::new(3),
+        }
+    );
+
+    suite.swap_cash(&trader, 1000).unwrap();
+    suite
+        .assert_balances(&trader, 1001, 993)
+        .assert_balances(&pair, 9999, 10007);
+    assert!(suite.pool().unwrap().tick >= 0);
+}
+
+#[test]
+// Commission is earned only by liquidity in range the swap went through
+fn fees_earned_in_range() {
+    let mut suite = SuiteConfig::new()
+        .with_actor("liquidity-provider", 20000, 20000)
+        .with_actor("trader", 1000, 0)
+        .init()
+        .unwrap();
+
+    let (lp, trader) = (suite.actors[0].clone(), suite.actors[1].clone());
+    suite
+        .create_position(&lp, -1000, 1000, 10000, 10000)
+        .unwrap();
+    suite.create_position(&lp, 1000, 2000, 5000, 5000).unwrap();
+
+    suite.swap_usdc(&trader, 1000).unwrap();
+
+    // commission of 2cash is distributed per unit of liquidity, which is rounded down
+    let position = suite.position(1).unwrap();
+    assert_eq!(suite.amounts(&position.fees), (0, 1));
+    let position = suite.position(2).unwrap();
+    assert_eq!(suite.amounts(&position.fees), (0, 0));
+
+    suite.collect_fees(&lp, 1).unwrap();
+    suite.assert_balances(&lp, 5000, 10001);
+    assert_eq!(suite.amounts(&suite.position(1).unwrap().fees), (0, 0));
+
+    // position liquidity is untouched
+    assert_eq!(suite.position(1).unwrap().liquidity, Uint128::new(205051));
+}
+
+#[test]
+// Swap leaving range of a position crosses its tick, and goes on with remaining liquidity only
+fn swap_crossing_ticks() {
+    let mut suite = SuiteConfig::new()
+        .with_actor("liquidity-provider", 20000, 20000)
+        .with_actor("trader", 20000, 0)
+        .init()
+        .unwrap();
+
+    let (lp, trader) = (suite.actors[0].clone(), suite.actors[1].clone());
+    suite
+        .create_position(&lp, -1000, 1000, 10000, 10000)
+        .unwrap();
+    suite.create_position(&lp, -100, 100, 1000, 1000).unwrap();
+    let (wide, narrow) = (
+        suite.position(1).unwrap().liquidity,
+        suite.position(2).unwrap().liquidity,
+    );
+    assert_eq!(suite.pool().unwrap().liquidity, wide + narrow);
+
+    let simulation = suite.simulate_swap(3000, suite.usdc()).unwrap();
+    suite.swap_usdc(&trader, 3000).unwrap();
+    suite.assert_balances(&trader, 17000, simulation.return_amount.u128());
+
+    let pool = suite.pool().unwrap();
+    assert!(pool.tick < -100, "{:?}", pool);
+    assert_eq!(pool.liquidity, wide);
+
+    // narrow position is converted to usdc entirely
+    let position = suite.position(2).unwrap();
+    assert_eq!(suite.amounts(&position.assets).1, 0);
+
+    // swap going beyond all the liquidity fails
+    let err = suite.swap_usdc(&trader, 17000).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::InsufficientLiquidity {}.to_string()
+    );
+}
+
+#[test]
+// Offering reverse simulated amount returns at least the asked amount in both directions, also
+// when the swap crosses ticks
+fn reverse_simulate() {
+    let mut suite = SuiteConfig::new()
+        .with_actor("liquidity-provider", 20000, 20000)
+        .with_actor("trader", 20000, 20000)
+        .init()
+        .unwrap();
+
+    let (lp, trader) = (suite.actors[0].clone(), suite.actors[1].clone());
+    suite
+        .create_position(&lp, -1000, 1000, 10000, 10000)
+        .unwrap();
+    suite.create_position(&lp, -100, 100, 1000, 1000).unwrap();
+
+    // usdc is offered for 3000 cash, leaving range of the narrow position - offer is rounded up
+    // and commission down, so a few more cash may be returned, but never less
+    let offer = suite
+        .reverse_simulate_swap(3000, suite.cash())
+        .unwrap()
+        .offer_amount
+        .u128();
+    let simulation = suite.simulate_swap(offer, suite.usdc()).unwrap();
+    let cash = simulation.return_amount.u128();
+    assert!((3000..3005).contains(&cash), "{:?}", simulation);
+
+    suite.swap_usdc(&trader, offer).unwrap();
+    assert!(suite.pool().unwrap().tick < -100);
+
+    // and cash is offered for 2500 usdc back, crossing the narrow position again
+    let offer = suite
+        .reverse_simulate_swap(2500, suite.usdc())
+        .unwrap()
+        .offer_amount
+        .u128();
+    let simulation = suite.simulate_swap(offer, suite.cash()).unwrap();
+    assert!(simulation.return_amount.u128() >= 2500, "{:?}", simulation);
+    suite.swap_cash(&trader, offer).unwrap();
+    assert!(suite.pool().unwrap().tick > -100);
+
+    // asking for more than all the liquidity fails
+    let err = suite
+        .reverse_simulate_swap(30000, suite.cash())
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .ends_with(&ContractError::InsufficientLiquidity {}.to_string()),
+        "{}",
+        err
+    );
+}
+
+#[test]
+// Decreasing liquidity pays assets out, position without liquidity is closed
+fn decrease_liquidity() {
+    let mut suite = SuiteConfig::new()
+        .with_actor("liquidity-provider", 10000, 10000)
+        .with_actor("trader", 1000, 0)
+        .init()
+        .unwrap();
+
+    let (lp, trader, pair) = (
+        suite.actors[0].clone(),
+        suite.actors[1].clone(),
+        suite.pair.clone(),
+    );
+    suite
+        .create_position(&lp, -1000, 1000, 10000, 10000)
+        .unwrap();
+
+    let err = suite.decrease_liquidity(&trader, 1, 1000).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+    let err = suite.decrease_liquidity(&lp, 1, 205052).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::InsufficientPositionLiquidity {
+            liquidity: Uint128::new(205051),
+            requested: Uint128::new(205052),
+        }
+        .to_string()
+    );
+
+    suite.decrease_liquidity(&lp, 1, 105051).unwrap();
+    suite.assert_balances(&lp, 5123, 5123);
+    suite.swap_usdc(&trader, 1000).unwrap();
+    let position = suite.position(1).unwrap();
+    let (usdc, cash) = suite.amounts(&position.assets);
+    let (_, cash_fees) = suite.amounts(&position.fees);
+    assert_eq!((usdc, cash, cash_fees), (5876, 3886, 2));
+
+    suite.decrease_liquidity(&lp, 1, 100000).unwrap();
+    suite.position(1).unwrap_err();
+    suite.assert_balances(&lp, 5123 + usdc, 5123 + cash + cash_fees);
+    // only rounding dust is left on the pair
+    suite.assert_balances(&pair, 1, 1);
+    assert_eq!(suite.positions(&lp, None, None).unwrap(), Vec::<u64>::new());
+}
+
+#[test]
+fn transfer_position() {
+    let mut suite = SuiteConfig::new()
+        .with_actor("liquidity-provider", 10000, 10000)
+        .init()
+        .unwrap();
+
+    let (lp, recipient) = (suite.actors[0].clone(), Addr::unchecked("recipient"));
+    for _ in 0..4 {
+        suite.create_position(&lp, -100, 100, 1000, 1000).unwrap();
+    }
+
+    assert_eq!(suite.positions(&lp, None, None).unwrap(), vec![1, 2, 3, 4]);
+    assert_eq!(suite.positions(&lp, Some(1), Some(2)).unwrap(), vec![2, 3]);
+
+    let err = suite.transfer_position(&recipient, 2, &lp).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+
+    suite.transfer_position(&lp, 2, &recipient).unwrap();
+    assert_eq!(suite.positions(&lp, None, None).unwrap(), vec![1, 3, 4]);
+    assert_eq!(suite.positions(&recipient, None, None).unwrap(), vec![2]);
+    assert_eq!(suite.position(2).unwrap().owner, recipient);
+
+    // only the new owner can withdraw
+    let liquidity = suite.position(2).unwrap().liquidity.u128();
+    suite.decrease_liquidity(&lp, 2, liquidity).unwrap_err();
+    suite.decrease_liquidity(&recipient, 2, liquidity).unwrap();
+    suite.assert_balances(&recipient, 999, 999);
+}
+
+#[test]
+// Pair info is stored the same way as by other pairs, with no liquidity token
+fn pair_info() {
+    let suite = SuiteConfig::new().init().unwrap();
+
+    let pair_info = suite.pair_info().unwrap();
+    assert_eq!(pair_info.asset_infos, vec![suite.usdc(), suite.cash()]);
+    assert_eq!(pair_info.contract_addr, suite.pair);
+    assert_eq!(pair_info.liquidity_token, Addr::unchecked(""));
+    assert_eq!(pair_info.pair_type, CONCENTRATED_PAIR_TYPE);
+    assert_eq!(pair_info.admin, Some(suite.admin.clone()));
+}
+
+#[test]
+fn migrate_admin() {
+    let mut suite = SuiteConfig::new().init().unwrap();
+    let (admin, factory) = (suite.admin.clone(), Addr::unchecked("factory"));
+
+    // no admin keeps the current one
+    suite.migrate(None).unwrap();
+    assert_eq!(suite.pair_info().unwrap().admin, Some(admin.clone()));
+
+    suite.migrate(Some(&factory)).unwrap();
+    assert_eq!(suite.pair_info().unwrap().admin, Some(factory.clone()));
+
+    let err = suite
+        .update_pause(&admin, Some(true), None, None)
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+    suite
+        .update_pause(&factory, Some(true), None, None)
+        .unwrap();
+}
+
+#[test]
+fn paused_operations() {
+    let mut suite = SuiteConfig::new()
+        .with_actor("liquidity-provider", 10000, 10000)
+        .with_actor("trader", 1000, 0)
+        .init()
+        .unwrap();
+
+    let (admin, lp, trader) = (
+        suite.admin.clone(),
+        suite.actors[0].clone(),
+        suite.actors[1].clone(),
+    );
+    suite.create_position(&lp, -1000, 1000, 5000, 5000).unwrap();
+
+    let err = suite.update_pause(&lp, Some(true), None, None).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+
+    suite
+        .update_pause(&admin, Some(true), Some(true), Some(true))
+        .unwrap();
+    let err = suite.swap_usdc(&trader, 1000).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        tfi_pair::error::ContractError::Paused(Operation::Swaps).to_string()
+    );
+    let err = suite
+        .create_position(&lp, -100, 100, 1000, 1000)
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        tfi_pair::error::ContractError::Paused(Operation::Deposits).to_string()
+    );
+    let err = suite.decrease_liquidity(&lp, 1, 1000).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        tfi_pair::error::ContractError::Paused(Operation::Withdrawals).to_string()
+    );
+
+    // operations not mentioned stay paused
+    suite.update_pause(&admin, Some(false), None, None).unwrap();
+    suite.swap_usdc(&trader, 1000).unwrap();
+    suite.decrease_liquidity(&lp, 1, 1000).unwrap_err();
+}
+
+#[test]
+// Only whitelist members can swap, provide liquidity and receive positions
+fn whitelisted_pair() {
+    let mut suite = SuiteConfig::new()
+        .with_actor("liquidity-provider", 10000, 10000)
+        .with_actor("trader", 1000, 0)
+        .with_whitelist(&["liquidity-provider"])
+        .init()
+        .unwrap();
+
+    let (lp, trader) = (suite.actors[0].clone(), suite.actors[1].clone());
+    suite.create_position(&lp, -1000, 1000, 5000, 5000).unwrap();
+
+    let err = suite.swap_usdc(&trader, 1000).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        tfi_pair::error::ContractError::NotWhitelisted(trader.to_string()).to_string()
+    );
+    let err = suite.transfer_position(&lp, 1, &trader).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        tfi_pair::error::ContractError::NotWhitelisted(trader.to_string()).to_string()
+    );
+
+    suite.swap_usdc(&lp, 1000).unwrap();
+}
+
+#[test]
+// Swap going through too many initialized ticks fails, shorter swaps are not affected
+fn swap_crossing_too_many_ticks() {
+    let positions = MAX_TICKS_CROSSED as i32 + 10;
+    let mut suite = SuiteConfig::new()
+        .with_actor("liquidity-provider", 1, 1000 * positions as u128)
+        .with_actor("trader", 100_000, 0)
+        .init()
+        .unwrap();
+
+    let (lp, trader) = (suite.actors[0].clone(), suite.actors[1].clone());
+    // adjacent single tick positions below the current price, provided in cash only, so usdc
+    // sent is refunded
+    for tick in 0..positions {
+        suite
+            .create_position(&lp, -tick - 1, -tick, 1, 1000)
+            .unwrap();
+    }
+
+    let err = suite.swap_usdc(&trader, 100_000).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::TooManyTicksCrossed(MAX_TICKS_CROSSED).to_string()
+    );
+
+    suite.swap_usdc(&trader, 10_000).unwrap();
+    assert!(suite.pool().unwrap().tick < -5);
+}
@@ -0,0 +1,96 @@
+use cosmwasm_std::{Addr, Decimal256, Empty, Uint128, Uint256};
+use cw_storage_plus::{Item, Map};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tfi::asset::AssetInfo;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub asset_infos: [AssetInfo; 2],
+    pub tick_spacing: u32,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// Current state of the pool, moved by swaps
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PoolState {
+    /// Square root of price of the first asset in the second one
+    pub sqrt_price: Decimal256,
+    /// Greatest tick with price not above the current one. When swap moving the price down stops
+    /// exactly at an initialized tick, it is already crossed, so the tick is one below.
+    pub tick: i32,
+    /// Liquidity of positions containing the current tick
+    pub liquidity: Uint128,
+    /// Commission earned per unit of liquidity since the pool creation, per asset
+    pub fee_growth_global: [Uint256; 2],
+}
+
+pub const POOL: Item<PoolState> = Item::new("pool");
+
+/// Tick used as a bound of at least one position
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TickInfo {
+    /// Liquidity of positions with this lower tick
+    pub liquidity_lower: Uint128,
+    /// Liquidity of positions with this upper tick
+    pub liquidity_upper: Uint128,
+    /// Fee growth on the other side of this tick than the current one, per asset. Swaps crossing
+    /// the tick flip it.
+    pub fee_growth_outside: [Uint256; 2],
+}
+
+impl TickInfo {
+    /// Initializes tick assuming all fees so far were earned below it
+    pub fn new(tick: i32, pool: &PoolState) -> Self {
+        let fee_growth_outside = if tick <= pool.tick {
+            pool.fee_growth_global
+        } else {
+            [Uint256::zero(); 2]
+        };
+
+        Self {
+            liquidity_lower: Uint128::zero(),
+            liquidity_upper: Uint128::zero(),
+            fee_growth_outside,
+        }
+    }
+
+    /// Tick not used by any position anymore can be removed
+    pub fn is_empty(&self) -> bool {
+        self.liquidity_lower.is_zero() && self.liquidity_upper.is_zero()
+    }
+}
+
+pub const TICKS: Map<i32, TickInfo> = Map::new("ticks");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Position {
+    pub owner: Addr,
+    pub lower_tick: i32,
+    pub upper_tick: i32,
+    pub liquidity: Uint128,
+    /// Fee growth within position range as of the last fees accrual, per asset
+    pub fee_growth_inside_last: [Uint256; 2],
+    /// Fees accrued and not collected yet, per asset
+    pub fees_owed: [Uint128; 2],
+}
+
+impl Position {
+    pub fn new(owner: Addr, lower_tick: i32, upper_tick: i32) -> Self {
+        Self {
+            owner,
+            lower_tick,
+            upper_tick,
+            liquidity: Uint128::zero(),
+            fee_growth_inside_last: [Uint256::zero(); 2],
+            fees_owed: [Uint128::zero(); 2],
+        }
+    }
+}
+
+pub const POSITIONS: Map<u64, Position> = Map::new("positions");
+/// Ids of positions by their owners
+pub const OWNER_POSITIONS: Map<(&Addr, u64), Empty> = Map::new("owner_positions");
+/// Id of the most recently created position
+pub const LAST_POSITION_ID: Item<u64> = Item::new("last_position_id");
@@ -1,24 +1,29 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError,
-    StdResult, SubMsg, WasmMsg,
+    to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Reply, Response,
+    StdError, StdResult, Storage, SubMsg, WasmMsg,
 };
 use cw2::set_contract_version;
 
 use crate::error::ContractError;
-use crate::querier::{query_liquidity_token, query_migrate_admin};
+use crate::querier::{
+    query_liquidity_token, query_migrate_admin, query_pair_admin, query_pair_pause,
+};
 use crate::response::MsgInstantiateContractResponse;
 use crate::state::{
-    load_update_config, pair_key, read_pairs, Config, TmpPairInfo, CONFIG, PAIRS, TMP_PAIR_INFO,
+    load_update_config, migrate_pair_keys, pair_key, read_pairs, Config, TmpPairInfo, ALL_PAUSED,
+    CONFIG, LEGACY_CONFIG, PAIRS, PAIR_TYPES, TMP_PAIR_INFO,
 };
 
 use protobuf::Message;
-use tfi::asset::{AssetInfo, PairInfo};
+use tfi::asset::{
+    AssetInfo, PairInfo, PauseState, CONCENTRATED_PAIR_TYPE, STABLE_PAIR_TYPE, XYK_PAIR_TYPE,
+};
 use tfi::factory::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, PairsResponse, QueryMsg,
+    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, PairType, PairsResponse, QueryMsg,
 };
-use tfi::pair::InstantiateMsg as PairInstantiateMsg;
+use tfi::pair::{ExecuteMsg as PairExecuteMsg, InstantiateMsg as PairInstantiateMsg};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:tfi-factory";
@@ -32,20 +37,28 @@ pub fn instantiate(
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    if !(Decimal::zero()..=Decimal::one()).contains(&msg.default_commission) {
-        return Err(ContractError::InvalidCommission(msg.default_commission));
-    }
 
     let config = Config {
         owner: info.sender,
         token_code_id: msg.token_code_id,
-        pair_code_id: msg.pair_code_id,
-        default_commission: msg.default_commission,
         migrate_admin: None,
+        fee_collector: msg
+            .fee_collector
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?,
+        protocol_fee_share: msg.protocol_fee_share,
+        trusted_token_code_id: msg.trusted_token_code_id,
     };
 
+    validate_protocol_fee(&config)?;
     CONFIG.save(deps.storage, &config)?;
 
+    let xyk = PairType::new(XYK_PAIR_TYPE, msg.pair_code_id)
+        .with_default_commission(msg.default_commission);
+    for pair_type in std::iter::once(xyk).chain(msg.pair_types) {
+        save_pair_type(deps.storage, &pair_type)?;
+    }
+
     Ok(Response::default())
 }
 
@@ -60,40 +73,111 @@ pub fn execute(
         ExecuteMsg::UpdateConfig {
             owner,
             token_code_id,
-            pair_code_id,
-            default_commission,
+            fee_collector,
+            protocol_fee_share,
+            trusted_token_code_id,
         } => execute_update_config(
             deps,
             env,
             info,
             owner,
             token_code_id,
-            pair_code_id,
-            default_commission,
-        )
-        .map_err(Into::into),
+            fee_collector,
+            protocol_fee_share,
+            trusted_token_code_id,
+        ),
+        ExecuteMsg::UpdatePairType { pair_type } => {
+            execute_update_pair_type(deps, env, info, pair_type)
+        }
         ExecuteMsg::CreatePair {
+            pair_type,
+            asset_infos,
+            commission,
+            amp,
+            weights,
+            initial_price,
+            tick_spacing,
+            whitelist_group,
+        } => execute_create_pair(
+            deps,
+            env,
+            info,
+            pair_type,
+            asset_infos,
+            commission,
+            amp,
+            weights,
+            initial_price,
+            tick_spacing,
+            whitelist_group,
+        ),
+        ExecuteMsg::UpdatePairPause {
+            asset_infos,
+            pair_type,
+            swaps,
+            deposits,
+            withdrawals,
+        } => execute_update_pair_pause(
+            deps,
+            env,
+            info,
+            asset_infos,
+            pair_type,
+            swaps,
+            deposits,
+            withdrawals,
+        ),
+        ExecuteMsg::UpdatePairCommission {
             asset_infos,
+            pair_type,
             commission,
-        } => execute_create_pair(deps, env, info, asset_infos, commission),
+        } => execute_update_pair_commission(deps, env, info, asset_infos, pair_type, commission),
+        ExecuteMsg::PauseAll {
+            start_after,
+            start_after_pair_type,
+            limit,
+        } => execute_update_all_pause(
+            deps,
+            env,
+            info,
+            true,
+            start_after,
+            start_after_pair_type,
+            limit,
+        ),
+        ExecuteMsg::UnpauseAll {
+            start_after,
+            start_after_pair_type,
+            limit,
+        } => execute_update_all_pause(
+            deps,
+            env,
+            info,
+            false,
+            start_after,
+            start_after_pair_type,
+            limit,
+        ),
     }
 }
 
 // Only owner can execute it
+#[allow(clippy::too_many_arguments)]
 pub fn execute_update_config(
     mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     owner: Option<String>,
     token_code_id: Option<u64>,
-    pair_code_id: Option<u64>,
-    default_commission: Option<Decimal>,
-) -> StdResult<Response> {
+    fee_collector: Option<String>,
+    protocol_fee_share: Option<Decimal>,
+    trusted_token_code_id: Option<u64>,
+) -> Result<Response, ContractError> {
     let mut config = load_update_config(deps.branch(), &env)?;
 
     // permission check
     if info.sender != config.owner {
-        return Err(StdError::generic_err("unauthorized"));
+        return Err(StdError::generic_err("unauthorized").into());
     }
 
     if let Some(owner) = owner {
@@ -106,27 +190,99 @@ pub fn execute_update_config(
         config.token_code_id = token_code_id;
     }
 
-    if let Some(pair_code_id) = pair_code_id {
-        config.pair_code_id = pair_code_id;
+    if let Some(trusted_token_code_id) = trusted_token_code_id {
+        config.trusted_token_code_id = Some(trusted_token_code_id);
     }
 
-    if let Some(commission) = default_commission {
-        config.default_commission = commission;
+    if let Some(fee_collector) = fee_collector {
+        config.fee_collector = Some(deps.api.addr_validate(&fee_collector)?);
     }
 
+    if let Some(protocol_fee_share) = protocol_fee_share {
+        config.protocol_fee_share = protocol_fee_share;
+    }
+
+    validate_protocol_fee(&config)?;
     CONFIG.save(deps.storage, &config)?;
 
     Ok(Response::new().add_attribute("action", "update_config"))
 }
 
+// Only owner can execute it
+pub fn execute_update_pair_type(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    pair_type: PairType,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.owner {
+        return Err(StdError::generic_err("unauthorized").into());
+    }
+
+    save_pair_type(deps.storage, &pair_type)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_pair_type")
+        .add_attribute("pair_type", pair_type.name))
+}
+
+fn validate_protocol_fee(config: &Config) -> Result<(), ContractError> {
+    if !(Decimal::zero()..=Decimal::one()).contains(&config.protocol_fee_share) {
+        return Err(ContractError::InvalidProtocolFeeShare(
+            config.protocol_fee_share,
+        ));
+    }
+
+    if !config.protocol_fee_share.is_zero() && config.fee_collector.is_none() {
+        return Err(ContractError::MissingFeeCollector {});
+    }
+
+    Ok(())
+}
+
+fn save_pair_type(storage: &mut dyn Storage, pair_type: &PairType) -> Result<(), ContractError> {
+    if !(Decimal::zero()..=Decimal::one()).contains(&pair_type.default_commission) {
+        return Err(ContractError::InvalidCommission(
+            pair_type.default_commission,
+        ));
+    }
+
+    PAIR_TYPES.save(storage, &pair_type.name, pair_type)?;
+    Ok(())
+}
+
 // Anyone can execute it to create swap pair
+#[allow(clippy::too_many_arguments)]
 pub fn execute_create_pair(
     mut deps: DepsMut,
     env: Env,
     _info: MessageInfo,
-    asset_infos: [AssetInfo; 2],
+    pair_type: String,
+    asset_infos: Vec<AssetInfo>,
     commission: Option<Decimal>,
+    amp: Option<u64>,
+    weights: Option<Vec<Decimal>>,
+    initial_price: Option<Decimal>,
+    tick_spacing: Option<u32>,
+    whitelist_group: Option<String>,
 ) -> Result<Response, ContractError> {
+    if asset_infos.len() < 2 {
+        return Err(ContractError::NotEnoughAssets {});
+    }
+    // stable pair validates the maximum itself, other pair types hold exactly 2 assets
+    if asset_infos.len() > 2 && pair_type != STABLE_PAIR_TYPE {
+        return Err(ContractError::InvalidAssetCount(asset_infos.len()));
+    }
+    if let Some((_, duplicated)) = asset_infos
+        .iter()
+        .enumerate()
+        .find(|(idx, info)| asset_infos[..*idx].iter().any(|other| other.equal(info)))
+    {
+        return Err(ContractError::DuplicatedAsset(duplicated.to_string()));
+    }
     if let Some(commission) = commission {
         if !(Decimal::zero()..=Decimal::one()).contains(&commission) {
             return Err(ContractError::InvalidCommission(commission));
@@ -134,13 +290,26 @@ pub fn execute_create_pair(
     }
 
     let config = load_update_config(deps.branch(), &env)?;
+    let type_info = PAIR_TYPES
+        .may_load(deps.storage, &pair_type)?
+        .ok_or_else(|| ContractError::UnknownPairType(pair_type.clone()))?;
 
-    let pair_key = pair_key(&asset_infos);
+    let pair_key = pair_key(&asset_infos, &pair_type);
     if let Ok(Some(_)) = PAIRS.may_load(deps.storage, &pair_key) {
         return Err(StdError::generic_err("Pair already exists").into());
     }
 
-    let commission = commission.unwrap_or(config.default_commission);
+    let commission = commission.unwrap_or(type_info.default_commission);
+    let whitelist_group = whitelist_group
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    // liquidity tokens of whitelisted pair can be held by whitelisted addresses only
+    let token_code_id = match whitelist_group {
+        Some(_) => config
+            .trusted_token_code_id
+            .ok_or(ContractError::MissingTrustedTokenCodeId {})?,
+        None => config.token_code_id,
+    };
 
     TMP_PAIR_INFO.save(
         deps.storage,
@@ -148,30 +317,241 @@ pub fn execute_create_pair(
             pair_key,
             asset_infos: asset_infos.clone(),
             commission,
+            pair_type: pair_type.clone(),
+            whitelist_group: whitelist_group.clone(),
         },
     )?;
 
-    let pair_name = format!("{}-{}", asset_infos[0], asset_infos[1]);
+    let pair_name = asset_infos
+        .iter()
+        .map(|info| info.to_string())
+        .collect::<Vec<_>>()
+        .join("-");
+    // factory is the pair admin, so it can pause it
+    let mut instantiate_msg = PairInstantiateMsg::new(asset_infos, token_code_id)
+        .with_commission(commission)
+        .with_admin(env.contract.address);
+    if let Some(amp) = amp {
+        instantiate_msg = instantiate_msg.with_amp(amp);
+    }
+    if let Some(weights) = weights {
+        instantiate_msg = instantiate_msg.with_weights(weights);
+    }
+    if let Some(initial_price) = initial_price {
+        instantiate_msg = instantiate_msg.with_initial_price(initial_price);
+    }
+    if let Some(tick_spacing) = tick_spacing {
+        instantiate_msg = instantiate_msg.with_tick_spacing(tick_spacing);
+    }
+    if let Some(whitelist_group) = whitelist_group {
+        instantiate_msg = instantiate_msg.with_whitelist_group(whitelist_group);
+    }
+    // concentrated pairs pay whole commission to positions, they take no protocol fee
+    if let Some(fee_collector) = config.fee_collector {
+        if pair_type != CONCENTRATED_PAIR_TYPE {
+            instantiate_msg =
+                instantiate_msg.with_protocol_fee(fee_collector, config.protocol_fee_share);
+        }
+    }
+
     let msg = WasmMsg::Instantiate {
-        code_id: config.pair_code_id,
+        code_id: type_info.code_id,
         funds: vec![],
         admin: config.migrate_admin.unwrap(),
         label: "Tgrade finance trading pair".to_string(),
-        msg: to_binary(
-            &PairInstantiateMsg::new(asset_infos, config.token_code_id).with_commission(commission),
-        )?,
+        msg: to_binary(&instantiate_msg)?,
     };
     let msg = SubMsg::reply_on_success(msg, 1);
     let res = Response::new()
         .add_submessage(msg)
         .add_attribute("action", "create_pair")
-        .add_attribute("pair", pair_name);
+        .add_attribute("pair", pair_name)
+        .add_attribute("pair_type", pair_type);
     Ok(res)
 }
 
+// Only owner can execute it
+#[allow(clippy::too_many_arguments)]
+pub fn execute_update_pair_pause(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset_infos: Vec<AssetInfo>,
+    pair_type: String,
+    swaps: Option<bool>,
+    deposits: Option<bool>,
+    withdrawals: Option<bool>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.owner {
+        return Err(StdError::generic_err("unauthorized").into());
+    }
+
+    let pair_key = pair_key(&asset_infos, &pair_type);
+    let mut pair_info = PAIRS.load(deps.storage, &pair_key)?;
+    sync_pair_admin(deps.branch(), &mut pair_info)?;
+    if pair_info.admin.as_ref() != Some(&env.contract.address) {
+        return Err(ContractError::NotPairAdmin {});
+    }
+
+    // keep the factory copy of pair info in sync with the pair
+    let paused = &mut pair_info.paused;
+    paused.swaps = swaps.unwrap_or(paused.swaps);
+    paused.deposits = deposits.unwrap_or(paused.deposits);
+    paused.withdrawals = withdrawals.unwrap_or(paused.withdrawals);
+    PAIRS.save(deps.storage, &pair_key, &pair_info)?;
+
+    Ok(Response::new()
+        .add_message(update_pause_msg(
+            &pair_info.contract_addr,
+            &effective_pause(deps.storage, &pair_info)?,
+        )?)
+        .add_attribute("action", "update_pair_pause")
+        .add_attribute("pair", pair_info.contract_addr))
+}
+
+// Only owner can execute it
+pub fn execute_update_pair_commission(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset_infos: Vec<AssetInfo>,
+    pair_type: String,
+    commission: Decimal,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.owner {
+        return Err(StdError::generic_err("unauthorized").into());
+    }
+
+    if !(Decimal::zero()..=Decimal::one()).contains(&commission) {
+        return Err(ContractError::InvalidCommission(commission));
+    }
+
+    let pair_key = pair_key(&asset_infos, &pair_type);
+    let mut pair_info = PAIRS.load(deps.storage, &pair_key)?;
+    sync_pair_admin(deps.branch(), &mut pair_info)?;
+    if pair_info.admin.as_ref() != Some(&env.contract.address) {
+        return Err(ContractError::NotPairAdmin {});
+    }
+
+    // keep the factory copy of pair info in sync with the pair
+    pair_info.commission = commission;
+    PAIRS.save(deps.storage, &pair_key, &pair_info)?;
+
+    let msg = WasmMsg::Execute {
+        contract_addr: pair_info.contract_addr.to_string(),
+        msg: to_binary(&PairExecuteMsg::UpdateCommission { commission })?,
+        funds: vec![],
+    };
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "update_pair_commission")
+        .add_attribute("pair", pair_info.contract_addr)
+        .add_attribute("commission", commission.to_string()))
+}
+
+// Only owner can execute it
+pub fn execute_update_all_pause(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    all_paused: bool,
+    start_after: Option<Vec<AssetInfo>>,
+    start_after_pair_type: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.owner {
+        return Err(StdError::generic_err("unauthorized").into());
+    }
+
+    ALL_PAUSED.save(deps.storage, &all_paused)?;
+
+    let mut pairs = read_pairs(deps.storage, start_after, start_after_pair_type, limit)?;
+    let last_pair = pairs.last().map(|pair| pair.contract_addr.to_string());
+    for pair in &mut pairs {
+        sync_pair_admin(deps.branch(), pair)?;
+    }
+
+    // pairs created before the factory became pairs admin cannot be updated until they are
+    // migrated with the factory as their admin, so they are reported as skipped
+    let (pairs, skipped): (Vec<_>, Vec<_>) = pairs
+        .into_iter()
+        .partition(|pair| pair.admin.as_ref() == Some(&env.contract.address));
+    let msgs = pairs
+        .iter()
+        .map(|pair| update_pause_msg(&pair.contract_addr, &effective_pause(deps.storage, pair)?))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "update_all_pause")
+        .add_attribute("all_paused", all_paused.to_string())
+        .add_attribute("pairs", msgs.len().to_string());
+    if !skipped.is_empty() {
+        let skipped: Vec<_> = skipped
+            .iter()
+            .map(|pair| pair.contract_addr.as_str())
+            .collect();
+        res = res.add_attribute("skipped_pairs", skipped.join(", "));
+    }
+    if let Some(last_pair) = last_pair {
+        res = res.add_attribute("last_pair", last_pair);
+    }
+    Ok(res.add_messages(msgs))
+}
+
+/// Updates the factory copy of pair info with the admin set on the pair - pairs created before the
+/// factory became pairs admin get it on migration
+fn sync_pair_admin(deps: DepsMut, pair_info: &mut PairInfo) -> StdResult<()> {
+    let admin = query_pair_admin(deps.as_ref(), pair_info.contract_addr.clone())?;
+    if pair_info.admin != admin {
+        pair_info.admin = admin;
+        let pair_key = pair_key(&pair_info.asset_infos, &pair_info.pair_type);
+        PAIRS.save(deps.storage, &pair_key, pair_info)?;
+    }
+    Ok(())
+}
+
+/// Pause state to be set on the pair - everything is paused while `ALL_PAUSED` is set, otherwise
+/// the pause state of the pair itself applies
+fn effective_pause(storage: &dyn Storage, pair_info: &PairInfo) -> StdResult<PauseState> {
+    if ALL_PAUSED.may_load(storage)?.unwrap_or_default() {
+        Ok(PauseState::all())
+    } else {
+        Ok(pair_info.paused.clone())
+    }
+}
+
+/// Pair info as reported by queries, with pause state enforced by the pair itself - it may differ
+/// from `effective_pause` until `PauseAll` or `UnpauseAll` reaches the pair
+fn with_pair_pause(deps: Deps, mut pair_info: PairInfo) -> StdResult<PairInfo> {
+    pair_info.paused = query_pair_pause(deps, pair_info.contract_addr.clone())?;
+    Ok(pair_info)
+}
+
+fn update_pause_msg(pair: &Addr, paused: &PauseState) -> StdResult<WasmMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: pair.to_string(),
+        msg: to_binary(&PairExecuteMsg::UpdatePause {
+            swaps: Some(paused.swaps),
+            deposits: Some(paused.deposits),
+            withdrawals: Some(paused.withdrawals),
+        })?,
+        funds: vec![],
+    })
+}
+
 /// This just stores the result for future query
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> StdResult<Response> {
     let tmp_pair_info = TMP_PAIR_INFO.load(deps.storage)?;
 
     let res: MsgInstantiateContractResponse =
@@ -182,30 +562,49 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
     let pair_contract = deps.api.addr_validate(res.get_contract_address())?;
     let liquidity_token = query_liquidity_token(deps.as_ref(), pair_contract.clone())?;
 
-    PAIRS.save(
-        deps.storage,
-        &tmp_pair_info.pair_key,
-        &PairInfo::new(
-            tmp_pair_info.asset_infos,
-            pair_contract.clone(),
-            liquidity_token.clone(),
-        )
-        .with_commission(tmp_pair_info.commission),
-    )?;
+    let mut pair_info = PairInfo::new(
+        tmp_pair_info.asset_infos,
+        pair_contract.clone(),
+        liquidity_token.clone(),
+    )
+    .with_commission(tmp_pair_info.commission)
+    .with_pair_type(tmp_pair_info.pair_type)
+    .with_admin(env.contract.address);
+    if let Some(whitelist_group) = tmp_pair_info.whitelist_group {
+        pair_info = pair_info.with_whitelist_group(whitelist_group);
+    }
+    PAIRS.save(deps.storage, &tmp_pair_info.pair_key, &pair_info)?;
 
-    Ok(Response::new()
-        .add_attribute("pair_contract_addr", pair_contract)
-        .add_attribute("liquidity_token_addr", liquidity_token))
+    let mut res = Response::new().add_attribute("pair_contract_addr", pair_contract.clone());
+    // concentrated pairs keep liquidity in positions, with no liquidity token
+    if !liquidity_token.as_str().is_empty() {
+        res = res.add_attribute("liquidity_token_addr", liquidity_token);
+    }
+    // pairs created while all pairs are paused start paused too
+    if ALL_PAUSED.may_load(deps.storage)?.unwrap_or_default() {
+        res = res.add_message(update_pause_msg(&pair_contract, &PauseState::all())?);
+    }
+    Ok(res)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps, &env)?),
-        QueryMsg::Pair { asset_infos } => to_binary(&query_pair(deps, asset_infos)?),
-        QueryMsg::Pairs { start_after, limit } => {
-            to_binary(&query_pairs(deps, start_after, limit)?)
-        }
+        QueryMsg::Pair {
+            asset_infos,
+            pair_type,
+        } => to_binary(&query_pair(deps, asset_infos, pair_type)?),
+        QueryMsg::Pairs {
+            start_after,
+            start_after_pair_type,
+            limit,
+        } => to_binary(&query_pairs(
+            deps,
+            start_after,
+            start_after_pair_type,
+            limit,
+        )?),
     }
 }
 
@@ -217,33 +616,74 @@ pub fn query_config(deps: Deps, env: &Env) -> StdResult<ConfigResponse> {
         None => query_migrate_admin(deps, env)?,
     };
 
+    let pair_types = PAIR_TYPES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| Ok(item?.1))
+        .collect::<StdResult<_>>()?;
+
     Ok(ConfigResponse {
         owner: state.owner.into(),
         token_code_id: state.token_code_id,
-        pair_code_id: state.pair_code_id,
-        default_commission: state.default_commission,
         migrate_admin,
+        pair_types,
+        fee_collector: state.fee_collector.map(Into::into),
+        protocol_fee_share: state.protocol_fee_share,
+        trusted_token_code_id: state.trusted_token_code_id,
+        all_paused: ALL_PAUSED.may_load(deps.storage)?.unwrap_or_default(),
     })
 }
 
-pub fn query_pair(deps: Deps, asset_infos: [AssetInfo; 2]) -> StdResult<PairInfo> {
-    let pair_key = pair_key(&asset_infos);
+pub fn query_pair(
+    deps: Deps,
+    asset_infos: Vec<AssetInfo>,
+    pair_type: String,
+) -> StdResult<PairInfo> {
+    let pair_key = pair_key(&asset_infos, &pair_type);
     let pair_info: PairInfo = PAIRS.load(deps.storage, &pair_key)?;
-    Ok(pair_info)
+    with_pair_pause(deps, pair_info)
 }
 
 pub fn query_pairs(
     deps: Deps,
-    start_after: Option<[AssetInfo; 2]>,
+    start_after: Option<Vec<AssetInfo>>,
+    start_after_pair_type: Option<String>,
     limit: Option<u32>,
 ) -> StdResult<PairsResponse> {
-    let pairs: Vec<PairInfo> = read_pairs(deps.storage, start_after, limit)?;
+    let pairs: Vec<PairInfo> = read_pairs(deps.storage, start_after, start_after_pair_type, limit)?
+        .into_iter()
+        .map(|pair_info| with_pair_pause(deps, pair_info))
+        .collect::<StdResult<_>>()?;
     let resp = PairsResponse { pairs };
 
     Ok(resp)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    // Factories from before the pair types registry keep the pair code id and default commission
+    // in the config, they become the `xyk` pair type
+    let legacy = LEGACY_CONFIG.load(deps.storage)?;
+    if let Some(pair_code_id) = legacy.pair_code_id {
+        let mut xyk = PairType::new(XYK_PAIR_TYPE, pair_code_id);
+        if let Some(default_commission) = legacy.default_commission {
+            xyk = xyk.with_default_commission(default_commission);
+        }
+        if !PAIR_TYPES.has(deps.storage, XYK_PAIR_TYPE) {
+            PAIR_TYPES.save(deps.storage, XYK_PAIR_TYPE, &xyk)?;
+        }
+
+        // Drops legacy fields
+        let config = CONFIG.load(deps.storage)?;
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    // Pairs stored under keys of previous versions are moved to current keys, one page per
+    // migration, so factories with many pairs don't run out of gas
+    let last_key = migrate_pair_keys(deps.storage, msg.start_after.map(Binary::into), msg.limit)?;
+
+    let mut res = Response::new().add_attribute("action", "migrate");
+    if let Some(last_key) = last_key {
+        res = res.add_attribute("last_pair_key", Binary::from(last_key).to_base64());
+    }
+    Ok(res)
 }
@@ -8,4 +8,28 @@ pub enum ContractError {
 
     #[error("Invalid commission value: {0}")]
     InvalidCommission(Decimal),
+
+    #[error("Pair requires at least 2 assets")]
+    NotEnoughAssets {},
+
+    #[error("Only stable pairs support more than 2 assets, got {0}")]
+    InvalidAssetCount(usize),
+
+    #[error("Asset is given more than once: {0}")]
+    DuplicatedAsset(String),
+
+    #[error("Unknown pair type: {0}")]
+    UnknownPairType(String),
+
+    #[error("Invalid protocol fee share: {0}")]
+    InvalidProtocolFeeShare(Decimal),
+
+    #[error("Fee collector is required for non-zero protocol fee share")]
+    MissingFeeCollector {},
+
+    #[error("Trusted token code id is required to create whitelisted pair")]
+    MissingTrustedTokenCodeId {},
+
+    #[error("Factory is not admin of the pair")]
+    NotPairAdmin {},
 }
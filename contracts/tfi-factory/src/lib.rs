@@ -3,6 +3,8 @@ pub mod state;
 
 mod error;
 mod querier;
+// Generated by rust-protobuf, newer compilers warn about its code
+#[allow(renamed_and_removed_lints, unused_parens, mismatched_lifetime_syntaxes)]
 mod response;
 
 #[cfg(test)]
@@ -1,7 +1,15 @@
 mod suite;
 
 use anyhow::Error;
-use cosmwasm_std::Empty;
+use cosmwasm_std::{coin, Addr, Decimal, Empty, StdError, Uint128};
+use cw_multi_test::Executor;
+use tfi::asset::{
+    Asset, AssetInfo, PauseState, CONCENTRATED_PAIR_TYPE, STABLE_PAIR_TYPE, WEIGHTED_PAIR_TYPE,
+    XYK_PAIR_TYPE,
+};
+use tfi::factory::ExecuteMsg;
+use tfi::pair::ExecuteMsg as PairExecuteMsg;
+use tfi_pair::contract::Operation;
 
 /// Compares if error is as expected
 ///
@@ -65,6 +73,180 @@ fn everyone_whitelisted() {
         .unwrap();
 }
 
+/// Same flow as `everyone_whitelisted`, but performed on stable pair created by factory
+#[test]
+fn stable_pair() {
+    let mut suite = suite::Config::new()
+        .with_actor("liquidity-provider", 2000, 2000, true)
+        .with_actor("trader", 1000, 1000, true)
+        .init()
+        .unwrap();
+
+    let (cash, lp, trader) = (
+        suite.cash.clone(),
+        suite.actors[0].clone(),
+        suite.actors[1].clone(),
+    );
+
+    let (pair, lt) = suite
+        .create_stable_pair([suite.btc(), suite.cash()], 100)
+        .unwrap();
+
+    suite
+        .add_member(&pair)
+        .unwrap()
+        .increase_allowance(&cash.addr(), &lp, &pair, 2000)
+        .unwrap()
+        .provide_liquidity(&pair, &lp, 2000, 2000)
+        .unwrap()
+        .swap_btc(&pair, &trader, 1000)
+        .unwrap()
+        .swap_cash(&pair, &trader, 1000)
+        .unwrap();
+
+    let share = lt.balance::<_, _, Empty>(&suite.app, &lp).unwrap();
+
+    suite
+        .withdraw_liquidity(&pair, &lt.addr(), &lp, share.into())
+        .unwrap();
+}
+
+/// Same flow as `everyone_whitelisted`, but performed on 20/80 weighted pair created by factory
+#[test]
+fn weighted_pair() {
+    let mut suite = suite::Config::new()
+        .with_actor("liquidity-provider", 2000, 8000, true)
+        .with_actor("trader", 500, 500, true)
+        .init()
+        .unwrap();
+
+    let (cash, lp, trader) = (
+        suite.cash.clone(),
+        suite.actors[0].clone(),
+        suite.actors[1].clone(),
+    );
+
+    let (pair, lt) = suite
+        .create_weighted_pair(
+            [suite.btc(), suite.cash()],
+            [Decimal::percent(20), Decimal::percent(80)],
+        )
+        .unwrap();
+    assert_eq!(
+        suite.pair_info(&pair).unwrap().pair_type,
+        WEIGHTED_PAIR_TYPE
+    );
+
+    suite
+        .add_member(&pair)
+        .unwrap()
+        .increase_allowance(&cash.addr(), &lp, &pair, 8000)
+        .unwrap()
+        .provide_liquidity(&pair, &lp, 2000, 8000)
+        .unwrap()
+        .swap_btc(&pair, &trader, 500)
+        .unwrap()
+        .swap_cash(&pair, &trader, 500)
+        .unwrap();
+
+    let share = lt.balance::<_, _, Empty>(&suite.app, &lp).unwrap();
+
+    suite
+        .withdraw_liquidity(&pair, &lt.addr(), &lp, share.into())
+        .unwrap();
+}
+
+/// Concentrated pair created by factory is registered without liquidity token, and is paused
+/// together with other pairs
+#[test]
+fn concentrated_pair() {
+    let mut suite = suite::Config::new()
+        .with_actor("liquidity-provider", 2000, 2000, true)
+        .with_actor("trader", 1000, 1000, true)
+        .init()
+        .unwrap();
+
+    let (owner, factory, cash, lp, trader) = (
+        suite.owner.clone(),
+        suite.factory.clone(),
+        suite.cash.clone(),
+        suite.actors[0].clone(),
+        suite.actors[1].clone(),
+    );
+
+    let pair = suite
+        .create_concentrated_pair([suite.btc(), suite.cash()], Decimal::one())
+        .unwrap();
+
+    let registered = suite.pairs(None, None).unwrap();
+    assert_eq!(registered.len(), 1);
+    assert_eq!(registered[0].contract_addr, pair);
+    assert_eq!(registered[0].pair_type, CONCENTRATED_PAIR_TYPE);
+    assert_eq!(registered[0].liquidity_token, Addr::unchecked(""));
+    let pair_info = suite.pair_info(&pair).unwrap();
+    assert_eq!(pair_info.pair_type, CONCENTRATED_PAIR_TYPE);
+    assert_eq!(pair_info.admin, Some(factory));
+
+    suite
+        .add_member(&pair)
+        .unwrap()
+        .increase_allowance(&cash.addr(), &lp, &pair, 2000)
+        .unwrap()
+        .create_position(&pair, &lp, -1000, 1000, 2000, 2000)
+        .unwrap()
+        .swap_btc(&pair, &trader, 500)
+        .unwrap()
+        .swap_cash(&pair, &trader, 500)
+        .unwrap();
+
+    suite.pause_all(&owner).unwrap();
+    assert_eq!(suite.pair_info(&pair).unwrap().paused, PauseState::all());
+    let err = suite.swap_btc(&pair, &trader, 500).unwrap_err();
+    assert_error(
+        err,
+        tfi_pair::error::ContractError::Paused(Operation::Swaps),
+    );
+}
+
+/// Pairs of the same assets can be created once per pair type, and are listed separately
+#[test]
+fn same_assets_different_types() {
+    let mut suite = suite::Config::new().init().unwrap();
+
+    let (xyk, _) = suite
+        .create_pair([suite.btc(), suite.cash()], None)
+        .unwrap();
+    let (stable, _) = suite
+        .create_stable_pair([suite.cash(), suite.btc()], 100)
+        .unwrap();
+    assert_ne!(xyk, stable);
+
+    let err = suite
+        .create_stable_pair([suite.btc(), suite.cash()], 100)
+        .unwrap_err();
+    assert_error(err, StdError::generic_err("Pair already exists"));
+
+    let pairs = suite.pairs(None, None).unwrap();
+    let pairs: Vec<_> = pairs
+        .into_iter()
+        .map(|pair| (pair.contract_addr, pair.pair_type))
+        .collect();
+    // Pair types are length prefixed in pair keys, so shorter names are listed first
+    assert_eq!(
+        pairs,
+        vec![
+            (xyk.clone(), XYK_PAIR_TYPE.to_owned()),
+            (stable.clone(), STABLE_PAIR_TYPE.to_owned()),
+        ]
+    );
+
+    let pairs = suite
+        .pairs(Some(([suite.btc(), suite.cash()], XYK_PAIR_TYPE)), None)
+        .unwrap();
+    assert_eq!(pairs.len(), 1);
+    assert_eq!(pairs[0].contract_addr, stable);
+}
+
 /// Failure test showing up, that it is impossible to provide liquidity to pair if it is not part
 /// of the whitelist
 #[test]
@@ -146,3 +328,424 @@ fn actors_not_whitelisted() {
     let err = suite.swap_cash(&pair, &trader, 1000).unwrap_err();
     assert_error(err, trusted_token::error::ContractError::Unauthorized {});
 }
+
+/// Liquidity token of whitelisted pair is trusted token with the pair whitelist, so pool shares
+/// can't be moved to non-whitelisted addresses
+#[test]
+fn whitelisted_liquidity_token() {
+    let mut suite = suite::Config::new()
+        .with_actor("liquidity-provider", 2000, 6000, true)
+        .with_actor("outsider", 1, 1, false)
+        .init()
+        .unwrap();
+
+    let (cash, lp, outsider) = (
+        suite.cash.clone(),
+        suite.actors[0].clone(),
+        suite.actors[1].clone(),
+    );
+
+    let (pair, lt) = suite
+        .create_whitelisted_pair([suite.btc(), suite.cash()])
+        .unwrap();
+
+    let whitelist: trusted_token::msg::WhitelistResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(lt.addr(), &trusted_token::msg::QueryMsg::Whitelist {})
+        .unwrap();
+    assert_eq!(whitelist.address, suite.whitelist.addr().to_string());
+
+    suite
+        .add_member(&pair)
+        .unwrap()
+        .increase_allowance(&cash.addr(), &lp, &pair, 6000)
+        .unwrap()
+        .provide_liquidity(&pair, &lp, 2000, 6000)
+        .unwrap();
+
+    let share = lt.balance::<_, _, Empty>(&suite.app, &lp).unwrap();
+
+    let err = suite
+        .app
+        .execute_contract(
+            lp.clone(),
+            lt.addr(),
+            &cw20::Cw20ExecuteMsg::Transfer {
+                recipient: outsider.to_string(),
+                amount: share,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_error(err, trusted_token::error::ContractError::Unauthorized {});
+
+    suite
+        .withdraw_liquidity(&pair, &lt.addr(), &lp, share.into())
+        .unwrap();
+}
+
+/// Whitelisted pair of native assets is usable without the pair being a member of the whitelist,
+/// as its liquidity token exempts the pair as minter
+#[test]
+fn whitelisted_pair_not_a_member() {
+    let mut suite = suite::Config::new()
+        .with_actor("liquidity-provider", 2000, 1, true)
+        .init()
+        .unwrap();
+
+    let lp = suite.actors[0].clone();
+    let eth = AssetInfo::Native("eth".to_owned());
+    suite
+        .app
+        .init_modules(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &lp, vec![coin(2000, "btc"), coin(2000, "eth")])
+        })
+        .unwrap();
+
+    let (pair, lt) = suite
+        .create_whitelisted_pair([suite.btc(), eth.clone()])
+        .unwrap();
+
+    suite
+        .app
+        .execute_contract(
+            lp.clone(),
+            pair.clone(),
+            &PairExecuteMsg::ProvideLiquidity {
+                assets: vec![
+                    Asset {
+                        info: suite.btc(),
+                        amount: Uint128::new(2000),
+                    },
+                    Asset {
+                        info: eth,
+                        amount: Uint128::new(2000),
+                    },
+                ],
+                slippage_tolerance: None,
+                deadline: None,
+            },
+            &[coin(2000, "btc"), coin(2000, "eth")],
+        )
+        .unwrap();
+
+    // minimum liquidity is locked on the pair
+    let locked = lt.balance::<_, _, Empty>(&suite.app, &pair).unwrap();
+    assert_eq!(locked, Uint128::new(1000));
+
+    let share = lt.balance::<_, _, Empty>(&suite.app, &lp).unwrap();
+    suite
+        .withdraw_liquidity(&pair, &lt.addr(), &lp, share.into())
+        .unwrap();
+    assert_eq!(
+        lt.balance::<_, _, Empty>(&suite.app, &lp).unwrap(),
+        Uint128::zero()
+    );
+}
+
+/// Factory is admin of created pairs, so its owner can pause them all at once, or operations of
+/// single pair separately
+#[test]
+fn pause_pairs() {
+    let mut suite = suite::Config::new()
+        .with_actor("liquidity-provider", 2000, 8000, true)
+        .with_actor("trader", 1000, 1000, true)
+        .init()
+        .unwrap();
+
+    let (owner, cash, lp, trader) = (
+        suite.owner.clone(),
+        suite.cash.clone(),
+        suite.actors[0].clone(),
+        suite.actors[1].clone(),
+    );
+
+    let (pair, lt) = suite
+        .create_pair([suite.btc(), suite.cash()], None)
+        .unwrap();
+    let (stable, _) = suite
+        .create_stable_pair([suite.btc(), suite.cash()], 100)
+        .unwrap();
+
+    suite
+        .add_member(&pair)
+        .unwrap()
+        .add_member(&stable)
+        .unwrap()
+        .increase_allowance(&cash.addr(), &lp, &pair, 6000)
+        .unwrap()
+        .provide_liquidity(&pair, &lp, 1000, 6000)
+        .unwrap();
+
+    let err = suite.pause_all(&trader).unwrap_err();
+    assert_error(err, StdError::generic_err("unauthorized"));
+
+    suite.pause_all(&owner).unwrap();
+
+    // Pairs are paused entirely, which is reported by the factory too
+    assert_eq!(suite.pair_info(&pair).unwrap().paused, PauseState::all());
+    assert_eq!(suite.pair_info(&stable).unwrap().paused, PauseState::all());
+    assert!(suite.config().unwrap().all_paused);
+    let pairs = suite.pairs(None, None).unwrap();
+    assert!(pairs.iter().all(|pair| pair.paused == PauseState::all()));
+
+    let err = suite.swap_btc(&pair, &trader, 1000).unwrap_err();
+    assert_error(
+        err,
+        tfi_pair::error::ContractError::Paused(Operation::Swaps),
+    );
+    let err = suite.swap_cash(&stable, &trader, 1000).unwrap_err();
+    assert_error(
+        err,
+        tfi_pair::error::ContractError::Paused(Operation::Swaps),
+    );
+    let err = suite
+        .increase_allowance(&cash.addr(), &lp, &stable, 2000)
+        .unwrap()
+        .provide_liquidity(&stable, &lp, 1000, 2000)
+        .unwrap_err();
+    assert_error(
+        err,
+        tfi_pair::error::ContractError::Paused(Operation::Deposits),
+    );
+    let err = suite
+        .withdraw_liquidity(&pair, &lt.addr(), &lp, 100)
+        .unwrap_err();
+    assert_error(
+        err,
+        tfi_pair::error::ContractError::Paused(Operation::Withdrawals),
+    );
+
+    // Swaps are paused on single pair, which is applied only after all pairs are unpaused
+    suite
+        .update_pair_pause(&owner, [suite.btc(), suite.cash()], true, None, None)
+        .unwrap();
+    assert_eq!(suite.pair_info(&pair).unwrap().paused, PauseState::all());
+
+    suite
+        .unpause_all(&owner)
+        .unwrap()
+        .withdraw_liquidity(&pair, &lt.addr(), &lp, 100)
+        .unwrap()
+        .provide_liquidity(&stable, &lp, 1000, 2000)
+        .unwrap()
+        .swap_cash(&stable, &trader, 1000)
+        .unwrap();
+    let err = suite.swap_btc(&pair, &trader, 1000).unwrap_err();
+    assert_error(
+        err,
+        tfi_pair::error::ContractError::Paused(Operation::Swaps),
+    );
+    assert_eq!(
+        suite.pair_info(&pair).unwrap().paused,
+        PauseState {
+            swaps: true,
+            ..PauseState::default()
+        }
+    );
+    assert_eq!(
+        suite.pair_info(&stable).unwrap().paused,
+        PauseState::default()
+    );
+    assert!(!suite.config().unwrap().all_paused);
+}
+
+/// Pairs created before the factory became their admin are skipped when all pairs are paused, until
+/// they are migrated with the factory as admin. Skipped pairs are reported in the response.
+#[test]
+fn pause_all_migrated_pair() {
+    let mut suite = suite::Config::new().init().unwrap();
+    let owner = suite.owner.clone();
+
+    let legacy = suite
+        .create_legacy_pair([suite.btc(), suite.cash()])
+        .unwrap();
+    assert_eq!(suite.pair_info(&legacy).unwrap().admin, None);
+
+    let res = suite
+        .app
+        .execute_contract(
+            owner.clone(),
+            suite.factory.clone(),
+            &ExecuteMsg::PauseAll {
+                start_after: None,
+                start_after_pair_type: None,
+                limit: None,
+            },
+            &[],
+        )
+        .unwrap();
+    let wasm = res.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert!(wasm
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "skipped_pairs" && attr.value == legacy.as_str()));
+    assert!(wasm
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "pairs" && attr.value == "0"));
+    assert_eq!(
+        suite.pair_info(&legacy).unwrap().paused,
+        PauseState::default()
+    );
+    let pairs = suite.pairs(None, None).unwrap();
+    assert_eq!(pairs[0].admin, None);
+    assert_eq!(pairs[0].paused, PauseState::default());
+
+    suite
+        .unpause_all(&owner)
+        .unwrap()
+        .migrate_pair_to_factory_admin(&legacy)
+        .unwrap();
+    assert_eq!(
+        suite.pair_info(&legacy).unwrap().admin,
+        Some(suite.factory.clone())
+    );
+
+    suite.pause_all(&owner).unwrap();
+    assert_eq!(suite.pair_info(&legacy).unwrap().paused, PauseState::all());
+    let pairs = suite.pairs(None, None).unwrap();
+    assert_eq!(pairs[0].admin, Some(suite.factory.clone()));
+    assert_eq!(pairs[0].paused, PauseState::all());
+
+    suite.unpause_all(&owner).unwrap();
+    assert_eq!(
+        suite.pair_info(&legacy).unwrap().paused,
+        PauseState::default()
+    );
+}
+
+/// Pausing all pairs can be split into pages, and pairs created while all pairs are paused start
+/// paused
+#[test]
+fn pause_all_paginated() {
+    let mut suite = suite::Config::new()
+        .with_actor("liquidity-provider", 2000, 8000, true)
+        .with_actor("trader", 1000, 1000, true)
+        .init()
+        .unwrap();
+    let (owner, cash, lp, trader) = (
+        suite.owner.clone(),
+        suite.cash.clone(),
+        suite.actors[0].clone(),
+        suite.actors[1].clone(),
+    );
+
+    let (pair, _) = suite
+        .create_pair([suite.btc(), suite.cash()], None)
+        .unwrap();
+    let (stable, _) = suite
+        .create_stable_pair([suite.btc(), suite.cash()], 100)
+        .unwrap();
+
+    let pairs = suite.pairs(None, None).unwrap();
+    let (first, second) = (pairs[0].clone(), pairs[1].clone());
+    suite
+        .add_member(&pair)
+        .unwrap()
+        .add_member(&stable)
+        .unwrap()
+        .increase_allowance(&cash.addr(), &lp, &second.contract_addr, 4000)
+        .unwrap()
+        .provide_liquidity(&second.contract_addr, &lp, 1000, 4000)
+        .unwrap();
+    let first_key = (
+        [first.asset_infos[0].clone(), first.asset_infos[1].clone()],
+        first.pair_type.as_str(),
+    );
+
+    suite.update_all_pause(&owner, true, None, 1).unwrap();
+    assert_eq!(
+        suite.pair_info(&first.contract_addr).unwrap().paused,
+        PauseState::all()
+    );
+    assert_eq!(
+        suite.pair_info(&second.contract_addr).unwrap().paused,
+        PauseState::default()
+    );
+
+    // Until the second page is executed, factory reports pause state enforced by pairs
+    assert!(suite.config().unwrap().all_paused);
+    let pairs = suite.pairs(None, None).unwrap();
+    assert_eq!(pairs[0].paused, PauseState::all());
+    assert_eq!(pairs[1].paused, PauseState::default());
+    suite
+        .swap_cash(&second.contract_addr, &trader, 100)
+        .unwrap();
+
+    suite
+        .update_all_pause(&owner, true, Some(first_key.clone()), 1)
+        .unwrap();
+    assert_eq!(
+        suite.pair_info(&second.contract_addr).unwrap().paused,
+        PauseState::all()
+    );
+
+    let (weighted, _) = suite
+        .create_weighted_pair(
+            [suite.btc(), suite.cash()],
+            [Decimal::percent(50), Decimal::percent(50)],
+        )
+        .unwrap();
+    assert_eq!(
+        suite.pair_info(&weighted).unwrap().paused,
+        PauseState::all()
+    );
+
+    suite
+        .update_all_pause(&owner, false, None, 1)
+        .unwrap()
+        .update_all_pause(&owner, false, Some(first_key), None)
+        .unwrap();
+    for pair in [pair, stable, weighted] {
+        assert_eq!(
+            suite.pair_info(&pair).unwrap().paused,
+            PauseState::default()
+        );
+    }
+}
+
+/// Factory owner can update commission of existing pair, which is reflected both on the pair and
+/// in the factory
+#[test]
+fn update_pair_commission() {
+    let mut suite = suite::Config::new()
+        .with_actor("trader", 1000, 1000, true)
+        .init()
+        .unwrap();
+
+    let (owner, trader) = (suite.owner.clone(), suite.actors[0].clone());
+    let asset_infos = [suite.btc(), suite.cash()];
+
+    let (pair, _) = suite.create_pair(asset_infos.clone(), None).unwrap();
+    assert_eq!(
+        suite.pair_info(&pair).unwrap().commission,
+        Decimal::permille(3)
+    );
+
+    let err = suite
+        .update_pair_commission(&trader, asset_infos.clone(), Decimal::percent(1))
+        .unwrap_err();
+    assert_error(err, StdError::generic_err("unauthorized"));
+
+    let err = suite
+        .update_pair_commission(&owner, asset_infos.clone(), Decimal::percent(101))
+        .unwrap_err();
+    assert_error(
+        err,
+        crate::error::ContractError::InvalidCommission(Decimal::percent(101)),
+    );
+
+    suite
+        .update_pair_commission(&owner, asset_infos, Decimal::percent(1))
+        .unwrap();
+    assert_eq!(
+        suite.pair_info(&pair).unwrap().commission,
+        Decimal::percent(1)
+    );
+    let pairs = suite.pairs(None, None).unwrap();
+    assert_eq!(pairs[0].commission, Decimal::percent(1));
+}
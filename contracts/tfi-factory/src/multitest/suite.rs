@@ -4,13 +4,24 @@ use cosmwasm_std::{coins, to_binary, Addr, BankMsg, Decimal, Empty, Uint128};
 use cw20::{Cw20Coin, Cw20Contract, Cw20ExecuteMsg};
 use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper, Executor};
 use derivative::Derivative;
-use tfi::asset::{Asset, AssetInfo, PairInfo};
-use tfi::factory::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use tfi::pair::{Cw20HookMsg, ExecuteMsg as PairExecuteMsg};
+use tfi::asset::{
+    Asset, AssetInfo, PairInfo, CONCENTRATED_PAIR_TYPE, STABLE_PAIR_TYPE, WEIGHTED_PAIR_TYPE,
+    XYK_PAIR_TYPE,
+};
+use tfi::concentrated::ExecuteMsg as ConcentratedExecuteMsg;
+use tfi::factory::{
+    ConfigResponse, ExecuteCreatePair, ExecuteMsg, InstantiateMsg, PairType, PairsResponse,
+    QueryMsg,
+};
+use tfi::pair::{
+    Cw20HookMsg, ExecuteMsg as PairExecuteMsg, InstantiateMsg as PairInstantiateMsg,
+    MigrateMsg as PairMigrateMsg, QueryMsg as PairQueryMsg,
+};
 use tg4::{Member, Tg4Contract};
 use tg4_group::msg::ExecuteMsg as Tg4ExecuteMsg;
 
 const TEST_RESERVE: &str = "reserve";
+const LEGACY_PAIR_TYPE: &str = "legacy";
 const DENOM: &str = "btc";
 
 fn mock_app() -> App {
@@ -46,10 +57,56 @@ fn contract_pair() -> Box<dyn Contract<Empty>> {
             tfi_pair::contract::instantiate,
             tfi_pair::contract::query,
         )
+        .with_reply(tfi_pair::contract::reply)
+        .with_migrate(tfi_pair::contract::migrate),
+    )
+}
+
+/// Pair as instantiated before the factory became pairs admin
+fn contract_legacy_pair() -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new(
+            tfi_pair::contract::execute,
+            |deps, env, info, mut msg: PairInstantiateMsg| {
+                msg.admin = None;
+                tfi_pair::contract::instantiate(deps, env, info, msg)
+            },
+            tfi_pair::contract::query,
+        )
         .with_reply(tfi_pair::contract::reply),
     )
 }
 
+fn contract_stable_pair() -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new(
+            tfi_stable_pair::contract::execute,
+            tfi_stable_pair::contract::instantiate,
+            tfi_stable_pair::contract::query,
+        )
+        .with_reply(tfi_stable_pair::contract::reply),
+    )
+}
+
+fn contract_weighted_pair() -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new(
+            tfi_weighted_pair::contract::execute,
+            tfi_weighted_pair::contract::instantiate,
+            tfi_weighted_pair::contract::query,
+        )
+        .with_reply(tfi_weighted_pair::contract::reply),
+    )
+}
+
+fn contract_concentrated_pair() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        tfi_concentrated_pair::contract::execute,
+        tfi_concentrated_pair::contract::instantiate,
+        tfi_concentrated_pair::contract::query,
+    ))
+}
+
 fn contract_cw20() -> Box<dyn Contract<Empty>> {
     Box::new(ContractWrapper::new(
         cw20_base::contract::execute,
@@ -99,6 +156,8 @@ pub struct Suite {
     pub cash: Cw20Contract,
     /// tfi-factory contract address
     pub factory: Addr,
+    /// tfi-pair code id
+    pub pair_code_id: u64,
 }
 
 impl Suite {
@@ -115,33 +174,296 @@ impl Suite {
         AssetInfo::Token(self.cash.addr())
     }
 
-    /// Executes CreatePair on `factory`. Returns created pair address and its liquidity token
-    /// address.
+    /// Executes CreatePair on `factory` for `xyk` pair type. Returns created pair address and its
+    /// liquidity token address.
     pub fn create_pair(
         &mut self,
         asset_infos: [AssetInfo; 2],
         commission: impl Into<Option<Decimal>>,
+    ) -> Result<(Addr, Cw20Contract)> {
+        self.create_pair_of_type(XYK_PAIR_TYPE, asset_infos, commission.into(), None)
+    }
+
+    /// Executes CreatePair on `factory` for `stable` pair type. Returns created pair address and
+    /// its liquidity token address.
+    pub fn create_stable_pair(
+        &mut self,
+        asset_infos: [AssetInfo; 2],
+        amp: u64,
+    ) -> Result<(Addr, Cw20Contract)> {
+        self.create_pair_of_type(STABLE_PAIR_TYPE, asset_infos, None, amp)
+    }
+
+    /// Executes CreatePair on `factory` for any pair type. Returns created pair address and its
+    /// liquidity token address.
+    pub fn create_pair_of_type(
+        &mut self,
+        pair_type: &str,
+        asset_infos: [AssetInfo; 2],
+        commission: impl Into<Option<Decimal>>,
+        amp: impl Into<Option<u64>>,
+    ) -> Result<(Addr, Cw20Contract)> {
+        self.create_pair_with_whitelist(pair_type, asset_infos, commission, amp, None, None)
+    }
+
+    /// Executes CreatePair on `factory` for `weighted` pair type. Returns created pair address
+    /// and its liquidity token address.
+    pub fn create_weighted_pair(
+        &mut self,
+        asset_infos: [AssetInfo; 2],
+        weights: [Decimal; 2],
+    ) -> Result<(Addr, Cw20Contract)> {
+        self.create_pair_with_whitelist(WEIGHTED_PAIR_TYPE, asset_infos, None, None, weights, None)
+    }
+
+    /// Executes CreatePair on `factory` for `concentrated` pair type. Returns created pair address,
+    /// as concentrated pairs have no liquidity token.
+    pub fn create_concentrated_pair(
+        &mut self,
+        asset_infos: [AssetInfo; 2],
+        initial_price: Decimal,
+    ) -> Result<Addr> {
+        let msg: ExecuteMsg = ExecuteCreatePair::new(asset_infos.clone())
+            .with_pair_type(CONCENTRATED_PAIR_TYPE)
+            .with_initial_price(initial_price)
+            .into();
+        self.app
+            .execute_contract(self.owner.clone(), self.factory.clone(), &msg, &[])
+            .map_err(|err| anyhow!(err))?;
+
+        let res: PairInfo = self.app.wrap().query_wasm_smart(
+            self.factory.clone(),
+            &QueryMsg::Pair {
+                asset_infos: asset_infos.to_vec(),
+                pair_type: CONCENTRATED_PAIR_TYPE.to_owned(),
+            },
+        )?;
+
+        Ok(res.contract_addr)
+    }
+
+    /// Executes CreatePair on `factory` for `xyk` pair type whitelisted by suite whitelist.
+    /// Returns created pair address and its liquidity token address.
+    pub fn create_whitelisted_pair(
+        &mut self,
+        asset_infos: [AssetInfo; 2],
+    ) -> Result<(Addr, Cw20Contract)> {
+        let whitelist = self.whitelist.addr().to_string();
+        self.create_pair_with_whitelist(XYK_PAIR_TYPE, asset_infos, None, None, None, whitelist)
+    }
+
+    /// Executes CreatePair on `factory` for pair type with pairs instantiated without admin, as
+    /// before the factory became pairs admin. Returns created pair address.
+    pub fn create_legacy_pair(&mut self, asset_infos: [AssetInfo; 2]) -> Result<Addr> {
+        let code_id = self.app.store_code(contract_legacy_pair());
+        self.app
+            .execute_contract(
+                self.owner.clone(),
+                self.factory.clone(),
+                &ExecuteMsg::UpdatePairType {
+                    pair_type: PairType::new(LEGACY_PAIR_TYPE, code_id),
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        let (pair, _) = self.create_pair_of_type(LEGACY_PAIR_TYPE, asset_infos, None, None)?;
+        Ok(pair)
+    }
+
+    /// Migrates pair to current tfi-pair code, making the factory its admin
+    pub fn migrate_pair_to_factory_admin(&mut self, pair: &Addr) -> Result<&mut Self> {
+        self.app
+            .migrate_contract(
+                self.owner.clone(),
+                pair.clone(),
+                &PairMigrateMsg {
+                    admin: Some(self.factory.to_string()),
+                },
+                self.pair_code_id,
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    fn create_pair_with_whitelist(
+        &mut self,
+        pair_type: &str,
+        asset_infos: [AssetInfo; 2],
+        commission: impl Into<Option<Decimal>>,
+        amp: impl Into<Option<u64>>,
+        weights: impl Into<Option<[Decimal; 2]>>,
+        whitelist_group: impl Into<Option<String>>,
     ) -> Result<(Addr, Cw20Contract)> {
         self.app
             .execute_contract(
                 self.owner.clone(),
                 self.factory.clone(),
                 &ExecuteMsg::CreatePair {
-                    asset_infos: asset_infos.clone(),
+                    pair_type: pair_type.to_owned(),
+                    asset_infos: asset_infos.to_vec(),
                     commission: commission.into(),
+                    amp: amp.into(),
+                    weights: weights.into().map(Vec::from),
+                    initial_price: None,
+                    tick_spacing: None,
+                    whitelist_group: whitelist_group.into(),
                 },
                 &[],
             )
             .map_err(|err| anyhow!(err))?;
 
-        let res: PairInfo = self
-            .app
-            .wrap()
-            .query_wasm_smart(self.factory.clone(), &QueryMsg::Pair { asset_infos })?;
+        let res: PairInfo = self.app.wrap().query_wasm_smart(
+            self.factory.clone(),
+            &QueryMsg::Pair {
+                asset_infos: asset_infos.to_vec(),
+                pair_type: pair_type.to_owned(),
+            },
+        )?;
 
         Ok((res.contract_addr, Cw20Contract(res.liquidity_token)))
     }
 
+    /// Queries factory for registered pairs
+    pub fn pairs(
+        &self,
+        start_after: Option<([AssetInfo; 2], &str)>,
+        limit: impl Into<Option<u32>>,
+    ) -> Result<Vec<PairInfo>> {
+        let (start_after, start_after_pair_type) = match start_after {
+            Some((asset_infos, pair_type)) => {
+                (Some(asset_infos.to_vec()), Some(pair_type.to_owned()))
+            }
+            None => (None, None),
+        };
+
+        let res: PairsResponse = self.app.wrap().query_wasm_smart(
+            self.factory.clone(),
+            &QueryMsg::Pairs {
+                start_after,
+                start_after_pair_type,
+                limit: limit.into(),
+            },
+        )?;
+
+        Ok(res.pairs)
+    }
+
+    /// Queries pair info from the pair itself
+    pub fn pair_info(&self, pair: &Addr) -> Result<PairInfo> {
+        self.app
+            .wrap()
+            .query_wasm_smart(pair.clone(), &PairQueryMsg::Pair {})
+            .map_err(|err| anyhow!(err))
+    }
+
+    /// Executes UpdatePairPause on `factory` for `xyk` pair
+    pub fn update_pair_pause(
+        &mut self,
+        sender: &Addr,
+        asset_infos: [AssetInfo; 2],
+        swaps: impl Into<Option<bool>>,
+        deposits: impl Into<Option<bool>>,
+        withdrawals: impl Into<Option<bool>>,
+    ) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                sender.clone(),
+                self.factory.clone(),
+                &ExecuteMsg::UpdatePairPause {
+                    asset_infos: asset_infos.to_vec(),
+                    pair_type: XYK_PAIR_TYPE.to_owned(),
+                    swaps: swaps.into(),
+                    deposits: deposits.into(),
+                    withdrawals: withdrawals.into(),
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    /// Executes UpdatePairCommission on `factory` for `xyk` pair
+    pub fn update_pair_commission(
+        &mut self,
+        sender: &Addr,
+        asset_infos: [AssetInfo; 2],
+        commission: Decimal,
+    ) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                sender.clone(),
+                self.factory.clone(),
+                &ExecuteMsg::UpdatePairCommission {
+                    asset_infos: asset_infos.to_vec(),
+                    pair_type: XYK_PAIR_TYPE.to_owned(),
+                    commission,
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    /// Executes PauseAll on `factory` for all pairs
+    pub fn pause_all(&mut self, sender: &Addr) -> Result<&mut Self> {
+        self.update_all_pause(sender, true, None, None)
+    }
+
+    /// Executes UnpauseAll on `factory` for all pairs
+    pub fn unpause_all(&mut self, sender: &Addr) -> Result<&mut Self> {
+        self.update_all_pause(sender, false, None, None)
+    }
+
+    /// Executes PauseAll (or UnpauseAll if `paused` is false) on `factory` for single page of
+    /// pairs
+    pub fn update_all_pause(
+        &mut self,
+        sender: &Addr,
+        paused: bool,
+        start_after: Option<([AssetInfo; 2], &str)>,
+        limit: impl Into<Option<u32>>,
+    ) -> Result<&mut Self> {
+        let (start_after, start_after_pair_type) = match start_after {
+            Some((asset_infos, pair_type)) => {
+                (Some(asset_infos.to_vec()), Some(pair_type.to_owned()))
+            }
+            None => (None, None),
+        };
+        let limit = limit.into();
+
+        let msg = if paused {
+            ExecuteMsg::PauseAll {
+                start_after,
+                start_after_pair_type,
+                limit,
+            }
+        } else {
+            ExecuteMsg::UnpauseAll {
+                start_after,
+                start_after_pair_type,
+                limit,
+            }
+        };
+
+        self.app
+            .execute_contract(sender.clone(), self.factory.clone(), &msg, &[])
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    /// Queries factory config
+    pub fn config(&self) -> Result<ConfigResponse> {
+        Ok(self
+            .app
+            .wrap()
+            .query_wasm_smart(self.factory.clone(), &QueryMsg::Config {})?)
+    }
+
     /// Adds member to whitelist
     pub fn add_member(&mut self, addr: &Addr) -> Result<&mut Self> {
         self.app
@@ -187,6 +509,42 @@ impl Suite {
         Ok(self)
     }
 
+    /// Opens position on given concentrated pair, cash allowance has to be increased first
+    pub fn create_position(
+        &mut self,
+        pair: &Addr,
+        owner: &Addr,
+        lower_tick: i32,
+        upper_tick: i32,
+        btc: u128,
+        cash: u128,
+    ) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                owner.clone(),
+                pair.clone(),
+                &ConcentratedExecuteMsg::CreatePosition {
+                    lower_tick,
+                    upper_tick,
+                    assets: vec![
+                        Asset {
+                            info: self.btc(),
+                            amount: btc.into(),
+                        },
+                        Asset {
+                            info: self.cash(),
+                            amount: cash.into(),
+                        },
+                    ],
+                    deadline: None,
+                },
+                &coins(btc, "btc"),
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
     /// Provides liquidity on given pair
     pub fn provide_liquidity(
         &mut self,
@@ -200,7 +558,7 @@ impl Suite {
                 liquidity_provider.clone(),
                 pair.clone(),
                 &PairExecuteMsg::ProvideLiquidity {
-                    assets: [
+                    assets: vec![
                         Asset {
                             info: self.btc(),
                             amount: btc.into(),
@@ -211,6 +569,7 @@ impl Suite {
                         },
                     ],
                     slippage_tolerance: None,
+                    deadline: None,
                 },
                 &coins(btc, "btc"),
             )
@@ -229,9 +588,13 @@ impl Suite {
                         info: AssetInfo::Native("btc".to_owned()),
                         amount: Uint128::new(btc),
                     },
+                    ask_asset_info: None,
                     belief_price: None,
                     max_spread: None,
                     to: None,
+                    min_return: None,
+                    deadline: None,
+                    to_msg: None,
                 },
                 &coins(btc, "btc"),
             )
@@ -250,9 +613,13 @@ impl Suite {
                     contract: pair.to_string(),
                     amount: Uint128::new(cash),
                     msg: to_binary(&Cw20HookMsg::Swap {
+                        ask_asset_info: None,
                         belief_price: None,
                         max_spread: None,
                         to: None,
+                        min_return: None,
+                        deadline: None,
+                        to_msg: None,
                     })
                     .unwrap(),
                 },
@@ -278,7 +645,7 @@ impl Suite {
                 &cw20_base::msg::ExecuteMsg::Send {
                     contract: pair.to_string(),
                     amount: amount.into(),
-                    msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {}).unwrap(),
+                    msg: to_binary(&Cw20HookMsg::WithdrawLiquidity { deadline: None }).unwrap(),
                 },
                 &[],
             )
@@ -425,6 +792,7 @@ impl Config {
                 mint: None,
                 marketing: None,
                 whitelist_group: whitelist.to_string(),
+                exempt_minter: false,
             },
             &[],
             "Cash",
@@ -434,21 +802,31 @@ impl Config {
         .map_err(|err| anyhow!(err))
     }
 
-    /// Initializes factory contract
+    /// Initializes factory contract, with `pair_id` registered as `xyk` pair type
     fn init_factory(
         pair_id: u64,
+        pair_types: Vec<PairType>,
         cw20_id: u64,
+        token_id: u64,
         app: &mut App,
         owner: &Addr,
         factory_id: u64,
     ) -> Result<Addr> {
+        let msg = pair_types
+            .into_iter()
+            .fold(InstantiateMsg::new(pair_id, cw20_id), |msg, pair_type| {
+                msg.with_pair_type(pair_type)
+            })
+            .with_trusted_token_code_id(token_id);
+
+        // owner is the factory migrate admin, so it can migrate created pairs
         app.instantiate_contract(
             factory_id,
             owner.clone(),
-            &InstantiateMsg::new(pair_id, cw20_id),
+            &msg,
             &[],
             "Factory",
-            None,
+            Some(owner.to_string()),
         )
         .map_err(|err| anyhow!(err))
     }
@@ -460,6 +838,9 @@ impl Config {
         let cw20_id = app.store_code(contract_cw20());
         let token_id = app.store_code(contract_token());
         let pair_id = app.store_code(contract_pair());
+        let stable_pair_id = app.store_code(contract_stable_pair());
+        let weighted_pair_id = app.store_code(contract_weighted_pair());
+        let concentrated_pair_id = app.store_code(contract_concentrated_pair());
         let factory_id = app.store_code(contract_factory());
 
         let (actors, initial_cash) = Self::init_actors(self.actors, &mut app)?;
@@ -470,7 +851,19 @@ impl Config {
         let whitelist = Self::init_whitelist(members, &mut app, &owner, tg4_id)?;
         let actors = actors.into_iter().map(|actor| actor.addr).collect();
         let cash = Self::init_cash(initial_cash, &whitelist.addr(), &mut app, &owner, token_id)?;
-        let factory = Self::init_factory(pair_id, cw20_id, &mut app, &owner, factory_id)?;
+        let factory = Self::init_factory(
+            pair_id,
+            vec![
+                PairType::new(STABLE_PAIR_TYPE, stable_pair_id),
+                PairType::new(WEIGHTED_PAIR_TYPE, weighted_pair_id),
+                PairType::new(CONCENTRATED_PAIR_TYPE, concentrated_pair_id),
+            ],
+            cw20_id,
+            token_id,
+            &mut app,
+            &owner,
+            factory_id,
+        )?;
 
         Ok(Suite {
             app,
@@ -479,6 +872,7 @@ impl Config {
             whitelist,
             cash,
             factory,
+            pair_code_id: pair_id,
         })
     }
 }
@@ -1,16 +1,28 @@
 use cosmwasm_std::{
     Addr, Binary, ContractInfoResponse, Deps, Env, QueryRequest, StdResult, WasmQuery,
 };
-use tfi::asset::PairInfo;
+use tfi::asset::{PairInfo, PauseState};
 
-pub fn query_liquidity_token(deps: Deps, contract_addr: Addr) -> StdResult<Addr> {
+fn query_pair_info(deps: Deps, contract_addr: Addr) -> StdResult<PairInfo> {
     // load pair_info form the pair contract
-    let pair_info: PairInfo = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Raw {
+    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Raw {
         contract_addr: contract_addr.to_string(),
         key: Binary::from("pair_info".as_bytes()),
-    }))?;
+    }))
+}
+
+pub fn query_liquidity_token(deps: Deps, contract_addr: Addr) -> StdResult<Addr> {
+    Ok(query_pair_info(deps, contract_addr)?.liquidity_token)
+}
+
+/// Admin of the pair as known by the pair itself, which changes on pair migration
+pub fn query_pair_admin(deps: Deps, contract_addr: Addr) -> StdResult<Option<Addr>> {
+    Ok(query_pair_info(deps, contract_addr)?.admin)
+}
 
-    Ok(pair_info.liquidity_token)
+/// Pause state enforced by the pair itself
+pub fn query_pair_pause(deps: Deps, contract_addr: Addr) -> StdResult<PauseState> {
+    Ok(query_pair_info(deps, contract_addr)?.paused)
 }
 
 pub fn query_migrate_admin(deps: Deps, env: &Env) -> StdResult<Option<String>> {
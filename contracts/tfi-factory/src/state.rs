@@ -4,21 +4,39 @@ use serde::{Deserialize, Serialize};
 use crate::querier::query_migrate_admin;
 use cosmwasm_std::{Addr, Decimal, DepsMut, Env, Order, StdResult, Storage};
 use cw_storage_plus::{Bound, Item, Map};
-use tfi::asset::{AssetInfo, PairInfo};
+use tfi::asset::{AssetInfo, PairInfo, XYK_PAIR_TYPE};
+use tfi::factory::PairType;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub owner: Addr,
-    pub pair_code_id: u64,
     pub token_code_id: u64,
-    pub default_commission: Decimal,
     // Our migrate admin (part of wasm's `ContractInfo`).
     // Stored here for convenience (used during instantiation of pair contracts)
     pub migrate_admin: Option<Option<String>>,
+    /// Protocol fee settings passed to newly created pairs
+    #[serde(default)]
+    pub fee_collector: Option<Addr>,
+    #[serde(default)]
+    pub protocol_fee_share: Decimal,
+    /// `trusted-token` code ID used for liquidity tokens of whitelisted pairs
+    #[serde(default)]
+    pub trusted_token_code_id: Option<u64>,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
 
+/// Config fields of factories from before the pair types registry, moved to `xyk` pair type
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
+    pub pair_code_id: Option<u64>,
+    pub default_commission: Option<Decimal>,
+}
+
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
+/// Registered pair types by their names
+pub const PAIR_TYPES: Map<&str, PairType> = Map::new("pair_types");
+
 pub fn load_update_config(deps: DepsMut, env: &Env) -> StdResult<Config> {
     let mut config: Config = CONFIG.load(deps.storage)?;
 
@@ -34,18 +52,37 @@ pub fn load_update_config(deps: DepsMut, env: &Env) -> StdResult<Config> {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct TmpPairInfo {
     pub pair_key: Vec<u8>,
-    pub asset_infos: [AssetInfo; 2],
+    pub asset_infos: Vec<AssetInfo>,
     pub commission: Decimal,
+    pub pair_type: String,
+    #[serde(default)]
+    pub whitelist_group: Option<Addr>,
 }
 
+/// Set by `PauseAll` and cleared by `UnpauseAll`. While set, pairs reached by `PauseAll` pages and
+/// pairs created since are paused entirely, regardless of their own pause state kept in `PAIRS`.
+pub const ALL_PAUSED: Item<bool> = Item::new("all_paused");
+
 pub const TMP_PAIR_INFO: Item<TmpPairInfo> = Item::new("tmp_pair_info");
 pub const PAIRS: Map<&[u8], PairInfo> = Map::new("pair_info");
 
-pub fn pair_key(asset_infos: &[AssetInfo; 2]) -> Vec<u8> {
+/// Key of the pair in `PAIRS` - sorted asset infos followed by the pair type, so the same assets
+/// can be pooled once per every pair type. Every part is prefixed with its length, so different
+/// assets and types never produce the same key.
+pub fn pair_key(asset_infos: &[AssetInfo], pair_type: &str) -> Vec<u8> {
     let mut asset_infos = asset_infos.to_vec();
     asset_infos.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
 
-    [asset_infos[0].as_bytes(), asset_infos[1].as_bytes()].concat()
+    let mut key = vec![];
+    for part in asset_infos
+        .iter()
+        .map(AssetInfo::as_bytes)
+        .chain(std::iter::once(pair_type.as_bytes()))
+    {
+        key.extend_from_slice(&(part.len() as u16).to_be_bytes());
+        key.extend_from_slice(part);
+    }
+    key
 }
 
 // settings for pagination
@@ -54,11 +91,12 @@ const DEFAULT_LIMIT: u32 = 10;
 
 pub fn read_pairs(
     storage: &dyn Storage,
-    start_after: Option<[AssetInfo; 2]>,
+    start_after: Option<Vec<AssetInfo>>,
+    start_after_pair_type: Option<String>,
     limit: Option<u32>,
 ) -> StdResult<Vec<PairInfo>> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start = calc_range_start(start_after).map(Bound::ExclusiveRaw);
+    let start = calc_range_start(start_after, start_after_pair_type).map(Bound::ExclusiveRaw);
 
     PAIRS
         .range(storage, start, None, Order::Ascending)
@@ -67,16 +105,40 @@ pub fn read_pairs(
         .collect()
 }
 
+/// Moves single page of up to `limit` pairs after raw key `start_after` from keys of previous
+/// versions to current keys. Returns the last visited key, if any - pairs moved to keys further
+/// in the map are visited again, but they are kept as they are.
+pub fn migrate_pair_keys(
+    storage: &mut dyn Storage,
+    start_after: Option<Vec<u8>>,
+    limit: Option<u32>,
+) -> StdResult<Option<Vec<u8>>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::ExclusiveRaw);
+
+    let pairs = PAIRS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let last_key = pairs.last().map(|(key, _)| key.clone());
+    for (key, pair_info) in pairs {
+        let new_key = pair_key(&pair_info.asset_infos, &pair_info.pair_type);
+        if key != new_key {
+            PAIRS.remove(storage, &key);
+            PAIRS.save(storage, &new_key, &pair_info)?;
+        }
+    }
+    Ok(last_key)
+}
+
 // this will set the first key after the provided key, by appending a 1 byte
-fn calc_range_start(start_after: Option<[AssetInfo; 2]>) -> Option<Vec<u8>> {
+fn calc_range_start(
+    start_after: Option<Vec<AssetInfo>>,
+    pair_type: Option<String>,
+) -> Option<Vec<u8>> {
     start_after.map(|asset_infos| {
-        let mut asset_infos = asset_infos.to_vec();
-        asset_infos.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
-
-        let mut v = [asset_infos[0].as_bytes(), asset_infos[1].as_bytes()]
-            .concat()
-            .as_slice()
-            .to_vec();
+        let pair_type = pair_type.as_deref().unwrap_or(XYK_PAIR_TYPE);
+        let mut v = pair_key(&asset_infos, pair_type);
         v.push(1);
         v
     })
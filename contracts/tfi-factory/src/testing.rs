@@ -1,23 +1,27 @@
-use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Addr, Decimal, Reply, ReplyOn, StdError, SubMsg, SubMsgResponse,
-    SubMsgResult, WasmMsg,
+    attr, from_binary, from_slice, to_binary, Addr, Binary, Decimal, Reply, ReplyOn, StdError,
+    Storage, SubMsg, SubMsgResponse, SubMsgResult, WasmMsg,
 };
 
-use tfi::asset::{AssetInfo, PairInfo};
-use tfi::factory::{ConfigResponse, ExecuteCreatePair, ExecuteMsg, InstantiateMsg, QueryMsg};
+use tfi::asset::{AssetInfo, PairInfo, STABLE_PAIR_TYPE, XYK_PAIR_TYPE};
+use tfi::factory::{
+    ConfigResponse, ExecuteCreatePair, ExecuteMsg, ExecuteUpdateConfig, InstantiateMsg, MigrateMsg,
+    PairType, QueryMsg,
+};
 use tfi::pair::InstantiateMsg as PairInstantiateMsg;
 
-use crate::contract::{execute, instantiate, query, reply};
+use crate::contract::{execute, instantiate, migrate, query, reply};
 use crate::error::ContractError;
 use crate::mock_querier::{mock_dependencies, FACTORY_ADMIN};
-use crate::state::{pair_key, TmpPairInfo, TMP_PAIR_INFO};
+use crate::state::{pair_key, TmpPairInfo, PAIRS, TMP_PAIR_INFO};
 
 #[test]
 fn proper_initialization() {
     let mut deps = mock_dependencies(&[]);
 
-    let msg = InstantiateMsg::new(321u64, 123u64);
+    let msg =
+        InstantiateMsg::new(321u64, 123u64).with_pair_type(PairType::new(STABLE_PAIR_TYPE, 456u64));
 
     let info = mock_info("addr0000", &[]);
 
@@ -27,7 +31,13 @@ fn proper_initialization() {
     let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config_res: ConfigResponse = from_binary(&query_res).unwrap();
     assert_eq!(123u64, config_res.token_code_id);
-    assert_eq!(321u64, config_res.pair_code_id);
+    assert_eq!(
+        config_res.pair_types,
+        vec![
+            PairType::new(STABLE_PAIR_TYPE, 456u64),
+            PairType::new(XYK_PAIR_TYPE, 321u64),
+        ]
+    );
     assert_eq!("addr0000".to_string(), config_res.owner);
 }
 
@@ -46,9 +56,10 @@ fn update_config() {
     let info = mock_info("addr0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
         owner: Some("addr0001".to_string()),
-        pair_code_id: None,
         token_code_id: None,
-        default_commission: None,
+        fee_collector: None,
+        protocol_fee_share: None,
+        trusted_token_code_id: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -58,18 +69,17 @@ fn update_config() {
     let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config_res: ConfigResponse = from_binary(&query_res).unwrap();
     assert_eq!(123u64, config_res.token_code_id);
-    assert_eq!(321u64, config_res.pair_code_id);
     assert_eq!("addr0001".to_string(), config_res.owner);
-    assert_eq!(Decimal::permille(3), config_res.default_commission);
 
     // update ids
     let env = mock_env();
     let info = mock_info("addr0001", &[]);
     let msg = ExecuteMsg::UpdateConfig {
         owner: None,
-        pair_code_id: Some(100u64),
         token_code_id: Some(200u64),
-        default_commission: None,
+        fee_collector: None,
+        protocol_fee_share: None,
+        trusted_token_code_id: None,
     };
 
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
@@ -79,42 +89,156 @@ fn update_config() {
     let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config_res: ConfigResponse = from_binary(&query_res).unwrap();
     assert_eq!(200u64, config_res.token_code_id);
-    assert_eq!(100u64, config_res.pair_code_id);
     assert_eq!("addr0001".to_string(), config_res.owner);
-    assert_eq!(Decimal::permille(3), config_res.default_commission);
+    assert_eq!(
+        config_res.pair_types,
+        vec![PairType::new(XYK_PAIR_TYPE, 321u64)]
+    );
 
-    // update default commission
+    // Unauthorized err
     let env = mock_env();
-    let info = mock_info("addr0001", &[]);
+    let info = mock_info("addr0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
         owner: None,
-        pair_code_id: None,
         token_code_id: None,
-        default_commission: Some(Decimal::permille(5)),
+        fee_collector: None,
+        protocol_fee_share: None,
+        trusted_token_code_id: None,
     };
 
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    assert_eq!(0, res.messages.len());
+    let res = execute(deps.as_mut(), env, info, msg);
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "unauthorized")
+        }
+        _ => panic!("Must return unauthorized error"),
+    }
+}
+
+#[test]
+fn update_protocol_fee() {
+    let mut deps = mock_dependencies(&[]);
+
+    // share without collector
+    let mut msg = InstantiateMsg::new(321u64, 123u64);
+    msg.protocol_fee_share = Decimal::percent(10);
+    let err = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::MissingFeeCollector {});
+
+    let msg =
+        InstantiateMsg::new(321u64, 123u64).with_protocol_fee("collector", Decimal::percent(10));
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
 
-    // it worked, let's query the state
     let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config_res: ConfigResponse = from_binary(&query_res).unwrap();
-    assert_eq!(200u64, config_res.token_code_id);
-    assert_eq!(100u64, config_res.pair_code_id);
-    assert_eq!("addr0001".to_string(), config_res.owner);
-    assert_eq!(Decimal::permille(5), config_res.default_commission);
+    assert_eq!(config_res.fee_collector, Some("collector".to_owned()));
+    assert_eq!(config_res.protocol_fee_share, Decimal::percent(10));
+
+    // update only share
+    let msg = ExecuteUpdateConfig::new()
+        .with_protocol_fee_share(Decimal::percent(20))
+        .into();
+    execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!(config_res.fee_collector, Some("collector".to_owned()));
+    assert_eq!(config_res.protocol_fee_share, Decimal::percent(20));
+
+    // invalid share
+    let msg = ExecuteUpdateConfig::new()
+        .with_protocol_fee_share(Decimal::percent(101))
+        .into();
+    let err = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidProtocolFeeShare(Decimal::percent(101))
+    );
+
+    // new pairs are created with protocol fee
+    let asset_infos = vec![
+        AssetInfo::Native("uusd".to_owned()),
+        AssetInfo::Token(Addr::unchecked("asset0000")),
+    ];
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteCreatePair::new(asset_infos.clone()).into(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        WasmMsg::Instantiate {
+            msg: to_binary(
+                &PairInstantiateMsg::new(asset_infos, 123u64)
+                    .with_admin(MOCK_CONTRACT_ADDR)
+                    .with_protocol_fee("collector", Decimal::percent(20))
+            )
+            .unwrap(),
+            code_id: 321u64,
+            funds: vec![],
+            label: "Tgrade finance trading pair".to_string(),
+            admin: Some(FACTORY_ADMIN.to_owned()),
+        }
+        .into()
+    );
+}
+
+#[test]
+fn update_pair_type() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg::new(321u64, 123u64);
 
-    // Unauthorized err
-    let env = mock_env();
     let info = mock_info("addr0000", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
-        pair_code_id: None,
-        token_code_id: None,
-        default_commission: None,
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // overwrite existing type
+    let info = mock_info("addr0000", &[]);
+    let xyk = PairType::new(XYK_PAIR_TYPE, 100u64).with_default_commission(Decimal::permille(5));
+    let msg = ExecuteMsg::UpdatePairType {
+        pair_type: xyk.clone(),
     };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let res = execute(deps.as_mut(), env, info, msg);
+    // register new type
+    let info = mock_info("addr0000", &[]);
+    let stable = PairType::new(STABLE_PAIR_TYPE, 300u64);
+    let msg = ExecuteMsg::UpdatePairType {
+        pair_type: stable.clone(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_pair_type"),
+            attr("pair_type", STABLE_PAIR_TYPE)
+        ]
+    );
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!(config_res.pair_types, vec![stable, xyk]);
+
+    // Invalid commission
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::UpdatePairType {
+        pair_type: PairType::new(XYK_PAIR_TYPE, 100u64)
+            .with_default_commission(Decimal::permille(1001)),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidCommission(Decimal::permille(1001))
+    );
+
+    // Unauthorized err
+    let info = mock_info("addr0001", &[]);
+    let msg = ExecuteMsg::UpdatePairType {
+        pair_type: PairType::new(XYK_PAIR_TYPE, 1u64),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
         Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
             assert_eq!(msg, "unauthorized")
@@ -135,7 +259,7 @@ fn create_pair() {
     // we can just call .unwrap() to assert this was a success
     let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
 
-    let asset_infos = [
+    let asset_infos = vec![
         AssetInfo::Token(Addr::unchecked("asset0000")),
         AssetInfo::Token(Addr::unchecked("asset0001")),
     ];
@@ -153,7 +277,8 @@ fn create_pair() {
         res.attributes,
         vec![
             attr("action", "create_pair"),
-            attr("pair", "asset0000-asset0001")
+            attr("pair", "asset0000-asset0001"),
+            attr("pair_type", XYK_PAIR_TYPE),
         ]
     );
     assert_eq!(
@@ -163,7 +288,11 @@ fn create_pair() {
             gas_limit: None,
             reply_on: ReplyOn::Success,
             msg: WasmMsg::Instantiate {
-                msg: to_binary(&PairInstantiateMsg::new(asset_infos.clone(), 123u64)).unwrap(),
+                msg: to_binary(
+                    &PairInstantiateMsg::new(asset_infos.clone(), 123u64)
+                        .with_admin(MOCK_CONTRACT_ADDR)
+                )
+                .unwrap(),
                 code_id: 321u64,
                 funds: vec![],
                 label: "Tgrade finance trading pair".to_string(),
@@ -177,22 +306,303 @@ fn create_pair() {
         TMP_PAIR_INFO.load(&deps.storage).unwrap(),
         TmpPairInfo {
             asset_infos: asset_infos.clone(),
-            pair_key: pair_key(&asset_infos),
+            pair_key: pair_key(&asset_infos, XYK_PAIR_TYPE),
             commission: Decimal::permille(3),
+            pair_type: XYK_PAIR_TYPE.to_owned(),
+            whitelist_group: None,
         }
     );
 }
 
+#[test]
+fn create_stable_pair() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg =
+        InstantiateMsg::new(321u64, 123u64).with_pair_type(PairType::new(STABLE_PAIR_TYPE, 456u64));
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let asset_infos = vec![
+        AssetInfo::Token(Addr::unchecked("asset0000")),
+        AssetInfo::Token(Addr::unchecked("asset0001")),
+    ];
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        info,
+        ExecuteCreatePair::new(asset_infos.clone())
+            .with_pair_type(STABLE_PAIR_TYPE)
+            .with_amp(100)
+            .into(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            id: 1,
+            gas_limit: None,
+            reply_on: ReplyOn::Success,
+            msg: WasmMsg::Instantiate {
+                msg: to_binary(
+                    &PairInstantiateMsg::new(asset_infos, 123u64)
+                        .with_admin(MOCK_CONTRACT_ADDR)
+                        .with_amp(100)
+                )
+                .unwrap(),
+                code_id: 456u64,
+                funds: vec![],
+                label: "Tgrade finance trading pair".to_string(),
+                admin: Some(FACTORY_ADMIN.into()),
+            }
+            .into()
+        },]
+    );
+}
+
+#[test]
+fn create_pair_unknown_type() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg::new(321u64, 123u64);
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let asset_infos = vec![
+        AssetInfo::Token(Addr::unchecked("asset0000")),
+        AssetInfo::Token(Addr::unchecked("asset0001")),
+    ];
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        env,
+        info,
+        ExecuteCreatePair::new(asset_infos)
+            .with_pair_type(STABLE_PAIR_TYPE)
+            .into(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnknownPairType(STABLE_PAIR_TYPE.to_owned())
+    );
+}
+
+#[test]
+fn create_pair_invalid_assets() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg::new(321u64, 123u64);
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteCreatePair::new(vec![AssetInfo::Native("uusd".to_owned())]).into(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NotEnoughAssets {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteCreatePair::new(vec![
+            AssetInfo::Native("uusd".to_owned()),
+            AssetInfo::Native("uusd".to_owned()),
+        ])
+        .into(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::DuplicatedAsset("uusd".to_owned()));
+}
+
+#[test]
+fn create_multi_asset_pair() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg::new(321u64, 123u64);
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let asset_infos = vec![
+        AssetInfo::Native("uusd".to_owned()),
+        AssetInfo::Token(Addr::unchecked("asset0001")),
+        AssetInfo::Token(Addr::unchecked("asset0000")),
+    ];
+
+    // constant product supports 2 assets only
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteCreatePair::new(asset_infos.clone()).into(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidAssetCount(3));
+    assert_eq!(TMP_PAIR_INFO.may_load(&deps.storage).unwrap(), None);
+
+    // stable pair can pool more of them
+    let msg = ExecuteMsg::UpdatePairType {
+        pair_type: PairType::new(STABLE_PAIR_TYPE, 300u64),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteCreatePair::new(asset_infos.clone())
+            .with_pair_type(STABLE_PAIR_TYPE)
+            .with_amp(100)
+            .into(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "create_pair"),
+            attr("pair", "uusd-asset0001-asset0000"),
+            attr("pair_type", STABLE_PAIR_TYPE),
+        ]
+    );
+
+    // pair key does not depend on assets order
+    let mut reordered = asset_infos.clone();
+    reordered.reverse();
+    assert_eq!(
+        pair_key(&asset_infos, XYK_PAIR_TYPE),
+        pair_key(&reordered, XYK_PAIR_TYPE)
+    );
+    assert_ne!(
+        pair_key(&asset_infos, XYK_PAIR_TYPE),
+        pair_key(&asset_infos[..2], XYK_PAIR_TYPE)
+    );
+    // assets are not mixed with each other nor with the pair type
+    assert_ne!(
+        pair_key(
+            &[
+                AssetInfo::Native("ab".to_owned()),
+                AssetInfo::Native("c".to_owned())
+            ],
+            XYK_PAIR_TYPE
+        ),
+        pair_key(
+            &[
+                AssetInfo::Native("a".to_owned()),
+                AssetInfo::Native("bc".to_owned())
+            ],
+            XYK_PAIR_TYPE
+        )
+    );
+    assert_ne!(
+        pair_key(
+            &[
+                AssetInfo::Native("a".to_owned()),
+                AssetInfo::Native("bxyk".to_owned())
+            ],
+            ""
+        ),
+        pair_key(
+            &[
+                AssetInfo::Native("a".to_owned()),
+                AssetInfo::Native("b".to_owned())
+            ],
+            XYK_PAIR_TYPE
+        )
+    );
+    assert_eq!(
+        TMP_PAIR_INFO.load(&deps.storage).unwrap().pair_key,
+        pair_key(&reordered, STABLE_PAIR_TYPE)
+    );
+}
+
+#[test]
+fn create_whitelisted_pair() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg::new(321u64, 123u64);
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let asset_infos = vec![
+        AssetInfo::Native("uusd".to_owned()),
+        AssetInfo::Token(Addr::unchecked("asset0000")),
+    ];
+
+    // liquidity token has to be trusted token, but its code id is not known
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteCreatePair::new(asset_infos.clone())
+            .with_whitelist_group("whitelist")
+            .into(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MissingTrustedTokenCodeId {});
+
+    let msg = ExecuteUpdateConfig::new()
+        .with_trusted_token_code_id(124u64)
+        .into();
+    execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!(config_res.token_code_id, 123u64);
+    assert_eq!(config_res.trusted_token_code_id, Some(124u64));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteCreatePair::new(asset_infos.clone())
+            .with_whitelist_group("whitelist")
+            .into(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        WasmMsg::Instantiate {
+            msg: to_binary(
+                &PairInstantiateMsg::new(asset_infos.clone(), 124u64)
+                    .with_admin(MOCK_CONTRACT_ADDR)
+                    .with_whitelist_group("whitelist")
+            )
+            .unwrap(),
+            code_id: 321u64,
+            funds: vec![],
+            label: "Tgrade finance trading pair".to_string(),
+            admin: Some(FACTORY_ADMIN.to_owned()),
+        }
+        .into()
+    );
+
+    assert_eq!(
+        TMP_PAIR_INFO.load(&deps.storage).unwrap().whitelist_group,
+        Some(Addr::unchecked("whitelist"))
+    );
+}
+
 #[test]
 fn reply_test() {
     let mut deps = mock_dependencies(&[]);
 
-    let asset_infos = [
+    let asset_infos = vec![
         AssetInfo::Token(Addr::unchecked("asset0000")),
         AssetInfo::Token(Addr::unchecked("asset0001")),
     ];
 
-    let pair_key = pair_key(&asset_infos);
+    let pair_key = pair_key(&asset_infos, STABLE_PAIR_TYPE);
     TMP_PAIR_INFO
         .save(
             &mut deps.storage,
@@ -200,6 +610,8 @@ fn reply_test() {
                 asset_infos: asset_infos.clone(),
                 pair_key,
                 commission: Decimal::permille(3),
+                pair_type: STABLE_PAIR_TYPE.to_owned(),
+                whitelist_group: None,
             },
         )
         .unwrap();
@@ -232,6 +644,7 @@ fn reply_test() {
         mock_env(),
         QueryMsg::Pair {
             asset_infos: asset_infos.clone(),
+            pair_type: STABLE_PAIR_TYPE.to_owned(),
         },
     )
     .unwrap();
@@ -240,11 +653,24 @@ fn reply_test() {
     assert_eq!(
         pair_res,
         PairInfo::new(
-            asset_infos,
+            asset_infos.clone(),
             Addr::unchecked("pair0000"),
             Addr::unchecked("liquidity0000"),
         )
+        .with_pair_type(STABLE_PAIR_TYPE)
+        .with_admin(Addr::unchecked(MOCK_CONTRACT_ADDR))
     );
+
+    // Pair of the same assets, but other type is not created
+    query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pair {
+            asset_infos,
+            pair_type: XYK_PAIR_TYPE.to_owned(),
+        },
+    )
+    .unwrap_err();
 }
 
 #[test]
@@ -259,7 +685,7 @@ fn custom_default_commission() {
     // we can just call .unwrap() to assert this was a success
     instantiate(deps.as_mut(), env, info, msg).unwrap();
 
-    let asset_infos = [
+    let asset_infos = vec![
         AssetInfo::Token(Addr::unchecked("asset0000")),
         AssetInfo::Token(Addr::unchecked("asset0001")),
     ];
@@ -276,7 +702,8 @@ fn custom_default_commission() {
         res.attributes,
         vec![
             attr("action", "create_pair"),
-            attr("pair", "asset0000-asset0001")
+            attr("pair", "asset0000-asset0001"),
+            attr("pair_type", XYK_PAIR_TYPE),
         ]
     );
 
@@ -289,6 +716,7 @@ fn custom_default_commission() {
             msg: WasmMsg::Instantiate {
                 msg: to_binary(
                     &PairInstantiateMsg::new(asset_infos.clone(), 123u64)
+                        .with_admin(MOCK_CONTRACT_ADDR)
                         .with_commission(Decimal::permille(5))
                 )
                 .unwrap(),
@@ -305,8 +733,10 @@ fn custom_default_commission() {
         TMP_PAIR_INFO.load(&deps.storage).unwrap(),
         TmpPairInfo {
             asset_infos: asset_infos.clone(),
-            pair_key: pair_key(&asset_infos),
+            pair_key: pair_key(&asset_infos, XYK_PAIR_TYPE),
             commission: Decimal::permille(5),
+            pair_type: XYK_PAIR_TYPE.to_owned(),
+            whitelist_group: None,
         }
     );
 }
@@ -340,7 +770,7 @@ fn custom_pair_commission() {
     // we can just call .unwrap() to assert this was a success
     instantiate(deps.as_mut(), env, info, msg).unwrap();
 
-    let asset_infos = [
+    let asset_infos = vec![
         AssetInfo::Token(Addr::unchecked("asset0000")),
         AssetInfo::Token(Addr::unchecked("asset0001")),
     ];
@@ -359,7 +789,8 @@ fn custom_pair_commission() {
         res.attributes,
         vec![
             attr("action", "create_pair"),
-            attr("pair", "asset0000-asset0001")
+            attr("pair", "asset0000-asset0001"),
+            attr("pair_type", XYK_PAIR_TYPE),
         ]
     );
 
@@ -372,6 +803,7 @@ fn custom_pair_commission() {
             msg: WasmMsg::Instantiate {
                 msg: to_binary(
                     &PairInstantiateMsg::new(asset_infos.clone(), 123u64)
+                        .with_admin(MOCK_CONTRACT_ADDR)
                         .with_commission(Decimal::permille(5))
                 )
                 .unwrap(),
@@ -388,8 +820,10 @@ fn custom_pair_commission() {
         TMP_PAIR_INFO.load(&deps.storage).unwrap(),
         TmpPairInfo {
             asset_infos: asset_infos.clone(),
-            pair_key: pair_key(&asset_infos),
+            pair_key: pair_key(&asset_infos, XYK_PAIR_TYPE),
             commission: Decimal::permille(5),
+            pair_type: XYK_PAIR_TYPE.to_owned(),
+            whitelist_group: None,
         }
     );
 }
@@ -406,7 +840,7 @@ fn invalid_custom_pair_commission() {
     // we can just call .unwrap() to assert this was a success
     instantiate(deps.as_mut(), env, info, msg).unwrap();
 
-    let asset_infos = [
+    let asset_infos = vec![
         AssetInfo::Token(Addr::unchecked("asset0000")),
         AssetInfo::Token(Addr::unchecked("asset0001")),
     ];
@@ -426,3 +860,160 @@ fn invalid_custom_pair_commission() {
         ContractError::InvalidCommission(Decimal::permille(1001))
     );
 }
+
+#[test]
+fn migrate_legacy_factory() {
+    let mut deps = mock_dependencies(&[]);
+
+    // State of factory from before the pair types registry
+    deps.storage.set(
+        b"config",
+        br#"{
+            "owner": "addr0000",
+            "pair_code_id": 321,
+            "token_code_id": 123,
+            "default_commission": "0.005",
+            "migrate_admin": null
+        }"#,
+    );
+    let asset_infos = vec![
+        AssetInfo::Token(Addr::unchecked("asset0001")),
+        AssetInfo::Token(Addr::unchecked("asset0000")),
+    ];
+    let legacy_key = [b"asset0000".as_ref(), b"asset0001".as_ref()].concat();
+    let pair_info: PairInfo = from_slice(
+        br#"{
+            "asset_infos": [{ "token": "asset0000" }, { "token": "asset0001" }],
+            "contract_addr": "pair0000",
+            "liquidity_token": "liquidity0000",
+            "commission": "0.005"
+        }"#,
+    )
+    .unwrap();
+    PAIRS
+        .save(&mut deps.storage, &legacy_key, &pair_info)
+        .unwrap();
+
+    deps.querier
+        .with_tfi_pairs(&[(&"pair0000".to_string(), &pair_info)]);
+
+    migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap();
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!(config_res.token_code_id, 123u64);
+    assert_eq!(
+        config_res.pair_types,
+        vec![PairType::new(XYK_PAIR_TYPE, 321u64).with_default_commission(Decimal::permille(5))]
+    );
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pair {
+            asset_infos: asset_infos.clone(),
+            pair_type: XYK_PAIR_TYPE.to_owned(),
+        },
+    )
+    .unwrap();
+    let pair_res: PairInfo = from_binary(&query_res).unwrap();
+    assert_eq!(pair_res, pair_info);
+    assert!(!PAIRS.has(&deps.storage, &legacy_key));
+
+    // Pairs can be created with the migrated pair type, but not duplicated
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteCreatePair::new(asset_infos).into(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err("Pair already exists"))
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteCreatePair::new(vec![
+            AssetInfo::Token(Addr::unchecked("asset0000")),
+            AssetInfo::Native("uusd".to_owned()),
+        ])
+        .into(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn migrate_legacy_pairs_paginated() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg::new(321u64, 123u64);
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // Pairs stored under keys from before the pair types registry
+    let legacy_pairs: Vec<(Vec<u8>, PairInfo)> = ["asset0001", "asset0002", "asset0003"]
+        .iter()
+        .enumerate()
+        .map(|(idx, asset)| {
+            let asset_infos = [
+                AssetInfo::Token(Addr::unchecked("asset0000")),
+                AssetInfo::Token(Addr::unchecked(*asset)),
+            ];
+            let legacy_key = [b"asset0000".as_ref(), asset.as_bytes()].concat();
+            let pair_info = PairInfo::new(
+                asset_infos,
+                Addr::unchecked(format!("pair000{}", idx)),
+                Addr::unchecked(format!("liquidity000{}", idx)),
+            );
+            (legacy_key, pair_info)
+        })
+        .collect();
+    for (legacy_key, pair_info) in &legacy_pairs {
+        PAIRS
+            .save(&mut deps.storage, legacy_key, pair_info)
+            .unwrap();
+    }
+
+    // Every migration moves single page, and reports the last visited key to continue after
+    let mut start_after = None;
+    let mut migrations = 0;
+    loop {
+        let res = migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                start_after,
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+        migrations += 1;
+        start_after = match res
+            .attributes
+            .iter()
+            .find(|attr| attr.key == "last_pair_key")
+        {
+            Some(attr) => Some(Binary::from_base64(&attr.value).unwrap()),
+            None => break,
+        };
+    }
+    // current keys are length prefixed, so moved pairs are never visited again - the last
+    // migration just finds no pairs left
+    assert_eq!(migrations, legacy_pairs.len() + 1);
+
+    for (legacy_key, pair_info) in legacy_pairs {
+        assert!(!PAIRS.has(&deps.storage, &legacy_key));
+        assert_eq!(
+            PAIRS
+                .load(
+                    &deps.storage,
+                    &pair_key(&pair_info.asset_infos, XYK_PAIR_TYPE)
+                )
+                .unwrap(),
+            pair_info
+        );
+    }
+}
@@ -5,8 +5,9 @@ use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use tfi::asset::PairInfo;
 use tfi::pair::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse, QueryMsg,
-    ReverseSimulationResponse, SimulationResponse,
+    AccruedFeesResponse, CommissionTiersResponse, CumulativePricesResponse, Cw20HookMsg,
+    ExecuteMsg, FlashSwapCallbackMsg, InstantiateMsg, MigrateMsg, PoolResponse, QueryMsg,
+    ReverseSimulationResponse, SimulationResponse, WithdrawSingleSidedSimulationResponse,
 };
 
 fn main() {
@@ -19,9 +20,17 @@ fn main() {
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(Cw20HookMsg), &out_dir);
+    export_schema(&schema_for!(FlashSwapCallbackMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(PairInfo), &out_dir);
     export_schema(&schema_for!(PoolResponse), &out_dir);
     export_schema(&schema_for!(ReverseSimulationResponse), &out_dir);
     export_schema(&schema_for!(SimulationResponse), &out_dir);
+    export_schema(&schema_for!(CumulativePricesResponse), &out_dir);
+    export_schema(&schema_for!(AccruedFeesResponse), &out_dir);
+    export_schema(&schema_for!(CommissionTiersResponse), &out_dir);
+    export_schema(
+        &schema_for!(WithdrawSingleSidedSimulationResponse),
+        &out_dir,
+    );
 }
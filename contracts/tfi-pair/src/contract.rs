@@ -1,30 +1,57 @@
 use crate::error::ContractError;
-use crate::math::{decimal_multiplication, decimal_subtraction, reverse_decimal};
-use crate::state::PAIR_INFO;
+use crate::math::{before_commission, decimal256, div_ceil, uint512_sqrt};
+use crate::state::{
+    CommissionTiers, CumulativePrices, LimitOrder, PendingFlashSwap, ProtocolFee, ACCRUED_FEES,
+    COMMISSION_TIERS, CUMULATIVE_PRICES, ESCROWED, FLASH_SWAP, LAST_LIMIT_ORDER_ID, LIMIT_ORDERS,
+    LIMIT_ORDER_PROCEEDS, ORDER_BOOK, OWNER_LIMIT_ORDERS, PAIR_INFO, PROTOCOL_FEE, RESERVES,
+};
 
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Binary, Coin, Decimal, Deps, DepsMut, Env, MessageInfo, Reply,
-    Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
+    from_binary, to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, Decimal256, Deps, DepsMut,
+    Empty, Env, Event, MessageInfo, Order, Reply, Response, StdError, StdResult, Storage, SubMsg,
+    Uint128, Uint256, Uint512, WasmMsg,
 };
 
 use cw2::set_contract_version;
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
-use integer_sqrt::IntegerSquareRoot;
-use tfi::asset::{Asset, AssetInfo, PairInfo};
+use cw_storage_plus::Bound;
+use std::convert::TryInto;
+use std::fmt;
+use tfi::asset::{Asset, AssetInfo, PairInfo, PauseState};
 use tfi::pair::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse, QueryMsg,
-    ReverseSimulationResponse, SimulationResponse,
+    AccruedFeesResponse, CommissionTier, CommissionTiersResponse, CumulativePricesResponse,
+    Cw20HookMsg, ExecuteMsg, FlashSwapCallback, InstantiateMsg, LimitOrderProceedsResponse,
+    LimitOrderResponse, LimitOrdersResponse, MigrateMsg, PoolResponse, QueryMsg,
+    ReverseSimulationResponse, SimulationResponse, WithdrawSingleSidedSimulationResponse,
 };
 use tfi::querier::query_supply;
 use tfi::token::InstantiateMsg as TokenInstantiateMsg;
+use tg4::Tg4Contract;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:tfi-pair";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Reply to the liquidity token instantiation
+pub const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
+/// Reply to the flash swap callback, verifying repayment
+pub const FLASH_SWAP_REPLY_ID: u64 = 2;
+
+/// Liquidity tokens minted to the pair itself on the first deposit. As the pair never sends them,
+/// they are locked forever, which makes inflating the share price with dust pools too expensive.
+pub const MINIMUM_LIQUIDITY: Uint128 = Uint128::new(1000);
+
+/// Maximum number of limit orders filled by a single operation, so placing many small orders
+/// cannot make swaps run out of gas. Orders left crossed are filled by subsequent swaps.
+pub const MAX_LIMIT_ORDER_FILLS: usize = 10;
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -33,19 +60,74 @@ pub fn instantiate(
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    if msg.asset_infos.len() != 2 {
+        return Err(ContractError::InvalidAssetCount(msg.asset_infos.len()));
+    }
+    CUMULATIVE_PRICES.save(
+        deps.storage,
+        &CumulativePrices::new(env.block.time.seconds()),
+    )?;
+    instantiate_pair(deps, env, msg)
+}
+
+/// Instantiates pair of any number of assets, including its liquidity token. Constant product
+/// supports only two of them, so the count is validated by entry points.
+pub fn instantiate_pair(
+    mut deps: DepsMut,
+    env: Env,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
     if !(Decimal::zero()..=Decimal::one()).contains(&msg.commission) {
         return Err(ContractError::InvalidCommission(msg.commission));
     }
+    if !(Decimal::zero()..=Decimal::one()).contains(&msg.protocol_fee_share) {
+        return Err(ContractError::InvalidProtocolFeeShare(
+            msg.protocol_fee_share,
+        ));
+    }
+    if !msg.protocol_fee_share.is_zero() && msg.fee_collector.is_none() {
+        return Err(ContractError::MissingFeeCollector {});
+    }
+    save_commission_tiers(deps.branch(), msg.commission_group, msg.commission_tiers)?;
+
+    let fee_collector = msg
+        .fee_collector
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
 
-    let pair_info: &PairInfo = &PairInfo::new(
+    let whitelist_group = msg
+        .whitelist_group
+        .map(|addr| validate_group(deps.as_ref(), &addr))
+        .transpose()?;
+
+    let mut pair_info = PairInfo::new(
         msg.asset_infos,
         env.contract.address.clone(),
         // ugly placeholder, but we set this in the callback
         Addr::unchecked(""),
     )
     .with_commission(msg.commission);
+    if let Some(whitelist_group) = whitelist_group {
+        pair_info = pair_info.with_whitelist_group(whitelist_group);
+    }
+    if let Some(admin) = msg.admin {
+        pair_info = pair_info.with_admin(deps.api.addr_validate(&admin)?);
+    }
+    let pair_info = &pair_info;
+    let zeros = vec![Uint128::zero(); pair_info.asset_infos.len()];
 
     PAIR_INFO.save(deps.storage, pair_info)?;
+    RESERVES.save(deps.storage, &zeros)?;
+    PROTOCOL_FEE.save(
+        deps.storage,
+        &ProtocolFee {
+            fee_collector,
+            share: msg.protocol_fee_share,
+        },
+    )?;
+    ACCRUED_FEES.save(deps.storage, &zeros)?;
+    ESCROWED.save(deps.storage, &zeros)?;
+    LAST_LIMIT_ORDER_ID.save(deps.storage, &0)?;
 
     let token_init = &TokenInstantiateMsg {
         name: "tfi liquidity token".to_string(),
@@ -56,6 +138,10 @@ pub fn instantiate(
             minter: env.contract.address.to_string(),
             cap: None,
         }),
+        // liquidity token of whitelisted pair is `trusted-token` with the same whitelist, which
+        // lets the pair hold and burn shares without being whitelisted itself
+        whitelist_group: pair_info.whitelist_group.as_ref().map(Addr::to_string),
+        exempt_minter: pair_info.whitelist_group.as_ref().map(|_| true),
     };
     let msg = WasmMsg::Instantiate {
         admin: None,
@@ -64,7 +150,7 @@ pub fn instantiate(
         funds: vec![],
         label: "uLP liquidity token".to_string(),
     };
-    let msg = SubMsg::reply_on_success(msg, 1);
+    let msg = SubMsg::reply_on_success(msg, INSTANTIATE_TOKEN_REPLY_ID);
     Ok(Response::new().add_submessage(msg))
 }
 
@@ -75,18 +161,34 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // reserves are not valid until the flash swap is repaid
+    if FLASH_SWAP.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::FlashSwapInProgress {});
+    }
+
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::ProvideLiquidity {
             assets,
             slippage_tolerance,
-        } => provide_liquidity(deps, env, info, assets, slippage_tolerance),
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Deposits])?;
+            provide_liquidity(deps, env, info, assets, slippage_tolerance)
+        }
         ExecuteMsg::Swap {
             offer_asset,
+            ask_asset_info,
             belief_price,
             max_spread,
             to,
+            min_return,
+            deadline,
+            to_msg,
         } => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Swaps])?;
             if !offer_asset.is_native_token() {
                 return Err(ContractError::Unauthorized {});
             }
@@ -103,11 +205,99 @@ pub fn execute(
                 info.clone(),
                 info.sender,
                 offer_asset,
+                ask_asset_info,
                 belief_price,
                 max_spread,
+                min_return,
+                to_addr,
+                to_msg,
+            )
+        }
+        ExecuteMsg::SwapExactOut {
+            offer_asset,
+            ask_asset_info,
+            ask_amount,
+            to,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Swaps])?;
+            if !offer_asset.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = to.map(|to| deps.api.addr_validate(&to)).transpose()?;
+
+            swap_exact_out(
+                deps,
+                env,
+                info.clone(),
+                info.sender,
+                offer_asset,
+                ask_asset_info,
+                ask_amount,
                 to_addr,
             )
         }
+        ExecuteMsg::ProvideSingleSided {
+            offer_asset,
+            slippage_tolerance,
+            min_share,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Swaps, Operation::Deposits])?;
+            if !offer_asset.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            provide_single_sided(
+                deps,
+                env,
+                info.clone(),
+                info.sender,
+                offer_asset,
+                slippage_tolerance,
+                min_share,
+            )
+        }
+        ExecuteMsg::Sync {} => {
+            accumulate_prices(deps.storage, &env)?;
+            sync(deps, env)
+        }
+        ExecuteMsg::Skim { to } => {
+            let to_addr = to
+                .map(|to| deps.api.addr_validate(&to))
+                .transpose()?
+                .unwrap_or(info.sender);
+            skim(deps, env, to_addr)
+        }
+        ExecuteMsg::CollectFees {} => collect_fees(deps),
+        ExecuteMsg::FlashSwap { ask_asset, msg } => {
+            assert_not_paused(deps.storage, &[Operation::Swaps])?;
+            flash_swap(deps, info, ask_asset, msg)
+        }
+        ExecuteMsg::UpdatePause {
+            swaps,
+            deposits,
+            withdrawals,
+        } => update_pause(deps, info, swaps, deposits, withdrawals),
+        ExecuteMsg::UpdateCommission { commission } => update_commission(deps, info, commission),
+        ExecuteMsg::PlaceLimitOrder {
+            offer_asset,
+            price,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Swaps])?;
+            if !offer_asset.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            place_limit_order(deps, env, info.clone(), info.sender, offer_asset, price)
+        }
+        ExecuteMsg::CancelLimitOrder { order_id } => cancel_limit_order(deps, info, order_id),
+        ExecuteMsg::ClaimLimitOrderProceeds {} => claim_limit_order_proceeds(deps, info),
     }
 }
 
@@ -121,17 +311,22 @@ pub fn receive_cw20(
 
     match from_binary(&cw20_msg.msg) {
         Ok(Cw20HookMsg::Swap {
+            ask_asset_info,
             belief_price,
             max_spread,
             to,
+            min_return,
+            deadline,
+            to_msg,
         }) => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Swaps])?;
+
             // only asset contract can execute this message
             let mut authorized: bool = false;
             let config: PairInfo = PAIR_INFO.load(deps.storage)?;
-            let pools: [Asset; 2] =
-                config.query_pools(&deps.querier, env.contract.address.clone())?;
-            for pool in pools.iter() {
-                if let AssetInfo::Token(contract_addr) = &pool.info {
+            for asset_info in config.asset_infos.iter() {
+                if let AssetInfo::Token(contract_addr) = asset_info {
                     if contract_addr == &info.sender {
                         authorized = true;
                     }
@@ -158,20 +353,141 @@ pub fn receive_cw20(
                     info: AssetInfo::Token(contract_addr),
                     amount: cw20_msg.amount,
                 },
+                ask_asset_info,
                 belief_price,
                 max_spread,
+                min_return,
+                to_addr,
+                to_msg,
+            )
+        }
+        Ok(Cw20HookMsg::SwapExactOut {
+            ask_asset_info,
+            ask_amount,
+            to,
+            deadline,
+        }) => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Swaps])?;
+
+            // only asset contract can execute this message
+            let config: PairInfo = PAIR_INFO.load(deps.storage)?;
+            let authorized = config.asset_infos.iter().any(
+                |asset_info| matches!(asset_info, AssetInfo::Token(addr) if addr == &info.sender),
+            );
+            if !authorized {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = to.map(|to| deps.api.addr_validate(&to)).transpose()?;
+            let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+            swap_exact_out(
+                deps,
+                env,
+                info,
+                sender,
+                Asset {
+                    info: AssetInfo::Token(contract_addr),
+                    amount: cw20_msg.amount,
+                },
+                ask_asset_info,
+                ask_amount,
                 to_addr,
             )
         }
-        Ok(Cw20HookMsg::WithdrawLiquidity {}) => {
+        Ok(Cw20HookMsg::ProvideSingleSided {
+            slippage_tolerance,
+            min_share,
+            deadline,
+        }) => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Swaps, Operation::Deposits])?;
+
+            // only asset contract can execute this message
+            let config: PairInfo = PAIR_INFO.load(deps.storage)?;
+            let authorized = config.asset_infos.iter().any(
+                |asset_info| matches!(asset_info, AssetInfo::Token(addr) if addr == &info.sender),
+            );
+            if !authorized {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+            provide_single_sided(
+                deps,
+                env,
+                info,
+                sender,
+                Asset {
+                    info: AssetInfo::Token(contract_addr),
+                    amount: cw20_msg.amount,
+                },
+                slippage_tolerance,
+                min_share,
+            )
+        }
+        Ok(Cw20HookMsg::WithdrawLiquidity { deadline }) => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Withdrawals])?;
+
             let config: PairInfo = PAIR_INFO.load(deps.storage)?;
             if info.sender != config.liquidity_token {
                 return Err(ContractError::Unauthorized {});
             }
 
             let sender_addr = deps.api.addr_validate(cw20_msg.sender.as_str())?;
+            accumulate_prices(deps.storage, &env)?;
             withdraw_liquidity(deps, env, info, sender_addr, cw20_msg.amount)
         }
+        Ok(Cw20HookMsg::WithdrawSingleSided {
+            ask_asset,
+            min_return,
+            deadline,
+        }) => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Swaps, Operation::Withdrawals])?;
+
+            let config: PairInfo = PAIR_INFO.load(deps.storage)?;
+            if info.sender != config.liquidity_token {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let sender_addr = deps.api.addr_validate(cw20_msg.sender.as_str())?;
+            withdraw_single_sided(
+                deps,
+                env,
+                sender_addr,
+                cw20_msg.amount,
+                ask_asset,
+                min_return,
+            )
+        }
+        Ok(Cw20HookMsg::PlaceLimitOrder { price, deadline }) => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Swaps])?;
+
+            // only asset contract can execute this message
+            let config: PairInfo = PAIR_INFO.load(deps.storage)?;
+            let authorized = config.asset_infos.iter().any(
+                |asset_info| matches!(asset_info, AssetInfo::Token(addr) if addr == &info.sender),
+            );
+            if !authorized {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+            place_limit_order(
+                deps,
+                env,
+                info,
+                sender,
+                Asset {
+                    info: AssetInfo::Token(contract_addr),
+                    amount: cw20_msg.amount,
+                },
+                price,
+            )
+        }
         Err(err) => Err(ContractError::Std(err)),
     }
 }
@@ -205,12 +521,17 @@ fn parse_init_addr(init_result: &[u8]) -> Result<&str, ContractError> {
 
 /// This just stores the result for future query
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
-    // this is the only expected one from init
-    if msg.id != 1 {
-        return Err(StdError::generic_err("Unsupported reply id").into());
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        INSTANTIATE_TOKEN_REPLY_ID => instantiate_token_reply(deps, msg),
+        // only successful callbacks are replied, failed one reverts whole flash swap anyway
+        FLASH_SWAP_REPLY_ID => flash_swap_repay(deps, env),
+        _ => Err(StdError::generic_err("Unsupported reply id").into()),
     }
+}
 
+/// Stores address of the liquidity token instantiated by `instantiate`
+fn instantiate_token_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
     let data = msg
         .result
         .into_result()
@@ -233,60 +554,52 @@ pub fn provide_liquidity(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    assets: [Asset; 2],
+    assets: Vec<Asset>,
     slippage_tolerance: Option<Decimal>,
 ) -> Result<Response, ContractError> {
+    if assets.len() != 2 {
+        return Err(ContractError::InvalidAssetCount(assets.len()));
+    }
     for asset in assets.iter() {
         asset.assert_sent_native_token_balance(&info)?;
     }
 
     let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
-    // we really should do this locally...
-    let mut pools: [Asset; 2] =
-        pair_info.query_pools(&deps.querier, env.contract.address.clone())?;
-    let deposits: [Uint128; 2] = [
-        assets
-            .iter()
-            .find(|a| a.info.equal(&pools[0].info))
-            .ok_or_else(|| ContractError::AssetMismatch(pools[0].info.to_string()))?
-            .amount,
-        assets
-            .iter()
-            .find(|a| a.info.equal(&pools[1].info))
-            .ok_or_else(|| ContractError::AssetMismatch(pools[1].info.to_string()))?
-            .amount,
-    ];
-
-    let mut res = Response::new()
-        .add_attribute("action", "provide_liquidity")
-        .add_attribute("assets", format!("{}, {}", assets[0], assets[1]));
-
-    for (i, pool) in pools.iter_mut().enumerate() {
-        // If the pool is token contract, then we need to execute TransferFrom msg to receive funds
-        if let AssetInfo::Token(contract_addr) = &pool.info {
-            res = res.add_message(WasmMsg::Execute {
-                contract_addr: contract_addr.to_string(),
-                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                    owner: info.sender.to_string(),
-                    recipient: env.contract.address.to_string(),
-                    amount: deposits[i],
-                })?,
-                funds: vec![],
-            });
-        } else {
-            // If the asset is native token, balance is already increased
-            // To calculated properly we should subtract user deposit from the pool
-            pool.amount = pool.amount.checked_sub(deposits[i])?;
-        }
-    }
+    assert_whitelisted(deps.as_ref(), &pair_info, &[&info.sender])?;
+    let pools = load_pools(deps.storage, &pair_info)?;
+    let deposits = requested_deposits(&assets, &pools)?;
 
     // assert slippage tolerance
     assert_slippage_tolerance(&slippage_tolerance, &deposits, &pools)?;
 
     let total_share = query_supply(&deps.querier, pair_info.liquidity_token.clone())?;
+    let used = ratio_matched_deposits(&deposits, &pools, total_share);
+
+    let mut res = Response::new()
+        .add_attribute("action", "provide_liquidity")
+        .add_attribute("assets", format_amounts(&used, &pools))
+        .add_messages(collect_deposits(
+            &env,
+            &info.sender,
+            &pools,
+            &deposits,
+            &used,
+        )?);
+
+    update_reserves(
+        deps.storage,
+        &env,
+        &[
+            pools[0].amount.checked_add(used[0])?,
+            pools[1].amount.checked_add(used[1])?,
+        ],
+    )?;
+
     let share = if total_share == Uint128::zero() {
-        // Initial share = collateral amount
-        Uint128::new((deposits[0].u128() * deposits[1].u128()).integer_sqrt())
+        // Initial share = collateral amount, minimum liquidity is locked out of it
+        let share: Uint128 = uint512_sqrt(Uint256::from(used[0]).full_mul(used[1]))?.try_into()?;
+        res = res.add_message(lock_minimum_liquidity(&pair_info, &env)?);
+        share.saturating_sub(MINIMUM_LIQUIDITY)
     } else {
         // min(1, 2)
         // 1. sqrt(deposit_0 * exchange_rate_0_to_1 * deposit_0) * (total_share / sqrt(pool_0 * pool_1))
@@ -294,11 +607,15 @@ pub fn provide_liquidity(
         // 2. sqrt(deposit_1 * exchange_rate_1_to_0 * deposit_1) * (total_share / sqrt(pool_1 * pool_1))
         // == deposit_1 * total_share / pool_1
         std::cmp::min(
-            deposits[0].multiply_ratio(total_share, pools[0].amount),
-            deposits[1].multiply_ratio(total_share, pools[1].amount),
+            used[0].multiply_ratio(total_share, pools[0].amount),
+            used[1].multiply_ratio(total_share, pools[1].amount),
         )
     };
 
+    if share.is_zero() {
+        return Err(ContractError::ZeroShares {});
+    }
+
     // mint LP token to sender
     let msg = WasmMsg::Execute {
         contract_addr: pair_info.liquidity_token.into(),
@@ -314,182 +631,1405 @@ pub fn provide_liquidity(
     Ok(res)
 }
 
-pub fn withdraw_liquidity(
-    deps: DepsMut,
-    env: Env,
-    _info: MessageInfo,
-    sender: Addr,
-    amount: Uint128,
-) -> Result<Response, ContractError> {
-    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
-
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, env.contract.address)?;
-    let total_share: Uint128 = query_supply(&deps.querier, pair_info.liquidity_token.clone())?;
-
-    let share_ratio: Decimal = Decimal::from_ratio(amount, total_share);
-    let refund_assets: Vec<Asset> = pools
-        .iter()
-        .map(|a| Asset {
-            info: a.info.clone(),
-            amount: a.amount * share_ratio,
-        })
-        .collect();
-
-    // update pool info
-    let res = Response::new()
-        // refund asset tokens
-        .add_message(refund_assets[0].clone().into_msg(sender.clone())?)
-        .add_message(refund_assets[1].clone().into_msg(sender)?)
-        // burn liquidity token
-        .add_message(WasmMsg::Execute {
-            contract_addr: pair_info.liquidity_token.into(),
-            msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
-            funds: vec![],
-        })
-        .add_attribute("action", "withdraw_liquidity")
-        .add_attribute("withdrawn_share", amount.to_string())
-        .add_attribute(
-            "refund_assets",
-            format!("{}, {}", refund_assets[0], refund_assets[1]),
-        );
-    Ok(res)
-}
-
-// CONTRACT - a user must do token approval
+/// Provides liquidity with single asset. Part of `offer_asset` is swapped to the other asset
+/// inside the pair, then both are provided at pool ratio after the swap. Offer asset has to be
+/// already transferred to the pair.
 #[allow(clippy::too_many_arguments)]
-pub fn swap(
-    deps: DepsMut,
+pub fn provide_single_sided(
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     sender: Addr,
     offer_asset: Asset,
-    belief_price: Option<Decimal>,
-    max_spread: Option<Decimal>,
-    to: Option<Addr>,
+    slippage_tolerance: Option<Decimal>,
+    min_share: Option<Uint128>,
 ) -> Result<Response, ContractError> {
     offer_asset.assert_sent_native_token_balance(&info)?;
+    if offer_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
 
     let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    assert_whitelisted(deps.as_ref(), &pair_info, &[&sender])?;
+    let pools = load_pools(deps.storage, &pair_info)?;
 
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, env.contract.address)?;
-
-    let offer_pool: Asset;
-    let ask_pool: Asset;
+    let offer_idx = pools
+        .iter()
+        .position(|pool| offer_asset.info.equal(&pool.info))
+        .ok_or_else(|| ContractError::AssetMismatch(offer_asset.info.to_string()))?;
+    let ask_idx = 1 - offer_idx;
 
-    // If the asset balance is already increased
-    // To calculated properly we should subtract user deposit from the pool
-    if offer_asset.info.equal(&pools[0].info) {
-        offer_pool = Asset {
-            amount: pools[0].amount.checked_sub(offer_asset.amount)?,
-            info: pools[0].info.clone(),
-        };
-        ask_pool = pools[1].clone();
-    } else if offer_asset.info.equal(&pools[1].info) {
-        offer_pool = Asset {
-            amount: pools[1].amount.checked_sub(offer_asset.amount)?,
-            info: pools[1].info.clone(),
-        };
-        ask_pool = pools[0].clone();
-    } else {
-        return Err(ContractError::AssetMismatch(offer_asset.info.to_string()));
+    let total_share = query_supply(&deps.querier, pair_info.liquidity_token.clone())?;
+    if total_share.is_zero() || pools.iter().any(|pool| pool.amount.is_zero()) {
+        return Err(ContractError::EmptyPool {});
     }
 
-    let offer_amount = offer_asset.amount;
+    let commission = trader_commission(deps.as_ref(), &pair_info, Some(&sender))?;
+    let swap_amount =
+        compute_single_sided_swap_amount(pools[offer_idx].amount, offer_asset.amount, commission)?;
     let (return_amount, spread_amount, commission_amount) = compute_swap(
-        offer_pool.amount,
-        ask_pool.amount,
-        offer_amount,
-        pair_info.commission,
+        pools[offer_idx].amount,
+        pools[ask_idx].amount,
+        swap_amount,
+        commission,
     )?;
 
-    // check max spread limit if exist
     assert_max_spread(
-        belief_price,
-        max_spread,
-        offer_amount,
+        None,
+        slippage_tolerance,
+        swap_amount,
         return_amount + commission_amount,
         spread_amount,
     )?;
 
-    let return_msg = Asset {
-        info: ask_pool.info.clone(),
-        amount: return_amount,
+    // pools after the swap, the same way as in `swap`
+    let protocol_fee_amount = accrue_protocol_fee(deps.storage, ask_idx, commission_amount)?;
+    let mut pools = pools;
+    pools[offer_idx].amount = pools[offer_idx].amount.checked_add(swap_amount)?;
+    pools[ask_idx].amount = pools[ask_idx]
+        .amount
+        .checked_sub(return_amount)?
+        .checked_sub(protocol_fee_amount)?;
+
+    let mut deposits = vec![Uint128::zero(); 2];
+    deposits[offer_idx] = offer_asset.amount - swap_amount;
+    deposits[ask_idx] = return_amount;
+    let used = ratio_matched_deposits(&deposits, &pools, total_share);
+
+    let share = std::cmp::min(
+        used[0].multiply_ratio(total_share, pools[0].amount),
+        used[1].multiply_ratio(total_share, pools[1].amount),
+    );
+    if share.is_zero() {
+        return Err(ContractError::ZeroShares {});
+    }
+    if let Some(min_share) = min_share {
+        if share < min_share {
+            return Err(ContractError::MinShareAssertion { share, min_share });
+        }
     }
-    .into_msg(to.unwrap_or(sender))?;
 
-    // 1. send collateral token from the contract to a user
-    // 2. send inactive commission to collector
-    let res = Response::new()
-        .add_attribute("action", "swap")
+    update_reserves(
+        deps.storage,
+        &env,
+        &[
+            pools[0].amount.checked_add(used[0])?,
+            pools[1].amount.checked_add(used[1])?,
+        ],
+    )?;
+
+    // orders crossed by the internal swap are filled, see `swap`
+    let fill_events = fill_limit_orders(deps.branch(), &env, &pair_info, ask_idx)?;
+
+    // whole offer is already on the pair, so rounding leftovers are just sent back
+    let refunds = pools
+        .iter()
+        .enumerate()
+        .map(|(i, pool)| Asset {
+            info: pool.info.clone(),
+            amount: deposits[i] - used[i],
+        })
+        .filter(|refund| !refund.amount.is_zero())
+        .map(|refund| refund.into_msg(sender.clone()))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mint_msg = WasmMsg::Execute {
+        contract_addr: pair_info.liquidity_token.into(),
+        msg: to_binary(&Cw20ExecuteMsg::Mint {
+            recipient: sender.to_string(),
+            amount: share,
+        })?,
+        funds: vec![],
+    };
+
+    Ok(Response::new()
+        .add_attribute("action", "provide_single_sided")
         .add_attribute("offer_asset", offer_asset.info.to_string())
-        .add_attribute("ask_asset", ask_pool.info.to_string())
-        .add_attribute("offer_amount", offer_amount.to_string())
+        .add_attribute("ask_asset", pools[ask_idx].info.to_string())
+        .add_attribute("offer_amount", swap_amount.to_string())
         .add_attribute("return_amount", return_amount.to_string())
         .add_attribute("spread_amount", spread_amount.to_string())
         .add_attribute("commission_amount", commission_amount.to_string())
-        .add_message(return_msg);
-    Ok(res)
+        .add_attribute("protocol_fee_amount", protocol_fee_amount.to_string())
+        .add_attribute("assets", format_amounts(&used, &pools))
+        .add_attribute("share", share.to_string())
+        .add_messages(refunds)
+        .add_message(mint_msg)
+        .add_events(fill_events))
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
-    match msg {
-        QueryMsg::Pair {} => Ok(to_binary(&query_pair_info(deps)?)?),
-        QueryMsg::Pool {} => Ok(to_binary(&query_pool(deps)?)?),
-        QueryMsg::Simulation { offer_asset } => {
-            Ok(to_binary(&query_simulation(deps, offer_asset)?)?)
-        }
-        QueryMsg::ReverseSimulation { ask_asset } => {
-            Ok(to_binary(&query_reverse_simulation(deps, ask_asset)?)?)
-        }
+/// Amounts of `assets` in the order of `pools`. Every pool asset has to be deposited, and nothing
+/// else.
+pub fn requested_deposits(
+    assets: &[Asset],
+    pools: &[Asset],
+) -> Result<Vec<Uint128>, ContractError> {
+    if assets.len() != pools.len() {
+        return Err(ContractError::AssetCountMismatch {
+            expected: pools.len(),
+            got: assets.len(),
+        });
     }
-}
 
-pub fn query_pair_info(deps: Deps) -> Result<PairInfo, ContractError> {
-    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
-    Ok(pair_info)
+    pools
+        .iter()
+        .map(|pool| {
+            assets
+                .iter()
+                .find(|a| a.info.equal(&pool.info))
+                .map(|a| a.amount)
+                .ok_or_else(|| ContractError::AssetMismatch(pool.info.to_string()))
+        })
+        .collect()
 }
 
-pub fn query_pool(deps: Deps) -> Result<PoolResponse, ContractError> {
-    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
-    let assets: [Asset; 2] =
-        pair_info.query_pools(&deps.querier, pair_info.contract_addr.clone())?;
-    let total_share: Uint128 = query_supply(&deps.querier, pair_info.liquidity_token)?;
+/// Returns part of `deposits` matching current pools ratio. Only this part is added to pools, the
+/// excess of the larger sides is returned to the provider. On the first deposit there is no ratio
+/// to match yet, so whole deposits are used.
+pub fn ratio_matched_deposits(
+    deposits: &[Uint128],
+    pools: &[Asset],
+    total_share: Uint128,
+) -> Vec<Uint128> {
+    if total_share.is_zero() || pools.iter().any(|pool| pool.amount.is_zero()) {
+        return deposits.to_vec();
+    }
 
-    let resp = PoolResponse {
-        assets,
-        total_share,
+    // the smallest deposit relative to its pool limits all the others
+    let matched = |limiting: usize| -> Vec<Uint128> {
+        pools
+            .iter()
+            .enumerate()
+            .map(|(idx, pool)| match idx == limiting {
+                true => deposits[idx],
+                false => deposits[limiting].multiply_ratio(pool.amount, pools[limiting].amount),
+            })
+            .collect()
     };
+    (0..pools.len())
+        .map(matched)
+        .find(|used| {
+            used.iter()
+                .zip(deposits)
+                .all(|(used, deposit)| used <= deposit)
+        })
+        .unwrap_or_else(|| deposits.to_vec())
+}
 
-    Ok(resp)
+/// Formats amounts of pool assets for attributes
+pub fn format_amounts(amounts: &[Uint128], pools: &[Asset]) -> String {
+    amounts
+        .iter()
+        .zip(pools)
+        .map(|(amount, pool)| format!("{}{}", amount, pool.info))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Formats assets for attributes
+fn format_assets(assets: &[Asset]) -> String {
+    assets
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Builds messages collecting `used` part of deposits from `sender`. Cw20 tokens are pulled with
+/// `TransferFrom` of the used amount only, while native tokens are already sent with the message,
+/// so their excess is refunded.
+pub fn collect_deposits(
+    env: &Env,
+    sender: &Addr,
+    pools: &[Asset],
+    deposits: &[Uint128],
+    used: &[Uint128],
+) -> StdResult<Vec<CosmosMsg>> {
+    let mut msgs = vec![];
+    for (i, pool) in pools.iter().enumerate() {
+        match &pool.info {
+            AssetInfo::Token(contract_addr) => {
+                if !used[i].is_zero() {
+                    msgs.push(
+                        WasmMsg::Execute {
+                            contract_addr: contract_addr.to_string(),
+                            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                                owner: sender.to_string(),
+                                recipient: env.contract.address.to_string(),
+                                amount: used[i],
+                            })?,
+                            funds: vec![],
+                        }
+                        .into(),
+                    );
+                }
+            }
+            AssetInfo::Native(_) => {
+                let refund = deposits[i] - used[i];
+                if !refund.is_zero() {
+                    let refund = Asset {
+                        info: pool.info.clone(),
+                        amount: refund,
+                    };
+                    msgs.push(refund.into_msg(sender.clone())?);
+                }
+            }
+        }
+    }
+    Ok(msgs)
+}
+
+/// Mints `MINIMUM_LIQUIDITY` to the pair itself. Has to be done on the first deposit.
+pub fn lock_minimum_liquidity(pair_info: &PairInfo, env: &Env) -> StdResult<WasmMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: pair_info.liquidity_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Mint {
+            recipient: env.contract.address.to_string(),
+            amount: MINIMUM_LIQUIDITY,
+        })?,
+        funds: vec![],
+    })
+}
+
+pub fn withdraw_liquidity(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    sender: Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    assert_whitelisted(deps.as_ref(), &pair_info, &[&sender])?;
+
+    let pools = load_pools(deps.storage, &pair_info)?;
+    let total_share: Uint128 = query_supply(&deps.querier, pair_info.liquidity_token.clone())?;
+
+    let refund_assets: Vec<Asset> = pools
+        .iter()
+        .map(|a| Asset {
+            info: a.info.clone(),
+            amount: a.amount.multiply_ratio(amount, total_share),
+        })
+        .collect();
+
+    let reserves = pools
+        .iter()
+        .zip(refund_assets.iter())
+        .map(|(pool, refund)| pool.amount.checked_sub(refund.amount))
+        .collect::<Result<Vec<_>, _>>()?;
+    save_reserves(deps.storage, &reserves)?;
+
+    let refund_msgs = refund_assets
+        .iter()
+        .map(|refund| refund.clone().into_msg(sender.clone()))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    // update pool info
+    let res = Response::new()
+        // refund asset tokens
+        .add_messages(refund_msgs)
+        // burn liquidity token
+        .add_message(WasmMsg::Execute {
+            contract_addr: pair_info.liquidity_token.into(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+            funds: vec![],
+        })
+        .add_attribute("action", "withdraw_liquidity")
+        .add_attribute("withdrawn_share", amount.to_string())
+        .add_attribute("refund_assets", format_assets(&refund_assets));
+    Ok(res)
+}
+
+/// Withdraws liquidity paid out in `ask_asset` only. Withdrawn share of the other asset is swapped
+/// to `ask_asset` inside the pair.
+pub fn withdraw_single_sided(
+    mut deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    amount: Uint128,
+    ask_asset: AssetInfo,
+    min_return: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    assert_whitelisted(deps.as_ref(), &pair_info, &[&sender])?;
+    let pools = load_pools(deps.storage, &pair_info)?;
+    let total_share: Uint128 = query_supply(&deps.querier, pair_info.liquidity_token.clone())?;
+
+    let ask_idx = pools
+        .iter()
+        .position(|pool| ask_asset.equal(&pool.info))
+        .ok_or_else(|| ContractError::AssetMismatch(ask_asset.to_string()))?;
+
+    let commission = trader_commission(deps.as_ref(), &pair_info, Some(&sender))?;
+    let (withdrawn, return_amount, spread_amount, commission_amount) =
+        compute_withdraw_single_sided(&pools, total_share, amount, ask_idx, commission)?;
+
+    assert_min_return(return_amount, min_return)?;
+
+    // withdrawn other asset is swapped back in, so only the ask pool changes
+    let protocol_fee_amount = accrue_protocol_fee(deps.storage, ask_idx, commission_amount)?;
+    let mut reserves = [pools[0].amount, pools[1].amount];
+    reserves[ask_idx] = pools[ask_idx]
+        .amount
+        .checked_sub(return_amount)?
+        .checked_sub(protocol_fee_amount)?;
+    update_reserves(deps.storage, &env, &reserves)?;
+
+    // as in single sided provision, orders crossed by the internal swap are filled
+    let fill_events = fill_limit_orders(deps.branch(), &env, &pair_info, ask_idx)?;
+
+    let return_asset = Asset {
+        info: ask_asset,
+        amount: return_amount,
+    };
+
+    let res = Response::new()
+        .add_message(return_asset.clone().into_msg(sender)?)
+        // burn liquidity token
+        .add_message(WasmMsg::Execute {
+            contract_addr: pair_info.liquidity_token.into(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+            funds: vec![],
+        })
+        .add_attribute("action", "withdraw_single_sided")
+        .add_attribute("withdrawn_share", amount.to_string())
+        .add_attribute(
+            "withdrawn_assets",
+            format!(
+                "{}{}, {}{}",
+                withdrawn[0], pools[0].info, withdrawn[1], pools[1].info
+            ),
+        )
+        .add_attribute("refund_asset", return_asset.to_string())
+        .add_attribute("spread_amount", spread_amount.to_string())
+        .add_attribute("commission_amount", commission_amount.to_string())
+        .add_attribute("protocol_fee_amount", protocol_fee_amount.to_string())
+        .add_events(fill_events);
+    Ok(res)
+}
+
+/// Computes withdrawal of `share` paid out in the asset with index `ask_idx` only. Pro rata
+/// withdrawn other asset is swapped on pools already reduced by the withdrawal.
+///
+/// Returns pro rata withdrawn assets, total amount to be paid out, and spread and commission of
+/// the swap.
+fn compute_withdraw_single_sided(
+    pools: &[Asset],
+    total_share: Uint128,
+    share: Uint128,
+    ask_idx: usize,
+    commission: Decimal,
+) -> StdResult<([Uint128; 2], Uint128, Uint128, Uint128)> {
+    let withdrawn = [
+        pools[0].amount.multiply_ratio(share, total_share),
+        pools[1].amount.multiply_ratio(share, total_share),
+    ];
+
+    let offer_idx = 1 - ask_idx;
+    let (swap_return, spread_amount, commission_amount) = compute_swap(
+        pools[offer_idx].amount.checked_sub(withdrawn[offer_idx])?,
+        pools[ask_idx].amount.checked_sub(withdrawn[ask_idx])?,
+        withdrawn[offer_idx],
+        commission,
+    )?;
+
+    Ok((
+        withdrawn,
+        withdrawn[ask_idx].checked_add(swap_return)?,
+        spread_amount,
+        commission_amount,
+    ))
+}
+
+// CONTRACT - a user must do token approval
+#[allow(clippy::too_many_arguments)]
+pub fn swap(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    ask_asset_info: Option<AssetInfo>,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    min_return: Option<Uint128>,
+    to: Option<Addr>,
+    to_msg: Option<Binary>,
+) -> Result<Response, ContractError> {
+    offer_asset.assert_sent_native_token_balance(&info)?;
+
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    let receiver = to.unwrap_or_else(|| sender.clone());
+    assert_whitelisted(deps.as_ref(), &pair_info, &[&sender, &receiver])?;
+
+    let pools = load_pools(deps.storage, &pair_info)?;
+
+    let (offer_idx, ask_idx) = swap_idx(
+        &pair_info.asset_infos,
+        &offer_asset.info,
+        ask_asset_info.as_ref(),
+    )?;
+    let offer_pool = pools[offer_idx].clone();
+    let ask_pool = pools[ask_idx].clone();
+
+    let offer_amount = offer_asset.amount;
+    let commission = trader_commission(deps.as_ref(), &pair_info, Some(&sender))?;
+    let (return_amount, spread_amount, commission_amount) =
+        compute_swap(offer_pool.amount, ask_pool.amount, offer_amount, commission)?;
+
+    // check max spread limit if exist
+    assert_max_spread(
+        belief_price,
+        max_spread,
+        offer_amount,
+        return_amount + commission_amount,
+        spread_amount,
+    )?;
+    assert_min_return(return_amount, min_return)?;
+
+    // commission is not paid out, so it stays in the ask pool - except of the protocol fee part
+    let protocol_fee_amount = accrue_protocol_fee(deps.storage, ask_idx, commission_amount)?;
+    let mut reserves = [pools[0].amount, pools[1].amount];
+    reserves[offer_idx] = offer_pool.amount.checked_add(offer_amount)?;
+    reserves[ask_idx] = ask_pool
+        .amount
+        .checked_sub(return_amount)?
+        .checked_sub(protocol_fee_amount)?;
+    update_reserves(deps.storage, &env, &reserves)?;
+
+    // the swap made the ask asset more expensive, so orders selling it may be crossed now
+    let fill_events = fill_limit_orders(deps.branch(), &env, &pair_info, ask_idx)?;
+
+    let return_msg = return_msg(
+        Asset {
+            info: ask_pool.info.clone(),
+            amount: return_amount,
+        },
+        receiver,
+        to_msg,
+    )?;
+
+    // 1. send collateral token from the contract to a user
+    // 2. send inactive commission to collector
+    let res = Response::new()
+        .add_attribute("action", "swap")
+        .add_attribute("offer_asset", offer_asset.info.to_string())
+        .add_attribute("ask_asset", ask_pool.info.to_string())
+        .add_attribute("offer_amount", offer_amount.to_string())
+        .add_attribute("return_amount", return_amount.to_string())
+        .add_attribute("spread_amount", spread_amount.to_string())
+        .add_attribute("commission_amount", commission_amount.to_string())
+        .add_attribute("protocol_fee_amount", protocol_fee_amount.to_string())
+        .add_message(return_msg)
+        .add_events(fill_events);
+    Ok(res)
+}
+
+/// Builds message paying out swap return to `recipient`, executing `to_msg` on it if given
+pub fn return_msg(asset: Asset, recipient: Addr, to_msg: Option<Binary>) -> StdResult<CosmosMsg> {
+    match to_msg {
+        Some(msg) => asset.into_send_msg(recipient, msg),
+        None => asset.into_msg(recipient),
+    }
+}
+
+/// Swaps offer asset for exactly `ask_amount` of the other asset. `offer_asset` is the maximum
+/// offer, the part of it not required for the swap is refunded to the sender.
+#[allow(clippy::too_many_arguments)]
+pub fn swap_exact_out(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    ask_asset_info: Option<AssetInfo>,
+    ask_amount: Uint128,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    offer_asset.assert_sent_native_token_balance(&info)?;
+    if ask_amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    let receiver = to.unwrap_or_else(|| sender.clone());
+    assert_whitelisted(deps.as_ref(), &pair_info, &[&sender, &receiver])?;
+    let pools = load_pools(deps.storage, &pair_info)?;
+
+    let (offer_idx, ask_idx) = swap_idx(
+        &pair_info.asset_infos,
+        &offer_asset.info,
+        ask_asset_info.as_ref(),
+    )?;
+    let (offer_pool, ask_pool) = (pools[offer_idx].amount, &pools[ask_idx]);
+
+    let commission = trader_commission(deps.as_ref(), &pair_info, Some(&sender))?;
+    let (offer_amount, spread_amount, commission_amount) =
+        compute_offer_amount(offer_pool, ask_pool.amount, ask_amount, commission)?;
+
+    if offer_amount > offer_asset.amount {
+        return Err(ContractError::MaxOfferAssertion {
+            offer_amount,
+            max_offer: offer_asset.amount,
+        });
+    }
+
+    // commission stays in the ask pool as in `swap`
+    let protocol_fee_amount = accrue_protocol_fee(deps.storage, ask_idx, commission_amount)?;
+    let mut reserves = [pools[0].amount, pools[1].amount];
+    reserves[offer_idx] = offer_pool.checked_add(offer_amount)?;
+    reserves[ask_idx] = ask_pool
+        .amount
+        .checked_sub(ask_amount)?
+        .checked_sub(protocol_fee_amount)?;
+    update_reserves(deps.storage, &env, &reserves)?;
+
+    let fill_events = fill_limit_orders(deps.branch(), &env, &pair_info, ask_idx)?;
+
+    let return_msg = Asset {
+        info: ask_pool.info.clone(),
+        amount: ask_amount,
+    }
+    .into_msg(receiver)?;
+
+    let refund_amount = offer_asset.amount - offer_amount;
+    let mut res = Response::new()
+        .add_attribute("action", "swap_exact_out")
+        .add_attribute("offer_asset", offer_asset.info.to_string())
+        .add_attribute("ask_asset", ask_pool.info.to_string())
+        .add_attribute("offer_amount", offer_amount.to_string())
+        .add_attribute("return_amount", ask_amount.to_string())
+        .add_attribute("spread_amount", spread_amount.to_string())
+        .add_attribute("commission_amount", commission_amount.to_string())
+        .add_attribute("protocol_fee_amount", protocol_fee_amount.to_string())
+        .add_attribute("refund_amount", refund_amount.to_string())
+        .add_message(return_msg)
+        .add_events(fill_events);
+
+    if !refund_amount.is_zero() {
+        let refund = Asset {
+            info: offer_asset.info,
+            amount: refund_amount,
+        };
+        res = res.add_message(refund.into_msg(sender)?);
+    }
+
+    Ok(res)
+}
+
+/// Sends `ask_asset` to the sender and executes `FlashSwapCallback` on it. Repayment is verified
+/// in `reply` once the callback is finished.
+pub fn flash_swap(
+    deps: DepsMut,
+    info: MessageInfo,
+    ask_asset: Asset,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    if ask_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    assert_whitelisted(deps.as_ref(), &pair_info, &[&info.sender])?;
+    let pools = load_pools(deps.storage, &pair_info)?;
+
+    let ask_idx = pools
+        .iter()
+        .position(|pool| ask_asset.info.equal(&pool.info))
+        .ok_or_else(|| ContractError::AssetMismatch(ask_asset.info.to_string()))?;
+    // pool can't be drained completely, constant product could never be restored then
+    if ask_asset.amount >= pools[ask_idx].amount {
+        return Err(ContractError::InsufficientLiquidity {
+            pool: pools[ask_idx].amount,
+            requested: ask_asset.amount,
+        });
+    }
+
+    let mut borrowed = [Uint128::zero(); 2];
+    borrowed[ask_idx] = ask_asset.amount;
+    FLASH_SWAP.save(
+        deps.storage,
+        &PendingFlashSwap {
+            reserves: [pools[0].amount, pools[1].amount],
+            borrowed,
+            trader: info.sender.clone(),
+        },
+    )?;
+
+    let borrowed = Asset {
+        info: pools[ask_idx].info.clone(),
+        amount: ask_asset.amount,
+    };
+    let callback = FlashSwapCallback {
+        borrowed: borrowed.clone(),
+        msg,
+    }
+    .into_cosmos_msg(&info.sender)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "flash_swap")
+        .add_attribute("ask_asset", borrowed.info.to_string())
+        .add_attribute("return_amount", borrowed.amount.to_string())
+        .add_message(borrowed.into_msg(info.sender)?)
+        .add_submessage(SubMsg::reply_on_success(callback, FLASH_SWAP_REPLY_ID)))
+}
+
+/// Places limit order selling `offer_asset`, which has to be already transferred to the pair. The
+/// order is filled right away if the pool price already crossed `price`.
+pub fn place_limit_order(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    price: Decimal,
+) -> Result<Response, ContractError> {
+    offer_asset.assert_sent_native_token_balance(&info)?;
+    if offer_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    if price.is_zero() {
+        return Err(ContractError::InvalidLimitOrderPrice {});
+    }
+
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    assert_whitelisted(deps.as_ref(), &pair_info, &[&sender])?;
+    let offer_idx = pair_info
+        .asset_infos
+        .iter()
+        .position(|info| offer_asset.info.equal(info))
+        .ok_or_else(|| ContractError::AssetMismatch(offer_asset.info.to_string()))?;
+
+    let order_id = LAST_LIMIT_ORDER_ID.update(deps.storage, |id| -> StdResult<_> { Ok(id + 1) })?;
+    let order = LimitOrder {
+        owner: sender.clone(),
+        offer_idx: offer_idx as u8,
+        offer_amount: offer_asset.amount,
+        price,
+        filled_amount: Uint128::zero(),
+        return_amount: Uint128::zero(),
+    };
+    LIMIT_ORDERS.save(deps.storage, order_id, &order)?;
+    OWNER_LIMIT_ORDERS.save(deps.storage, (&sender, order_id), &Empty {})?;
+    ORDER_BOOK.save(deps.storage, order.book_key(order_id), &Empty {})?;
+    ESCROWED.update(deps.storage, |mut escrowed| -> StdResult<_> {
+        escrowed[offer_idx] = escrowed[offer_idx].checked_add(offer_asset.amount)?;
+        Ok(escrowed)
+    })?;
+
+    let fill_events = fill_limit_orders(deps.branch(), &env, &pair_info, offer_idx)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "place_limit_order")
+        .add_attribute("order_id", order_id.to_string())
+        .add_attribute("offer_asset", offer_asset.to_string())
+        .add_attribute(
+            "ask_asset",
+            pair_info.asset_infos[1 - offer_idx].to_string(),
+        )
+        .add_attribute("price", price.to_string())
+        .add_events(fill_events))
+}
+
+/// Cancels the order and refunds its unfilled offer to the owner
+pub fn cancel_limit_order(
+    deps: DepsMut,
+    info: MessageInfo,
+    order_id: u64,
+) -> Result<Response, ContractError> {
+    let order = LIMIT_ORDERS.load(deps.storage, order_id)?;
+    if order.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    let offer_idx = order.offer_idx as usize;
+    remove_limit_order(deps.storage, order_id, &order);
+    ESCROWED.update(deps.storage, |mut escrowed| -> StdResult<_> {
+        escrowed[offer_idx] = escrowed[offer_idx].checked_sub(order.offer_amount)?;
+        Ok(escrowed)
+    })?;
+
+    let refund = Asset {
+        info: pair_info.asset_infos[offer_idx].clone(),
+        amount: order.offer_amount,
+    };
+
+    Ok(Response::new()
+        .add_attribute("action", "cancel_limit_order")
+        .add_attribute("order_id", order_id.to_string())
+        .add_attribute("refund_asset", refund.to_string())
+        .add_message(refund.into_msg(order.owner)?))
+}
+
+fn remove_limit_order(storage: &mut dyn Storage, order_id: u64, order: &LimitOrder) {
+    LIMIT_ORDERS.remove(storage, order_id);
+    OWNER_LIMIT_ORDERS.remove(storage, (&order.owner, order_id));
+    ORDER_BOOK.remove(storage, order.book_key(order_id));
+}
+
+/// Fills orders selling the asset with index `offer_idx` against the pool, best priced first.
+/// Every order is swapped in the same way as `swap`, as far as its average return stays at or
+/// above the order price, so filling stops at the first order which is not filled entirely, or
+/// after `MAX_LIMIT_ORDER_FILLS` fills.
+///
+/// Returns are not sent to order owners, but credited to their `LIMIT_ORDER_PROCEEDS` - so an
+/// owner unable to receive them cannot make swaps fail. Returns events of fills with the same
+/// attributes as `swap`.
+pub fn fill_limit_orders(
+    deps: DepsMut,
+    env: &Env,
+    pair_info: &PairInfo,
+    offer_idx: usize,
+) -> Result<Vec<Event>, ContractError> {
+    let ask_idx = 1 - offer_idx;
+    let mut reserves = RESERVES.load(deps.storage)?;
+    let mut escrowed = ESCROWED.load(deps.storage)?;
+    let mut events = vec![];
+
+    while events.len() < MAX_LIMIT_ORDER_FILLS {
+        let best = ORDER_BOOK
+            .sub_prefix(offer_idx as u8)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .next()
+            .transpose()?;
+        let order_id = match best {
+            Some((_, order_id)) => order_id,
+            None => break,
+        };
+
+        let mut order = LIMIT_ORDERS.load(deps.storage, order_id)?;
+        let commission = trader_commission(deps.as_ref(), pair_info, Some(&order.owner))?;
+        let capacity = limit_order_capacity(
+            reserves[offer_idx],
+            reserves[ask_idx],
+            order.price,
+            commission,
+        )?;
+        let fill_amount = std::cmp::min(order.offer_amount, capacity);
+        if fill_amount.is_zero() {
+            break;
+        }
+
+        let (return_amount, spread_amount, commission_amount) = compute_swap(
+            reserves[offer_idx],
+            reserves[ask_idx],
+            fill_amount,
+            commission,
+        )?;
+        // capacity is exact, so rounding could only make the fill worse than the order price
+        if return_amount < fill_amount * order.price {
+            break;
+        }
+
+        let protocol_fee_amount = accrue_protocol_fee(deps.storage, ask_idx, commission_amount)?;
+        reserves[offer_idx] = reserves[offer_idx].checked_add(fill_amount)?;
+        reserves[ask_idx] = reserves[ask_idx]
+            .checked_sub(return_amount)?
+            .checked_sub(protocol_fee_amount)?;
+        escrowed[offer_idx] = escrowed[offer_idx].checked_sub(fill_amount)?;
+        escrowed[ask_idx] = escrowed[ask_idx].checked_add(return_amount)?;
+
+        order.offer_amount -= fill_amount;
+        order.filled_amount = order.filled_amount.checked_add(fill_amount)?;
+        order.return_amount = order.return_amount.checked_add(return_amount)?;
+
+        LIMIT_ORDER_PROCEEDS.update(deps.storage, &order.owner, |proceeds| -> StdResult<_> {
+            let mut proceeds = proceeds.unwrap_or_default();
+            proceeds[ask_idx] = proceeds[ask_idx].checked_add(return_amount)?;
+            Ok(proceeds)
+        })?;
+        events.push(
+            Event::new("limit_order_fill")
+                .add_attribute("action", "swap")
+                .add_attribute("offer_asset", pair_info.asset_infos[offer_idx].to_string())
+                .add_attribute("ask_asset", pair_info.asset_infos[ask_idx].to_string())
+                .add_attribute("offer_amount", fill_amount.to_string())
+                .add_attribute("return_amount", return_amount.to_string())
+                .add_attribute("spread_amount", spread_amount.to_string())
+                .add_attribute("commission_amount", commission_amount.to_string())
+                .add_attribute("protocol_fee_amount", protocol_fee_amount.to_string())
+                .add_attribute("order_id", order_id.to_string())
+                .add_attribute("owner", order.owner.to_string()),
+        );
+
+        if order.offer_amount.is_zero() {
+            remove_limit_order(deps.storage, order_id, &order);
+        } else {
+            LIMIT_ORDERS.save(deps.storage, order_id, &order)?;
+            break;
+        }
+    }
+
+    if !events.is_empty() {
+        update_reserves(deps.storage, env, &reserves)?;
+        ESCROWED.save(deps.storage, &escrowed)?;
+    }
+    Ok(events)
+}
+
+/// Pays out returns of filled orders of the sender
+pub fn claim_limit_order_proceeds(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let proceeds = LIMIT_ORDER_PROCEEDS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::NoLimitOrderProceeds {})?;
+    LIMIT_ORDER_PROCEEDS.remove(deps.storage, &info.sender);
+    ESCROWED.update(deps.storage, |mut escrowed| -> StdResult<_> {
+        for (escrowed, proceeds) in escrowed.iter_mut().zip(proceeds) {
+            *escrowed = escrowed.checked_sub(proceeds)?;
+        }
+        Ok(escrowed)
+    })?;
+
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    let assets: Vec<_> = pair_info
+        .asset_infos
+        .into_iter()
+        .zip(proceeds)
+        .map(|(info, amount)| Asset { info, amount })
+        .filter(|asset| !asset.amount.is_zero())
+        .collect();
+    let msgs = assets
+        .iter()
+        .map(|asset| asset.clone().into_msg(info.sender.clone()))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(Response::new()
+        .add_attribute("action", "claim_limit_order_proceeds")
+        .add_attribute(
+            "proceeds",
+            assets
+                .iter()
+                .map(Asset::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+        .add_messages(msgs))
+}
+
+/// Largest offer which can be swapped for at least `price` per unit on average, after commission.
+/// Average return of `x` offered is `ask_pool * (1 - commission) / (offer_pool + x)`, so it is
+/// `ask_pool * (1 - commission) / price - offer_pool`.
+fn limit_order_capacity(
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    price: Decimal,
+    commission: Decimal,
+) -> StdResult<Uint128> {
+    if offer_pool.is_zero() || ask_pool.is_zero() {
+        return Ok(Uint128::zero());
+    }
+
+    let net_ask_pool = Uint256::from(ask_pool) * decimal256(Decimal::one() - commission);
+    let capacity = net_ask_pool
+        .multiply_ratio(Decimal::one().atomics(), price.atomics())
+        .saturating_sub(offer_pool.into());
+    Ok(capacity.try_into().unwrap_or(Uint128::MAX))
+}
+
+/// Validates address of tg4 group contract
+pub fn validate_group(deps: Deps, addr: &str) -> StdResult<Addr> {
+    let addr = deps.api.addr_validate(addr)?;
+    // verify that the group contract is actually tg4-compatible
+    Tg4Contract(addr.clone()).list_members(&deps.querier, None, Some(1))?;
+    Ok(addr)
+}
+
+/// Validates commission tiers and stores them sorted by `min_points`, together with the group
+/// their points are taken from
+pub fn save_commission_tiers(
+    deps: DepsMut,
+    commission_group: Option<String>,
+    mut commission_tiers: Vec<CommissionTier>,
+) -> Result<(), ContractError> {
+    let group = match commission_group {
+        Some(group) => group,
+        None if commission_tiers.is_empty() => return Ok(()),
+        None => return Err(ContractError::MissingCommissionGroup {}),
+    };
+
+    commission_tiers.sort_by_key(|tier| tier.min_points);
+    if let Some(tier) = commission_tiers
+        .iter()
+        .find(|tier| !(Decimal::zero()..=Decimal::one()).contains(&tier.commission))
+    {
+        return Err(ContractError::InvalidCommission(tier.commission));
+    }
+    if let Some(tiers) = commission_tiers
+        .windows(2)
+        .find(|tiers| tiers[0].min_points == tiers[1].min_points)
+    {
+        return Err(ContractError::DuplicateCommissionTier(tiers[0].min_points));
+    }
+
+    let group = validate_group(deps.as_ref(), &group)?;
+    COMMISSION_TIERS.save(
+        deps.storage,
+        &CommissionTiers {
+            group,
+            tiers: commission_tiers,
+        },
+    )?;
+    Ok(())
+}
+
+/// Fails if the pair has a whitelist group and any of `addrs` is not its member
+pub fn assert_whitelisted(
+    deps: Deps,
+    pair_info: &PairInfo,
+    addrs: &[&Addr],
+) -> Result<(), ContractError> {
+    if let Some(group) = &pair_info.whitelist_group {
+        let whitelist = Tg4Contract(group.clone());
+        for addr in addrs {
+            if whitelist.is_member(&deps.querier, addr)?.is_none() {
+                return Err(ContractError::NotWhitelisted(addr.to_string()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns swap commission of `trader` - the one of the highest tier reached by its points in the
+/// commission group, or the pair commission if it reaches none (or is not given)
+pub fn trader_commission(
+    deps: Deps,
+    pair_info: &PairInfo,
+    trader: Option<&Addr>,
+) -> StdResult<Decimal> {
+    let (trader, commission_tiers) = match (trader, COMMISSION_TIERS.may_load(deps.storage)?) {
+        (Some(trader), Some(commission_tiers)) => (trader, commission_tiers),
+        _ => return Ok(pair_info.commission),
+    };
+
+    let points = Tg4Contract(commission_tiers.group.clone()).is_member(&deps.querier, trader)?;
+    let tier = points.and_then(|points| {
+        commission_tiers
+            .tiers
+            .iter()
+            .rev()
+            .find(|tier| tier.min_points <= points)
+    });
+    Ok(tier.map_or(pair_info.commission, |tier| tier.commission))
+}
+
+/// Verifies the flash swap was repaid, failing (and so reverting the flash swap) otherwise
+///
+/// Everything the pair holds above reserves left after borrowing is considered repaid. The
+/// commission is charged on repaid amounts, Uniswap v2 style: pools with the commission part of
+/// repaid amounts excluded have to keep the constant product from before the flash swap.
+fn flash_swap_repay(mut deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let flash_swap = FLASH_SWAP.load(deps.storage)?;
+    FLASH_SWAP.remove(deps.storage);
+
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    let fees = ACCRUED_FEES.load(deps.storage)?;
+    let escrowed = ESCROWED.load(deps.storage)?;
+    let balances = pair_info.query_pools(&deps.querier, env.contract.address.clone())?;
+    let commission = trader_commission(deps.as_ref(), &pair_info, Some(&flash_swap.trader))?;
+
+    let mut pools = [Uint128::zero(); 2];
+    let mut repaid = [Uint128::zero(); 2];
+    let mut commission_amounts = [Uint128::zero(); 2];
+    for idx in 0..2 {
+        // accrued protocol fees and escrowed orders are held by the pair, but are not part of
+        // pools
+        pools[idx] = balances[idx]
+            .amount
+            .saturating_sub(fees[idx] + escrowed[idx]);
+        let left = flash_swap.reserves[idx].checked_sub(flash_swap.borrowed[idx])?;
+        repaid[idx] = pools[idx].saturating_sub(left);
+        commission_amounts[idx] = repaid[idx] * commission;
+    }
+
+    let k = Uint256::from(flash_swap.reserves[0]) * Uint256::from(flash_swap.reserves[1]);
+    let adjusted_k = Uint256::from(pools[0] - commission_amounts[0])
+        * Uint256::from(pools[1] - commission_amounts[1]);
+    if adjusted_k < k {
+        return Err(ContractError::FlashSwapNotRepaid {});
+    }
+
+    let mut protocol_fee_amounts = [Uint128::zero(); 2];
+    for idx in 0..2 {
+        protocol_fee_amounts[idx] =
+            accrue_protocol_fee(deps.storage, idx, commission_amounts[idx])?;
+        pools[idx] = pools[idx].checked_sub(protocol_fee_amounts[idx])?;
+    }
+    update_reserves(deps.storage, &env, &pools)?;
+
+    // repayment may be in either asset, so the price may move either way - orders which are not
+    // crossed are left untouched
+    let mut fill_events = fill_limit_orders(deps.branch(), &env, &pair_info, 0)?;
+    fill_events.extend(fill_limit_orders(deps.branch(), &env, &pair_info, 1)?);
+
+    Ok(Response::new()
+        .add_attribute("action", "flash_swap_repay")
+        .add_attribute(
+            "repaid_assets",
+            format!(
+                "{}{}, {}{}",
+                repaid[0], pair_info.asset_infos[0], repaid[1], pair_info.asset_infos[1]
+            ),
+        )
+        .add_attribute(
+            "commission_amount",
+            format!("{}, {}", commission_amounts[0], commission_amounts[1]),
+        )
+        .add_attribute(
+            "protocol_fee_amount",
+            format!("{}, {}", protocol_fee_amounts[0], protocol_fee_amounts[1]),
+        )
+        .add_events(fill_events))
+}
+
+/// Moves the protocol fee part of `commission_amount` of the asset with index `asset_idx` to
+/// accrued fees. Returns moved amount.
+pub fn accrue_protocol_fee(
+    storage: &mut dyn Storage,
+    asset_idx: usize,
+    commission_amount: Uint128,
+) -> StdResult<Uint128> {
+    let protocol_fee = PROTOCOL_FEE.load(storage)?;
+    let amount = commission_amount * protocol_fee.share;
+
+    if !amount.is_zero() {
+        ACCRUED_FEES.update(storage, |mut fees| -> StdResult<_> {
+            fees[asset_idx] = fees[asset_idx].checked_add(amount)?;
+            Ok(fees)
+        })?;
+    }
+
+    Ok(amount)
+}
+
+/// Sends all accrued protocol fees to the fee collector
+pub fn collect_fees(deps: DepsMut) -> Result<Response, ContractError> {
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    let fee_collector = PROTOCOL_FEE
+        .load(deps.storage)?
+        .fee_collector
+        .ok_or(ContractError::MissingFeeCollector {})?;
+
+    let fees = load_accrued_fees(deps.storage, &pair_info)?;
+    ACCRUED_FEES.save(deps.storage, &vec![Uint128::zero(); fees.len()])?;
+
+    let msgs = fees
+        .iter()
+        .filter(|fee| !fee.amount.is_zero())
+        .map(|fee| fee.clone().into_msg(fee_collector.clone()))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attribute("action", "collect_fees")
+        .add_attribute("fee_collector", fee_collector)
+        .add_attribute("collected_fees", format_assets(&fees)))
+}
+
+/// Pair operations which can be paused by the pair admin
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operation {
+    Swaps,
+    Deposits,
+    Withdrawals,
+}
+
+impl Operation {
+    fn is_paused(self, paused: &PauseState) -> bool {
+        match self {
+            Operation::Swaps => paused.swaps,
+            Operation::Deposits => paused.deposits,
+            Operation::Withdrawals => paused.withdrawals,
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operation::Swaps => write!(f, "swaps"),
+            Operation::Deposits => write!(f, "deposits"),
+            Operation::Withdrawals => write!(f, "withdrawals"),
+        }
+    }
+}
+
+/// Fails if any of `operations` is paused on the pair
+pub fn assert_not_paused(
+    storage: &dyn Storage,
+    operations: &[Operation],
+) -> Result<(), ContractError> {
+    let paused = PAIR_INFO.load(storage)?.paused;
+    match operations.iter().find(|op| op.is_paused(&paused)) {
+        Some(op) => Err(ContractError::Paused(*op)),
+        None => Ok(()),
+    }
+}
+
+// Only admin can execute it
+pub fn update_pause(
+    deps: DepsMut,
+    info: MessageInfo,
+    swaps: Option<bool>,
+    deposits: Option<bool>,
+    withdrawals: Option<bool>,
+) -> Result<Response, ContractError> {
+    let mut pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    if pair_info.admin.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let paused = &mut pair_info.paused;
+    paused.swaps = swaps.unwrap_or(paused.swaps);
+    paused.deposits = deposits.unwrap_or(paused.deposits);
+    paused.withdrawals = withdrawals.unwrap_or(paused.withdrawals);
+    PAIR_INFO.save(deps.storage, &pair_info)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_pause")
+        .add_attribute("swaps_paused", pair_info.paused.swaps.to_string())
+        .add_attribute("deposits_paused", pair_info.paused.deposits.to_string())
+        .add_attribute(
+            "withdrawals_paused",
+            pair_info.paused.withdrawals.to_string(),
+        ))
+}
+
+// Only admin can execute it
+pub fn update_commission(
+    deps: DepsMut,
+    info: MessageInfo,
+    commission: Decimal,
+) -> Result<Response, ContractError> {
+    let mut pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    if pair_info.admin.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !(Decimal::zero()..=Decimal::one()).contains(&commission) {
+        return Err(ContractError::InvalidCommission(commission));
+    }
+
+    pair_info.commission = commission;
+    PAIR_INFO.save(deps.storage, &pair_info)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_commission")
+        .add_attribute("commission", commission.to_string()))
+}
+
+/// Builds assets from accrued protocol fees
+fn load_accrued_fees(storage: &dyn Storage, pair_info: &PairInfo) -> StdResult<Vec<Asset>> {
+    let fees = ACCRUED_FEES.load(storage)?;
+    Ok(assets_of(pair_info, &fees))
+}
+
+/// Builds assets of `amounts` given in the order of `PAIR_INFO.asset_infos`
+fn assets_of(pair_info: &PairInfo, amounts: &[Uint128]) -> Vec<Asset> {
+    pair_info
+        .asset_infos
+        .iter()
+        .zip(amounts)
+        .map(|(info, amount)| Asset {
+            info: info.clone(),
+            amount: *amount,
+        })
+        .collect()
+}
+
+/// Updates stored reserves to match actual pair balances, so tokens sent directly to the pair
+/// are added to pools
+pub fn sync(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    let fees = ACCRUED_FEES.load(deps.storage)?;
+    let escrowed = ESCROWED.load(deps.storage)?;
+    let balances = pair_info.query_pools(&deps.querier, env.contract.address.clone())?;
+
+    // accrued protocol fees and escrowed orders are held by the pair, but are not part of pools
+    let reserves: Vec<Asset> = balances
+        .iter()
+        .zip(fees.iter().zip(escrowed.iter()))
+        .map(|(balance, (fee, escrowed))| Asset {
+            info: balance.info.clone(),
+            amount: balance.amount.saturating_sub(*fee + *escrowed),
+        })
+        .collect();
+
+    let amounts: Vec<Uint128> = reserves.iter().map(|reserve| reserve.amount).collect();
+    save_reserves(deps.storage, &amounts)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "sync")
+        .add_attribute("reserves", format_assets(&reserves)))
+}
+
+/// Sends pair balances exceeding stored reserves to `to`
+pub fn skim(deps: DepsMut, env: Env, to: Addr) -> Result<Response, ContractError> {
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    let reserves = RESERVES.load(deps.storage)?;
+    let fees = ACCRUED_FEES.load(deps.storage)?;
+    let escrowed = ESCROWED.load(deps.storage)?;
+    let balances = pair_info.query_pools(&deps.querier, env.contract.address)?;
+
+    // accrued protocol fees and escrowed orders are not excess, they are waiting for collection
+    // and fills
+    let excess: Vec<Asset> = balances
+        .iter()
+        .enumerate()
+        .map(|(idx, balance)| Asset {
+            info: balance.info.clone(),
+            amount: balance
+                .amount
+                .saturating_sub(reserves[idx] + fees[idx] + escrowed[idx]),
+        })
+        .collect();
+
+    let msgs = excess
+        .iter()
+        .filter(|asset| !asset.amount.is_zero())
+        .map(|asset| asset.clone().into_msg(to.clone()))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attribute("action", "skim")
+        .add_attribute("to", to)
+        .add_attribute("skimmed_assets", format_assets(&excess)))
+}
+
+/// Builds pools from stored reserves
+pub fn load_pools(storage: &dyn Storage, pair_info: &PairInfo) -> StdResult<Vec<Asset>> {
+    let reserves = RESERVES.load(storage)?;
+    Ok(assets_of(pair_info, &reserves))
+}
+
+/// Stores new reserves of a constant product pair. Prices are accumulated for the time previous
+/// reserves were valid first.
+pub fn update_reserves(
+    storage: &mut dyn Storage,
+    env: &Env,
+    reserves: &[Uint128],
+) -> StdResult<()> {
+    accumulate_prices(storage, env)?;
+    save_reserves(storage, reserves)
+}
+
+/// Accumulates constant product prices for the time stored reserves were valid
+pub fn accumulate_prices(storage: &mut dyn Storage, env: &Env) -> StdResult<()> {
+    let prices = CUMULATIVE_PRICES
+        .load(storage)?
+        .accumulate(env.block.time.seconds(), &RESERVES.load(storage)?);
+    CUMULATIVE_PRICES.save(storage, &prices)
+}
+
+/// Stores new reserves without accumulating prices. Used directly by operations shared with
+/// curve pairs, which don't keep price accumulators.
+pub fn save_reserves(storage: &mut dyn Storage, reserves: &[Uint128]) -> StdResult<()> {
+    RESERVES.save(storage, &reserves.to_vec())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::Pair {} => Ok(to_binary(&query_pair_info(deps)?)?),
+        QueryMsg::Pool {} => Ok(to_binary(&query_pool(deps)?)?),
+        QueryMsg::Simulation {
+            offer_asset,
+            ask_asset_info,
+            trader,
+        } => Ok(to_binary(&query_simulation(
+            deps,
+            offer_asset,
+            ask_asset_info,
+            trader,
+        )?)?),
+        QueryMsg::ReverseSimulation {
+            ask_asset,
+            offer_asset_info,
+            trader,
+        } => Ok(to_binary(&query_reverse_simulation(
+            deps,
+            ask_asset,
+            offer_asset_info,
+            trader,
+        )?)?),
+        QueryMsg::CumulativePrices {} => Ok(to_binary(&query_cumulative_prices(deps, env)?)?),
+        QueryMsg::AccruedFees {} => Ok(to_binary(&query_accrued_fees(deps)?)?),
+        QueryMsg::WithdrawSingleSidedSimulation {
+            share,
+            ask_asset,
+            trader,
+        } => Ok(to_binary(&query_withdraw_single_sided_simulation(
+            deps, share, ask_asset, trader,
+        )?)?),
+        QueryMsg::CommissionTiers {} => Ok(to_binary(&query_commission_tiers(deps)?)?),
+        QueryMsg::LimitOrder { order_id } => Ok(to_binary(&query_limit_order(deps, order_id)?)?),
+        QueryMsg::LimitOrderProceeds { owner } => {
+            let owner = deps.api.addr_validate(&owner)?;
+            Ok(to_binary(&query_limit_order_proceeds(deps, owner)?)?)
+        }
+        QueryMsg::LimitOrders {
+            owner,
+            start_after,
+            limit,
+        } => {
+            let owner = deps.api.addr_validate(&owner)?;
+            Ok(to_binary(&query_limit_orders(
+                deps,
+                owner,
+                start_after,
+                limit,
+            )?)?)
+        }
+    }
+}
+
+pub fn query_pair_info(deps: Deps) -> Result<PairInfo, ContractError> {
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    Ok(pair_info)
+}
+
+pub fn query_pool(deps: Deps) -> Result<PoolResponse, ContractError> {
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    let assets = load_pools(deps.storage, &pair_info)?;
+    let total_share: Uint128 = query_supply(&deps.querier, pair_info.liquidity_token)?;
+
+    let resp = PoolResponse {
+        assets,
+        total_share,
+        weights: None,
+    };
+
+    Ok(resp)
 }
 
 pub fn query_simulation(
     deps: Deps,
     offer_asset: Asset,
+    ask_asset_info: Option<AssetInfo>,
+    trader: Option<String>,
 ) -> Result<SimulationResponse, ContractError> {
     let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
-
-    let pools: [Asset; 2] =
-        pair_info.query_pools(&deps.querier, pair_info.contract_addr.clone())?;
-
-    let offer_pool: Asset;
-    let ask_pool: Asset;
-    if offer_asset.info.equal(&pools[0].info) {
-        offer_pool = pools[0].clone();
-        ask_pool = pools[1].clone();
-    } else if offer_asset.info.equal(&pools[1].info) {
-        offer_pool = pools[1].clone();
-        ask_pool = pools[0].clone();
-    } else {
-        return Err(ContractError::AssetMismatch(offer_asset.info.to_string()));
-    }
+    let trader = trader
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let commission = trader_commission(deps, &pair_info, trader.as_ref())?;
+
+    let pools = load_pools(deps.storage, &pair_info)?;
+    let (offer_idx, ask_idx) = swap_idx(
+        &pair_info.asset_infos,
+        &offer_asset.info,
+        ask_asset_info.as_ref(),
+    )?;
 
     let (return_amount, spread_amount, commission_amount) = compute_swap(
-        offer_pool.amount,
-        ask_pool.amount,
+        pools[offer_idx].amount,
+        pools[ask_idx].amount,
         offer_asset.amount,
-        pair_info.commission,
+        commission,
     )?;
 
     Ok(SimulationResponse {
@@ -502,29 +2042,27 @@ pub fn query_simulation(
 pub fn query_reverse_simulation(
     deps: Deps,
     ask_asset: Asset,
+    offer_asset_info: Option<AssetInfo>,
+    trader: Option<String>,
 ) -> Result<ReverseSimulationResponse, ContractError> {
     let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
-
-    let pools: [Asset; 2] =
-        pair_info.query_pools(&deps.querier, pair_info.contract_addr.clone())?;
-
-    let offer_pool: Asset;
-    let ask_pool: Asset;
-    if ask_asset.info.equal(&pools[0].info) {
-        ask_pool = pools[0].clone();
-        offer_pool = pools[1].clone();
-    } else if ask_asset.info.equal(&pools[1].info) {
-        ask_pool = pools[1].clone();
-        offer_pool = pools[0].clone();
-    } else {
-        return Err(ContractError::AssetMismatch(ask_asset.info.to_string()));
-    }
+    let trader = trader
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let commission = trader_commission(deps, &pair_info, trader.as_ref())?;
+
+    let pools = load_pools(deps.storage, &pair_info)?;
+    let (ask_idx, offer_idx) = swap_idx(
+        &pair_info.asset_infos,
+        &ask_asset.info,
+        offer_asset_info.as_ref(),
+    )?;
 
     let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
-        offer_pool.amount,
-        ask_pool.amount,
+        pools[offer_idx].amount,
+        pools[ask_idx].amount,
         ask_asset.amount,
-        pair_info.commission,
+        commission,
     )?;
 
     Ok(ReverseSimulationResponse {
@@ -534,6 +2072,152 @@ pub fn query_reverse_simulation(
     })
 }
 
+pub fn query_withdraw_single_sided_simulation(
+    deps: Deps,
+    share: Uint128,
+    ask_asset: AssetInfo,
+    trader: Option<String>,
+) -> Result<WithdrawSingleSidedSimulationResponse, ContractError> {
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    let trader = trader
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let commission = trader_commission(deps, &pair_info, trader.as_ref())?;
+    let pools = load_pools(deps.storage, &pair_info)?;
+    let total_share: Uint128 = query_supply(&deps.querier, pair_info.liquidity_token)?;
+
+    let ask_idx = pools
+        .iter()
+        .position(|pool| ask_asset.equal(&pool.info))
+        .ok_or_else(|| ContractError::AssetMismatch(ask_asset.to_string()))?;
+
+    let (_, return_amount, spread_amount, commission_amount) =
+        compute_withdraw_single_sided(&pools, total_share, share, ask_idx, commission)?;
+
+    Ok(WithdrawSingleSidedSimulationResponse {
+        return_amount,
+        spread_amount,
+        commission_amount,
+    })
+}
+
+pub fn query_cumulative_prices(
+    deps: Deps,
+    env: Env,
+) -> Result<CumulativePricesResponse, ContractError> {
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    let assets = load_pools(deps.storage, &pair_info)?;
+
+    // Pools didn't change since last update, so accumulators can be advanced to current block
+    let prices = CUMULATIVE_PRICES.load(deps.storage)?.accumulate(
+        env.block.time.seconds(),
+        &[assets[0].amount, assets[1].amount],
+    );
+
+    Ok(CumulativePricesResponse {
+        assets: [assets[0].clone(), assets[1].clone()],
+        price0_cumulative: prices.price0_cumulative,
+        price1_cumulative: prices.price1_cumulative,
+        timestamp: prices.last_updated,
+    })
+}
+
+pub fn query_accrued_fees(deps: Deps) -> Result<AccruedFeesResponse, ContractError> {
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    let protocol_fee = PROTOCOL_FEE.load(deps.storage)?;
+
+    Ok(AccruedFeesResponse {
+        fees: load_accrued_fees(deps.storage, &pair_info)?,
+        fee_collector: protocol_fee.fee_collector.map(Into::into),
+        protocol_fee_share: protocol_fee.share,
+    })
+}
+
+pub fn query_commission_tiers(deps: Deps) -> Result<CommissionTiersResponse, ContractError> {
+    let commission_tiers = COMMISSION_TIERS.may_load(deps.storage)?;
+
+    Ok(CommissionTiersResponse {
+        commission_group: commission_tiers
+            .as_ref()
+            .map(|commission_tiers| commission_tiers.group.to_string()),
+        commission_tiers: commission_tiers
+            .map(|commission_tiers| commission_tiers.tiers)
+            .unwrap_or_default(),
+    })
+}
+
+pub fn query_limit_order_proceeds(
+    deps: Deps,
+    owner: Addr,
+) -> Result<LimitOrderProceedsResponse, ContractError> {
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    let proceeds = LIMIT_ORDER_PROCEEDS
+        .may_load(deps.storage, &owner)?
+        .unwrap_or_default();
+    Ok(LimitOrderProceedsResponse {
+        proceeds: pair_info
+            .asset_infos
+            .into_iter()
+            .zip(proceeds)
+            .map(|(info, amount)| Asset { info, amount })
+            .collect(),
+    })
+}
+
+pub fn query_limit_order(deps: Deps, order_id: u64) -> Result<LimitOrderResponse, ContractError> {
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    let order = LIMIT_ORDERS.load(deps.storage, order_id)?;
+    Ok(limit_order_response(&pair_info, order_id, order))
+}
+
+pub fn query_limit_orders(
+    deps: Deps,
+    owner: Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<LimitOrdersResponse, ContractError> {
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let orders = OWNER_LIMIT_ORDERS
+        .prefix(&owner)
+        .keys(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|order_id| -> StdResult<_> {
+            let order_id = order_id?;
+            let order = LIMIT_ORDERS.load(deps.storage, order_id)?;
+            Ok(limit_order_response(&pair_info, order_id, order))
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(LimitOrdersResponse { orders })
+}
+
+fn limit_order_response(
+    pair_info: &PairInfo,
+    order_id: u64,
+    order: LimitOrder,
+) -> LimitOrderResponse {
+    let offer_idx = order.offer_idx as usize;
+    LimitOrderResponse {
+        order_id,
+        owner: order.owner.to_string(),
+        offer_asset: Asset {
+            info: pair_info.asset_infos[offer_idx].clone(),
+            amount: order.offer_amount,
+        },
+        ask_asset: pair_info.asset_infos[1 - offer_idx].clone(),
+        price: order.price,
+        filled_amount: order.filled_amount,
+        return_amount: order.return_amount,
+    }
+}
+
 pub fn amount_of(coins: &[Coin], denom: String) -> Uint128 {
     match coins.iter().find(|x| x.denom == denom) {
         Some(coin) => coin.amount,
@@ -541,60 +2225,141 @@ pub fn amount_of(coins: &[Coin], denom: String) -> Uint128 {
     }
 }
 
-fn compute_swap(
+pub(crate) fn compute_swap(
     offer_pool: Uint128,
     ask_pool: Uint128,
     offer_amount: Uint128,
     commission: Decimal,
 ) -> StdResult<(Uint128, Uint128, Uint128)> {
-    // offer => ask
-    // ask_amount = (ask_pool - cp / (offer_pool + offer_amount)) * (1 - commission_rate)
-    let cp = Uint128::new(offer_pool.u128() * ask_pool.u128());
-    let return_amount =
-        ask_pool.checked_sub(cp.multiply_ratio(1u128, offer_pool + offer_amount))?;
-
-    // calculate spread & commission
     if offer_pool.is_zero() {
-        // return Err(StdError::divide_by_zero(ask_pool.to_string()).into());
         return Err(StdError::generic_err(
             "Divide by zero error computing the swap",
         ));
     }
-    let spread_amount: Uint128 = (offer_amount * Decimal::from_ratio(ask_pool, offer_pool))
-        .checked_sub(return_amount)
-        .unwrap_or_else(|_| Uint128::zero());
-    let commission_amount: Uint128 = return_amount * commission;
+
+    // offer => ask
+    // ask_amount = (ask_pool - cp / (offer_pool + offer_amount)) * (1 - commission_rate)
+    let (offer_pool, ask_pool, offer_amount) = (
+        Uint256::from(offer_pool),
+        Uint256::from(ask_pool),
+        Uint256::from(offer_amount),
+    );
+    let cp = offer_pool * ask_pool;
+    // ask pool left is rounded up, so the constant product never decreases
+    let return_amount =
+        ask_pool.checked_sub(div_ceil(cp, offer_pool.checked_add(offer_amount)?)?)?;
+
+    // calculate spread & commission
+    let spread_amount = offer_amount
+        .multiply_ratio(ask_pool, offer_pool)
+        .saturating_sub(return_amount);
+    let commission_amount = return_amount * decimal256(commission);
 
     // commission will be absorbed to pool
-    let return_amount: Uint128 = return_amount.checked_sub(commission_amount)?;
+    let return_amount = return_amount.checked_sub(commission_amount)?;
+
+    Ok((
+        return_amount.try_into()?,
+        spread_amount.try_into()?,
+        commission_amount.try_into()?,
+    ))
+}
+
+/// Computes part of `offer_amount` to be swapped, so the swap return and the rest of offer match
+/// pools ratio after the swap. As commission is deducted from the return and stays in the pool,
+/// it is the positive root of:
+///
+/// `s^2 + ((2 - commission) * offer_pool - commission * offer_amount) * s - offer_amount * offer_pool = 0`
+fn compute_single_sided_swap_amount(
+    offer_pool: Uint128,
+    offer_amount: Uint128,
+    commission: Decimal,
+) -> Result<Uint128, ContractError> {
+    let one = Uint256::from(Decimal::one().atomics());
+    let commission = Uint256::from(commission.atomics());
+    let (offer_pool, offer_amount) = (Uint256::from(offer_pool), Uint256::from(offer_amount));
+
+    // linear coefficient may be negative, so its sign is tracked separately
+    let b_pos = (one + one - commission).checked_mul(offer_pool)?;
+    let b_neg = commission.checked_mul(offer_amount)?;
+    let b = if b_pos >= b_neg {
+        (b_pos - b_neg) / one
+    } else {
+        (b_neg - b_pos) / one
+    };
+
+    let discriminant = b.full_mul(b).checked_add(
+        Uint512::from(4u8)
+            .checked_mul(offer_amount.into())?
+            .checked_mul(offer_pool.into())?,
+    )?;
+    let root = uint512_sqrt(discriminant)?;
+    let swap_amount = if b_pos >= b_neg {
+        (root - b) / Uint256::from(2u8)
+    } else {
+        (root + b) / Uint256::from(2u8)
+    };
 
-    Ok((return_amount, spread_amount, commission_amount))
+    Ok(std::cmp::min(swap_amount, offer_amount).try_into()?)
 }
 
-fn compute_offer_amount(
+pub(crate) fn compute_offer_amount(
     offer_pool: Uint128,
     ask_pool: Uint128,
     ask_amount: Uint128,
     commission: Decimal,
 ) -> StdResult<(Uint128, Uint128, Uint128)> {
+    if offer_pool.is_zero() {
+        return Err(StdError::generic_err(
+            "Divide by zero error computing the swap",
+        ));
+    }
+
     // ask => offer
     // offer_amount = cp / (ask_pool - ask_amount / (1 - commission_rate)) - offer_pool
-    let cp = Uint128::new(offer_pool.u128() * ask_pool.u128());
-    let one_minus_commission = decimal_subtraction(Decimal::one(), commission)?;
+    // rounded up, so swapping it never returns less than `ask_amount`
+    let (offer_pool, ask_pool) = (Uint256::from(offer_pool), Uint256::from(ask_pool));
+    let cp = offer_pool * ask_pool;
 
-    let offer_amount: Uint128 = cp
-        .multiply_ratio(
-            1u128,
-            ask_pool.checked_sub(ask_amount * reverse_decimal(one_minus_commission))?,
-        )
+    let before_commission_deduction = before_commission(ask_amount.into(), commission)?;
+    let offer_amount = div_ceil(cp, ask_pool.checked_sub(before_commission_deduction)?)?
         .checked_sub(offer_pool)?;
 
-    let before_commission_deduction = ask_amount * reverse_decimal(one_minus_commission);
-    let spread_amount = (offer_amount * Decimal::from_ratio(ask_pool, offer_pool))
-        .checked_sub(before_commission_deduction)
-        .unwrap_or_else(|_| Uint128::zero());
-    let commission_amount = before_commission_deduction * commission;
-    Ok((offer_amount, spread_amount, commission_amount))
+    let spread_amount = offer_amount
+        .multiply_ratio(ask_pool, offer_pool)
+        .saturating_sub(before_commission_deduction);
+    let commission_amount = before_commission_deduction * decimal256(commission);
+    Ok((
+        offer_amount.try_into()?,
+        spread_amount.try_into()?,
+        commission_amount.try_into()?,
+    ))
+}
+
+/// Indices of offer and ask assets of a swap. Ask asset may be omitted only if the pair has two
+/// assets, it's the other one then.
+pub fn swap_idx(
+    asset_infos: &[AssetInfo],
+    offer_asset_info: &AssetInfo,
+    ask_asset_info: Option<&AssetInfo>,
+) -> Result<(usize, usize), ContractError> {
+    let idx = |asset_info: &AssetInfo| {
+        asset_infos
+            .iter()
+            .position(|info| asset_info.equal(info))
+            .ok_or_else(|| ContractError::AssetMismatch(asset_info.to_string()))
+    };
+
+    let offer_idx = idx(offer_asset_info)?;
+    let ask_idx = match ask_asset_info {
+        Some(ask_asset_info) if ask_asset_info.equal(offer_asset_info) => {
+            return Err(ContractError::AssetMismatch(ask_asset_info.to_string()))
+        }
+        Some(ask_asset_info) => idx(ask_asset_info)?,
+        None if asset_infos.len() == 2 => 1 - offer_idx,
+        None => return Err(ContractError::MissingSwapAsset {}),
+    };
+    Ok((offer_idx, ask_idx))
 }
 
 /// If `belief_price` and `max_spread` both are given,
@@ -608,21 +2373,30 @@ pub fn assert_max_spread(
     spread_amount: Uint128,
 ) -> Result<(), ContractError> {
     if let (Some(max_spread), Some(belief_price)) = (max_spread, belief_price) {
-        let expected_return = offer_amount * reverse_decimal(belief_price);
-        let spread_amount = expected_return
-            .checked_sub(return_amount)
-            .unwrap_or_else(|_| Uint128::zero());
-
-        let spread_ratio = Decimal::from_ratio(spread_amount, expected_return);
-        if return_amount < expected_return && spread_ratio > max_spread {
-            return Err(ContractError::MaxSpreadAssertion {
-                spread_ratio,
-                max_spread,
-            });
+        let expected_return = if belief_price.is_zero() {
+            Uint256::zero()
+        } else {
+            Uint256::from(offer_amount)
+                .multiply_ratio(Decimal::one().atomics(), belief_price.atomics())
+        };
+        let return_amount = Uint256::from(return_amount);
+
+        if return_amount < expected_return {
+            let spread_ratio =
+                Decimal256::from_ratio(expected_return - return_amount, expected_return);
+            if spread_ratio > decimal256(max_spread) {
+                return Err(ContractError::MaxSpreadAssertion {
+                    spread_ratio,
+                    max_spread,
+                });
+            }
         }
     } else if let Some(max_spread) = max_spread {
-        let spread_ratio = Decimal::from_ratio(spread_amount, return_amount + spread_amount);
-        if spread_ratio > max_spread {
+        let spread_ratio = Decimal256::from_ratio(
+            spread_amount,
+            Uint256::from(return_amount) + Uint256::from(spread_amount),
+        );
+        if spread_ratio > decimal256(max_spread) {
             return Err(ContractError::MaxSpreadAssertion {
                 spread_ratio,
                 max_spread,
@@ -633,33 +2407,54 @@ pub fn assert_max_spread(
     Ok(())
 }
 
-fn assert_slippage_tolerance(
+/// Fails if `return_amount` is lower than `min_return`, if given
+pub fn assert_min_return(
+    return_amount: Uint128,
+    min_return: Option<Uint128>,
+) -> Result<(), ContractError> {
+    match min_return {
+        Some(min_return) if return_amount < min_return => Err(ContractError::MinReturnAssertion {
+            return_amount,
+            min_return,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Fails if current block time is past `deadline` (in seconds), if given
+pub fn assert_deadline(env: &Env, deadline: Option<u64>) -> Result<(), ContractError> {
+    let now = env.block.time.seconds();
+    match deadline {
+        Some(deadline) if now > deadline => Err(ContractError::DeadlineExceeded { deadline, now }),
+        _ => Ok(()),
+    }
+}
+
+pub fn assert_slippage_tolerance(
     slippage_tolerance: &Option<Decimal>,
-    deposits: &[Uint128; 2],
-    pools: &[Asset; 2],
+    deposits: &[Uint128],
+    pools: &[Asset],
 ) -> Result<(), ContractError> {
     if let Some(slippage_tolerance) = *slippage_tolerance {
-        let one_minus_slippage_tolerance = decimal_subtraction(Decimal::one(), slippage_tolerance)?;
+        let one_minus_slippage_tolerance = Decimal256::new(
+            Decimal256::one()
+                .atomics()
+                .checked_sub(decimal256(slippage_tolerance).atomics())?,
+        );
 
         // Ensure each prices are not dropped as much as slippage tolerance rate
-        let deposits_ratio = Decimal::from_ratio(deposits[0], deposits[1]);
-        let pools_ratio = Decimal::from_ratio(pools[0].amount, pools[1].amount);
-        if decimal_multiplication(deposits_ratio, one_minus_slippage_tolerance) > pools_ratio {
-            return Err(ContractError::MaxSlippageAssertion {
-                deposits_ratio,
-                pools_ratio,
-                slippage_tolerance,
-            });
-        }
-
-        let deposits_ratio = Decimal::from_ratio(deposits[1], deposits[0]);
-        let pools_ratio = Decimal::from_ratio(pools[1].amount, pools[0].amount);
-        if decimal_multiplication(deposits_ratio, one_minus_slippage_tolerance) > pools_ratio {
-            return Err(ContractError::MaxSlippageAssertion {
-                deposits_ratio,
-                pools_ratio,
-                slippage_tolerance,
-            });
+        for i in 0..pools.len() {
+            for j in (0..pools.len()).filter(|j| *j != i) {
+                let deposits_ratio = Decimal256::from_ratio(deposits[i], deposits[j]);
+                let pools_ratio = Decimal256::from_ratio(pools[i].amount, pools[j].amount);
+                if deposits_ratio * one_minus_slippage_tolerance > pools_ratio {
+                    return Err(ContractError::MaxSlippageAssertion {
+                        deposits_ratio,
+                        pools_ratio,
+                        slippage_tolerance,
+                    });
+                }
+            }
         }
     }
 
@@ -667,6 +2462,53 @@ fn assert_slippage_tolerance(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    // Pairs instantiated before price accumulators were introduced start accumulating now
+    if CUMULATIVE_PRICES.may_load(deps.storage)?.is_none() {
+        CUMULATIVE_PRICES.save(
+            deps.storage,
+            &CumulativePrices::new(env.block.time.seconds()),
+        )?;
+    }
+
+    migrate_pair(deps, env, msg)
+}
+
+/// Migrates state shared by pairs of any curve. Price accumulators are constant product specific,
+/// so they are initialized by `migrate`.
+pub fn migrate_pair(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    // Pairs instantiated before reserves were tracked start with their current balances
+    if RESERVES.may_load(deps.storage)?.is_none() {
+        let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+        let balances = pair_info.query_pools(&deps.querier, env.contract.address)?;
+        let reserves: Vec<Uint128> = balances.iter().map(|balance| balance.amount).collect();
+        RESERVES.save(deps.storage, &reserves)?;
+    }
+
+    // Pairs instantiated before protocol fee was introduced don't charge it
+    if PROTOCOL_FEE.may_load(deps.storage)?.is_none() {
+        PROTOCOL_FEE.save(
+            deps.storage,
+            &ProtocolFee {
+                fee_collector: None,
+                share: Decimal::zero(),
+            },
+        )?;
+        ACCRUED_FEES.save(deps.storage, &vec![Uint128::zero(); 2])?;
+    }
+
+    // Pairs instantiated before limit orders were introduced have none
+    if ESCROWED.may_load(deps.storage)?.is_none() {
+        ESCROWED.save(deps.storage, &vec![Uint128::zero(); 2])?;
+        LAST_LIMIT_ORDER_ID.save(deps.storage, &0)?;
+    }
+
+    if let Some(admin) = msg.admin {
+        let admin = deps.api.addr_validate(&admin)?;
+        PAIR_INFO.update(deps.storage, |pair_info| -> StdResult<_> {
+            Ok(pair_info.with_admin(admin))
+        })?;
+    }
+
     Ok(Response::default())
 }
@@ -0,0 +1,701 @@
+//! Execute and query implementation shared by pairs using other invariants than constant product.
+//!
+//! Such pairs keep reserves, fees, pausing and liquidity tokens the same way `tfi-pair` does, and
+//! differ only by the curve used to price swaps and the initial share. The curve is provided by
+//! implementing `Curve`, and pair entry points just call the generic functions of this module.
+//! Curves may pool more than two assets (up to `Curve::MAX_ASSETS`), swaps of such pools name
+//! both the offer and the ask asset. Single sided liquidity, flash swaps, limit orders and price
+//! accumulators are constant product specific, so they are not supported.
+
+use std::fmt;
+
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Binary, ConversionOverflowError, Decimal, Deps, DepsMut, Env,
+    MessageInfo, OverflowError, Response, StdError, StdResult, Storage, Uint128, WasmMsg,
+};
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use tfi::asset::{Asset, AssetInfo, PairInfo};
+use tfi::pair::{
+    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, QueryMsg, ReverseSimulationResponse,
+    SimulationResponse,
+};
+use tfi::querier::query_supply;
+
+use crate::contract::{
+    accrue_protocol_fee, assert_deadline, assert_max_spread, assert_min_return, assert_not_paused,
+    assert_slippage_tolerance, assert_whitelisted, collect_deposits, format_amounts, load_pools,
+    lock_minimum_liquidity, ratio_matched_deposits, requested_deposits, return_msg, save_reserves,
+    swap_idx, trader_commission, Operation, MINIMUM_LIQUIDITY,
+};
+use crate::error::ContractError;
+use crate::state::PAIR_INFO;
+
+/// Pair operations which are not supported by curve pairs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unsupported {
+    CumulativePrices,
+    SingleSided,
+    FlashSwap,
+    LimitOrders,
+}
+
+impl fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Unsupported::CumulativePrices => write!(f, "cumulative prices"),
+            Unsupported::SingleSided => write!(f, "single sided liquidity provision"),
+            Unsupported::FlashSwap => write!(f, "flash swaps"),
+            Unsupported::LimitOrders => write!(f, "limit orders"),
+        }
+    }
+}
+
+/// Swap pricing of the pair, together with its parameters stored in the pair state
+pub trait Curve: Sized {
+    /// Contract name stored with `cw2`
+    const CONTRACT_NAME: &'static str;
+    /// Contract version stored with `cw2`
+    const CONTRACT_VERSION: &'static str;
+    /// Pair type set in `PairInfo`
+    const PAIR_TYPE: &'static str;
+    /// Maximum number of pooled assets
+    const MAX_ASSETS: usize;
+
+    type Error: From<ContractError>
+        + From<StdError>
+        + From<OverflowError>
+        + From<ConversionOverflowError>;
+
+    /// Validates curve parameters given on instantiation
+    fn from_instantiate_msg(msg: &InstantiateMsg) -> Result<Self, Self::Error>;
+
+    fn load(storage: &dyn Storage) -> StdResult<Self>;
+
+    fn save(&self, storage: &mut dyn Storage) -> StdResult<()>;
+
+    /// Computes return of swapping `offer_amount` of the pool at `offer_idx` for the pool at
+    /// `ask_idx`, returning return amount, spread amount and commission amount. The return has to
+    /// be rounded in favour of the pool, so the invariant never decreases.
+    fn compute_swap(
+        &self,
+        pools: &[Uint128],
+        offer_idx: usize,
+        ask_idx: usize,
+        offer_amount: Uint128,
+        commission: Decimal,
+    ) -> Result<(Uint128, Uint128, Uint128), Self::Error>;
+
+    /// Computes amount of the pool at `offer_idx` required to receive `ask_amount` of the pool at
+    /// `ask_idx`, returning offer amount, spread amount and commission amount. The offer has to be
+    /// rounded in favour of the pool.
+    fn compute_offer_amount(
+        &self,
+        pools: &[Uint128],
+        offer_idx: usize,
+        ask_idx: usize,
+        ask_amount: Uint128,
+        commission: Decimal,
+    ) -> Result<(Uint128, Uint128, Uint128), Self::Error>;
+
+    /// Computes liquidity tokens minted for the first deposit, before minimum liquidity is locked
+    fn initial_share(&self, deposits: &[Uint128]) -> Result<Uint128, Self::Error>;
+
+    /// Extends the `pool` query response with curve parameters
+    fn pool_response(&self, res: PoolResponse) -> PoolResponse {
+        res
+    }
+
+    /// Error returned for operations not supported by curve pairs
+    fn unsupported(operation: Unsupported) -> Self::Error;
+}
+
+/// Instantiates pair the same way as `tfi-pair` does (including liquidity token creation), and
+/// additionally stores curve parameters and pair type
+pub fn instantiate<C: Curve>(
+    mut deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, C::Error> {
+    if !(2..=C::MAX_ASSETS).contains(&msg.asset_infos.len()) {
+        return Err(ContractError::AssetCountOutOfRange {
+            max: C::MAX_ASSETS,
+            got: msg.asset_infos.len(),
+        }
+        .into());
+    }
+    let curve = C::from_instantiate_msg(&msg)?;
+
+    let res = crate::contract::instantiate_pair(deps.branch(), env, msg)?;
+    set_contract_version(deps.storage, C::CONTRACT_NAME, C::CONTRACT_VERSION)?;
+    curve.save(deps.storage)?;
+    PAIR_INFO.update(deps.storage, |pair_info| -> StdResult<_> {
+        Ok(pair_info.with_pair_type(C::PAIR_TYPE))
+    })?;
+
+    Ok(res)
+}
+
+pub fn execute<C: Curve>(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, C::Error> {
+    match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20::<C>(deps, env, info, msg),
+        ExecuteMsg::ProvideLiquidity {
+            assets,
+            slippage_tolerance,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Deposits])?;
+            provide_liquidity::<C>(deps, env, info, assets, slippage_tolerance)
+        }
+        ExecuteMsg::Swap {
+            offer_asset,
+            ask_asset_info,
+            belief_price,
+            max_spread,
+            to,
+            min_return,
+            deadline,
+            to_msg,
+        } => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Swaps])?;
+            if !offer_asset.is_native_token() {
+                return Err(ContractError::Unauthorized {}.into());
+            }
+
+            let to_addr = to.map(|to| deps.api.addr_validate(&to)).transpose()?;
+
+            swap::<C>(
+                deps,
+                env,
+                info.clone(),
+                info.sender,
+                offer_asset,
+                ask_asset_info,
+                belief_price,
+                max_spread,
+                min_return,
+                to_addr,
+                to_msg,
+            )
+        }
+        ExecuteMsg::SwapExactOut {
+            offer_asset,
+            ask_asset_info,
+            ask_amount,
+            to,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Swaps])?;
+            if !offer_asset.is_native_token() {
+                return Err(ContractError::Unauthorized {}.into());
+            }
+
+            let to_addr = to.map(|to| deps.api.addr_validate(&to)).transpose()?;
+            swap_exact_out::<C>(
+                deps,
+                env,
+                info.clone(),
+                info.sender,
+                offer_asset,
+                ask_asset_info,
+                ask_amount,
+                to_addr,
+            )
+        }
+        // reserves are tracked the same way as in `tfi-pair`
+        ExecuteMsg::Sync {} => Ok(crate::contract::sync(deps, env)?),
+        ExecuteMsg::Skim { to } => {
+            let to_addr = to
+                .map(|to| deps.api.addr_validate(&to))
+                .transpose()?
+                .unwrap_or(info.sender);
+            Ok(crate::contract::skim(deps, env, to_addr)?)
+        }
+        ExecuteMsg::CollectFees {} => Ok(crate::contract::collect_fees(deps)?),
+        ExecuteMsg::ProvideSingleSided { .. } => Err(C::unsupported(Unsupported::SingleSided)),
+        ExecuteMsg::FlashSwap { .. } => Err(C::unsupported(Unsupported::FlashSwap)),
+        ExecuteMsg::PlaceLimitOrder { .. }
+        | ExecuteMsg::CancelLimitOrder { .. }
+        | ExecuteMsg::ClaimLimitOrderProceeds {} => Err(C::unsupported(Unsupported::LimitOrders)),
+        ExecuteMsg::UpdatePause {
+            swaps,
+            deposits,
+            withdrawals,
+        } => Ok(crate::contract::update_pause(
+            deps,
+            info,
+            swaps,
+            deposits,
+            withdrawals,
+        )?),
+        ExecuteMsg::UpdateCommission { commission } => {
+            Ok(crate::contract::update_commission(deps, info, commission)?)
+        }
+    }
+}
+
+pub fn receive_cw20<C: Curve>(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, C::Error> {
+    match from_binary(&cw20_msg.msg)? {
+        Cw20HookMsg::Swap {
+            ask_asset_info,
+            belief_price,
+            max_spread,
+            to,
+            min_return,
+            deadline,
+            to_msg,
+        } => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Swaps])?;
+
+            let offer_asset = sent_token(deps.storage, &info, &cw20_msg)?;
+            let to_addr = to.map(|to| deps.api.addr_validate(&to)).transpose()?;
+            let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+
+            swap::<C>(
+                deps,
+                env,
+                info,
+                sender,
+                offer_asset,
+                ask_asset_info,
+                belief_price,
+                max_spread,
+                min_return,
+                to_addr,
+                to_msg,
+            )
+        }
+        Cw20HookMsg::SwapExactOut {
+            ask_asset_info,
+            ask_amount,
+            to,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Swaps])?;
+
+            let offer_asset = sent_token(deps.storage, &info, &cw20_msg)?;
+            let to_addr = to.map(|to| deps.api.addr_validate(&to)).transpose()?;
+            let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+
+            swap_exact_out::<C>(
+                deps,
+                env,
+                info,
+                sender,
+                offer_asset,
+                ask_asset_info,
+                ask_amount,
+                to_addr,
+            )
+        }
+        Cw20HookMsg::ProvideSingleSided { .. } | Cw20HookMsg::WithdrawSingleSided { .. } => {
+            Err(C::unsupported(Unsupported::SingleSided))
+        }
+        Cw20HookMsg::PlaceLimitOrder { .. } => Err(C::unsupported(Unsupported::LimitOrders)),
+        Cw20HookMsg::WithdrawLiquidity { deadline } => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(deps.storage, &[Operation::Withdrawals])?;
+
+            let config: PairInfo = PAIR_INFO.load(deps.storage)?;
+            if info.sender != config.liquidity_token {
+                return Err(ContractError::Unauthorized {}.into());
+            }
+
+            let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+            // withdrawal is pro rata, so it doesn't depend on the invariant
+            Ok(crate::contract::withdraw_liquidity(
+                deps,
+                env,
+                info,
+                sender,
+                cw20_msg.amount,
+            )?)
+        }
+    }
+}
+
+/// Token sent with cw20 `Send` as an offer asset, only pair asset contracts can send it
+fn sent_token(
+    storage: &dyn Storage,
+    info: &MessageInfo,
+    cw20_msg: &Cw20ReceiveMsg,
+) -> Result<Asset, ContractError> {
+    let config: PairInfo = PAIR_INFO.load(storage)?;
+    let authorized = config
+        .asset_infos
+        .iter()
+        .any(|asset_info| matches!(asset_info, AssetInfo::Token(addr) if addr == &info.sender));
+    if !authorized {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(Asset {
+        info: AssetInfo::Token(info.sender.clone()),
+        amount: cw20_msg.amount,
+    })
+}
+
+/// CONTRACT - should approve contract to use the amount of token
+pub fn provide_liquidity<C: Curve>(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    assets: Vec<Asset>,
+    slippage_tolerance: Option<Decimal>,
+) -> Result<Response, C::Error> {
+    for asset in assets.iter() {
+        asset.assert_sent_native_token_balance(&info)?;
+    }
+
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    assert_whitelisted(deps.as_ref(), &pair_info, &[&info.sender])?;
+    let pools = load_pools(deps.storage, &pair_info)?;
+    let requested = requested_deposits(&assets, &pools)?;
+
+    // assert slippage tolerance
+    assert_slippage_tolerance(&slippage_tolerance, &requested, &pools)?;
+
+    // Same as in `tfi-pair` - only the part of deposit matching pool ratio is used, the rest is
+    // returned to the provider
+    let total_share = query_supply(&deps.querier, pair_info.liquidity_token.clone())?;
+    let deposits = ratio_matched_deposits(&requested, &pools, total_share);
+
+    let mut res = Response::new()
+        .add_attribute("action", "provide_liquidity")
+        .add_attribute("assets", format_amounts(&deposits, &pools))
+        .add_messages(collect_deposits(
+            &env,
+            &info.sender,
+            &pools,
+            &requested,
+            &deposits,
+        )?);
+
+    let share = if total_share.is_zero() {
+        // minimum liquidity is locked out of initial share the same way as in `tfi-pair`
+        let share = C::load(deps.storage)?.initial_share(&deposits)?;
+        res = res.add_message(lock_minimum_liquidity(&pair_info, &env)?);
+        share.saturating_sub(MINIMUM_LIQUIDITY)
+    } else {
+        deposits
+            .iter()
+            .zip(pools.iter())
+            .map(|(deposit, pool)| deposit.multiply_ratio(total_share, pool.amount))
+            .min()
+            .unwrap_or_default()
+    };
+
+    if share.is_zero() {
+        return Err(ContractError::ZeroShares {}.into());
+    }
+
+    let reserves = pools
+        .iter()
+        .zip(deposits.iter())
+        .map(|(pool, deposit)| pool.amount.checked_add(*deposit))
+        .collect::<Result<Vec<_>, _>>()?;
+    save_reserves(deps.storage, &reserves)?;
+
+    // mint LP token to sender
+    let msg = WasmMsg::Execute {
+        contract_addr: pair_info.liquidity_token.into(),
+        msg: to_binary(&Cw20ExecuteMsg::Mint {
+            recipient: info.sender.to_string(),
+            amount: share,
+        })?,
+        funds: vec![],
+    };
+    let res = res
+        .add_attribute("share", share.to_string())
+        .add_message(msg);
+    Ok(res)
+}
+
+/// Swaps offer asset for exactly `ask_amount` of the ask asset. `offer_asset` is the maximum
+/// offer, the part of it not required for the swap is refunded to the sender.
+#[allow(clippy::too_many_arguments)]
+pub fn swap_exact_out<C: Curve>(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    ask_asset_info: Option<AssetInfo>,
+    ask_amount: Uint128,
+    to: Option<Addr>,
+) -> Result<Response, C::Error> {
+    offer_asset.assert_sent_native_token_balance(&info)?;
+    if ask_amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {}.into());
+    }
+
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    let receiver = to.unwrap_or_else(|| sender.clone());
+    assert_whitelisted(deps.as_ref(), &pair_info, &[&sender, &receiver])?;
+    let curve = C::load(deps.storage)?;
+    let pools = load_pools(deps.storage, &pair_info)?;
+    let reserves: Vec<Uint128> = pools.iter().map(|pool| pool.amount).collect();
+
+    let (offer_idx, ask_idx) = swap_idx(
+        &pair_info.asset_infos,
+        &offer_asset.info,
+        ask_asset_info.as_ref(),
+    )?;
+    let (offer_pool, ask_pool) = (pools[offer_idx].amount, &pools[ask_idx]);
+
+    let commission = trader_commission(deps.as_ref(), &pair_info, Some(&sender))?;
+    let (offer_amount, spread_amount, commission_amount) =
+        curve.compute_offer_amount(&reserves, offer_idx, ask_idx, ask_amount, commission)?;
+
+    if offer_amount > offer_asset.amount {
+        return Err(ContractError::MaxOfferAssertion {
+            offer_amount,
+            max_offer: offer_asset.amount,
+        }
+        .into());
+    }
+
+    // commission stays in the ask pool as in `swap`
+    let protocol_fee_amount = accrue_protocol_fee(deps.storage, ask_idx, commission_amount)?;
+    let mut reserves = reserves;
+    reserves[offer_idx] = offer_pool.checked_add(offer_amount)?;
+    reserves[ask_idx] = ask_pool
+        .amount
+        .checked_sub(ask_amount)?
+        .checked_sub(protocol_fee_amount)?;
+    save_reserves(deps.storage, &reserves)?;
+
+    let return_msg = Asset {
+        info: ask_pool.info.clone(),
+        amount: ask_amount,
+    }
+    .into_msg(receiver)?;
+
+    let refund_amount = offer_asset.amount - offer_amount;
+    let mut res = Response::new()
+        .add_attribute("action", "swap_exact_out")
+        .add_attribute("offer_asset", offer_asset.info.to_string())
+        .add_attribute("ask_asset", ask_pool.info.to_string())
+        .add_attribute("offer_amount", offer_amount.to_string())
+        .add_attribute("return_amount", ask_amount.to_string())
+        .add_attribute("spread_amount", spread_amount.to_string())
+        .add_attribute("commission_amount", commission_amount.to_string())
+        .add_attribute("protocol_fee_amount", protocol_fee_amount.to_string())
+        .add_attribute("refund_amount", refund_amount.to_string())
+        .add_message(return_msg);
+
+    if !refund_amount.is_zero() {
+        let refund = Asset {
+            info: offer_asset.info,
+            amount: refund_amount,
+        };
+        res = res.add_message(refund.into_msg(sender)?);
+    }
+
+    Ok(res)
+}
+
+// CONTRACT - a user must do token approval
+#[allow(clippy::too_many_arguments)]
+pub fn swap<C: Curve>(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    ask_asset_info: Option<AssetInfo>,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    min_return: Option<Uint128>,
+    to: Option<Addr>,
+    to_msg: Option<Binary>,
+) -> Result<Response, C::Error> {
+    offer_asset.assert_sent_native_token_balance(&info)?;
+
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    let receiver = to.unwrap_or_else(|| sender.clone());
+    assert_whitelisted(deps.as_ref(), &pair_info, &[&sender, &receiver])?;
+    let curve = C::load(deps.storage)?;
+
+    let pools = load_pools(deps.storage, &pair_info)?;
+    let reserves: Vec<Uint128> = pools.iter().map(|pool| pool.amount).collect();
+
+    let (offer_idx, ask_idx) = swap_idx(
+        &pair_info.asset_infos,
+        &offer_asset.info,
+        ask_asset_info.as_ref(),
+    )?;
+    let (offer_pool, ask_pool) = (pools[offer_idx].amount, &pools[ask_idx]);
+
+    let offer_amount = offer_asset.amount;
+    let commission = trader_commission(deps.as_ref(), &pair_info, Some(&sender))?;
+    let (return_amount, spread_amount, commission_amount) =
+        curve.compute_swap(&reserves, offer_idx, ask_idx, offer_amount, commission)?;
+
+    // check max spread limit if exist
+    assert_max_spread(
+        belief_price,
+        max_spread,
+        offer_amount,
+        return_amount + commission_amount,
+        spread_amount,
+    )?;
+    assert_min_return(return_amount, min_return)?;
+
+    // commission stays in the ask pool as in `crate::contract::swap`
+    let protocol_fee_amount = accrue_protocol_fee(deps.storage, ask_idx, commission_amount)?;
+    let mut reserves = reserves;
+    reserves[offer_idx] = offer_pool.checked_add(offer_amount)?;
+    reserves[ask_idx] = ask_pool
+        .amount
+        .checked_sub(return_amount)?
+        .checked_sub(protocol_fee_amount)?;
+    save_reserves(deps.storage, &reserves)?;
+
+    let return_msg = return_msg(
+        Asset {
+            info: ask_pool.info.clone(),
+            amount: return_amount,
+        },
+        receiver,
+        to_msg,
+    )?;
+
+    let res = Response::new()
+        .add_attribute("action", "swap")
+        .add_attribute("offer_asset", offer_asset.info.to_string())
+        .add_attribute("ask_asset", ask_pool.info.to_string())
+        .add_attribute("offer_amount", offer_amount.to_string())
+        .add_attribute("return_amount", return_amount.to_string())
+        .add_attribute("spread_amount", spread_amount.to_string())
+        .add_attribute("commission_amount", commission_amount.to_string())
+        .add_attribute("protocol_fee_amount", protocol_fee_amount.to_string())
+        .add_message(return_msg);
+    Ok(res)
+}
+
+pub fn query<C: Curve>(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, C::Error> {
+    match msg {
+        QueryMsg::Pool {} => Ok(to_binary(&query_pool::<C>(deps)?)?),
+        QueryMsg::Pair {} | QueryMsg::AccruedFees {} | QueryMsg::CommissionTiers {} => {
+            Ok(crate::contract::query(deps, env, msg)?)
+        }
+        QueryMsg::Simulation {
+            offer_asset,
+            ask_asset_info,
+            trader,
+        } => Ok(to_binary(&query_simulation::<C>(
+            deps,
+            offer_asset,
+            ask_asset_info,
+            trader,
+        )?)?),
+        QueryMsg::ReverseSimulation {
+            ask_asset,
+            offer_asset_info,
+            trader,
+        } => Ok(to_binary(&query_reverse_simulation::<C>(
+            deps,
+            ask_asset,
+            offer_asset_info,
+            trader,
+        )?)?),
+        // Reserves ratio is not the price of the curve, so accumulating it would be misleading
+        QueryMsg::CumulativePrices {} => Err(C::unsupported(Unsupported::CumulativePrices)),
+        QueryMsg::LimitOrder { .. }
+        | QueryMsg::LimitOrders { .. }
+        | QueryMsg::LimitOrderProceeds { .. } => Err(C::unsupported(Unsupported::LimitOrders)),
+        QueryMsg::WithdrawSingleSidedSimulation { .. } => {
+            Err(C::unsupported(Unsupported::SingleSided))
+        }
+    }
+}
+
+/// Reports pools the same way as `tfi-pair`, extended with curve parameters
+pub fn query_pool<C: Curve>(deps: Deps) -> Result<PoolResponse, C::Error> {
+    let res = crate::contract::query_pool(deps)?;
+    Ok(C::load(deps.storage)?.pool_response(res))
+}
+
+pub fn query_simulation<C: Curve>(
+    deps: Deps,
+    offer_asset: Asset,
+    ask_asset_info: Option<AssetInfo>,
+    trader: Option<String>,
+) -> Result<SimulationResponse, C::Error> {
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    let trader = trader
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let commission = trader_commission(deps, &pair_info, trader.as_ref())?;
+    let curve = C::load(deps.storage)?;
+
+    let pools = load_pools(deps.storage, &pair_info)?;
+    let reserves: Vec<Uint128> = pools.iter().map(|pool| pool.amount).collect();
+    let (offer_idx, ask_idx) = swap_idx(
+        &pair_info.asset_infos,
+        &offer_asset.info,
+        ask_asset_info.as_ref(),
+    )?;
+
+    let (return_amount, spread_amount, commission_amount) = curve.compute_swap(
+        &reserves,
+        offer_idx,
+        ask_idx,
+        offer_asset.amount,
+        commission,
+    )?;
+
+    Ok(SimulationResponse {
+        return_amount,
+        spread_amount,
+        commission_amount,
+    })
+}
+
+pub fn query_reverse_simulation<C: Curve>(
+    deps: Deps,
+    ask_asset: Asset,
+    offer_asset_info: Option<AssetInfo>,
+    trader: Option<String>,
+) -> Result<ReverseSimulationResponse, C::Error> {
+    let pair_info: PairInfo = PAIR_INFO.load(deps.storage)?;
+    let trader = trader
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let commission = trader_commission(deps, &pair_info, trader.as_ref())?;
+    let curve = C::load(deps.storage)?;
+
+    let pools = load_pools(deps.storage, &pair_info)?;
+    let reserves: Vec<Uint128> = pools.iter().map(|pool| pool.amount).collect();
+    let (ask_idx, offer_idx) = swap_idx(
+        &pair_info.asset_infos,
+        &ask_asset.info,
+        offer_asset_info.as_ref(),
+    )?;
+
+    let (offer_amount, spread_amount, commission_amount) =
+        curve.compute_offer_amount(&reserves, offer_idx, ask_idx, ask_asset.amount, commission)?;
+
+    Ok(ReverseSimulationResponse {
+        offer_amount,
+        spread_amount,
+        commission_amount,
+    })
+}
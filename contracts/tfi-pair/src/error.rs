@@ -1,6 +1,10 @@
-use cosmwasm_std::{Decimal, OverflowError, StdError};
+use cosmwasm_std::{
+    ConversionOverflowError, Decimal, Decimal256, OverflowError, StdError, Uint128,
+};
 use thiserror::Error;
 
+use crate::contract::Operation;
+
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
     #[error("{0}")]
@@ -9,28 +13,79 @@ pub enum ContractError {
     #[error("{0}")]
     OverflowError(#[from] OverflowError),
 
+    #[error("{0}")]
+    ConversionOverflowError(#[from] ConversionOverflowError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Address not whitelisted: {0}")]
+    NotWhitelisted(String),
+
     #[error("Invalid zero amount")]
     InvalidZeroAmount {},
 
+    #[error("Deposit too small, no liquidity tokens would be minted")]
+    ZeroShares {},
+
+    #[error("Minimum share not met, share: {share}, min share: {min_share}")]
+    MinShareAssertion { share: Uint128, min_share: Uint128 },
+
+    #[error("Minimum return not met, return amount: {return_amount}, min return: {min_return}")]
+    MinReturnAssertion {
+        return_amount: Uint128,
+        min_return: Uint128,
+    },
+
+    #[error("Deadline exceeded, deadline: {deadline}, block time: {now}")]
+    DeadlineExceeded { deadline: u64, now: u64 },
+
+    #[error("Max offer exceeded, offer amount: {offer_amount}, max offer: {max_offer}")]
+    MaxOfferAssertion {
+        offer_amount: Uint128,
+        max_offer: Uint128,
+    },
+
+    #[error("Flash swap in progress, pair operations are not allowed until it is repaid")]
+    FlashSwapInProgress {},
+
+    #[error("Insufficient liquidity, pool: {pool}, requested: {requested}")]
+    InsufficientLiquidity { pool: Uint128, requested: Uint128 },
+
+    #[error("Flash swap not repaid, constant product after commission is lower than before")]
+    FlashSwapNotRepaid {},
+
+    #[error("Pool is empty, initial liquidity has to be provided with both assets")]
+    EmptyPool {},
+
     #[error("Max spread exceeded, spread ratio: {spread_ratio}, max spread: {max_spread}")]
     MaxSpreadAssertion {
-        spread_ratio: Decimal,
+        spread_ratio: Decimal256,
         max_spread: Decimal,
     },
 
     #[error("Max slippage exceeded, deposits ratio: {deposits_ratio}, pools ratio: {pools_ratio}, slippage tolerance: {slippage_tolerance}")]
     MaxSlippageAssertion {
-        deposits_ratio: Decimal,
-        pools_ratio: Decimal,
+        deposits_ratio: Decimal256,
+        pools_ratio: Decimal256,
         slippage_tolerance: Decimal,
     },
 
     #[error("Asset mismatch: {0}")]
     AssetMismatch(String),
 
+    #[error("Pair supports exactly 2 assets, got {0}")]
+    InvalidAssetCount(usize),
+
+    #[error("Pair supports from 2 to {max} assets, got {got}")]
+    AssetCountOutOfRange { max: usize, got: usize },
+
+    #[error("Pair has {expected} assets, got {got}")]
+    AssetCountMismatch { expected: usize, got: usize },
+
+    #[error("Both offer and ask asset are required by pair of more than 2 assets")]
+    MissingSwapAsset {},
+
     #[error("Explicit failure in message: {0}")]
     MessageFailure(String),
 
@@ -42,4 +97,25 @@ pub enum ContractError {
 
     #[error("Invalid commission value: {0}")]
     InvalidCommission(Decimal),
+
+    #[error("Invalid protocol fee share: {0}")]
+    InvalidProtocolFeeShare(Decimal),
+
+    #[error("Fee collector is required for non-zero protocol fee share")]
+    MissingFeeCollector {},
+
+    #[error("Commission group is required for commission tiers")]
+    MissingCommissionGroup {},
+
+    #[error("Duplicated commission tier for {0} points")]
+    DuplicateCommissionTier(u64),
+
+    #[error("Pair {0} are paused")]
+    Paused(Operation),
+
+    #[error("Limit order price has to be positive")]
+    InvalidLimitOrderPrice {},
+
+    #[error("No limit order proceeds to claim")]
+    NoLimitOrderProceeds {},
 }
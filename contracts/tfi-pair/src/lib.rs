@@ -1,8 +1,9 @@
 pub mod contract;
+pub mod curve;
 pub mod math;
 pub mod state;
 
-mod error;
+pub mod error;
 
 #[cfg(test)]
 mod multitest;
@@ -1,23 +1,58 @@
-use cosmwasm_std::{Decimal, StdResult, Uint128};
+use cosmwasm_std::{Decimal, Decimal256, StdError, StdResult, Uint256, Uint512};
+use std::convert::TryInto;
 
-/////////////////////////////////////////////////////////////
-const DECIMAL_FRACTIONAL: Uint128 = Uint128::new(1_000_000_000u128);
+/// Converts `Decimal` to `Decimal256`, both have the same precision so it is lossless
+pub fn decimal256(decimal: Decimal) -> Decimal256 {
+    Decimal256::new(decimal.atomics().into())
+}
 
-pub fn reverse_decimal(decimal: Decimal) -> Decimal {
-    if decimal.is_zero() {
-        return Decimal::zero();
-    }
+/// Adds wrapping around on overflow, Uniswap v2 style. Only differences of price accumulators are
+/// meaningful, and they stay correct across a single wrap.
+pub fn wrapping_add(lhs: Decimal256, rhs: Decimal256) -> Decimal256 {
+    let (lhs, rhs) = (lhs.atomics(), rhs.atomics());
+    Decimal256::new(
+        lhs.checked_add(rhs)
+            .unwrap_or_else(|_| lhs - (Uint256::MAX - rhs) - Uint256::from(1u8)),
+    )
+}
 
-    Decimal::from_ratio(DECIMAL_FRACTIONAL, decimal * DECIMAL_FRACTIONAL)
+/// Divides rounding up
+pub fn div_ceil(numerator: Uint256, denominator: Uint256) -> StdResult<Uint256> {
+    let quotient = numerator.checked_div(denominator)?;
+    if quotient * denominator == numerator {
+        Ok(quotient)
+    } else {
+        Ok(quotient + Uint256::from(1u8))
+    }
 }
 
-pub fn decimal_subtraction(a: Decimal, b: Decimal) -> StdResult<Decimal> {
-    Ok(Decimal::from_ratio(
-        (a * DECIMAL_FRACTIONAL).checked_sub(b * DECIMAL_FRACTIONAL)?,
-        DECIMAL_FRACTIONAL,
-    ))
+/// Returns the amount before commission deduction, which is `amount` after it, rounded up
+pub fn before_commission(amount: Uint256, commission: Decimal) -> StdResult<Uint256> {
+    let one = Decimal256::one().atomics();
+    let one_minus_commission = one.checked_sub(decimal256(commission).atomics())?;
+    if one_minus_commission.is_zero() {
+        return Err(StdError::generic_err(
+            "Divide by zero error computing the amount before commission",
+        ));
+    }
+
+    div_ceil(amount.checked_mul(one)?, one_minus_commission)
 }
 
-pub fn decimal_multiplication(a: Decimal, b: Decimal) -> Decimal {
-    Decimal::from_ratio(a * DECIMAL_FRACTIONAL * b, DECIMAL_FRACTIONAL)
+/// Integer square root, rounded down. Takes 512-bit value, so squares of any 256-bit values can
+/// be rooted.
+pub fn uint512_sqrt(value: Uint512) -> StdResult<Uint256> {
+    if value < Uint512::from(4u8) {
+        return Ok(std::cmp::min(value, Uint512::from(1u8)).try_into()?);
+    }
+
+    // Newton's method, starting above the root it decreases until converged
+    let two = Uint512::from(2u8);
+    let mut x = value;
+    let mut y = value / two + Uint512::from(1u8);
+    while y < x {
+        x = y;
+        y = (x + value / x) / two;
+    }
+    Ok(x.try_into()?)
 }
@@ -1,15 +1,25 @@
 use anyhow::{anyhow, Result};
-use cosmwasm_std::{coin, coins, to_binary, Addr, BankMsg, Decimal, Empty, StdError, Uint128};
-use cw20::{Cw20Coin, Cw20ExecuteMsg};
-use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper, Executor};
+use cosmwasm_std::{
+    coin, coins, from_binary, to_binary, Addr, BankMsg, Binary, CosmosMsg, Decimal, Decimal256,
+    Deps, DepsMut, Empty, Env, Event, MessageInfo, Response, StdError, StdResult, Uint128, WasmMsg,
+};
+use cw20::{Cw20Coin, Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_multi_test::{App, AppBuilder, AppResponse, Contract, ContractWrapper, Executor};
 use derivative::Derivative;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
+use crate::contract::MAX_LIMIT_ORDER_FILLS;
 use crate::error::ContractError;
 use tfi::asset::{Asset, AssetInfo, PairInfo};
 use tfi::pair::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg, ReverseSimulationResponse,
-    SimulationResponse,
+    AccruedFeesResponse, CommissionTier, CommissionTiersResponse, CumulativePricesResponse,
+    Cw20HookMsg, ExecuteMsg, FlashSwapCallbackMsg, InstantiateMsg, LimitOrderProceedsResponse,
+    LimitOrderResponse, LimitOrdersResponse, PoolResponse, QueryMsg, ReverseSimulationResponse,
+    SimulationResponse, WithdrawSingleSidedSimulationResponse,
 };
+use tfi::querier::compute_twap;
+use tg4::Member;
 
 const TEST_RESERVE: &str = "reserve";
 const DENOM: &str = "btc";
@@ -39,6 +49,15 @@ pub fn contract_pair() -> Box<dyn Contract<Empty>> {
     Box::new(contract)
 }
 
+pub fn contract_group() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        tg4_group::contract::execute,
+        tg4_group::contract::instantiate,
+        tg4_group::contract::query,
+    );
+    Box::new(contract)
+}
+
 pub fn contract_cw20() -> Box<dyn Contract<Empty>> {
     let contract = ContractWrapper::new(
         cw20_base::contract::execute,
@@ -48,6 +67,74 @@ pub fn contract_cw20() -> Box<dyn Contract<Empty>> {
     Box::new(contract)
 }
 
+/// Flash swap borrower, executing messages passed in the callback message
+pub fn contract_flash_borrower() -> Box<dyn Contract<Empty>> {
+    fn execute(
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: FlashSwapCallbackMsg,
+    ) -> StdResult<Response> {
+        let FlashSwapCallbackMsg::FlashSwapCallback(callback) = msg;
+        let msgs: Vec<CosmosMsg> = from_binary(&callback.msg)?;
+        Ok(Response::new().add_messages(msgs))
+    }
+
+    fn instantiate(
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty,
+    ) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    fn query(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+        Err(StdError::generic_err("Flash borrower has no queries"))
+    }
+
+    Box::new(ContractWrapper::new(execute, instantiate, query))
+}
+
+/// Execute messages of `contract_swap_receiver`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum ReceiverExecuteMsg {
+    Receive(Cw20ReceiveMsg),
+    Deposit {},
+}
+
+/// Contract receiving swap return, reporting received amount in `deposit` attribute
+pub fn contract_swap_receiver() -> Box<dyn Contract<Empty>> {
+    fn execute(
+        _deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        msg: ReceiverExecuteMsg,
+    ) -> StdResult<Response> {
+        let deposit = match msg {
+            ReceiverExecuteMsg::Receive(msg) => format!("{}{}", msg.amount, info.sender),
+            ReceiverExecuteMsg::Deposit {} => info.funds[0].to_string(),
+        };
+        Ok(Response::new().add_attribute("deposit", deposit))
+    }
+
+    fn instantiate(
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty,
+    ) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    fn query(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+        Err(StdError::generic_err("Swap receiver has no queries"))
+    }
+
+    Box::new(ContractWrapper::new(execute, instantiate, query))
+}
+
 /// Helper struct providing unified environment for tfi-pair testing
 ///
 /// It assumes actors:
@@ -122,7 +209,7 @@ impl Suite {
                 lp.clone(),
                 self.pair.clone(),
                 &ExecuteMsg::ProvideLiquidity {
-                    assets: [
+                    assets: vec![
                         Asset {
                             info: AssetInfo::Native("btc".to_owned()),
                             amount: Uint128::new(btc),
@@ -133,6 +220,7 @@ impl Suite {
                         },
                     ],
                     slippage_tolerance: slippage_tolerance.into(),
+                    deadline: None,
                 },
                 &coins(btc, "btc"),
             )
@@ -161,9 +249,13 @@ impl Suite {
                         info: AssetInfo::Native("btc".to_owned()),
                         amount: Uint128::new(btc),
                     },
+                    ask_asset_info: None,
                     belief_price: belief_price.into(),
                     max_spread: max_spread.into(),
                     to: to.into().as_ref().map(ToString::to_string),
+                    min_return: None,
+                    deadline: None,
+                    to_msg: None,
                 },
                 &coins(btc, "btc"),
             )
@@ -191,9 +283,197 @@ impl Suite {
                     contract: self.pair.to_string(),
                     amount: Uint128::new(cash),
                     msg: to_binary(&Cw20HookMsg::Swap {
+                        ask_asset_info: None,
                         belief_price: belief_price.into(),
                         max_spread: max_spread.into(),
                         to: to.into().as_ref().map(ToString::to_string),
+                        min_return: None,
+                        deadline: None,
+                        to_msg: None,
+                    })
+                    .unwrap(),
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    /// Helper swapping btc for cash delivered to `contract` with `to_msg`
+    fn swap_btc_to_contract(
+        &mut self,
+        trader: &Addr,
+        btc: u128,
+        contract: &Addr,
+        to_msg: Binary,
+    ) -> Result<AppResponse> {
+        self.app
+            .execute_contract(
+                trader.clone(),
+                self.pair.clone(),
+                &ExecuteMsg::Swap {
+                    offer_asset: Asset {
+                        info: AssetInfo::Native("btc".to_owned()),
+                        amount: Uint128::new(btc),
+                    },
+                    ask_asset_info: None,
+                    belief_price: None,
+                    max_spread: None,
+                    to: Some(contract.to_string()),
+                    min_return: None,
+                    deadline: None,
+                    to_msg: Some(to_msg),
+                },
+                &coins(btc, "btc"),
+            )
+            .map_err(|err| anyhow!(err))
+    }
+
+    /// Helper swapping cash for btc delivered to `contract` with `to_msg`
+    fn swap_cash_to_contract(
+        &mut self,
+        trader: &Addr,
+        cash: u128,
+        contract: &Addr,
+        to_msg: Binary,
+    ) -> Result<AppResponse> {
+        self.app
+            .execute_contract(
+                trader.clone(),
+                self.cash.clone(),
+                &cw20_base::msg::ExecuteMsg::Send {
+                    contract: self.pair.to_string(),
+                    amount: Uint128::new(cash),
+                    msg: to_binary(&Cw20HookMsg::Swap {
+                        ask_asset_info: None,
+                        belief_price: None,
+                        max_spread: None,
+                        to: Some(contract.to_string()),
+                        min_return: None,
+                        deadline: None,
+                        to_msg: Some(to_msg),
+                    })
+                    .unwrap(),
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))
+    }
+
+    /// Helper buying exact amount of cash for at most `max_btc`
+    ///
+    /// Executes `SwapExactOut` message on pair
+    fn swap_exact_out_btc(
+        &mut self,
+        trader: &Addr,
+        max_btc: u128,
+        cash: u128,
+    ) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                trader.clone(),
+                self.pair.clone(),
+                &ExecuteMsg::SwapExactOut {
+                    offer_asset: Asset {
+                        info: AssetInfo::Native("btc".to_owned()),
+                        amount: Uint128::new(max_btc),
+                    },
+                    ask_asset_info: None,
+                    ask_amount: Uint128::new(cash),
+                    to: None,
+                    deadline: None,
+                },
+                &coins(max_btc, "btc"),
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    /// Helper buying exact amount of btc for at most `max_cash`
+    ///
+    /// Executes `Send` message on cash contract, with `Cw20HookMsg::SwapExactOut` message as hook
+    fn swap_exact_out_cash(
+        &mut self,
+        trader: &Addr,
+        max_cash: u128,
+        btc: u128,
+    ) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                trader.clone(),
+                self.cash.clone(),
+                &cw20_base::msg::ExecuteMsg::Send {
+                    contract: self.pair.to_string(),
+                    amount: Uint128::new(max_cash),
+                    msg: to_binary(&Cw20HookMsg::SwapExactOut {
+                        ask_asset_info: None,
+                        ask_amount: Uint128::new(btc),
+                        to: None,
+                        deadline: None,
+                    })
+                    .unwrap(),
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    /// Helper providing liquidity with btc only
+    ///
+    /// Executes `ProvideSingleSided` message on pair
+    fn provide_single_sided_btc(
+        &mut self,
+        lp: &Addr,
+        btc: u128,
+        slippage_tolerance: impl Into<Option<Decimal>>,
+        min_share: impl Into<Option<u128>>,
+    ) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                lp.clone(),
+                self.pair.clone(),
+                &ExecuteMsg::ProvideSingleSided {
+                    offer_asset: Asset {
+                        info: AssetInfo::Native("btc".to_owned()),
+                        amount: Uint128::new(btc),
+                    },
+                    slippage_tolerance: slippage_tolerance.into(),
+                    min_share: min_share.into().map(Uint128::new),
+                    deadline: None,
+                },
+                &coins(btc, "btc"),
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    /// Helper providing liquidity with cash only
+    ///
+    /// Executes `Send` message on cash contract, with `Cw20HookMsg::ProvideSingleSided` message
+    /// as hook
+    fn provide_single_sided_cash(
+        &mut self,
+        lp: &Addr,
+        cash: u128,
+        slippage_tolerance: impl Into<Option<Decimal>>,
+        min_share: impl Into<Option<u128>>,
+    ) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                lp.clone(),
+                self.cash.clone(),
+                &cw20_base::msg::ExecuteMsg::Send {
+                    contract: self.pair.to_string(),
+                    amount: Uint128::new(cash),
+                    msg: to_binary(&Cw20HookMsg::ProvideSingleSided {
+                        slippage_tolerance: slippage_tolerance.into(),
+                        min_share: min_share.into().map(Uint128::new),
+                        deadline: None,
                     })
                     .unwrap(),
                 },
@@ -208,6 +488,16 @@ impl Suite {
     ///
     /// Queries with `QueryMsg::Simulation` and retuns `SimulationResponse`
     fn simulate_swap(&mut self, offer: u128, asset: AssetInfo) -> Result<SimulationResponse> {
+        self.simulate_swap_as(None, offer, asset)
+    }
+
+    /// Helper for swap simulation with commission tier of `trader`
+    fn simulate_swap_as<'a>(
+        &mut self,
+        trader: impl Into<Option<&'a Addr>>,
+        offer: u128,
+        asset: AssetInfo,
+    ) -> Result<SimulationResponse> {
         self.app
             .wrap()
             .query_wasm_smart(
@@ -217,6 +507,8 @@ impl Suite {
                         info: asset,
                         amount: Uint128::new(offer),
                     },
+                    ask_asset_info: None,
+                    trader: trader.into().map(ToString::to_string),
                 },
             )
             .map_err(|err| anyhow!(err))
@@ -239,254 +531,764 @@ impl Suite {
                         info: asset,
                         amount: Uint128::new(ask),
                     },
+                    offer_asset_info: None,
+                    trader: None,
                 },
             )
             .map_err(|err| anyhow!(err))
     }
 
-    /// Helper for withdrawing liquidity from pair
+    /// Helper for querying pools
     ///
-    /// Executes `Send` on lt contract with `Cw20HookMsg::WithdrawLiquidity` as send hook message
-    fn withdraw_liquidity(&mut self, lp: &Addr, lt: u128) -> Result<&mut Self> {
-        self.app
-            .execute_contract(
-                lp.clone(),
-                self.lt.clone(),
-                &cw20_base::msg::ExecuteMsg::Send {
-                    contract: self.pair.to_string(),
-                    amount: Uint128::new(lt),
-                    msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {}).unwrap(),
-                },
-                &[],
-            )
+    /// Queries with `QueryMsg::Pool` and returns pools amounts as (btc, cash)
+    fn pools(&self) -> Result<(u128, u128)> {
+        let resp: PoolResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(self.pair.clone(), &QueryMsg::Pool {})
             .map_err(|err| anyhow!(err))?;
 
-        Ok(self)
+        Ok((resp.assets[0].amount.u128(), resp.assets[1].amount.u128()))
     }
 
-    /// Asserts if balances on account are as expected
-    #[track_caller]
-    fn assert_balances(&mut self, addr: &Addr, btc: u128, cash: u128, lt: u128) -> &mut Self {
-        let btc_balance = self
-            .app
-            .wrap()
-            .query_balance(addr.clone(), "btc")
-            .unwrap_or_else(|_| panic!("Query for balance of btc for {} failed", addr));
+    /// Sends btc and cash directly to pair, not through any of pair messages
+    fn donate(&mut self, sender: &Addr, btc: u128, cash: u128) -> Result<&mut Self> {
+        if btc > 0 {
+            self.app
+                .send_tokens(sender.clone(), self.pair.clone(), &coins(btc, "btc"))
+                .map_err(|err| anyhow!(err))?;
+        }
 
-        assert_eq!(
-            btc_balance,
-            coin(btc, "btc"),
-            "Btc balance mismatch, expected: {}, actual: {}",
-            btc,
-            btc_balance.amount
-        );
+        if cash > 0 {
+            self.app
+                .execute_contract(
+                    sender.clone(),
+                    self.cash.clone(),
+                    &Cw20ExecuteMsg::Transfer {
+                        recipient: self.pair.to_string(),
+                        amount: Uint128::new(cash),
+                    },
+                    &[],
+                )
+                .map_err(|err| anyhow!(err))?;
+        }
 
-        let cash_balance: cw20::BalanceResponse = self
+        Ok(self)
+    }
+
+    /// Instantiates flash swap borrower contract, funded with btc and cash by `funder`
+    fn instantiate_flash_borrower(&mut self, funder: &Addr, btc: u128, cash: u128) -> Result<Addr> {
+        let code_id = self.app.store_code(contract_flash_borrower());
+        let borrower = self
             .app
-            .wrap()
-            .query_wasm_smart(
+            .instantiate_contract(
+                code_id,
+                self.admin.clone(),
+                &Empty {},
+                &[],
+                "Flash borrower",
+                None,
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        self.app
+            .send_tokens(funder.clone(), borrower.clone(), &coins(btc, "btc"))
+            .map_err(|err| anyhow!(err))?;
+        self.app
+            .execute_contract(
+                funder.clone(),
                 self.cash.clone(),
-                &cw20_base::msg::QueryMsg::Balance {
-                    address: addr.to_string(),
+                &Cw20ExecuteMsg::Transfer {
+                    recipient: borrower.to_string(),
+                    amount: Uint128::new(cash),
                 },
+                &[],
             )
-            .unwrap_or_else(|_| panic!("Query for balance of cash on {} failed", addr));
+            .map_err(|err| anyhow!(err))?;
 
-        assert_eq!(
-            cash_balance,
-            cw20::BalanceResponse {
-                balance: Uint128::new(cash)
-            },
-            "Cash balance mismatch, expected: {}, actual: {}",
-            cash,
-            cash_balance.balance
-        );
+        Ok(borrower)
+    }
 
-        let lt_balance: cw20::BalanceResponse = self
-            .app
-            .wrap()
-            .query_wasm_smart(
-                self.lt.clone(),
-                &cw20_base::msg::QueryMsg::Balance {
-                    address: addr.to_string(),
-                },
-            )
-            .unwrap_or_else(|_| panic!("Query for balance of liquidity tokens on {} failed", addr));
+    /// Builds messages sending btc and cash to pair
+    fn repay_msgs(&self, btc: u128, cash: u128) -> Result<Vec<CosmosMsg>> {
+        let mut msgs = vec![];
+        if btc > 0 {
+            msgs.push(
+                BankMsg::Send {
+                    to_address: self.pair.to_string(),
+                    amount: coins(btc, "btc"),
+                }
+                .into(),
+            );
+        }
 
-        assert_eq!(
-            lt_balance,
-            cw20::BalanceResponse {
-                balance: Uint128::new(lt)
-            },
-            "Liquidity tokens balance missmatch, expected: {}, actual: {}",
-            lt,
-            lt_balance.balance
-        );
+        if cash > 0 {
+            msgs.push(
+                WasmMsg::Execute {
+                    contract_addr: self.cash.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: self.pair.to_string(),
+                        amount: Uint128::new(cash),
+                    })?,
+                    funds: vec![],
+                }
+                .into(),
+            );
+        }
 
-        self
+        Ok(msgs)
     }
-}
 
-/// Initial trader/luqudity provider state
-#[derive(Debug)]
-struct ActorConfig {
-    /// Actor addr
-    addr: Addr,
-    /// Actor initial btc balance
-    btc: u128,
-    /// Actor initial cash balance
-    cash: u128,
-}
+    /// Executes `FlashSwap` on pair by `borrower` contract, which executes `callback_msgs` when
+    /// called back
+    fn flash_swap(
+        &mut self,
+        borrower: &Addr,
+        ask_asset: AssetInfo,
+        amount: u128,
+        callback_msgs: Vec<CosmosMsg>,
+    ) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                borrower.clone(),
+                self.pair.clone(),
+                &ExecuteMsg::FlashSwap {
+                    ask_asset: Asset {
+                        info: ask_asset,
+                        amount: Uint128::new(amount),
+                    },
+                    msg: to_binary(&callback_msgs)?,
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
 
-/// Builder helping construction of `Suite` helper
-#[derive(Debug, Default)]
-struct SuiteConfig {
-    /// Initial traders config
-    traders: Vec<ActorConfig>,
-    /// Initial liquidity providers config
-    lps: Vec<ActorConfig>,
-    /// Commission to initialize pair with
-    commission: Option<Decimal>,
-}
+        Ok(self)
+    }
 
-impl SuiteConfig {
-    /// Creates new config without extra actors
-    fn new() -> Self {
-        Self::default()
+    /// Executes `Sync` on pair
+    fn sync(&mut self, sender: &Addr) -> Result<&mut Self> {
+        self.app
+            .execute_contract(sender.clone(), self.pair.clone(), &ExecuteMsg::Sync {}, &[])
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
     }
 
-    /// Adds new traider to test suite
-    fn with_trader(mut self, addr: &str, btc: u128, cash: u128) -> Self {
-        self.traders.push(ActorConfig {
-            addr: Addr::unchecked(addr),
-            btc,
-            cash,
-        });
+    /// Executes `Skim` on pair
+    fn skim<'a>(&mut self, sender: &Addr, to: impl Into<Option<&'a Addr>>) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                sender.clone(),
+                self.pair.clone(),
+                &ExecuteMsg::Skim {
+                    to: to.into().map(Addr::to_string),
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
 
-        self
+        Ok(self)
     }
 
-    /// Adds new liquidity provider to test suite
-    fn with_liquidity_provider(mut self, addr: &str, btc: u128, cash: u128) -> Self {
-        self.lps.push(ActorConfig {
-            addr: Addr::unchecked(addr),
-            btc,
-            cash,
-        });
+    /// Helper for querying accrued protocol fees
+    ///
+    /// Queries with `QueryMsg::AccruedFees` and returns fees amounts as (btc, cash)
+    fn accrued_fees(&self) -> Result<(u128, u128)> {
+        let resp: AccruedFeesResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(self.pair.clone(), &QueryMsg::AccruedFees {})
+            .map_err(|err| anyhow!(err))?;
 
-        self
+        Ok((resp.fees[0].amount.u128(), resp.fees[1].amount.u128()))
     }
 
-    fn with_commission(mut self, commission: Decimal) -> Self {
-        self.commission = Some(commission);
-        self
+    /// Executes `CollectFees` on pair
+    fn collect_fees(&mut self, sender: &Addr) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                sender.clone(),
+                self.pair.clone(),
+                &ExecuteMsg::CollectFees {},
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
     }
 
-    /// Initializes given actors with initial btc balance, returning back actors addresses and
-    /// configuration of initial cash balance to be set later while creating cash contract
-    fn init_actors(app: &mut App, actors: Vec<ActorConfig>) -> Result<(Vec<Addr>, Vec<Cw20Coin>)> {
-        let pairs = actors
-            .into_iter()
-            .map(|lp| -> Result<_> {
-                app.execute(
-                    Addr::unchecked(TEST_RESERVE),
-                    BankMsg::Send {
-                        to_address: lp.addr.to_string(),
-                        amount: coins(lp.btc, DENOM),
-                    }
-                    .into(),
-                )
-                .unwrap();
+    /// Helper for querying price accumulators
+    ///
+    /// Queries with `QueryMsg::CumulativePrices` and returns `CumulativePricesResponse`
+    fn cumulative_prices(&self) -> Result<CumulativePricesResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.pair.clone(), &QueryMsg::CumulativePrices {})
+            .map_err(|err| anyhow!(err))
+    }
 
-                let cash = Cw20Coin {
-                    address: lp.addr.to_string(),
-                    amount: Uint128::new(lp.cash),
-                };
+    /// Moves block time forward by `seconds`
+    fn advance_time(&mut self, seconds: u64) -> &mut Self {
+        self.app
+            .update_block(|block| block.time = block.time.plus_seconds(seconds));
+        self
+    }
 
-                Ok((lp.addr, cash))
-            })
-            .collect::<Result<Vec<_>>>()?;
+    /// Helper for withdrawing liquidity from pair
+    ///
+    /// Executes `Send` on lt contract with `Cw20HookMsg::WithdrawLiquidity` as send hook message
+    fn withdraw_liquidity(&mut self, lp: &Addr, lt: u128) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                lp.clone(),
+                self.lt.clone(),
+                &cw20_base::msg::ExecuteMsg::Send {
+                    contract: self.pair.to_string(),
+                    amount: Uint128::new(lt),
+                    msg: to_binary(&Cw20HookMsg::WithdrawLiquidity { deadline: None }).unwrap(),
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
 
-        Ok(pairs.into_iter().unzip())
+        Ok(self)
     }
 
-    /// Initializes actors and returns proper test suite helper
-    fn init(self) -> Result<Suite> {
-        let mut app = mock_app();
+    /// Helper withdrawing liquidity paid out in single asset
+    ///
+    /// Executes `Send` message on liquidity token contract, with `Cw20HookMsg::WithdrawSingleSided`
+    /// message as hook
+    fn withdraw_single_sided(
+        &mut self,
+        lp: &Addr,
+        lt: u128,
+        ask_asset: AssetInfo,
+        min_return: impl Into<Option<u128>>,
+    ) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                lp.clone(),
+                self.lt.clone(),
+                &cw20_base::msg::ExecuteMsg::Send {
+                    contract: self.pair.to_string(),
+                    amount: Uint128::new(lt),
+                    msg: to_binary(&Cw20HookMsg::WithdrawSingleSided {
+                        ask_asset,
+                        min_return: min_return.into().map(Uint128::new),
+                        deadline: None,
+                    })
+                    .unwrap(),
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
 
-        let cw20_id = app.store_code(contract_cw20());
-        let pair_id = app.store_code(contract_pair());
+        Ok(self)
+    }
 
-        let admin = Addr::unchecked("admin");
+    /// Helper for single sided withdrawal simulation
+    ///
+    /// Queries with `QueryMsg::WithdrawSingleSidedSimulation`
+    fn simulate_withdraw_single_sided(
+        &self,
+        lt: u128,
+        ask_asset: AssetInfo,
+    ) -> Result<WithdrawSingleSidedSimulationResponse> {
+        self.simulate_withdraw_single_sided_as(None, lt, ask_asset)
+    }
 
-        // Initialize actors
-        let (lps, lp_balances) = Self::init_actors(&mut app, self.lps)?;
-        let (traders, traders_balances) = Self::init_actors(&mut app, self.traders)?;
+    /// Helper for single sided withdrawal simulation with commission tier of `trader`
+    fn simulate_withdraw_single_sided_as<'a>(
+        &self,
+        trader: impl Into<Option<&'a Addr>>,
+        lt: u128,
+        ask_asset: AssetInfo,
+    ) -> Result<WithdrawSingleSidedSimulationResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                self.pair.clone(),
+                &QueryMsg::WithdrawSingleSidedSimulation {
+                    share: Uint128::new(lt),
+                    ask_asset,
+                    trader: trader.into().map(ToString::to_string),
+                },
+            )
+            .map_err(|err| anyhow!(err))
+    }
 
-        let initial_balances = [lp_balances, traders_balances].concat();
-        let cash = app
-            .instantiate_contract(
-                cw20_id,
-                admin.clone(),
-                &cw20_base::msg::InstantiateMsg {
-                    name: "Cash Money".to_owned(),
-                    symbol: "cash".to_owned(),
-                    decimals: 2,
-                    initial_balances,
-                    mint: None,
-                    marketing: None,
+    /// Adds `member` to the commission group of the pair with `points`
+    fn add_commission_member(&mut self, member: &Addr, points: u64) -> Result<&mut Self> {
+        let tiers: CommissionTiersResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(self.pair.clone(), &QueryMsg::CommissionTiers {})
+            .map_err(|err| anyhow!(err))?;
+        let group = tiers
+            .commission_group
+            .ok_or_else(|| anyhow!("No commission group"))?;
+
+        self.app
+            .execute_contract(
+                self.admin.clone(),
+                Addr::unchecked(group),
+                &tg4_group::msg::ExecuteMsg::UpdateMembers {
+                    remove: vec![],
+                    add: vec![Member {
+                        addr: member.to_string(),
+                        points,
+                        start_height: None,
+                    }],
                 },
                 &[],
-                "Cash",
-                None,
             )
             .map_err(|err| anyhow!(err))?;
 
-        let instantiate_msg = InstantiateMsg::new(
-            [
-                AssetInfo::Native("btc".to_owned()),
-                AssetInfo::Token(cash.clone()),
-            ],
-            cw20_id,
-        );
+        Ok(self)
+    }
 
-        let instantiate_msg = if let Some(commission) = self.commission {
-            instantiate_msg.with_commission(commission)
-        } else {
-            instantiate_msg
-        };
+    /// Places limit order selling btc for cash with `ExecuteMsg::PlaceLimitOrder`
+    fn place_limit_order_btc(
+        &mut self,
+        owner: &Addr,
+        btc: u128,
+        price: Decimal,
+    ) -> Result<AppResponse> {
+        self.app
+            .execute_contract(
+                owner.clone(),
+                self.pair.clone(),
+                &ExecuteMsg::PlaceLimitOrder {
+                    offer_asset: Asset {
+                        info: self.btc(),
+                        amount: Uint128::new(btc),
+                    },
+                    price,
+                    deadline: None,
+                },
+                &coins(btc, "btc"),
+            )
+            .map_err(|err| anyhow!(err))
+    }
 
-        let pair = app
-            .instantiate_contract(pair_id, admin.clone(), &instantiate_msg, &[], "Pair", None)
-            .map_err(|err| anyhow!(err))?;
+    /// Places limit order selling cash for btc with cw20 `Send` and `Cw20HookMsg::PlaceLimitOrder`
+    fn place_limit_order_cash(
+        &mut self,
+        owner: &Addr,
+        cash: u128,
+        price: Decimal,
+    ) -> Result<AppResponse> {
+        self.app
+            .execute_contract(
+                owner.clone(),
+                self.cash.clone(),
+                &cw20_base::msg::ExecuteMsg::Send {
+                    contract: self.pair.to_string(),
+                    amount: Uint128::new(cash),
+                    msg: to_binary(&Cw20HookMsg::PlaceLimitOrder {
+                        price,
+                        deadline: None,
+                    })
+                    .unwrap(),
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))
+    }
 
-        let PairInfo {
-            liquidity_token: lt,
-            ..
-        } = app
-            .wrap()
-            .query_wasm_smart(pair.clone(), &QueryMsg::Pair {})
+    fn cancel_limit_order(&mut self, owner: &Addr, order_id: u64) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                owner.clone(),
+                self.pair.clone(),
+                &ExecuteMsg::CancelLimitOrder { order_id },
+                &[],
+            )
             .map_err(|err| anyhow!(err))?;
 
-        Ok(Suite {
-            app,
-            admin,
-            cash,
-            pair,
-            lt,
-            traders,
-            lps,
-        })
+        Ok(self)
     }
-}
-
-#[test]
-// just do basic setup
-fn setup_liquidity_pool() {
-    let mut app = mock_app();
 
-    // set personal balance
-    let owner = Addr::unchecked("owner");
+    fn claim_limit_order_proceeds(&mut self, owner: &Addr) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                owner.clone(),
+                self.pair.clone(),
+                &ExecuteMsg::ClaimLimitOrderProceeds {},
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    /// Returns btc and cash proceeds of filled orders of `owner`, not claimed yet
+    fn limit_order_proceeds(&self, owner: &Addr) -> Result<(u128, u128)> {
+        let resp: LimitOrderProceedsResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                self.pair.clone(),
+                &QueryMsg::LimitOrderProceeds {
+                    owner: owner.to_string(),
+                },
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok((
+            resp.proceeds[0].amount.u128(),
+            resp.proceeds[1].amount.u128(),
+        ))
+    }
+
+    fn limit_order(&self, order_id: u64) -> Result<LimitOrderResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.pair.clone(), &QueryMsg::LimitOrder { order_id })
+            .map_err(|err| anyhow!(err))
+    }
+
+    /// Returns ids of open orders of `owner`
+    fn limit_orders(
+        &self,
+        owner: &Addr,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> Result<Vec<u64>> {
+        let resp: LimitOrdersResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                self.pair.clone(),
+                &QueryMsg::LimitOrders {
+                    owner: owner.to_string(),
+                    start_after,
+                    limit,
+                },
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(resp
+            .orders
+            .into_iter()
+            .map(|order| order.order_id)
+            .collect())
+    }
+
+    /// Asserts if balances on account are as expected
+    #[track_caller]
+    fn assert_balances(&mut self, addr: &Addr, btc: u128, cash: u128, lt: u128) -> &mut Self {
+        let btc_balance = self
+            .app
+            .wrap()
+            .query_balance(addr.clone(), "btc")
+            .unwrap_or_else(|_| panic!("Query for balance of btc for {} failed", addr));
+
+        assert_eq!(
+            btc_balance,
+            coin(btc, "btc"),
+            "Btc balance mismatch, expected: {}, actual: {}",
+            btc,
+            btc_balance.amount
+        );
+
+        let cash_balance: cw20::BalanceResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                self.cash.clone(),
+                &cw20_base::msg::QueryMsg::Balance {
+                    address: addr.to_string(),
+                },
+            )
+            .unwrap_or_else(|_| panic!("Query for balance of cash on {} failed", addr));
+
+        assert_eq!(
+            cash_balance,
+            cw20::BalanceResponse {
+                balance: Uint128::new(cash)
+            },
+            "Cash balance mismatch, expected: {}, actual: {}",
+            cash,
+            cash_balance.balance
+        );
+
+        let lt_balance: cw20::BalanceResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                self.lt.clone(),
+                &cw20_base::msg::QueryMsg::Balance {
+                    address: addr.to_string(),
+                },
+            )
+            .unwrap_or_else(|_| panic!("Query for balance of liquidity tokens on {} failed", addr));
+
+        assert_eq!(
+            lt_balance,
+            cw20::BalanceResponse {
+                balance: Uint128::new(lt)
+            },
+            "Liquidity tokens balance missmatch, expected: {}, actual: {}",
+            lt,
+            lt_balance.balance
+        );
+
+        self
+    }
+}
+
+/// Initial trader/luqudity provider state
+#[derive(Debug)]
+struct ActorConfig {
+    /// Actor addr
+    addr: Addr,
+    /// Actor initial btc balance
+    btc: u128,
+    /// Actor initial cash balance
+    cash: u128,
+}
+
+/// Builder helping construction of `Suite` helper
+#[derive(Debug, Default)]
+struct SuiteConfig {
+    /// Initial traders config
+    traders: Vec<ActorConfig>,
+    /// Initial liquidity providers config
+    lps: Vec<ActorConfig>,
+    /// Commission to initialize pair with
+    commission: Option<Decimal>,
+    /// Fee collector and protocol fee share to initialize pair with
+    protocol_fee: Option<(String, Decimal)>,
+    /// Members of the pair whitelist group, the pair is not whitelisted if none
+    whitelist: Option<Vec<String>>,
+    /// Members of the pair commission group, the pair has no commission tiers if none
+    commission_group: Option<Vec<Member>>,
+    /// Commission tiers to initialize pair with
+    commission_tiers: Vec<CommissionTier>,
+}
+
+impl SuiteConfig {
+    /// Creates new config without extra actors
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds new traider to test suite
+    fn with_trader(mut self, addr: &str, btc: u128, cash: u128) -> Self {
+        self.traders.push(ActorConfig {
+            addr: Addr::unchecked(addr),
+            btc,
+            cash,
+        });
+
+        self
+    }
+
+    /// Adds new liquidity provider to test suite
+    fn with_liquidity_provider(mut self, addr: &str, btc: u128, cash: u128) -> Self {
+        self.lps.push(ActorConfig {
+            addr: Addr::unchecked(addr),
+            btc,
+            cash,
+        });
+
+        self
+    }
+
+    fn with_commission(mut self, commission: Decimal) -> Self {
+        self.commission = Some(commission);
+        self
+    }
+
+    fn with_protocol_fee(mut self, fee_collector: &str, share: Decimal) -> Self {
+        self.protocol_fee = Some((fee_collector.to_owned(), share));
+        self
+    }
+
+    fn with_whitelist(mut self, members: &[&str]) -> Self {
+        self.whitelist = Some(members.iter().map(|member| member.to_string()).collect());
+        self
+    }
+
+    fn with_commission_tiers(mut self, members: &[(&str, u64)], tiers: &[(u64, Decimal)]) -> Self {
+        self.commission_group = Some(
+            members
+                .iter()
+                .map(|(addr, points)| Member {
+                    addr: addr.to_string(),
+                    points: *points,
+                    start_height: None,
+                })
+                .collect(),
+        );
+        self.commission_tiers = tiers
+            .iter()
+            .map(|(min_points, commission)| CommissionTier::new(*min_points, *commission))
+            .collect();
+        self
+    }
+
+    /// Initializes given actors with initial btc balance, returning back actors addresses and
+    /// configuration of initial cash balance to be set later while creating cash contract
+    fn init_actors(app: &mut App, actors: Vec<ActorConfig>) -> Result<(Vec<Addr>, Vec<Cw20Coin>)> {
+        let pairs = actors
+            .into_iter()
+            .map(|lp| -> Result<_> {
+                app.execute(
+                    Addr::unchecked(TEST_RESERVE),
+                    BankMsg::Send {
+                        to_address: lp.addr.to_string(),
+                        amount: coins(lp.btc, DENOM),
+                    }
+                    .into(),
+                )
+                .unwrap();
+
+                let cash = Cw20Coin {
+                    address: lp.addr.to_string(),
+                    amount: Uint128::new(lp.cash),
+                };
+
+                Ok((lp.addr, cash))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(pairs.into_iter().unzip())
+    }
+
+    /// Initializes actors and returns proper test suite helper
+    fn init(self) -> Result<Suite> {
+        let mut app = mock_app();
+
+        let cw20_id = app.store_code(contract_cw20());
+        let pair_id = app.store_code(contract_pair());
+
+        let admin = Addr::unchecked("admin");
+
+        // Initialize actors
+        let (lps, lp_balances) = Self::init_actors(&mut app, self.lps)?;
+        let (traders, traders_balances) = Self::init_actors(&mut app, self.traders)?;
+
+        let initial_balances = [lp_balances, traders_balances].concat();
+        let cash = app
+            .instantiate_contract(
+                cw20_id,
+                admin.clone(),
+                &cw20_base::msg::InstantiateMsg {
+                    name: "Cash Money".to_owned(),
+                    symbol: "cash".to_owned(),
+                    decimals: 2,
+                    initial_balances,
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                "Cash",
+                None,
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        let instantiate_msg = InstantiateMsg::new(
+            [
+                AssetInfo::Native("btc".to_owned()),
+                AssetInfo::Token(cash.clone()),
+            ],
+            cw20_id,
+        );
+
+        let instantiate_msg = if let Some(commission) = self.commission {
+            instantiate_msg.with_commission(commission)
+        } else {
+            instantiate_msg
+        };
+
+        let instantiate_msg = if let Some((fee_collector, share)) = self.protocol_fee {
+            instantiate_msg.with_protocol_fee(fee_collector, share)
+        } else {
+            instantiate_msg
+        };
+
+        let instantiate_msg = if let Some(members) = self.whitelist {
+            let group_id = app.store_code(contract_group());
+            let members = members
+                .into_iter()
+                .map(|addr| Member {
+                    addr,
+                    points: 1,
+                    start_height: None,
+                })
+                .collect();
+            let whitelist = app
+                .instantiate_contract(
+                    group_id,
+                    admin.clone(),
+                    &tg4_group::msg::InstantiateMsg {
+                        admin: Some(admin.to_string()),
+                        members,
+                    },
+                    &[],
+                    "Whitelist",
+                    None,
+                )
+                .map_err(|err| anyhow!(err))?;
+            instantiate_msg.with_whitelist_group(whitelist)
+        } else {
+            instantiate_msg
+        };
+
+        let instantiate_msg = if let Some(members) = self.commission_group {
+            let group_id = app.store_code(contract_group());
+            let group = app
+                .instantiate_contract(
+                    group_id,
+                    admin.clone(),
+                    &tg4_group::msg::InstantiateMsg {
+                        admin: Some(admin.to_string()),
+                        members,
+                    },
+                    &[],
+                    "Commission group",
+                    None,
+                )
+                .map_err(|err| anyhow!(err))?;
+            instantiate_msg.with_commission_tiers(group, self.commission_tiers)
+        } else {
+            instantiate_msg
+        };
+
+        let pair = app
+            .instantiate_contract(pair_id, admin.clone(), &instantiate_msg, &[], "Pair", None)
+            .map_err(|err| anyhow!(err))?;
+
+        let PairInfo {
+            liquidity_token: lt,
+            ..
+        } = app
+            .wrap()
+            .query_wasm_smart(pair.clone(), &QueryMsg::Pair {})
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(Suite {
+            app,
+            admin,
+            cash,
+            pair,
+            lt,
+            traders,
+            lps,
+        })
+    }
+}
+
+#[test]
+// just do basic setup
+fn setup_liquidity_pool() {
+    let mut app = mock_app();
+
+    // set personal balance
+    let owner = Addr::unchecked("owner");
     let init_funds = coins(20000, DENOM);
     app.execute(
         Addr::unchecked(TEST_RESERVE),
@@ -494,291 +1296,1542 @@ fn setup_liquidity_pool() {
             to_address: owner.to_string(),
             amount: init_funds,
         }
-        .into(),
-    )
-    .unwrap();
+        .into(),
+    )
+    .unwrap();
+
+    // set up cw20 contract with some tokens
+    let cw20_id = app.store_code(contract_cw20());
+    let msg = cw20_base::msg::InstantiateMsg {
+        name: "Cash Money".to_string(),
+        symbol: "CASH".to_string(),
+        decimals: 2,
+        initial_balances: vec![Cw20Coin {
+            address: owner.to_string(),
+            amount: Uint128::new(50000),
+        }],
+        mint: None,
+        marketing: None,
+    };
+    let cash_addr = app
+        .instantiate_contract(cw20_id, owner.clone(), &msg, &[], "CASH", None)
+        .unwrap();
+
+    // set up pair contract
+    let pair_id = app.store_code(contract_pair());
+    let msg = InstantiateMsg::new(
+        [
+            AssetInfo::Native("btc".into()),
+            AssetInfo::Token(cash_addr.clone()),
+        ],
+        cw20_id,
+    );
+    let pair_addr = app
+        .instantiate_contract(pair_id, owner.clone(), &msg, &[], "Pair", None)
+        .unwrap();
+
+    // run a simulate query with wrong token
+    let query_msg = QueryMsg::Simulation {
+        offer_asset: Asset {
+            info: AssetInfo::Native("foobar".into()),
+            amount: Uint128::new(1000),
+        },
+        ask_asset_info: None,
+        trader: None,
+    };
+    let res: std::result::Result<SimulationResponse, _> =
+        app.wrap().query_wasm_smart(&pair_addr, &query_msg);
+    let err = res.unwrap_err();
+    let expected_err = ContractError::AssetMismatch(AssetInfo::Native("foobar".into()).to_string());
+    assert!(
+        err.to_string().ends_with(&expected_err.to_string()),
+        "got: {}, expected: {}",
+        err,
+        expected_err
+    );
+
+    // simulate with proper token
+    let query_msg = QueryMsg::Simulation {
+        offer_asset: Asset {
+            info: AssetInfo::Token(cash_addr.clone()),
+            amount: Uint128::new(7000),
+        },
+        ask_asset_info: None,
+        trader: None,
+    };
+    let res: std::result::Result<SimulationResponse, _> =
+        app.wrap().query_wasm_smart(&pair_addr, &query_msg);
+    let err = res.unwrap_err();
+    let expected_err = StdError::generic_err("Divide by zero error computing the swap");
+    assert!(
+        err.to_string().ends_with(&expected_err.to_string()),
+        "got: {}, expected: {}",
+        err,
+        expected_err,
+    );
+
+    // provide an allowance to pay into LP
+    // let cash = Cw20Contract(cash_addr.clone());
+    let allow_msg = Cw20ExecuteMsg::IncreaseAllowance {
+        spender: pair_addr.to_string(),
+        amount: Uint128::new(10000),
+        expires: None,
+    };
+    let _ = app
+        .execute_contract(owner.clone(), cash_addr.clone(), &allow_msg, &[])
+        .unwrap();
+
+    // provide liquidity with proper tokens
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: vec![
+            Asset {
+                info: AssetInfo::Native("btc".into()),
+                amount: Uint128::new(1000),
+            },
+            Asset {
+                info: AssetInfo::Token(cash_addr),
+                amount: Uint128::new(7000),
+            },
+        ],
+        slippage_tolerance: None,
+        deadline: None,
+    };
+    let _ = app
+        .execute_contract(owner, pair_addr.clone(), &msg, &coins(1000, "btc"))
+        .unwrap();
+
+    // simulate again
+    let res: SimulationResponse = app.wrap().query_wasm_smart(&pair_addr, &query_msg).unwrap();
+    // doubling the amount of cash should return half the BTC from the LP (minus commission)
+    assert_eq!(res.return_amount, Uint128::new(499));
+}
+
+#[test]
+// Simple swap scenario
+//
+// * Create (token, native token) pair
+// * Give allowance for pair
+// * Provide liquidity to pair
+// * Validate proper allowance on token as well as tokens posession
+// * Perform single swap of native tokens to tokens
+// * Verify proper amount of tokens (including 0.3% fee)
+// * Perform single swap of tokens to native tokens
+// * Verify proper amount of tokens (including 0.3% fee)
+// * Withdraw liquidity, all fees should be also added
+fn swap() {
+    // Initialize suite:
+    // liquidity provider (lp): 2000btc + 6000cash
+    // trader: 1000btc
+    // trader_recv: nothing (should receive funds later)
+    let mut suite = SuiteConfig::new()
+        .with_liquidity_provider("liquidity-provider", 2000, 6000)
+        .with_trader("trader", 1000, 0)
+        .with_trader("trader-recv", 1, 1)
+        .init()
+        .unwrap();
+
+    let (lp, trader, trader_recv, pair) = (
+        suite.lps[0].clone(),
+        suite.traders[0].clone(),
+        suite.traders[1].clone(),
+        suite.pair.clone(),
+    );
+
+    suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
+
+    // liquidity provider -> pair: 6000btc + 2000cash
+    // liquidity provider: 2464lt minted by pair (provided sqrt(6000 [btc] * 2000 [cash]) minus
+    // 1000lt of minimum liquidity locked on pair forever
+    suite
+        .assert_balances(&lp, 0, 0, 2464)
+        .assert_balances(&trader, 1000, 0, 0)
+        .assert_balances(&trader_recv, 1, 1, 0)
+        .assert_balances(&pair, 2000, 6000, 1000);
+
+    suite.swap_btc(&trader, 1000, None, None, None).unwrap();
+
+    // trader -> pair: 1000btc
+    // pair -> trader: 1994cash, explanaction:
+    //   cash to be left on contract: 6000 * 2000 / (2000 + 1000) = 4000
+    //   cash to be paid out: 6000 - 4000 = 2000
+    //   cash to be paid out after commission: 2000 - 2000 * 0.3% = 2000 - 2000 * 0.003= 1994
+    suite
+        .assert_balances(&lp, 0, 0, 2464)
+        .assert_balances(&trader, 0, 1994, 0)
+        .assert_balances(&trader_recv, 1, 1, 0)
+        .assert_balances(&pair, 3000, 4006, 1000);
+
+    suite
+        .swap_cash(&trader, 1000, None, None, trader_recv.clone())
+        .unwrap();
+
+    // trader -> pair: 1000cash
+    // pair -> trader_recv: 598btc, explanation:
+    //   btc to be left on contract: 3000 * 4006 / (4006 + 1000) = 2401 (rounded up)
+    //   btc to be paid out: 3000 - 2401 = 599
+    //   btc to be paid out after commission: 599 - 599 * 0.003 = 598
+    suite
+        .assert_balances(&lp, 0, 0, 2464)
+        .assert_balances(&trader, 0, 994, 0)
+        .assert_balances(&trader_recv, 599, 1, 0)
+        .assert_balances(&pair, 2402, 5006, 1000);
+
+    suite.withdraw_liquidity(&lp, 2464).unwrap();
+
+    // liquidity provider -> pair: 2464lt (all burned in pair)
+    // pair -> liquidity provider: 1708btc + 3560cash (2464 / 3464 of pair, the rest belongs to
+    // locked minimum liquidity and stays in pair)
+    suite
+        .assert_balances(&lp, 1708, 3560, 0)
+        .assert_balances(&trader, 0, 994, 0)
+        .assert_balances(&trader_recv, 599, 1, 0)
+        .assert_balances(&pair, 694, 1446, 1000);
+}
+
+#[test]
+// Checks if simulation works properly
+// * Provide liquidity for test pair contract
+// * Simulate swap in both ways, ensure result match expectations
+fn simulate() {
+    // Initialize suite:
+    // liquidity provider (lp): 2000btc + 6000cash
+    let mut suite = SuiteConfig::new()
+        .with_liquidity_provider("liquidity-provider", 2000, 6000)
+        .init()
+        .unwrap();
+
+    let lp = suite.lps[0].clone();
+
+    suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
+    let simulation_resp = suite.simulate_swap(1000, suite.btc()).unwrap();
+
+    // cash to be left on contract: 6000 * 2000 / (2000 + 1000) = 4000
+    // cash to be paid out: 6000 - 4000 = 2000
+    // commission: 2000 * 0.003 = 6
+    // cash to be paid out after commission: 2000 - 6 = 1994
+    // spread: 1000 * 6000 / 2000 - 2000 = 3000 - 2000 = 1000
+    assert_eq!(
+        simulation_resp,
+        SimulationResponse {
+            return_amount: Uint128::new(1994),
+            spread_amount: Uint128::new(1000),
+            commission_amount: Uint128::new(6),
+        }
+    );
+
+    let simulation_resp = suite.simulate_swap(14000, suite.cash()).unwrap();
+
+    // btc to be left on contract: 6000 * 2000 / (6000 + 14000) = 600
+    // btc to be paid out: 2000 - 600 = 1400
+    // comission: 1400 * 0.003 = 4
+    // btc to be paid out after commission: 1400 - 4 = 1396
+    // spread: 14000 * 2000 / 6000 - 1400 = 3266
+    assert_eq!(
+        simulation_resp,
+        SimulationResponse {
+            return_amount: Uint128::new(1396),
+            spread_amount: Uint128::new(3266),
+            commission_amount: Uint128::new(4),
+        }
+    );
+}
+
+#[test]
+// Checks if reverse simulation works properly
+// * Provide liquidity for test pair contract
+// * Reverse simulate swap in both ways
+// * Check, that after simulating with given results, ammounts are as expected
+//
+// Reverse simulation results are not validated directly, as due to calculation precision it is
+// poosible, reverse simulation might return range of results. Offer is rounded up, so it never
+// returns less than requested.
+fn reverse_simulate() {
+    // Initialize suite:
+    // liquidity provider (lp): 2000btc + 6000cash
+    let mut suite = SuiteConfig::new()
+        .with_liquidity_provider("liquidity-provider", 2000, 6000)
+        .init()
+        .unwrap();
+
+    let lp = suite.lps[0].clone();
+
+    suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
+    let rev_simulation_resp = suite.simulate_reverse_swap(1000, suite.btc()).unwrap();
+    let simulation_resp = suite
+        .simulate_swap(rev_simulation_resp.offer_amount.into(), suite.cash())
+        .unwrap();
+
+    assert!(simulation_resp.return_amount >= Uint128::new(1000));
+    // rounding offer up may add to the spread
+    assert!(
+        simulation_resp
+            .spread_amount
+            .u128()
+            .abs_diff(rev_simulation_resp.spread_amount.u128())
+            <= 1
+    );
+    assert_eq!(
+        simulation_resp.commission_amount,
+        rev_simulation_resp.commission_amount
+    );
+
+    let rev_simulation_resp = suite.simulate_reverse_swap(1000, suite.cash()).unwrap();
+    let simulation_resp = suite
+        .simulate_swap(rev_simulation_resp.offer_amount.into(), suite.btc())
+        .unwrap();
+
+    assert!(simulation_resp.return_amount >= Uint128::new(1000));
+    // rounding offer up may add to the spread
+    assert!(
+        simulation_resp
+            .spread_amount
+            .u128()
+            .abs_diff(rev_simulation_resp.spread_amount.u128())
+            <= 1
+    );
+    assert_eq!(
+        simulation_resp.commission_amount,
+        rev_simulation_resp.commission_amount
+    );
+}
+
+#[test]
+// Checks if price accumulators are growing by pools ratio for every second pools stay unchanged
+// * Provide liquidity for test pair contract
+// * Let some time pass, verify accumulators
+// * Perform swap, let some time pass, verify accumulators
+// * Compute TWAP between observations
+fn cumulative_prices() {
+    // Initialize suite:
+    // liquidity provider (lp): 2000btc + 6000cash
+    // trader: 1000btc
+    let mut suite = SuiteConfig::new()
+        .with_liquidity_provider("liquidity-provider", 2000, 6000)
+        .with_trader("trader", 1000, 0)
+        .init()
+        .unwrap();
+
+    let (lp, trader) = (suite.lps[0].clone(), suite.traders[0].clone());
+
+    let start = suite.cumulative_prices().unwrap();
+    assert_eq!(start.price0_cumulative, Decimal256::zero());
+    assert_eq!(start.price1_cumulative, Decimal256::zero());
+
+    // Nothing is accumulated while pools are empty
+    suite.advance_time(50);
+    suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
+    let provided = suite.cumulative_prices().unwrap();
+    assert_eq!(provided.price0_cumulative, Decimal256::zero());
+    assert_eq!(provided.price1_cumulative, Decimal256::zero());
+    assert_eq!(provided.timestamp, start.timestamp + 50);
+
+    // btc price is 3cash, cash price is 1/3btc
+    suite.advance_time(100);
+    let before_swap = suite.cumulative_prices().unwrap();
+    assert_eq!(
+        before_swap.price0_cumulative,
+        Decimal256::from_ratio(300u128, 1u128)
+    );
+    assert_eq!(
+        before_swap.price1_cumulative,
+        Decimal256::from_ratio(100u128, 3u128)
+    );
+    assert_eq!(before_swap.timestamp, provided.timestamp + 100);
+
+    // Pools after swap: 3000btc, 4006cash
+    suite.swap_btc(&trader, 1000, None, None, None).unwrap();
+    suite.advance_time(10);
+    let after_swap = suite.cumulative_prices().unwrap();
+    assert_eq!(
+        after_swap.price0_cumulative,
+        before_swap.price0_cumulative + Decimal256::from_ratio(40060u128, 3000u128)
+    );
+    assert_eq!(
+        after_swap.price1_cumulative,
+        before_swap.price1_cumulative + Decimal256::from_ratio(30000u128, 4006u128)
+    );
+
+    let twap = compute_twap(&provided, &before_swap).unwrap();
+    assert_eq!(
+        twap,
+        [
+            Decimal256::from_ratio(3u128, 1u128),
+            Decimal256::from_ratio(1u128, 3u128)
+        ]
+    );
+}
+
+#[test]
+// Checks if tokens sent directly to pair are not affecting price
+// * Provide liquidity for test pair contract
+// * Donate tokens to pair, ensure pools and simulation didn't change
+// * Skim donated tokens
+// * Donate again and sync, ensure pools are updated
+fn donation_is_not_accounted_until_sync() {
+    // Initialize suite:
+    // liquidity provider (lp): 2000btc + 6000cash
+    // trader: 1000btc + 1000cash
+    // trader_recv: 1btc + 1cash
+    let mut suite = SuiteConfig::new()
+        .with_liquidity_provider("liquidity-provider", 2000, 6000)
+        .with_trader("trader", 1000, 1000)
+        .with_trader("trader-recv", 1, 1)
+        .init()
+        .unwrap();
 
-    // set up cw20 contract with some tokens
-    let cw20_id = app.store_code(contract_cw20());
-    let msg = cw20_base::msg::InstantiateMsg {
-        name: "Cash Money".to_string(),
-        symbol: "CASH".to_string(),
-        decimals: 2,
-        initial_balances: vec![Cw20Coin {
-            address: owner.to_string(),
-            amount: Uint128::new(50000),
-        }],
-        mint: None,
-        marketing: None,
-    };
-    let cash_addr = app
-        .instantiate_contract(cw20_id, owner.clone(), &msg, &[], "CASH", None)
+    let (lp, trader, trader_recv, pair) = (
+        suite.lps[0].clone(),
+        suite.traders[0].clone(),
+        suite.traders[1].clone(),
+        suite.pair.clone(),
+    );
+
+    suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
+    let simulation = suite.simulate_swap(1000, suite.btc()).unwrap();
+
+    suite.donate(&trader, 500, 300).unwrap();
+    suite.assert_balances(&pair, 2500, 6300, 1000);
+    assert_eq!(suite.pools().unwrap(), (2000, 6000));
+    assert_eq!(suite.simulate_swap(1000, suite.btc()).unwrap(), simulation);
+
+    // Excess goes to the receiver, reserves stay untouched
+    suite.skim(&trader, &trader_recv).unwrap();
+    suite
+        .assert_balances(&pair, 2000, 6000, 1000)
+        .assert_balances(&trader_recv, 501, 301, 0);
+    assert_eq!(suite.pools().unwrap(), (2000, 6000));
+
+    // Nothing to skim anymore
+    suite.skim(&trader, None).unwrap();
+    suite.assert_balances(&trader, 500, 700, 0);
+
+    suite.donate(&trader, 500, 700).unwrap();
+    suite.sync(&trader).unwrap();
+    assert_eq!(suite.pools().unwrap(), (2500, 6700));
+
+    // Donation is now shared between liquidity providers
+    suite
+        .withdraw_liquidity(&lp, 2464)
+        .unwrap()
+        .assert_balances(&lp, 1778, 4765, 0)
+        .assert_balances(&pair, 722, 1935, 1000);
+}
+
+#[test]
+// Checks if protocol part of commission is accrued for fee collector
+// * Provide liquidity for test pair contract
+// * Perform swap, verify accrued fees
+// * Collect fees, verify they are sent to collector
+// * Withdraw liquidity, protocol fee should not be included
+fn protocol_fee() {
+    // Initialize suite:
+    // liquidity provider (lp): 2000btc + 6000cash
+    // trader: 1000btc
+    // protocol fee share: 50% of commission
+    let mut suite = SuiteConfig::new()
+        .with_liquidity_provider("liquidity-provider", 2000, 6000)
+        .with_trader("trader", 1000, 0)
+        .with_protocol_fee("collector", Decimal::percent(50))
+        .init()
         .unwrap();
 
-    // set up pair contract
-    let pair_id = app.store_code(contract_pair());
-    let msg = InstantiateMsg::new(
-        [
-            AssetInfo::Native("btc".into()),
-            AssetInfo::Token(cash_addr.clone()),
-        ],
-        cw20_id,
+    let (lp, trader, pair) = (
+        suite.lps[0].clone(),
+        suite.traders[0].clone(),
+        suite.pair.clone(),
     );
-    let pair_addr = app
-        .instantiate_contract(pair_id, owner.clone(), &msg, &[], "Pair", None)
+    let collector = Addr::unchecked("collector");
+
+    suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
+    suite.swap_btc(&trader, 1000, None, None, None).unwrap();
+
+    // Trader receives the same as without protocol fee - 1994cash. Out of 6cash of commission,
+    // 3cash is accrued for collector, the rest stays in pool.
+    suite
+        .assert_balances(&trader, 0, 1994, 0)
+        .assert_balances(&pair, 3000, 4006, 1000);
+    assert_eq!(suite.pools().unwrap(), (3000, 4003));
+    assert_eq!(suite.accrued_fees().unwrap(), (0, 3));
+
+    // Accrued fees are not an excess to be skimmed
+    suite.skim(&trader, None).unwrap();
+    suite.assert_balances(&trader, 0, 1994, 0);
+
+    // Anyone can trigger collection, fees always go to collector
+    suite.collect_fees(&trader).unwrap();
+    suite
+        .assert_balances(&collector, 0, 3, 0)
+        .assert_balances(&pair, 3000, 4003, 1000);
+    assert_eq!(suite.accrued_fees().unwrap(), (0, 0));
+
+    suite
+        .withdraw_liquidity(&lp, 2464)
+        .unwrap()
+        .assert_balances(&lp, 2133, 2847, 0)
+        .assert_balances(&pair, 867, 1156, 1000);
+}
+
+#[test]
+fn protocol_fee_requires_collector() {
+    let mut suite = SuiteConfig::new().init().unwrap();
+
+    let err = suite.collect_fees(&suite.admin.clone()).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::MissingFeeCollector {}.to_string()
+    );
+}
+
+#[test]
+// Checks if only the part of deposit matching pool ratio is taken by pair
+// * Provide liquidity with excess of native token, the excess is refunded
+// * Provide liquidity with excess of cw20 token, the excess is never pulled from provider
+fn provide_liquidity_refunds_excess() {
+    // Initialize suite:
+    // liquidity provider (lp): 2000btc + 6000cash
+    // second liquidity provider (lp2): 1000btc + 3000cash
+    let mut suite = SuiteConfig::new()
+        .with_liquidity_provider("liquidity-provider", 2000, 6000)
+        .with_liquidity_provider("liquidity-provider2", 1000, 3000)
+        .init()
         .unwrap();
 
-    // run a simulate query with wrong token
-    let query_msg = QueryMsg::Simulation {
+    let (lp, lp2, pair) = (
+        suite.lps[0].clone(),
+        suite.lps[1].clone(),
+        suite.pair.clone(),
+    );
+
+    suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
+
+    // Only 1000 * 2000 / 6000 = 333btc matches 1000cash, the other 667btc is refunded
+    // lp2: min(333 * 3464 / 2000, 1000 * 3464 / 6000) = 576lt
+    suite
+        .provide_liquidity(&lp2, 1000, 1000, None)
+        .unwrap()
+        .assert_balances(&lp2, 667, 2000, 576)
+        .assert_balances(&pair, 2333, 7000, 1000);
+    assert_eq!(suite.pools().unwrap(), (2333, 7000));
+
+    // Only 100 * 7000 / 2333 = 300cash matches 100btc, the rest is not pulled
+    // lp2: min(100 * 4040 / 2333, 300 * 4040 / 7000) = 173lt
+    suite
+        .provide_liquidity(&lp2, 100, 2000, None)
+        .unwrap()
+        .assert_balances(&lp2, 567, 1700, 749)
+        .assert_balances(&pair, 2433, 7300, 1000);
+    assert_eq!(suite.pools().unwrap(), (2433, 7300));
+}
+
+#[test]
+// Checks minimum return and deadline guards
+// * Swap with minimum return not met fails
+// * Swap, liquidity provision and withdrawal after deadline fail
+// * Swap meeting both guards succeeds
+fn min_return_and_deadline() {
+    // Initialize suite:
+    // liquidity provider (lp): 4000btc + 12000cash
+    // trader: 1000btc
+    let mut suite = SuiteConfig::new()
+        .with_liquidity_provider("liquidity-provider", 4000, 12000)
+        .with_trader("trader", 1000, 0)
+        .init()
+        .unwrap();
+
+    let (lp, trader, pair) = (
+        suite.lps[0].clone(),
+        suite.traders[0].clone(),
+        suite.pair.clone(),
+    );
+    let now = suite.app.block_info().time.seconds();
+
+    suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
+
+    let swap = |min_return: u128, deadline: u64| ExecuteMsg::Swap {
         offer_asset: Asset {
-            info: AssetInfo::Native("foobar".into()),
+            info: AssetInfo::Native("btc".to_owned()),
             amount: Uint128::new(1000),
         },
+        ask_asset_info: None,
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        min_return: Some(Uint128::new(min_return)),
+        deadline: Some(deadline),
+        to_msg: None,
     };
-    let res: std::result::Result<SimulationResponse, _> =
-        app.wrap().query_wasm_smart(&pair_addr, &query_msg);
-    let err = res.unwrap_err();
-    let expected_err = ContractError::AssetMismatch(AssetInfo::Native("foobar".into()).to_string());
+
+    // 1000btc returns 1994cash
+    let err = suite
+        .app
+        .execute_contract(
+            trader.clone(),
+            pair.clone(),
+            &swap(1995, now),
+            &coins(1000, "btc"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::MinReturnAssertion {
+            return_amount: Uint128::new(1994),
+            min_return: Uint128::new(1995),
+        }
+        .to_string()
+    );
+
+    suite.advance_time(10);
+    let deadline_err = ContractError::DeadlineExceeded {
+        deadline: now,
+        now: now + 10,
+    }
+    .to_string();
+
+    let err = suite
+        .app
+        .execute_contract(
+            trader.clone(),
+            pair.clone(),
+            &swap(1994, now),
+            &coins(1000, "btc"),
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), deadline_err);
+
+    let err = suite
+        .app
+        .execute_contract(
+            lp.clone(),
+            pair.clone(),
+            &ExecuteMsg::ProvideLiquidity {
+                assets: vec![
+                    Asset {
+                        info: suite.btc(),
+                        amount: Uint128::new(1000),
+                    },
+                    Asset {
+                        info: suite.cash(),
+                        amount: Uint128::new(0),
+                    },
+                ],
+                slippage_tolerance: None,
+                deadline: Some(now),
+            },
+            &coins(1000, "btc"),
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), deadline_err);
+
+    let err = suite
+        .app
+        .execute_contract(
+            lp.clone(),
+            suite.lt.clone(),
+            &cw20_base::msg::ExecuteMsg::Send {
+                contract: pair.to_string(),
+                amount: Uint128::new(1000),
+                msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+                    deadline: Some(now),
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), deadline_err);
+
+    suite
+        .app
+        .execute_contract(
+            trader.clone(),
+            pair.clone(),
+            &swap(1994, now + 10),
+            &coins(1000, "btc"),
+        )
+        .unwrap();
+    suite
+        .assert_balances(&trader, 0, 1994, 0)
+        .assert_balances(&pair, 3000, 4006, 1000);
+}
+
+#[test]
+// Checks if exact output swap works properly
+// * Buy exact amount of cash with btc, unused btc is refunded
+// * Buy exact amount of btc with cash, unused cash is never kept by pair
+// * Offer not covering requested amount fails
+fn swap_exact_out() {
+    // Initialize suite:
+    // liquidity provider (lp): 2000btc + 6000cash
+    // trader: 1000btc + 1000cash
+    let mut suite = SuiteConfig::new()
+        .with_liquidity_provider("liquidity-provider", 2000, 6000)
+        .with_trader("trader", 1000, 1000)
+        .init()
+        .unwrap();
+
+    let (lp, trader, pair) = (
+        suite.lps[0].clone(),
+        suite.traders[0].clone(),
+        suite.pair.clone(),
+    );
+
+    suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
+
+    // Exactly the reverse simulated offer is taken
+    let offer = suite
+        .simulate_reverse_swap(1000, suite.cash())
+        .unwrap()
+        .offer_amount
+        .u128();
+    suite
+        .swap_exact_out_btc(&trader, 1000, 1000)
+        .unwrap()
+        .assert_balances(&trader, 1000 - offer, 2000, 0)
+        .assert_balances(&pair, 2000 + offer, 5000, 1000);
+
+    let err = suite.swap_exact_out_cash(&trader, 100, 500).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::MaxOfferAssertion {
+            offer_amount: suite
+                .simulate_reverse_swap(500, suite.btc())
+                .unwrap()
+                .offer_amount,
+            max_offer: Uint128::new(100),
+        }
+        .to_string()
+    );
+
+    let cash_offer = suite
+        .simulate_reverse_swap(100, suite.btc())
+        .unwrap()
+        .offer_amount
+        .u128();
+    suite
+        .swap_exact_out_cash(&trader, 2000, 100)
+        .unwrap()
+        .assert_balances(&trader, 1100 - offer, 2000 - cash_offer, 0)
+        .assert_balances(&pair, 1900 + offer, 5000 + cash_offer, 1000);
+}
+
+#[test]
+// Checks if liquidity can be provided with single asset
+// * Provide liquidity with btc only, part of it is swapped so both sides are provided at ratio
+// * Provide liquidity with cash only, checking minimum share and slippage tolerance
+fn provide_single_sided() {
+    // Initialize suite:
+    // liquidity provider (lp): 2000btc + 6000cash
+    // second liquidity provider (lp2): 1000btc + 3000cash
+    let mut suite = SuiteConfig::new()
+        .with_liquidity_provider("liquidity-provider", 2000, 6000)
+        .with_liquidity_provider("liquidity-provider2", 1000, 3000)
+        .init()
+        .unwrap();
+
+    let (lp, lp2, pair) = (
+        suite.lps[0].clone(),
+        suite.lps[1].clone(),
+        suite.pair.clone(),
+    );
+
+    // No pool ratio to match yet
+    let err = suite
+        .provide_single_sided_btc(&lp2, 1000, None, None)
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::EmptyPool {}.to_string()
+    );
+
+    suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
+
+    // 450btc swapped for 6000 - 2000 * 6000 / 2450 = 1102cash (cash left rounded up), minus 3cash
+    // of commission, pools become 2450btc + 4901cash
+    // 1099cash is provided with 1099 * 2450 / 4901 = 549btc, remaining 1btc is refunded
+    // lp2: 1099 * 3464 / 4901 = 776lt
+    suite
+        .provide_single_sided_btc(&lp2, 1000, None, 776)
+        .unwrap()
+        .assert_balances(&lp2, 1, 3000, 776)
+        .assert_balances(&pair, 2999, 6000, 1000);
+    assert_eq!(suite.pools().unwrap(), (2999, 6000));
+
+    let err = suite
+        .provide_single_sided_cash(&lp2, 3000, None, 1000)
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::MinShareAssertion {
+            share: Uint128::new(950),
+            min_share: Uint128::new(1000),
+        }
+        .to_string()
+    );
+
+    // Swapping part of 3000cash moves price by more than 1%
+    let err = suite
+        .provide_single_sided_cash(&lp2, 3000, Decimal::percent(1), None)
+        .unwrap_err();
     assert!(
-        err.to_string().ends_with(&expected_err.to_string()),
-        "got: {}, expected: {}",
-        err,
-        expected_err
+        err.root_cause()
+            .to_string()
+            .starts_with("Max spread exceeded"),
+        "{:?}",
+        err
+    );
+
+    // Rounding leftover of cash not matched by swapped btc is refunded
+    suite
+        .provide_single_sided_cash(&lp2, 3000, None, None)
+        .unwrap()
+        .assert_balances(&lp2, 1, 3, 1726)
+        .assert_balances(&pair, 2999, 8997, 1000);
+    assert_eq!(suite.pools().unwrap(), (2999, 8997));
+}
+
+#[test]
+// Checks if liquidity can be withdrawn into single asset
+// * Simulate withdrawal into cash
+// * Withdraw with too high minimum return, it fails
+// * Withdraw into cash, result matches simulation
+fn withdraw_single_sided() {
+    // Initialize suite:
+    // liquidity provider (lp): 2000btc + 6000cash
+    let mut suite = SuiteConfig::new()
+        .with_liquidity_provider("liquidity-provider", 2000, 6000)
+        .init()
+        .unwrap();
+
+    let (lp, pair) = (suite.lps[0].clone(), suite.pair.clone());
+
+    suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
+
+    // 1232lt out of 3464lt withdraws 711btc + 2133cash, pools become 1289btc + 3867cash
+    // 711btc swapped for cash: 3867 - 1289 * 3867 / 2000 = 1374 (cash left rounded up), minus 4cash
+    // commission = 1370
+    // Paid out: 2133 + 1370 = 3503cash
+    let simulation = suite
+        .simulate_withdraw_single_sided(1232, suite.cash())
+        .unwrap();
+    assert_eq!(
+        simulation,
+        WithdrawSingleSidedSimulationResponse {
+            return_amount: Uint128::new(3503),
+            spread_amount: Uint128::new(759),
+            commission_amount: Uint128::new(4),
+        }
+    );
+
+    let err = suite
+        .withdraw_single_sided(&lp, 1232, suite.cash(), 3504)
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::MinReturnAssertion {
+            return_amount: Uint128::new(3503),
+            min_return: Uint128::new(3504),
+        }
+        .to_string()
+    );
+
+    suite
+        .withdraw_single_sided(&lp, 1232, suite.cash(), 3503)
+        .unwrap()
+        .assert_balances(&lp, 0, 3503, 1232)
+        .assert_balances(&pair, 2000, 2497, 1000);
+    assert_eq!(suite.pools().unwrap(), (2000, 2497));
+}
+
+#[test]
+// Checks that first depositor cannot inflate the share price to steal from following depositors
+// * Attacker provides minimal liquidity, and donates large amount to pair
+// * Victim deposit is either rejected, or gets fair share of pool
+// * Attacker loses most of the donation, as it is shared with locked liquidity
+fn inflation_attack() {
+    // Initialize suite:
+    // attacker: 6001btc + 6001cash
+    // victim: 1000btc + 1000cash
+    let mut suite = SuiteConfig::new()
+        .with_liquidity_provider("attacker", 6001, 6001)
+        .with_liquidity_provider("victim", 1000, 1000)
+        .init()
+        .unwrap();
+
+    let (attacker, victim, pair) = (
+        suite.lps[0].clone(),
+        suite.lps[1].clone(),
+        suite.pair.clone(),
+    );
+
+    // Deposit not covering minimum liquidity is rejected
+    let err = suite.provide_liquidity(&attacker, 1, 1, None).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::ZeroShares {}.to_string()
+    );
+
+    // attacker: sqrt(1001 * 1001) - 1000 = 1lt, 1000lt locked on pair
+    suite
+        .provide_liquidity(&attacker, 1001, 1001, None)
+        .unwrap()
+        .assert_balances(&attacker, 5000, 5000, 1)
+        .assert_balances(&pair, 1001, 1001, 1000);
+
+    suite
+        .donate(&attacker, 5000, 5000)
+        .unwrap()
+        .sync(&attacker)
+        .unwrap();
+    assert_eq!(suite.pools().unwrap(), (6001, 6001));
+
+    // 5 * 1001 / 6001 rounds down to 0lt - instead of losing funds, deposit is rejected
+    let err = suite.provide_liquidity(&victim, 5, 5, None).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::ZeroShares {}.to_string()
+    );
+
+    // victim: 1000 * 1001 / 6001 = 166lt
+    suite
+        .provide_liquidity(&victim, 1000, 1000, None)
+        .unwrap()
+        .assert_balances(&victim, 0, 0, 166);
+
+    // attacker: 7001 * 1 / 1167 = 5btc + 5cash out of 6001btc + 6001cash deposited
+    suite
+        .withdraw_liquidity(&attacker, 1)
+        .unwrap()
+        .assert_balances(&attacker, 5, 5, 0);
+
+    // victim: 6996 * 166 / 1166 = 996btc + 996cash out of 1000btc + 1000cash deposited
+    suite
+        .withdraw_liquidity(&victim, 166)
+        .unwrap()
+        .assert_balances(&victim, 996, 996, 0);
+}
+
+#[test]
+// Checks only whitelisted addresses can use whitelisted pair
+// * Whitelisted liquidity provider provides and withdraws liquidity
+// * Not whitelisted address can't provide liquidity or swap
+// * Whitelisted trader can't swap to not whitelisted address
+fn whitelist() {
+    // Initialize suite:
+    // liquidity provider (lp): 2000btc + 6000cash, whitelisted
+    // trader: 1000btc + 1000cash, whitelisted
+    // outsider: 1000btc + 1000cash
+    let mut suite = SuiteConfig::new()
+        .with_liquidity_provider("liquidity-provider", 2000, 6000)
+        .with_trader("trader", 1000, 1000)
+        .with_trader("outsider", 1000, 1000)
+        .with_whitelist(&["liquidity-provider", "trader"])
+        .init()
+        .unwrap();
+
+    let (lp, trader, outsider) = (
+        suite.lps[0].clone(),
+        suite.traders[0].clone(),
+        suite.traders[1].clone(),
+    );
+
+    let pair_info: PairInfo = suite
+        .app
+        .wrap()
+        .query_wasm_smart(suite.pair.clone(), &QueryMsg::Pair {})
+        .unwrap();
+    assert!(pair_info.whitelist_group.is_some());
+
+    suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
+
+    let err = suite
+        .provide_liquidity(&outsider, 100, 300, None)
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::NotWhitelisted(outsider.to_string()).to_string()
+    );
+
+    let err = suite
+        .swap_btc(&outsider, 1000, None, None, None)
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::NotWhitelisted(outsider.to_string()).to_string()
     );
 
-    // simulate with proper token
-    let query_msg = QueryMsg::Simulation {
-        offer_asset: Asset {
-            info: AssetInfo::Token(cash_addr.clone()),
-            amount: Uint128::new(7000),
-        },
-    };
-    let res: std::result::Result<SimulationResponse, _> =
-        app.wrap().query_wasm_smart(&pair_addr, &query_msg);
-    let err = res.unwrap_err();
-    let expected_err = StdError::generic_err("Divide by zero error computing the swap");
-    assert!(
-        err.to_string().ends_with(&expected_err.to_string()),
-        "got: {}, expected: {}",
-        err,
-        expected_err,
+    let err = suite
+        .swap_cash(&outsider, 1000, None, None, None)
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::NotWhitelisted(outsider.to_string()).to_string()
     );
 
-    // provide an allowance to pay into LP
-    // let cash = Cw20Contract(cash_addr.clone());
-    let allow_msg = Cw20ExecuteMsg::IncreaseAllowance {
-        spender: pair_addr.to_string(),
-        amount: Uint128::new(10000),
-        expires: None,
-    };
-    let _ = app
-        .execute_contract(owner.clone(), cash_addr.clone(), &allow_msg, &[])
-        .unwrap();
+    let err = suite
+        .swap_btc(&trader, 1000, None, None, outsider.clone())
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::NotWhitelisted(outsider.to_string()).to_string()
+    );
 
-    // provide liquidity with proper tokens
-    let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
-            Asset {
-                info: AssetInfo::Native("btc".into()),
-                amount: Uint128::new(10),
-            },
-            Asset {
-                info: AssetInfo::Token(cash_addr),
-                amount: Uint128::new(7000),
-            },
-        ],
-        slippage_tolerance: None,
-    };
-    let _ = app
-        .execute_contract(owner, pair_addr.clone(), &msg, &coins(10, "btc"))
-        .unwrap();
+    suite
+        .swap_btc(&trader, 1000, None, None, None)
+        .unwrap()
+        .assert_balances(&trader, 0, 2994, 0)
+        .assert_balances(&outsider, 1000, 1000, 0);
 
-    // simulate again
-    let res: SimulationResponse = app.wrap().query_wasm_smart(&pair_addr, &query_msg).unwrap();
-    // doubling the amount of cash should return half the BTC from the LP
-    assert_eq!(res.return_amount, Uint128::new(5));
+    // 1000 / 3464 of 3000btc + 4006cash pools
+    suite
+        .withdraw_liquidity(&lp, 1000)
+        .unwrap()
+        .assert_balances(&lp, 866, 1156, 1464);
 }
 
 #[test]
-// Simple swap scenario
-//
-// * Create (token, native token) pair
-// * Give allowance for pair
-// * Provide liquidity to pair
-// * Validate proper allowance on token as well as tokens posession
-// * Perform single swap of native tokens to tokens
-// * Verify proper amount of tokens (including 0.3% fee)
-// * Perform single swap of tokens to native tokens
-// * Verify proper amount of tokens (including 0.3% fee)
-// * Withdraw liquidity, all fees should be also added
-fn swap() {
+// Checks swap commission depends on trader points in commission group
+// * Simulations reflect commission tier of given trader
+// * Swaps charge commission tier of the sender
+// * Traders reaching no tier, and simulations without trader, use pair commission
+fn commission_tiers() {
     // Initialize suite:
     // liquidity provider (lp): 2000btc + 6000cash
-    // trader: 1000btc
-    // trader_recv: nothing (should receive funds later)
+    // market maker: 1000btc, 10 points in commission group
+    // member: 1000btc, 1 point in commission group
+    // trader: 1000btc, not a member of commission group
     let mut suite = SuiteConfig::new()
         .with_liquidity_provider("liquidity-provider", 2000, 6000)
+        .with_trader("market-maker", 1000, 0)
+        .with_trader("member", 1000, 0)
         .with_trader("trader", 1000, 0)
-        .with_trader("trader-recv", 1, 1)
+        .with_commission_tiers(
+            &[("market-maker", 10), ("member", 1)],
+            &[(10, Decimal::zero()), (1, Decimal::permille(1))],
+        )
         .init()
         .unwrap();
 
-    let (lp, trader, trader_recv, pair) = (
+    let (lp, market_maker, member, trader) = (
         suite.lps[0].clone(),
         suite.traders[0].clone(),
         suite.traders[1].clone(),
-        suite.pair.clone(),
+        suite.traders[2].clone(),
+    );
+
+    let tiers: CommissionTiersResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(suite.pair.clone(), &QueryMsg::CommissionTiers {})
+        .unwrap();
+    assert!(tiers.commission_group.is_some());
+    assert_eq!(
+        tiers.commission_tiers,
+        vec![
+            CommissionTier::new(1, Decimal::permille(1)),
+            CommissionTier::new(10, Decimal::zero()),
+        ]
     );
 
     suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
 
-    // liquidity provider -> pair: 6000btc + 2000cash
-    // liquidity provider: 3464lt minted by pair (provided sqrt(6000 [btc] * 2000 [cash])
-    suite
-        .assert_balances(&lp, 0, 0, 3464)
-        .assert_balances(&trader, 1000, 0, 0)
-        .assert_balances(&trader_recv, 1, 1, 0)
-        .assert_balances(&pair, 2000, 6000, 0);
+    // cash to be paid out before commission: 6000 - 6000 * 2000 / (2000 + 1000) = 2000
+    let btc = suite.btc();
+    let simulations = [
+        suite.simulate_swap_as(&market_maker, 1000, btc.clone()),
+        suite.simulate_swap_as(&member, 1000, btc.clone()),
+        suite.simulate_swap_as(&trader, 1000, btc.clone()),
+        suite.simulate_swap(1000, btc),
+    ]
+    .map(|simulation| simulation.unwrap().commission_amount.u128());
+    assert_eq!(simulations, [0, 2, 6, 6]);
 
-    suite.swap_btc(&trader, 1000, None, None, None).unwrap();
+    suite
+        .swap_btc(&market_maker, 1000, None, None, None)
+        .unwrap()
+        .assert_balances(&market_maker, 0, 2000, 0);
 
-    // trader -> pair: 1000btc
-    // pair -> trader: 1994cash, explanaction:
-    //   cash to be left on contract: 6000 * 2000 / (2000 + 1000) = 4000
-    //   cash to be paid out: 6000 - 4000 = 2000
-    //   cash to be paid out after commission: 2000 - 2000 * 0.3% = 2000 - 2000 * 0.003= 1994
+    // cash to be paid out before commission: 4000 - 4000 * 3000 / (3000 + 1000) = 1000
+    // commission: 1000 * 0.001 = 1
     suite
-        .assert_balances(&lp, 0, 0, 3464)
-        .assert_balances(&trader, 0, 1994, 0)
-        .assert_balances(&trader_recv, 1, 1, 0)
-        .assert_balances(&pair, 3000, 4006, 0);
+        .swap_btc(&member, 1000, None, None, None)
+        .unwrap()
+        .assert_balances(&member, 0, 999, 0);
 
+    // cash to be paid out before commission: 3001 - 3001 * 4000 / (4000 + 1000) = 600 (cash left
+    // rounded up)
+    // commission: 600 * 0.003 = 1
     suite
-        .swap_cash(&trader, 1000, None, None, trader_recv.clone())
+        .swap_btc(&trader, 1000, None, None, None)
+        .unwrap()
+        .assert_balances(&trader, 0, 599, 0);
+}
+
+#[test]
+// Checks commission tiers apply to every operation charging swap commission
+// * Single sided provision swaps without commission for a zero commission member
+// * Single sided withdrawal simulations reflect commission tier of given trader
+// * Single sided withdrawal charges commission tier of the sender
+// * Flash swap repayment charges commission tier of the borrower
+fn commission_tiers_single_sided_and_flash_swap() {
+    // Initialize suite:
+    // liquidity provider (lp): 2000btc + 6000cash
+    // market maker: 1000btc + 3000cash, 10 points in commission group
+    // trader: 1000btc + 2000cash, used to fund the borrower
+    let mut suite = SuiteConfig::new()
+        .with_liquidity_provider("liquidity-provider", 2000, 6000)
+        .with_liquidity_provider("market-maker", 1000, 3000)
+        .with_trader("trader", 1000, 2000)
+        .with_commission_tiers(&[("market-maker", 10)], &[(10, Decimal::zero())])
+        .init()
         .unwrap();
 
-    // trader -> pair: 1000cash
-    // pair -> trader_recv: 599 cash, explanation:
-    //   btc to be left on contract: 3000 * 4006 / (4006 + 1000) = 2400
-    //   btc to be paid out: 3000 - 2400 = 600
-    //   btc to be paid out after commission: 600 - 600 * 0.003 = 599
+    let (lp, market_maker, trader, pair) = (
+        suite.lps[0].clone(),
+        suite.lps[1].clone(),
+        suite.traders[0].clone(),
+        suite.pair.clone(),
+    );
+
+    suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
+
+    // Without commission only sqrt(2000 * 3000) - 2000 = 449btc are swapped for
+    // 6000 - 2000 * 6000 / 2449 = 1100cash, pools become 2449btc + 4900cash
+    // 1100cash match 1100 * 2449 / 4900 = 549btc of remaining 551btc, 2btc are refunded
+    // market maker: 549 * 3464 / 2449 = 776lt
     suite
-        .assert_balances(&lp, 0, 0, 3464)
-        .assert_balances(&trader, 0, 994, 0)
-        .assert_balances(&trader_recv, 600, 1, 0)
-        .assert_balances(&pair, 2401, 5006, 0);
+        .provide_single_sided_btc(&market_maker, 1000, None, None)
+        .unwrap()
+        .assert_balances(&market_maker, 2, 3000, 776)
+        .assert_balances(&pair, 2998, 6000, 1000);
+
+    // Commission of 2cash is charged unless simulated for the market maker
+    let cash = suite.cash();
+    let simulations = [
+        suite.simulate_withdraw_single_sided_as(&market_maker, 700, cash.clone()),
+        suite.simulate_withdraw_single_sided_as(&lp, 700, cash.clone()),
+        suite.simulate_withdraw_single_sided(700, cash.clone()),
+    ]
+    .map(|simulation| {
+        let simulation = simulation.unwrap();
+        (
+            simulation.return_amount.u128(),
+            simulation.commission_amount.u128(),
+        )
+    });
+    assert_eq!(simulations, [(1815, 0), (1813, 2), (1813, 2)]);
+
+    suite
+        .withdraw_single_sided(&market_maker, 700, cash, None)
+        .unwrap()
+        .assert_balances(&market_maker, 2, 4815, 76)
+        .assert_balances(&pair, 2998, 4185, 1000);
 
-    suite.withdraw_liquidity(&lp, 3464).unwrap();
+    let borrower = suite
+        .instantiate_flash_borrower(&trader, 1000, 2000)
+        .unwrap();
 
-    // liquidity provider -> pair: 3464lt (all burned in pair)
-    // pair -> liquidity provider: 2401btc + 5006cash (whole pair - lp owned 100% of lt)
-    //
-    // Note, that lp provided initially 6000btc and 2000cash, 6000 * 2000 = 12*10^6
-    // Lp payed out 2401btc, and 5006 cash, 2401 * 5006 > 12 * 10^6
-    // 1btc and 6cash is what lp earned on commissions, as 2400 * 5000 = 12*10^6
+    // (3185 + 1000 - 3) * 2998 < 2998 * 4185
+    let repay = suite.repay_msgs(0, 1000).unwrap();
+    let err = suite
+        .flash_swap(&borrower, suite.cash(), 1000, repay)
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::FlashSwapNotRepaid {}.to_string()
+    );
+
+    // Without commission (3185 + 1000) * 2998 = 2998 * 4185
+    suite.add_commission_member(&borrower, 10).unwrap();
+    let repay = suite.repay_msgs(0, 1000).unwrap();
     suite
-        .assert_balances(&lp, 2401, 5006, 0)
-        .assert_balances(&trader, 0, 994, 0)
-        .assert_balances(&trader_recv, 600, 1, 0)
-        .assert_balances(&pair, 0, 0, 0);
+        .flash_swap(&borrower, suite.cash(), 1000, repay)
+        .unwrap()
+        .assert_balances(&borrower, 1000, 2000, 0)
+        .assert_balances(&pair, 2998, 4185, 1000);
 }
 
 #[test]
-// Checks if simulation works properly
-// * Provide liquidity for test pair contract
-// * Simulate swap in both ways, ensure result match expectations
-fn simulate() {
+// Checks swap return can be delivered to a contract, which is notified about it
+// * Cash return is sent with cw20 `Send`, contract receives `Receive` hook
+// * Btc return is sent as funds of `to_msg` execution
+fn swap_to_contract() {
     // Initialize suite:
     // liquidity provider (lp): 2000btc + 6000cash
+    // trader: 1000btc + 1000cash
     let mut suite = SuiteConfig::new()
         .with_liquidity_provider("liquidity-provider", 2000, 6000)
+        .with_trader("trader", 1000, 1000)
         .init()
         .unwrap();
 
-    let lp = suite.lps[0].clone();
+    let (lp, trader) = (suite.lps[0].clone(), suite.traders[0].clone());
+    suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
+
+    let code_id = suite.app.store_code(contract_swap_receiver());
+    let receiver = suite
+        .app
+        .instantiate_contract(
+            code_id,
+            suite.admin.clone(),
+            &Empty {},
+            &[],
+            "Receiver",
+            None,
+        )
+        .unwrap();
+
+    // cash to be paid out: 2000 - 2000 * 0.003 = 1994
+    let resp = suite
+        .swap_btc_to_contract(
+            &trader,
+            1000,
+            &receiver,
+            to_binary(&ReceiverExecuteMsg::Deposit {}).unwrap(),
+        )
+        .unwrap();
+    assert!(resp.has_event(
+        &Event::new("wasm")
+            .add_attribute("_contract_addr", receiver.as_str())
+            .add_attribute("deposit", format!("1994{}", suite.cash))
+    ));
+    suite.assert_balances(&receiver, 0, 1994, 0);
+
+    let btc = suite
+        .simulate_swap(1000, suite.cash())
+        .unwrap()
+        .return_amount
+        .u128();
+    let resp = suite
+        .swap_cash_to_contract(
+            &trader,
+            1000,
+            &receiver,
+            to_binary(&ReceiverExecuteMsg::Deposit {}).unwrap(),
+        )
+        .unwrap();
+    assert!(resp.has_event(
+        &Event::new("wasm")
+            .add_attribute("_contract_addr", receiver.as_str())
+            .add_attribute("deposit", format!("{}btc", btc))
+    ));
+    suite.assert_balances(&receiver, btc, 1994, 0);
+}
+
+#[test]
+// Checks flash swaps are executed only when repaid
+// * Borrowed amount repaid without enough commission reverts whole flash swap
+// * Pair operations are not allowed during the callback
+// * Borrowed cash is repaid with cash
+// * Borrowed btc is repaid with cash, working as a swap
+fn flash_swap() {
+    // Initialize suite:
+    // liquidity provider (lp): 2000btc + 6000cash
+    // trader: 1000btc + 2000cash, used to fund the borrower
+    let mut suite = SuiteConfig::new()
+        .with_liquidity_provider("liquidity-provider", 2000, 6000)
+        .with_trader("trader", 1000, 2000)
+        .init()
+        .unwrap();
+
+    let (lp, trader, pair) = (
+        suite.lps[0].clone(),
+        suite.traders[0].clone(),
+        suite.pair.clone(),
+    );
 
     suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
-    let simulation_resp = suite.simulate_swap(1000, suite.btc()).unwrap();
+    let borrower = suite
+        .instantiate_flash_borrower(&trader, 1000, 2000)
+        .unwrap();
 
-    // cash to be left on contract: 6000 * 2000 / (2000 + 1000) = 4000
-    // cash to be paid out: 6000 - 4000 = 2000
-    // commission: 2000 * 0.003 = 6
-    // cash to be paid out after commission: 2000 - 6 = 1994
-    // spread: 1000 * 6000 / 2000 - 2000 = 3000 - 2000 = 1000
+    // (5000 + 1002 - 3) * 2000 < 2000 * 6000
+    let repay = suite.repay_msgs(0, 1002).unwrap();
+    let err = suite
+        .flash_swap(&borrower, suite.cash(), 1000, repay)
+        .unwrap_err();
     assert_eq!(
-        simulation_resp,
-        SimulationResponse {
-            return_amount: Uint128::new(1994),
-            spread_amount: Uint128::new(1000),
-            commission_amount: Uint128::new(6),
-        }
+        err.root_cause().to_string(),
+        ContractError::FlashSwapNotRepaid {}.to_string()
     );
+    suite
+        .assert_balances(&borrower, 1000, 2000, 0)
+        .assert_balances(&pair, 2000, 6000, 1000);
 
-    let simulation_resp = suite.simulate_swap(14000, suite.cash()).unwrap();
+    let sync = WasmMsg::Execute {
+        contract_addr: pair.to_string(),
+        msg: to_binary(&ExecuteMsg::Sync {}).unwrap(),
+        funds: vec![],
+    };
+    let err = suite
+        .flash_swap(&borrower, suite.cash(), 1000, vec![sync.into()])
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::FlashSwapInProgress {}.to_string()
+    );
 
-    // btc to be left on contract: 6000 * 2000 / (6000 + 14000) = 600
-    // btc to be paid out: 2000 - 600 = 1400
-    // comission: 1400 * 0.003 = 4
-    // btc to be paid out after commission: 1400 - 4 = 1396
-    // spread: 14000 * 2000 / 6000 - 1400 = 3266
+    // (5000 + 1003 - 3) * 2000 = 2000 * 6000
+    let repay = suite.repay_msgs(0, 1003).unwrap();
+    suite
+        .flash_swap(&borrower, suite.cash(), 1000, repay)
+        .unwrap()
+        .assert_balances(&borrower, 1000, 1997, 0)
+        .assert_balances(&pair, 2000, 6003, 1000);
+    assert_eq!(suite.pools().unwrap(), (2000, 6003));
+
+    // 1900 * (6003 + 317) >= 2000 * 6003, commission of 317cash rounds down to 0
+    let repay = suite.repay_msgs(0, 317).unwrap();
+    suite
+        .flash_swap(&borrower, suite.btc(), 100, repay)
+        .unwrap()
+        .assert_balances(&borrower, 1100, 1680, 0)
+        .assert_balances(&pair, 1900, 6320, 1000);
+    assert_eq!(suite.pools().unwrap(), (1900, 6320));
+
+    let err = suite
+        .flash_swap(&borrower, suite.btc(), 1900, vec![])
+        .unwrap_err();
     assert_eq!(
-        simulation_resp,
-        SimulationResponse {
-            return_amount: Uint128::new(1396),
-            spread_amount: Uint128::new(3266),
-            commission_amount: Uint128::new(4),
+        err.root_cause().to_string(),
+        ContractError::InsufficientLiquidity {
+            pool: Uint128::new(1900),
+            requested: Uint128::new(1900),
         }
+        .to_string()
     );
 }
 
 #[test]
-// Checks if reverse simulation works properly
-// * Provide liquidity for test pair contract
-// * Reverse simulate swap in both ways
-// * Check, that after simulating with given results, ammounts are as expected
-//
-// Reverse simulation results are not validated directly, as due to calculation precision it is
-// poosible, reverse simulation might return range of results.
-fn reverse_simulate() {
+// Checks if limit orders are filled by swaps moving the price across them
+// * Place two orders selling btc, escrowed btc is not part of pools
+// * Swap cash for btc, the first order is filled entirely, the second one partially
+// * Fill returns are credited to the seller, and paid out when claimed
+// * Cancel the second order, its unfilled part is refunded
+fn limit_orders() {
     // Initialize suite:
     // liquidity provider (lp): 2000btc + 6000cash
+    // trader: 100btc + 1000cash
+    // seller: 1100btc
     let mut suite = SuiteConfig::new()
         .with_liquidity_provider("liquidity-provider", 2000, 6000)
+        .with_trader("trader", 100, 1000)
+        .with_trader("seller", 1100, 0)
         .init()
         .unwrap();
 
-    let lp = suite.lps[0].clone();
+    let (lp, trader, seller, pair) = (
+        suite.lps[0].clone(),
+        suite.traders[0].clone(),
+        suite.traders[1].clone(),
+        suite.pair.clone(),
+    );
 
     suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
-    let rev_simulation_resp = suite.simulate_reverse_swap(1000, suite.btc()).unwrap();
-    let simulation_resp = suite
-        .simulate_swap(rev_simulation_resp.offer_amount.into(), suite.cash())
+
+    // 6000 * 0.997 / 2000 = 2.991cash is paid for btc now, so none of orders is crossed
+    suite
+        .place_limit_order_btc(&seller, 100, Decimal::percent(330))
         .unwrap();
+    suite
+        .place_limit_order_btc(&seller, 1000, Decimal::percent(350))
+        .unwrap();
+    assert_eq!(suite.limit_orders(&seller, None, None).unwrap(), vec![1, 2]);
+    assert_eq!(suite.limit_orders(&seller, Some(1), None).unwrap(), vec![2]);
+    assert_eq!(suite.limit_orders(&seller, None, Some(1)).unwrap(), vec![1]);
+
+    // escrowed btc is neither in pools nor an excess
+    suite.assert_balances(&pair, 3100, 6000, 1000);
+    assert_eq!(suite.pools().unwrap(), (2000, 6000));
+    suite.skim(&trader, None).unwrap();
+    suite.assert_balances(&pair, 3100, 6000, 1000);
+
+    // trader buys 285btc from the pool, moving price above both orders
+    // seller sells 100btc with the first order for 384cash, and 69btc with the second one for 242cash,
+    // after which pool price is back at the second order price
+    suite.swap_cash(&trader, 1000, None, None, None).unwrap();
+    suite.assert_balances(&trader, 385, 0, 0);
+    suite.assert_balances(&seller, 0, 0, 0);
+    assert_eq!(suite.limit_order_proceeds(&seller).unwrap(), (0, 626));
+    suite.assert_balances(&pair, 2815, 7000, 1000);
+    assert_eq!(suite.pools().unwrap(), (1884, 6374));
+    assert_eq!(suite.limit_orders(&seller, None, None).unwrap(), vec![2]);
+
+    // unclaimed proceeds are not an excess either
+    suite.skim(&trader, None).unwrap();
+    suite.assert_balances(&pair, 2815, 7000, 1000);
+
+    suite.claim_limit_order_proceeds(&seller).unwrap();
+    suite.assert_balances(&seller, 0, 626, 0);
+    suite.assert_balances(&pair, 2815, 6374, 1000);
+    assert_eq!(suite.limit_order_proceeds(&seller).unwrap(), (0, 0));
+    let err = suite.claim_limit_order_proceeds(&seller).unwrap_err();
+    assert_eq!(
+        ContractError::NoLimitOrderProceeds {}.to_string(),
+        err.root_cause().to_string()
+    );
 
-    assert_eq!(simulation_resp.return_amount, Uint128::new(1000));
+    let order = suite.limit_order(2).unwrap();
+    assert_eq!(order.offer_asset.amount, Uint128::new(931));
+    assert_eq!(order.filled_amount, Uint128::new(69));
+    assert_eq!(order.return_amount, Uint128::new(242));
+
+    let err = suite.cancel_limit_order(&trader, 2).unwrap_err();
     assert_eq!(
-        simulation_resp.spread_amount,
-        rev_simulation_resp.spread_amount
+        ContractError::Unauthorized {}.to_string(),
+        err.root_cause().to_string()
     );
+
+    suite.cancel_limit_order(&seller, 2).unwrap();
+    suite.assert_balances(&seller, 931, 626, 0);
+    suite.assert_balances(&pair, 1884, 6374, 1000);
     assert_eq!(
-        simulation_resp.commission_amount,
-        rev_simulation_resp.commission_amount
+        suite.limit_orders(&seller, None, None).unwrap(),
+        Vec::<u64>::new()
     );
+    suite.limit_order(2).unwrap_err();
+}
 
-    let rev_simulation_resp = suite.simulate_reverse_swap(1000, suite.cash()).unwrap();
-    let simulation_resp = suite
-        .simulate_swap(rev_simulation_resp.offer_amount.into(), suite.btc())
+#[test]
+// Checks if limit order crossed by pool price is filled on placement
+fn limit_order_crossed_on_placement() {
+    // Initialize suite:
+    // liquidity provider (lp): 2000btc + 6000cash
+    // seller: 1btc + 600cash
+    let mut suite = SuiteConfig::new()
+        .with_liquidity_provider("liquidity-provider", 2000, 6000)
+        .with_trader("seller", 1, 600)
+        .init()
         .unwrap();
 
-    assert_eq!(simulation_resp.return_amount, Uint128::new(1000));
+    let (lp, seller, pair) = (
+        suite.lps[0].clone(),
+        suite.traders[0].clone(),
+        suite.pair.clone(),
+    );
+
+    suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
+
+    let err = suite
+        .place_limit_order_cash(&seller, 300, Decimal::zero())
+        .unwrap_err();
     assert_eq!(
-        simulation_resp.spread_amount,
-        rev_simulation_resp.spread_amount
+        ContractError::InvalidLimitOrderPrice {}.to_string(),
+        err.root_cause().to_string()
     );
+
+    // 2000 * 0.997 / 6000 = 0.332btc is paid for cash now, so the order is filled right away
+    let resp = suite
+        .place_limit_order_cash(&seller, 300, Decimal::percent(30))
+        .unwrap();
+    assert!(resp.has_event(
+        &Event::new("wasm-limit_order_fill")
+            .add_attribute("action", "swap")
+            .add_attribute("offer_asset", suite.cash.as_str())
+            .add_attribute("ask_asset", "btc")
+            .add_attribute("offer_amount", "300")
+            .add_attribute("return_amount", "95")
+            .add_attribute("spread_amount", "5")
+            .add_attribute("commission_amount", "0")
+            .add_attribute("protocol_fee_amount", "0")
+            .add_attribute("order_id", "1")
+            .add_attribute("owner", seller.as_str())
+    ));
+    suite.assert_balances(&seller, 1, 300, 0);
+    suite.assert_balances(&pair, 2000, 6300, 1000);
+    assert_eq!(suite.pools().unwrap(), (1905, 6300));
+    assert_eq!(suite.limit_order_proceeds(&seller).unwrap(), (95, 0));
     assert_eq!(
-        simulation_resp.commission_amount,
-        rev_simulation_resp.commission_amount
+        suite.limit_orders(&seller, None, None).unwrap(),
+        Vec::<u64>::new()
+    );
+}
+
+#[test]
+// Checks if limit orders are filled by single sided deposits and withdrawals, which swap internally
+// the same way as `swap`
+fn limit_order_crossed_by_single_sided() {
+    // Initialize suite:
+    // liquidity provider (lp): 2000btc + 9000cash
+    // seller: 200btc
+    let mut suite = SuiteConfig::new()
+        .with_liquidity_provider("liquidity-provider", 2000, 9000)
+        .with_trader("seller", 200, 0)
+        .init()
+        .unwrap();
+
+    let (lp, seller) = (suite.lps[0].clone(), suite.traders[0].clone());
+
+    suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
+    suite
+        .place_limit_order_btc(&seller, 100, Decimal::percent(310))
+        .unwrap();
+    suite
+        .place_limit_order_btc(&seller, 100, Decimal::percent(500))
+        .unwrap();
+
+    // part of deposited cash is swapped for btc, moving price above the first order, which is
+    // filled right away
+    suite
+        .provide_single_sided_cash(&lp, 3000, None, None)
+        .unwrap();
+    assert_eq!(suite.limit_orders(&seller, None, None).unwrap(), vec![2]);
+    assert_eq!(suite.limit_order_proceeds(&seller).unwrap(), (0, 427));
+    assert_eq!(suite.pools().unwrap(), (2100, 8569));
+
+    // withdrawn cash is swapped for btc, moving price above the second order, which is filled
+    // partially
+    suite
+        .withdraw_single_sided(&lp, 500, suite.btc(), None)
+        .unwrap();
+    assert_eq!(suite.limit_orders(&seller, None, None).unwrap(), vec![2]);
+    assert_eq!(suite.limit_order_proceeds(&seller).unwrap(), (0, 792));
+    assert_eq!(suite.pools().unwrap(), (1708, 8204));
+    let order = suite.limit_order(2).unwrap();
+    assert_eq!(order.filled_amount, Uint128::new(73));
+    assert_eq!(order.return_amount, Uint128::new(365));
+}
+
+#[test]
+// Single swap fills at most `MAX_LIMIT_ORDER_FILLS` orders, the rest is filled by next swaps
+fn limit_order_fills_capped() {
+    // Initialize suite:
+    // liquidity provider (lp): 2000btc + 6000cash
+    // trader: 1btc + 1100cash
+    // seller: 1000btc
+    let mut suite = SuiteConfig::new()
+        .with_liquidity_provider("liquidity-provider", 2000, 6000)
+        .with_trader("trader", 1, 1100)
+        .with_trader("seller", 1000, 0)
+        .init()
+        .unwrap();
+
+    let (lp, trader, seller) = (
+        suite.lps[0].clone(),
+        suite.traders[0].clone(),
+        suite.traders[1].clone(),
+    );
+
+    suite.provide_liquidity(&lp, 2000, 6000, None).unwrap();
+    for _ in 0..MAX_LIMIT_ORDER_FILLS + 2 {
+        suite
+            .place_limit_order_btc(&seller, 10, Decimal::percent(330))
+            .unwrap();
+    }
+
+    // the swap moves price far enough for all orders to be filled
+    suite.swap_cash(&trader, 1000, None, None, None).unwrap();
+    assert_eq!(
+        suite.limit_orders(&seller, None, None).unwrap(),
+        vec![11, 12]
+    );
+
+    suite.swap_cash(&trader, 100, None, None, None).unwrap();
+    assert_eq!(
+        suite.limit_orders(&seller, None, None).unwrap(),
+        Vec::<u64>::new()
     );
+    let (_, proceeds) = suite.limit_order_proceeds(&seller).unwrap();
+    assert!(proceeds >= 3300 * (MAX_LIMIT_ORDER_FILLS as u128 + 2) / 100);
 }
 
 mod custom_commission {
@@ -815,21 +2868,21 @@ mod custom_commission {
         //   cash to be paid out: 6000 - 4000 = 2000
         //   cash to be paid out after commission: 2000 - 2000 * 0.5% = 2000 - 2000 * 0.005 = 1990
         suite
-            .assert_balances(&lp, 0, 0, 3464)
+            .assert_balances(&lp, 0, 0, 2464)
             .assert_balances(&trader, 0, 1990, 0)
-            .assert_balances(&pair, 3000, 4010, 0);
+            .assert_balances(&pair, 3000, 4010, 1000);
 
         suite.swap_cash(&trader, 1000, None, None, None).unwrap();
 
         // trader -> pair: 1000cash
-        // pair -> trader_recv: 599 cash, explanation:
-        //   btc to be left on contract: 3000 * 4010 / (4010 + 1000) = 2401
-        //   btc to be paid out: 3000 - 2401 = 599
-        //   btc to be paid out after commission: 599 - 599 * 0.005 = 597
+        // pair -> trader_recv: 596btc, explanation:
+        //   btc to be left on contract: 3000 * 4010 / (4010 + 1000) = 2402 (rounded up)
+        //   btc to be paid out: 3000 - 2402 = 598
+        //   btc to be paid out after commission: 598 - 598 * 0.005 = 596
         suite
-            .assert_balances(&lp, 0, 0, 3464)
-            .assert_balances(&trader, 597, 990, 0)
-            .assert_balances(&pair, 2403, 5010, 0);
+            .assert_balances(&lp, 0, 0, 2464)
+            .assert_balances(&trader, 596, 990, 0)
+            .assert_balances(&pair, 2404, 5010, 1000);
     }
 
     #[test]
@@ -902,10 +2955,14 @@ mod custom_commission {
             .simulate_swap(rev_simulation_resp.offer_amount.into(), suite.cash())
             .unwrap();
 
-        assert_eq!(simulation_resp.return_amount, Uint128::new(1000));
-        assert_eq!(
-            simulation_resp.spread_amount,
-            rev_simulation_resp.spread_amount
+        assert!(simulation_resp.return_amount >= Uint128::new(1000));
+        // rounding offer up may add to the spread
+        assert!(
+            simulation_resp
+                .spread_amount
+                .u128()
+                .abs_diff(rev_simulation_resp.spread_amount.u128())
+                <= 1
         );
         assert_eq!(
             simulation_resp.commission_amount,
@@ -917,10 +2974,14 @@ mod custom_commission {
             .simulate_swap(rev_simulation_resp.offer_amount.into(), suite.btc())
             .unwrap();
 
-        assert_eq!(simulation_resp.return_amount, Uint128::new(1000));
-        assert_eq!(
-            simulation_resp.spread_amount,
-            rev_simulation_resp.spread_amount
+        assert!(simulation_resp.return_amount >= Uint128::new(1000));
+        // rounding offer up may add to the spread
+        assert!(
+            simulation_resp
+                .spread_amount
+                .u128()
+                .abs_diff(rev_simulation_resp.spread_amount.u128())
+                <= 1
         );
         assert_eq!(
             simulation_resp.commission_amount,
@@ -1,5 +1,144 @@
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Decimal, Decimal256, Empty, Uint128, Uint256};
+use cw_storage_plus::{Item, Map};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use tfi::asset::PairInfo;
+use tfi::pair::CommissionTier;
+
+use crate::math::wrapping_add;
 
 // put the length bytes at the first for compatibility with legacy singleton store
+// the factory reads it with raw query to find the liquidity token, so the key must not change
 pub const PAIR_INFO: Item<PairInfo> = Item::new("pair_info");
+
+/// Pool reserves, in the order of `PAIR_INFO.asset_infos`. Tokens sent directly to the pair are
+/// not accounted until `Sync`.
+pub const RESERVES: Item<Vec<Uint128>> = Item::new("reserves");
+
+/// Swap commission tiers
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CommissionTiers {
+    /// tg4 group which member points select the tier
+    pub group: Addr,
+    /// Tiers sorted by `min_points`
+    pub tiers: Vec<CommissionTier>,
+}
+
+/// Set only if the pair has a commission group
+pub const COMMISSION_TIERS: Item<CommissionTiers> = Item::new("commission_tiers");
+
+/// Protocol fee settings
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProtocolFee {
+    /// Address receiving collected fees
+    pub fee_collector: Option<Addr>,
+    /// Part of swap commission accrued for the fee collector
+    pub share: Decimal,
+}
+
+pub const PROTOCOL_FEE: Item<ProtocolFee> = Item::new("protocol_fee");
+
+/// Protocol fees accrued and not collected yet, in the order of `PAIR_INFO.asset_infos`. They are
+/// held by the pair, but are not part of `RESERVES`.
+pub const ACCRUED_FEES: Item<Vec<Uint128>> = Item::new("accrued_fees");
+
+/// Price accumulators, Uniswap v2 style
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CumulativePrices {
+    /// Sum of prices of first asset in second asset, multiplied by seconds they were valid
+    pub price0_cumulative: Decimal256,
+    /// Sum of prices of second asset in first asset, multiplied by seconds they were valid
+    pub price1_cumulative: Decimal256,
+    /// Block time (in seconds) of last accumulators update
+    pub last_updated: u64,
+}
+
+impl CumulativePrices {
+    pub fn new(now: u64) -> Self {
+        Self {
+            price0_cumulative: Decimal256::zero(),
+            price1_cumulative: Decimal256::zero(),
+            last_updated: now,
+        }
+    }
+
+    /// Returns accumulators advanced to `now`, assuming `pools` didn't change since last update.
+    /// Nothing is accumulated while any of pools is empty, as there is no price then, neither for
+    /// pools of more than two assets. Accumulators wrap around on overflow, so the pair never gets
+    /// stuck on them.
+    pub fn accumulate(&self, now: u64, pools: &[Uint128]) -> Self {
+        let elapsed = Uint256::from(now.saturating_sub(self.last_updated));
+        if elapsed.is_zero() || pools.len() != 2 || pools.iter().any(Uint128::is_zero) {
+            return Self {
+                last_updated: now.max(self.last_updated),
+                ..self.clone()
+            };
+        }
+
+        let [pool0, pool1] = [Uint256::from(pools[0]), Uint256::from(pools[1])];
+        Self {
+            price0_cumulative: wrapping_add(
+                self.price0_cumulative,
+                Decimal256::from_ratio(pool1 * elapsed, pool0),
+            ),
+            price1_cumulative: wrapping_add(
+                self.price1_cumulative,
+                Decimal256::from_ratio(pool0 * elapsed, pool1),
+            ),
+            last_updated: now,
+        }
+    }
+}
+
+pub const CUMULATIVE_PRICES: Item<CumulativePrices> = Item::new("cumulative_prices");
+
+/// Flash swap waiting for repayment check
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingFlashSwap {
+    /// Reserves before the flash swap
+    pub reserves: [Uint128; 2],
+    /// Amounts sent to the initiator, in the order of `PAIR_INFO.asset_infos`
+    pub borrowed: [Uint128; 2],
+    /// Initiator of the flash swap, its commission tier applies to the repayment
+    pub trader: Addr,
+}
+
+/// Set only while the flash swap callback is executed. No other pair operation is allowed then.
+pub const FLASH_SWAP: Item<PendingFlashSwap> = Item::new("flash_swap");
+
+/// Order selling the asset with index `offer_idx` for the other one, at `price` or better
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LimitOrder {
+    pub owner: Addr,
+    pub offer_idx: u8,
+    /// Unfilled part of the offer
+    pub offer_amount: Uint128,
+    /// Minimum amount of the other asset paid for a unit of the offer asset
+    pub price: Decimal,
+    /// Part of the offer filled so far
+    pub filled_amount: Uint128,
+    /// Total of the other asset paid out for filled part
+    pub return_amount: Uint128,
+}
+
+impl LimitOrder {
+    /// Key of the order in `ORDER_BOOK`
+    pub fn book_key(&self, order_id: u64) -> (u8, u128, u64) {
+        (self.offer_idx, self.price.atomics().u128(), order_id)
+    }
+}
+
+pub const LIMIT_ORDERS: Map<u64, LimitOrder> = Map::new("limit_orders");
+/// Ids of open orders by their owners
+pub const OWNER_LIMIT_ORDERS: Map<(&Addr, u64), Empty> = Map::new("owner_limit_orders");
+/// Open orders by offer asset index and price, so the best priced ones are filled first
+pub const ORDER_BOOK: Map<(u8, u128, u64), Empty> = Map::new("order_book");
+/// Id of the most recently placed order
+pub const LAST_LIMIT_ORDER_ID: Item<u64> = Item::new("last_limit_order_id");
+
+/// Unfilled offers of open orders and unclaimed returns of filled ones, in the order of
+/// `PAIR_INFO.asset_infos`. They are held by the pair, but are not part of `RESERVES`.
+pub const ESCROWED: Item<Vec<Uint128>> = Item::new("escrowed");
+/// Returns of filled orders by their owners, in the order of `PAIR_INFO.asset_infos`, waiting to
+/// be claimed
+pub const LIMIT_ORDER_PROCEEDS: Map<&Addr, [Uint128; 2]> = Map::new("limit_order_proceeds");
@@ -1,17 +1,19 @@
 use crate::contract::{
-    assert_max_spread, execute, instantiate, query_pair_info, query_pool, query_reverse_simulation,
-    query_simulation, reply,
+    assert_max_spread, compute_offer_amount, compute_swap, execute, instantiate, query_pair_info,
+    query_pool, query_reverse_simulation, query_simulation, reply, Operation,
 };
 use crate::error::ContractError;
-use crate::math::{decimal_multiplication, reverse_decimal};
+use crate::math::uint512_sqrt;
+use crate::state::{CumulativePrices, RESERVES};
 
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Reply, ReplyOn, Response, StdError,
-    SubMsg, SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
+    attr, coins, to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Decimal256, Reply, ReplyOn,
+    Response, StdError, SubMsg, SubMsgResponse, SubMsgResult, Uint128, Uint256, Uint512, WasmMsg,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
-use tfi::asset::{Asset, AssetInfo, PairInfo};
+use proptest::prelude::*;
+use tfi::asset::{Asset, AssetInfo, PairInfo, PauseState};
 use tfi::pair::{
     Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, ReverseSimulationResponse,
     SimulationResponse,
@@ -49,6 +51,8 @@ fn proper_initialization() {
                         minter: MOCK_CONTRACT_ADDR.to_string(),
                         cap: None,
                     }),
+                    whitelist_group: None,
+                    exempt_minter: None,
                 })
                 .unwrap(),
                 funds: vec![],
@@ -111,11 +115,96 @@ fn commission_out_of_range() {
     assert_eq!(err, ContractError::InvalidCommission(Decimal::percent(101)));
 }
 
+#[test]
+fn invalid_asset_count() {
+    let err = instantiate(
+        mock_dependencies(&[]).as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        InstantiateMsg::new(
+            [
+                AssetInfo::Native("eth".to_owned()),
+                AssetInfo::Native("btc".to_owned()),
+                AssetInfo::Native("uusd".to_owned()),
+            ],
+            1,
+        ),
+    )
+    .unwrap_err();
+
+    assert_eq!(err, ContractError::InvalidAssetCount(3));
+}
+
+#[test]
+fn update_pause() {
+    let mut deps = mock_dependencies(&[]);
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        InstantiateMsg::new(
+            [
+                AssetInfo::Native("eth".to_owned()),
+                AssetInfo::Native("btc".to_owned()),
+            ],
+            1,
+        )
+        .with_admin("admin"),
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::UpdatePause {
+        swaps: Some(true),
+        deposits: None,
+        withdrawals: None,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+    let pair_info = query_pair_info(deps.as_ref()).unwrap();
+    assert_eq!(
+        pair_info.paused,
+        PauseState {
+            swaps: true,
+            deposits: false,
+            withdrawals: false,
+        }
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("trader", &coins(100, "eth")),
+        ExecuteMsg::Swap {
+            ask_asset_info: None,
+            offer_asset: Asset {
+                info: AssetInfo::Native("eth".to_owned()),
+                amount: Uint128::new(100),
+            },
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            min_return: None,
+            deadline: None,
+            to_msg: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Paused(Operation::Swaps));
+}
+
 #[test]
 fn provide_liquidity() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
-        amount: Uint128::new(200),
+        amount: Uint128::new(2000),
     }]);
 
     deps.querier.with_token_balances(&[
@@ -157,17 +246,18 @@ fn provide_liquidity() {
 
     // successfully provide liquidity for the exist pool
     let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
+        assets: vec![
             Asset {
                 info: AssetInfo::Token(Addr::unchecked("asset0000".to_string())),
-                amount: Uint128::new(100),
+                amount: Uint128::new(2000),
             },
             Asset {
                 info: AssetInfo::Native("uusd".to_string()),
-                amount: Uint128::new(100),
+                amount: Uint128::new(2000),
             },
         ],
         slippage_tolerance: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -175,12 +265,13 @@ fn provide_liquidity() {
         "addr0000",
         &[Coin {
             denom: "uusd".to_string(),
-            amount: Uint128::new(100),
+            amount: Uint128::new(2000),
         }],
     );
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let transfer_from_msg = res.messages.get(0).expect("no message");
-    let mint_msg = res.messages.get(1).expect("no message");
+    let transfer_from_msg = res.messages.first().expect("no message");
+    let lock_msg = res.messages.get(1).expect("no message");
+    let mint_msg = res.messages.get(2).expect("no message");
     assert_eq!(
         transfer_from_msg,
         &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -188,7 +279,20 @@ fn provide_liquidity() {
             msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
                 owner: "addr0000".to_string(),
                 recipient: MOCK_CONTRACT_ADDR.to_string(),
-                amount: Uint128::new(100),
+                amount: Uint128::new(2000),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+    // minimum liquidity is locked on the pair itself
+    assert_eq!(
+        lock_msg,
+        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: MOCK_CONTRACT_ADDR.to_string(),
+                amount: Uint128::new(1000),
             })
             .unwrap(),
             funds: vec![],
@@ -200,7 +304,7 @@ fn provide_liquidity() {
             contract_addr: "liquidity0000".to_string(),
             msg: to_binary(&Cw20ExecuteMsg::Mint {
                 recipient: "addr0000".to_string(),
-                amount: Uint128::new(100),
+                amount: Uint128::new(1000),
             })
             .unwrap(),
             funds: vec![],
@@ -208,28 +312,21 @@ fn provide_liquidity() {
     );
 
     // provide more liquidity 1:2, which is not proportional to 1:1,
-    // then it must accept 1:1 and treat left amount as donation
-    deps.querier.with_balance(&[(
-        MOCK_CONTRACT_ADDR,
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::new(200 + 200 /* user deposit must be pre-applied */),
-        }],
-    )]);
+    // then it must accept 1:1 and return left amount to the sender
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &vec![Uint128::new(200), Uint128::new(200)],
+        )
+        .unwrap();
 
-    deps.querier.with_token_balances(&[
-        (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(100))],
-        ),
-        (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(200))],
-        ),
-    ]);
+    deps.querier.with_token_balances(&[(
+        &"liquidity0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(100))],
+    )]);
 
     let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
+        assets: vec![
             Asset {
                 info: AssetInfo::Token(Addr::unchecked("asset0000".to_string())),
                 amount: Uint128::new(100),
@@ -240,6 +337,7 @@ fn provide_liquidity() {
             },
         ],
         slippage_tolerance: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -253,8 +351,20 @@ fn provide_liquidity() {
 
     // only accept 100, then 50 share will be generated with 100 * (100 / 200)
     let res: Response = execute(deps.as_mut(), env, info, msg).unwrap();
-    let transfer_from_msg = res.messages.get(0).expect("no message");
-    let mint_msg = res.messages.get(1).expect("no message");
+    let refund_msg = res.messages.first().expect("no message");
+    let transfer_from_msg = res.messages.get(1).expect("no message");
+    let mint_msg = res.messages.get(2).expect("no message");
+    assert_eq!(
+        refund_msg,
+        &SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(100),
+            }],
+        }))
+    );
+    assert_eq!(res.attributes[1], attr("assets", "100uusd, 100asset0000"));
     assert_eq!(
         transfer_from_msg,
         &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -283,7 +393,7 @@ fn provide_liquidity() {
 
     // check wrong argument
     let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
+        assets: vec![
             Asset {
                 info: AssetInfo::Token(Addr::unchecked("asset0000".to_string())),
                 amount: Uint128::new(100),
@@ -294,6 +404,7 @@ fn provide_liquidity() {
             },
         ],
         slippage_tolerance: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -313,29 +424,17 @@ fn provide_liquidity() {
         _ => panic!("Must return generic error"),
     }
 
-    // initialize token balance to 1:1
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::new(100 + 100 /* user deposit must be pre-applied */),
-        }],
-    )]);
-
-    deps.querier.with_token_balances(&[
-        (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(100))],
-        ),
-        (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(100))],
-        ),
-    ]);
+    // initialize reserves to 1:1
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &vec![Uint128::new(100), Uint128::new(100)],
+        )
+        .unwrap();
 
     // failed because the price is under slippage_tolerance
     let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
+        assets: vec![
             Asset {
                 info: AssetInfo::Token(Addr::unchecked("asset0000".to_string())),
                 amount: Uint128::new(98),
@@ -346,6 +445,7 @@ fn provide_liquidity() {
             },
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
+        deadline: None,
     };
 
     let env = mock_env();
@@ -359,18 +459,17 @@ fn provide_liquidity() {
     let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
     assert!(matches!(res, ContractError::MaxSlippageAssertion { .. }));
 
-    // initialize token balance to 1:1
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::new(100 + 98 /* user deposit must be pre-applied */),
-        }],
-    )]);
+    // initialize reserves to 1:1
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &vec![Uint128::new(100), Uint128::new(100)],
+        )
+        .unwrap();
 
     // failed because the price is under slippage_tolerance
     let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
+        assets: vec![
             Asset {
                 info: AssetInfo::Token(Addr::unchecked("asset0000".to_string())),
                 amount: Uint128::new(100),
@@ -381,6 +480,7 @@ fn provide_liquidity() {
             },
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
+        deadline: None,
     };
 
     let env = mock_env();
@@ -394,18 +494,17 @@ fn provide_liquidity() {
     let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
     assert!(matches!(res, ContractError::MaxSlippageAssertion { .. }));
 
-    // initialize token balance to 1:1
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::new(100 + 100 /* user deposit must be pre-applied */),
-        }],
-    )]);
+    // initialize reserves to 1:1
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &vec![Uint128::new(100), Uint128::new(100)],
+        )
+        .unwrap();
 
     // successfully provides
     let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
+        assets: vec![
             Asset {
                 info: AssetInfo::Token(Addr::unchecked("asset0000".to_string())),
                 amount: Uint128::new(99),
@@ -416,6 +515,7 @@ fn provide_liquidity() {
             },
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
+        deadline: None,
     };
 
     let env = mock_env();
@@ -428,18 +528,17 @@ fn provide_liquidity() {
     );
     let _res = execute(deps.as_mut(), env, info, msg).unwrap();
 
-    // initialize token balance to 1:1
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::new(100 + 99 /* user deposit must be pre-applied */),
-        }],
-    )]);
+    // initialize reserves to 1:1
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &vec![Uint128::new(100), Uint128::new(100)],
+        )
+        .unwrap();
 
     // successfully provides
     let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
+        assets: vec![
             Asset {
                 info: AssetInfo::Token(Addr::unchecked("asset0000".to_string())),
                 amount: Uint128::new(100),
@@ -450,6 +549,7 @@ fn provide_liquidity() {
             },
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
+        deadline: None,
     };
 
     let env = mock_env();
@@ -509,11 +609,17 @@ fn withdraw_liquidity() {
     };
 
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &vec![Uint128::new(100), Uint128::new(100)],
+        )
+        .unwrap();
 
     // withdraw liquidity
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity { deadline: None }).unwrap(),
         amount: Uint128::new(100),
     });
 
@@ -522,7 +628,7 @@ fn withdraw_liquidity() {
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
     let log_withdrawn_share = res.attributes.get(1).expect("no log");
     let log_refund_assets = res.attributes.get(2).expect("no log");
-    let msg_refund_0 = res.messages.get(0).expect("no message");
+    let msg_refund_0 = res.messages.first().expect("no message");
     let msg_refund_1 = res.messages.get(1).expect("no message");
     let msg_burn_liquidity = res.messages.get(2).expect("no message");
     assert_eq!(
@@ -574,8 +680,6 @@ fn try_native_to_token() {
     let total_share = Uint128::new(30000000000);
     let asset_pool_amount = Uint128::new(20000000000);
     let collateral_pool_amount = Uint128::new(30000000000);
-    let price = Decimal::from_ratio(collateral_pool_amount, asset_pool_amount);
-    let exchange_rate = reverse_decimal(price);
     let offer_amount = Uint128::new(1500000000);
 
     let mut deps = mock_dependencies(&[Coin {
@@ -622,9 +726,16 @@ fn try_native_to_token() {
     };
 
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &vec![collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
 
     // normal swap
     let msg = ExecuteMsg::Swap {
+        ask_asset_info: None,
         offer_asset: Asset {
             info: AssetInfo::Native("uusd".to_string()),
             amount: offer_amount,
@@ -632,6 +743,9 @@ fn try_native_to_token() {
         belief_price: None,
         max_spread: None,
         to: None,
+        min_return: None,
+        deadline: None,
+        to_msg: None,
     };
     let env = mock_env();
     let info = mock_info(
@@ -642,12 +756,13 @@ fn try_native_to_token() {
         }],
     );
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let msg_transfer = res.messages.get(0).expect("no message");
+    let msg_transfer = res.messages.first().expect("no message");
 
     // current price is 1.5, so expected return without spread is 1000
-    // 952.380953 = 20000 - 20000 * 30000 / (30000 + 1500)
-    let expected_ret_amount = Uint128::new(952_380_953);
-    let expected_spread_amount = (offer_amount * exchange_rate)
+    // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500), ask pool left is rounded up
+    let expected_ret_amount = Uint128::new(952_380_952);
+    let expected_spread_amount = offer_amount
+        .multiply_ratio(asset_pool_amount, collateral_pool_amount)
         .checked_sub(expected_ret_amount)
         .unwrap();
     let expected_commission_amount = expected_ret_amount.multiply_ratio(3u128, 1000u128); // 0.3%
@@ -655,14 +770,13 @@ fn try_native_to_token() {
         .checked_sub(expected_commission_amount)
         .unwrap();
 
-    // check simulation res
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: collateral_pool_amount, /* user deposit must be pre-applied */
-        }],
-    )]);
+    // check simulation res against pools from before the swap
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &vec![collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
 
     let simulation_res: SimulationResponse = query_simulation(
         deps.as_ref(),
@@ -670,6 +784,8 @@ fn try_native_to_token() {
             info: AssetInfo::Native("uusd".to_string()),
             amount: offer_amount,
         },
+        None,
+        None,
     )
     .unwrap();
     assert_eq!(expected_return_amount, simulation_res.return_amount);
@@ -683,6 +799,8 @@ fn try_native_to_token() {
             info: AssetInfo::Token(Addr::unchecked("asset0000")),
             amount: expected_return_amount,
         },
+        None,
+        None,
     )
     .unwrap();
     assert!(
@@ -712,6 +830,7 @@ fn try_native_to_token() {
             attr("return_amount", expected_return_amount.to_string()),
             attr("spread_amount", expected_spread_amount.to_string()),
             attr("commission_amount", expected_commission_amount.to_string()),
+            attr("protocol_fee_amount", "0"),
         ]
     );
 
@@ -734,8 +853,6 @@ fn try_token_to_native() {
     let total_share = Uint128::new(20000000000);
     let asset_pool_amount = Uint128::new(30000000000);
     let collateral_pool_amount = Uint128::new(20000000000);
-    let price = Decimal::from_ratio(collateral_pool_amount, asset_pool_amount);
-    let exchange_rate = decimal_multiplication(price, Decimal::one());
     let offer_amount = Uint128::new(1500000000);
 
     let mut deps = mock_dependencies(&[Coin {
@@ -784,9 +901,16 @@ fn try_token_to_native() {
     };
 
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &vec![collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
 
     // unauthorized access; can not execute swap directly for token swap
     let msg = ExecuteMsg::Swap {
+        ask_asset_info: None,
         offer_asset: Asset {
             info: AssetInfo::Token(Addr::unchecked("asset0000")),
             amount: offer_amount,
@@ -794,6 +918,9 @@ fn try_token_to_native() {
         belief_price: None,
         max_spread: None,
         to: None,
+        min_return: None,
+        deadline: None,
+        to_msg: None,
     };
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
@@ -808,9 +935,13 @@ fn try_token_to_native() {
         sender: "addr0000".to_string(),
         amount: offer_amount,
         msg: to_binary(&Cw20HookMsg::Swap {
+            ask_asset_info: None,
             belief_price: None,
             max_spread: None,
             to: None,
+            min_return: None,
+            deadline: None,
+            to_msg: None,
         })
         .unwrap(),
     });
@@ -818,30 +949,26 @@ fn try_token_to_native() {
     let info = mock_info("asset0000", &[]);
 
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let msg_transfer = res.messages.get(0).expect("no message");
+    let msg_transfer = res.messages.first().expect("no message");
 
     // current price is 1.5, so expected return without spread is 1000
-    // 952.380953 = 20000 - 20000 * 30000 / (30000 + 1500)
-    let expected_ret_amount = Uint128::new(952_380_953);
-    let expected_spread_amount = (offer_amount * exchange_rate)
+    // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500), ask pool left is rounded up
+    let expected_ret_amount = Uint128::new(952_380_952);
+    let expected_spread_amount = offer_amount
+        .multiply_ratio(collateral_pool_amount, asset_pool_amount)
         .checked_sub(expected_ret_amount)
         .unwrap();
     let expected_commission_amount = expected_ret_amount.multiply_ratio(3u128, 1000u128); // 0.3%
     let expected_return_amount = expected_ret_amount
         .checked_sub(expected_commission_amount)
         .unwrap();
-    // check simulation res
-    // return asset token balance as normal
-    deps.querier.with_token_balances(&[
-        (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
-        ),
-        (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &(asset_pool_amount))],
-        ),
-    ]);
+    // check simulation res against pools from before the swap
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &vec![collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
 
     let simulation_res: SimulationResponse = query_simulation(
         deps.as_ref(),
@@ -849,6 +976,8 @@ fn try_token_to_native() {
             amount: offer_amount,
             info: AssetInfo::Token(Addr::unchecked("asset0000")),
         },
+        None,
+        None,
     )
     .unwrap();
     assert_eq!(expected_return_amount, simulation_res.return_amount);
@@ -862,6 +991,8 @@ fn try_token_to_native() {
             amount: expected_return_amount,
             info: AssetInfo::Native("uusd".to_string()),
         },
+        None,
+        None,
     )
     .unwrap();
     assert!(
@@ -891,6 +1022,7 @@ fn try_token_to_native() {
             attr("return_amount", expected_return_amount.to_string()),
             attr("spread_amount", expected_spread_amount.to_string()),
             attr("commission_amount", expected_commission_amount.to_string()),
+            attr("protocol_fee_amount", "0"),
         ]
     );
 
@@ -910,9 +1042,13 @@ fn try_token_to_native() {
         sender: "addr0000".to_string(),
         amount: offer_amount,
         msg: to_binary(&Cw20HookMsg::Swap {
+            ask_asset_info: None,
             belief_price: None,
             max_spread: None,
             to: None,
+            min_return: None,
+            deadline: None,
+            to_msg: None,
         })
         .unwrap(),
     });
@@ -1014,6 +1150,11 @@ fn test_query_pool() {
 
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
+    // pools are reported from stored reserves, not from balances
+    RESERVES
+        .save(deps.as_mut().storage, &vec![asset_0_amount, asset_1_amount])
+        .unwrap();
+
     let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
 
     assert_eq!(
@@ -1031,3 +1172,128 @@ fn test_query_pool() {
     );
     assert_eq!(res.total_share, total_share_amount);
 }
+
+#[test]
+fn compute_offer_amount_rounds_up() {
+    // 10 * 1000 / (1000 - 10) - 10 = 0.1 offer has to be paid in full
+    let (offer_amount, _spread_amount, _commission_amount) = compute_offer_amount(
+        Uint128::new(10),
+        Uint128::new(1000),
+        Uint128::new(10),
+        Decimal::zero(),
+    )
+    .unwrap();
+    assert_eq!(offer_amount, Uint128::new(1));
+}
+
+#[test]
+fn cumulative_prices_wrap_on_overflow() {
+    let prices = CumulativePrices {
+        price0_cumulative: Decimal256::new(Uint256::MAX - Uint256::from(1u8)),
+        price1_cumulative: Decimal256::zero(),
+        last_updated: 100,
+    };
+
+    // price 2 for 10 seconds overflows the first accumulator by 20 units (minus 2 atomics)
+    let prices = prices.accumulate(110, &[Uint128::new(100), Uint128::new(200)]);
+    assert_eq!(
+        prices,
+        CumulativePrices {
+            price0_cumulative: Decimal256::new(
+                Decimal256::from_ratio(20u8, 1u8).atomics() - Uint256::from(2u8)
+            ),
+            price1_cumulative: Decimal256::from_ratio(5u8, 1u8),
+            last_updated: 110,
+        }
+    );
+}
+
+/// Reserves close to `u128::MAX`, where products of pools overflow 128 bits
+fn huge_pool() -> impl Strategy<Value = u128> {
+    (u128::MAX / 2)..=u128::MAX
+}
+
+proptest! {
+    #[test]
+    fn swap_near_max_reserves(
+        offer_pool in huge_pool(),
+        ask_pool in huge_pool(),
+        offer_amount in 1..=u128::MAX / 2,
+        commission in 0u64..=1000,
+    ) {
+        let offer_amount = std::cmp::min(offer_amount, u128::MAX - offer_pool);
+        let commission = Decimal::permille(commission);
+        let (return_amount, _spread_amount, commission_amount) = compute_swap(
+            offer_pool.into(),
+            ask_pool.into(),
+            offer_amount.into(),
+            commission,
+        )
+        .unwrap();
+
+        prop_assert!(return_amount.u128() + commission_amount.u128() <= ask_pool);
+
+        // commission stays in the pool, and ask pool left is rounded up, so the constant product
+        // never decreases
+        let cp = Uint256::from(offer_pool).full_mul(ask_pool);
+        let new_offer_pool = Uint256::from(offer_pool + offer_amount);
+        let new_cp = new_offer_pool.full_mul(ask_pool - return_amount.u128());
+        prop_assert!(new_cp >= cp);
+    }
+
+    #[test]
+    fn reverse_swap_near_max_reserves(
+        offer_pool in huge_pool(),
+        ask_pool in huge_pool(),
+        ask_amount in 1..=u128::MAX / 8,
+        commission in 0u64..=500,
+    ) {
+        let commission = Decimal::permille(commission);
+        let (offer_amount, _spread_amount, _commission_amount) = compute_offer_amount(
+            offer_pool.into(),
+            ask_pool.into(),
+            ask_amount.into(),
+            commission,
+        )
+        .unwrap();
+        prop_assume!(offer_amount.u128() <= u128::MAX - offer_pool);
+
+        // swapping the computed offer never returns less than asked for
+        let (return_amount, _spread_amount, _commission_amount) =
+            compute_swap(offer_pool.into(), ask_pool.into(), offer_amount, commission).unwrap();
+        prop_assert!(return_amount.u128() >= ask_amount);
+    }
+
+    #[test]
+    fn offer_amount_buys_ask_amount(
+        offer_pool in 1..=1_000_000_000_000u128,
+        ask_pool in 2..=1_000_000_000_000u128,
+        ask_amount in 1..=1_000_000_000_000u128,
+        commission in 0u64..=100,
+    ) {
+        let commission = Decimal::permille(commission);
+        // asking for the whole pool is never possible
+        prop_assume!(ask_amount < ask_pool / 2);
+        let (offer_amount, _spread_amount, _commission_amount) = compute_offer_amount(
+            offer_pool.into(),
+            ask_pool.into(),
+            ask_amount.into(),
+            commission,
+        )
+        .unwrap();
+
+        let (return_amount, _spread_amount, _commission_amount) =
+            compute_swap(offer_pool.into(), ask_pool.into(), offer_amount, commission).unwrap();
+        prop_assert!(return_amount.u128() >= ask_amount);
+    }
+
+    #[test]
+    fn initial_share_near_max_deposits(deposit0 in huge_pool(), deposit1 in huge_pool()) {
+        let product = Uint256::from(deposit0).full_mul(deposit1);
+        let share = uint512_sqrt(product).unwrap();
+        let share = Uint512::from(share);
+
+        prop_assert!(share * share <= product);
+        prop_assert!((share + Uint512::from(1u8)) * (share + Uint512::from(1u8)) > product);
+    }
+}
@@ -0,0 +1,471 @@
+use crate::error::ContractError;
+use crate::state::{Config, CONFIG};
+
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
+    StdError, StdResult, Uint128, WasmMsg,
+};
+
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use tfi::asset::{Asset, AssetInfo};
+use tfi::pair::{Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg};
+use tfi::querier::{query_pair_info, reverse_simulate, simulate};
+use tfi::router::{
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
+    SimulateSwapOperationsResponse, SwapOperation, SwapOperationSimulation,
+};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:tfi-router";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let config = Config {
+        tfi_factory: deps.api.addr_validate(&msg.tfi_factory)?,
+    };
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::ExecuteSwapOperations {
+            operations,
+            minimum_receive,
+            to,
+        } => {
+            let offer_asset = match info.funds.as_slice() {
+                [coin] => Asset {
+                    info: AssetInfo::Native(coin.denom.clone()),
+                    amount: coin.amount,
+                },
+                _ => return Err(ContractError::InvalidFunds {}),
+            };
+            let to = to.map(|to| deps.api.addr_validate(&to)).transpose()?;
+            execute_swap_operations(
+                deps,
+                env,
+                info.sender,
+                offer_asset,
+                operations,
+                minimum_receive,
+                to,
+            )
+        }
+        ExecuteMsg::ExecuteSwapOperation {
+            operation,
+            prev_balance,
+            to,
+        } => {
+            let to = to.map(|to| deps.api.addr_validate(&to)).transpose()?;
+            execute_swap_operation(deps, env, info, operation, prev_balance, to)
+        }
+        ExecuteMsg::AssertMinimumReceive {
+            asset_info,
+            prev_balance,
+            minimum_receive,
+            receiver,
+        } => {
+            let receiver = deps.api.addr_validate(&receiver)?;
+            assert_minimum_receive(
+                deps.as_ref(),
+                asset_info,
+                prev_balance,
+                minimum_receive,
+                receiver,
+            )
+        }
+    }
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+    let offer_asset = Asset {
+        info: AssetInfo::Token(info.sender),
+        amount: cw20_msg.amount,
+    };
+
+    match from_binary(&cw20_msg.msg)? {
+        Cw20HookMsg::ExecuteSwapOperations {
+            operations,
+            minimum_receive,
+            to,
+        } => {
+            let to = to.map(|to| deps.api.addr_validate(&to)).transpose()?;
+            execute_swap_operations(
+                deps,
+                env,
+                sender,
+                offer_asset,
+                operations,
+                minimum_receive,
+                to,
+            )
+        }
+    }
+}
+
+/// Splits route into separate `ExecuteSwapOperation` messages sent to the router itself. The first
+/// hop swaps exactly `offer_asset` sent by the sender, and every next one swaps whatever the
+/// previous one returned - tokens the router held before are never swapped. If `minimum_receive`
+/// is given, the final `AssertMinimumReceive` reverts the whole route when the receiver got too
+/// little.
+pub fn execute_swap_operations(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    offer_asset: Asset,
+    operations: Vec<SwapOperation>,
+    minimum_receive: Option<Uint128>,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    assert_operations(&operations)?;
+
+    if !operations[0].offer_asset_info.equal(&offer_asset.info) {
+        return Err(ContractError::OfferAssetMismatch {
+            expected: operations[0].offer_asset_info.to_string(),
+            actual: offer_asset.info.to_string(),
+        });
+    }
+
+    if offer_asset.amount.is_zero() {
+        return Err(ContractError::InvalidFunds {});
+    }
+
+    let to = to.unwrap_or(sender);
+    let target_asset_info = operations.last().unwrap().ask_asset_info.clone();
+    let operations_len = operations.len();
+
+    let mut messages = operations
+        .into_iter()
+        .enumerate()
+        .map(|(idx, operation)| -> StdResult<CosmosMsg> {
+            let to = if idx == operations_len - 1 {
+                Some(to.to_string())
+            } else {
+                None
+            };
+
+            // Offer asset is already transferred to the router at this point
+            let mut prev_balance = operation
+                .offer_asset_info
+                .query_pool(&deps.querier, env.contract.address.clone())?;
+            if operation.offer_asset_info.equal(&offer_asset.info) {
+                prev_balance = prev_balance.checked_sub(offer_asset.amount)?;
+            }
+
+            Ok(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                    operation,
+                    prev_balance,
+                    to,
+                })?,
+                funds: vec![],
+            }
+            .into())
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    if let Some(minimum_receive) = minimum_receive {
+        let prev_balance = target_asset_info.query_pool(&deps.querier, to.clone())?;
+        messages.push(
+            WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                msg: to_binary(&ExecuteMsg::AssertMinimumReceive {
+                    asset_info: target_asset_info,
+                    prev_balance,
+                    minimum_receive,
+                    receiver: to.to_string(),
+                })?,
+                funds: vec![],
+            }
+            .into(),
+        );
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "execute_swap_operations")
+        .add_attribute("operations", operations_len.to_string()))
+}
+
+/// Swaps router balance of the offer asset above `prev_balance` on the pair resolved by the
+/// factory
+pub fn execute_swap_operation(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    operation: SwapOperation,
+    prev_balance: Uint128,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let pair_info = query_pair_info(
+        &deps.querier,
+        config.tfi_factory,
+        &operation.asset_infos(),
+        operation.pair_type(),
+    )?;
+
+    let offer_asset = Asset {
+        amount: operation
+            .offer_asset_info
+            .query_pool(&deps.querier, env.contract.address)?
+            .checked_sub(prev_balance)
+            .map_err(StdError::from)?,
+        info: operation.offer_asset_info,
+    };
+
+    let res = Response::new()
+        .add_attribute("action", "execute_swap_operation")
+        .add_attribute("offer_asset", offer_asset.to_string())
+        .add_attribute("ask_asset", operation.ask_asset_info.to_string())
+        .add_message(swap_msg(
+            pair_info.contract_addr,
+            offer_asset,
+            operation.ask_asset_info.clone(),
+            to,
+        )?);
+    Ok(res)
+}
+
+/// Creates message swapping `offer_asset` for `ask_asset_info` on `pair_contract` - `Swap` with
+/// funds for native tokens, and cw20 `Send` with `Swap` hook for tokens
+fn swap_msg(
+    pair_contract: Addr,
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+    to: Option<Addr>,
+) -> StdResult<CosmosMsg> {
+    let to = to.map(String::from);
+
+    let msg = match &offer_asset.info {
+        AssetInfo::Native(_) => WasmMsg::Execute {
+            contract_addr: pair_contract.to_string(),
+            funds: vec![offer_asset.to_coin()?],
+            msg: to_binary(&PairExecuteMsg::Swap {
+                offer_asset,
+                ask_asset_info: Some(ask_asset_info),
+                belief_price: None,
+                max_spread: None,
+                to,
+                min_return: None,
+                deadline: None,
+                to_msg: None,
+            })?,
+        },
+        AssetInfo::Token(contract_addr) => WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: pair_contract.to_string(),
+                amount: offer_asset.amount,
+                msg: to_binary(&PairCw20HookMsg::Swap {
+                    ask_asset_info: Some(ask_asset_info),
+                    belief_price: None,
+                    max_spread: None,
+                    to,
+                    min_return: None,
+                    deadline: None,
+                    to_msg: None,
+                })?,
+            })?,
+        },
+    };
+
+    Ok(msg.into())
+}
+
+pub fn assert_minimum_receive(
+    deps: Deps,
+    asset_info: AssetInfo,
+    prev_balance: Uint128,
+    minimum_receive: Uint128,
+    receiver: Addr,
+) -> Result<Response, ContractError> {
+    let balance = asset_info.query_pool(&deps.querier, receiver)?;
+    let received = balance.saturating_sub(prev_balance);
+
+    if received < minimum_receive {
+        return Err(ContractError::MinimumReceiveAssertion {
+            minimum_receive,
+            received,
+        });
+    }
+
+    Ok(Response::new())
+}
+
+/// Ensures route is not empty, and every operation offers what the previous one asked for
+fn assert_operations(operations: &[SwapOperation]) -> Result<(), ContractError> {
+    if operations.is_empty() {
+        return Err(ContractError::NoSwapOperations {});
+    }
+
+    for hop in operations.windows(2) {
+        if !hop[0].ask_asset_info.equal(&hop[1].offer_asset_info) {
+            return Err(ContractError::DisconnectedSwapOperations {
+                expected: hop[0].ask_asset_info.to_string(),
+                actual: hop[1].offer_asset_info.to_string(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::Config {} => Ok(to_binary(&query_config(deps)?)?),
+        QueryMsg::SimulateSwapOperations {
+            offer_amount,
+            operations,
+        } => Ok(to_binary(&simulate_swap_operations(
+            deps,
+            offer_amount,
+            operations,
+        )?)?),
+        QueryMsg::ReverseSimulateSwapOperations {
+            ask_amount,
+            operations,
+        } => Ok(to_binary(&reverse_simulate_swap_operations(
+            deps, ask_amount, operations,
+        )?)?),
+    }
+}
+
+pub fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        tfi_factory: config.tfi_factory.into(),
+    })
+}
+
+/// Chains `Simulation` queries on every pair, using return amount of each hop as offer amount
+/// of the next one
+pub fn simulate_swap_operations(
+    deps: Deps,
+    offer_amount: Uint128,
+    operations: Vec<SwapOperation>,
+) -> Result<SimulateSwapOperationsResponse, ContractError> {
+    assert_operations(&operations)?;
+
+    let config = CONFIG.load(deps.storage)?;
+
+    let mut amount = offer_amount;
+    let mut simulations = Vec::with_capacity(operations.len());
+    for operation in operations {
+        let pair_info = query_pair_info(
+            &deps.querier,
+            config.tfi_factory.clone(),
+            &operation.asset_infos(),
+            operation.pair_type(),
+        )?;
+
+        let res = simulate(
+            &deps.querier,
+            pair_info.contract_addr,
+            &Asset {
+                info: operation.offer_asset_info,
+                amount,
+            },
+            &operation.ask_asset_info,
+        )?;
+
+        simulations.push(SwapOperationSimulation {
+            offer_amount: amount,
+            return_amount: res.return_amount,
+            spread_amount: res.spread_amount,
+            commission_amount: res.commission_amount,
+        });
+        amount = res.return_amount;
+    }
+
+    Ok(SimulateSwapOperationsResponse {
+        amount,
+        operations: simulations,
+    })
+}
+
+/// Chains `ReverseSimulation` queries on every pair starting from the last one, using required
+/// offer amount of each hop as ask amount of the previous one
+pub fn reverse_simulate_swap_operations(
+    deps: Deps,
+    ask_amount: Uint128,
+    operations: Vec<SwapOperation>,
+) -> Result<SimulateSwapOperationsResponse, ContractError> {
+    assert_operations(&operations)?;
+
+    let config = CONFIG.load(deps.storage)?;
+
+    let mut amount = ask_amount;
+    let mut simulations = Vec::with_capacity(operations.len());
+    for operation in operations.into_iter().rev() {
+        let pair_info = query_pair_info(
+            &deps.querier,
+            config.tfi_factory.clone(),
+            &operation.asset_infos(),
+            operation.pair_type(),
+        )?;
+
+        let res = reverse_simulate(
+            &deps.querier,
+            pair_info.contract_addr,
+            &Asset {
+                info: operation.ask_asset_info,
+                amount,
+            },
+            &operation.offer_asset_info,
+        )?;
+
+        simulations.push(SwapOperationSimulation {
+            offer_amount: res.offer_amount,
+            return_amount: amount,
+            spread_amount: res.spread_amount,
+            commission_amount: res.commission_amount,
+        });
+        amount = res.offer_amount;
+    }
+    simulations.reverse();
+
+    Ok(SimulateSwapOperationsResponse {
+        amount,
+        operations: simulations,
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    Ok(Response::default())
+}
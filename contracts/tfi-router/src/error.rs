@@ -0,0 +1,31 @@
+use cosmwasm_std::{StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Must provide at least one swap operation")]
+    NoSwapOperations {},
+
+    #[error(
+        "Swap operations do not form a route, expected offer asset: {expected}, got: {actual}"
+    )]
+    DisconnectedSwapOperations { expected: String, actual: String },
+
+    #[error("Exactly one coin has to be sent as the offer asset")]
+    InvalidFunds {},
+
+    #[error("Sent asset does not match the first operation offer asset, expected: {expected}, got: {actual}")]
+    OfferAssetMismatch { expected: String, actual: String },
+
+    #[error("Minimum receive assertion failed, minimum receive: {minimum_receive}, received: {received}")]
+    MinimumReceiveAssertion {
+        minimum_receive: Uint128,
+        received: Uint128,
+    },
+}
@@ -0,0 +1,475 @@
+mod suite;
+
+use anyhow::Error;
+use cosmwasm_std::{coin, BankMsg, Uint128};
+use cw_multi_test::Executor;
+use tfi::asset::{CONCENTRATED_PAIR_TYPE, STABLE_PAIR_TYPE};
+use tfi::router::{ExecuteMsg, SwapOperation, SwapOperationSimulation};
+
+use crate::error::ContractError;
+
+/// Compares if error is as expected
+///
+/// Unfortunately, error types information is lost, as in multitest every error is just converted
+/// to its string representation. To solve this issue and still be able to reasonably test returned
+/// error, but to avoid maintaining error string validation, errors are passed strongly typed, but
+/// verified on their representation level. Additionally when error doesn't match, the actual
+/// error is printed in debug form so additional `anyhow` information is displayed.
+#[track_caller]
+fn assert_error(err: Error, expected: impl ToString + std::fmt::Debug) {
+    assert_eq!(
+        err.root_cause().to_string(),
+        expected.to_string(),
+        "received error {:?} while expected {:?}",
+        err,
+        expected
+    );
+}
+
+#[test]
+// Swaps btc to eth going through cash, as there is no direct btc/eth pair
+fn native_multi_hop() {
+    let mut suite = suite::Config::new()
+        .with_actor("trader", 1000, 0, 0)
+        .init()
+        .unwrap();
+
+    let trader = suite.actors[0].clone();
+    let operations = vec![
+        SwapOperation::new(suite.btc(), suite.cash()),
+        SwapOperation::new(suite.cash(), suite.eth()),
+    ];
+
+    suite
+        .swap_native(&trader, coin(1000, "btc"), operations, None, None)
+        .unwrap();
+
+    // btc -> cash: 1994cash, explanation:
+    //   cash to be left on contract: 2000 * 6000 / (2000 + 1000) = 4000
+    //   cash to be paid out after commission: 2000 - 2000 * 0.003 = 1994
+    // cash -> eth: 1194eth, explanation:
+    //   eth to be left on contract: 3000 * 3000 / (3000 + 1994) = 1803 (rounded up)
+    //   eth to be paid out after commission: 1197 - 1197 * 0.003 = 1194
+    suite
+        .assert_balances(&trader, 0, 1194, 0)
+        .assert_balances(&suite.router, 0, 0, 0);
+
+    let btc_pair = suite.pair([suite.btc(), suite.cash()]).unwrap();
+    let eth_pair = suite.pair([suite.eth(), suite.cash()]).unwrap();
+    suite
+        .assert_balances(&btc_pair, 3000, 0, 4006)
+        .assert_balances(&eth_pair, 0, 1806, 4994);
+}
+
+#[test]
+// Starts route with cw20 token using single `Send`, delivering result to other address
+fn cw20_start() {
+    let mut suite = suite::Config::new()
+        .with_actor("trader", 0, 0, 1000)
+        .with_actor("receiver", 0, 0, 0)
+        .init()
+        .unwrap();
+
+    let (trader, receiver) = (suite.actors[0].clone(), suite.actors[1].clone());
+    let operations = vec![SwapOperation::new(suite.cash(), suite.btc())];
+
+    suite
+        .swap_cash(&trader, 1000, operations, 285, receiver.clone())
+        .unwrap();
+
+    // cash -> btc: 285btc, explanation:
+    //   btc to be left on contract: 2000 * 6000 / (6000 + 1000) = 1715 (rounded up)
+    //   btc to be paid out after commission: 285 - 285 * 0.003 = 285
+    suite
+        .assert_balances(&trader, 0, 0, 0)
+        .assert_balances(&receiver, 285, 0, 0)
+        .assert_balances(&suite.router, 0, 0, 0);
+}
+
+#[test]
+// Swaps cash to eth on stable pair selected by operation pair type
+fn stable_pair_operation() {
+    let mut suite = suite::Config::new()
+        .with_actor("trader", 0, 0, 1000)
+        .init()
+        .unwrap();
+
+    let trader = suite.actors[0].clone();
+    let operations =
+        vec![SwapOperation::new(suite.cash(), suite.eth()).with_pair_type(STABLE_PAIR_TYPE)];
+
+    let res = suite.simulate(1000, operations.clone()).unwrap();
+    suite
+        .swap_cash(&trader, 1000, operations, None, None)
+        .unwrap();
+
+    let xyk_pair = suite.pair([suite.eth(), suite.cash()]).unwrap();
+    let stable_pair = suite
+        .pair_of_type([suite.eth(), suite.cash()], STABLE_PAIR_TYPE)
+        .unwrap();
+    let eth = res.amount.u128();
+    // Stable pair keeps much lower spread than 1000 * 3000 / 3000 - 750 = 250 of `xyk` pair
+    assert!(eth > 990, "eth returned: {}", eth);
+    suite
+        .assert_balances(&trader, 0, eth, 0)
+        .assert_balances(&xyk_pair, 0, 3000, 3000)
+        .assert_balances(&stable_pair, 0, 3000 - eth, 4000);
+}
+
+#[test]
+// Swaps btc to eth directly on btc/eth/cash basket, looked up by all its assets
+fn basket_operation() {
+    let mut suite = suite::Config::new()
+        .with_actor("trader", 100, 0, 0)
+        .init()
+        .unwrap();
+
+    let trader = suite.actors[0].clone();
+    let basket = vec![suite.btc(), suite.eth(), suite.cash()];
+
+    // There is no btc/eth pair, basket is reachable only by all its assets
+    let operation = SwapOperation::new(suite.btc(), suite.eth()).with_pair_type(STABLE_PAIR_TYPE);
+    let err = suite
+        .swap_native(
+            &trader,
+            coin(100, "btc"),
+            vec![operation.clone()],
+            None,
+            None,
+        )
+        .unwrap_err();
+    assert!(
+        err.root_cause().to_string().contains("not found"),
+        "{}",
+        err
+    );
+
+    let operations = vec![operation.with_pool_asset_infos(basket.clone())];
+    let res = suite.simulate(100, operations.clone()).unwrap();
+    suite
+        .swap_native(&trader, coin(100, "btc"), operations, None, None)
+        .unwrap();
+
+    let basket_pair = suite.pair_of_type(basket, STABLE_PAIR_TYPE).unwrap();
+    let eth = res.amount.u128();
+    assert!(eth > 95 && eth < 100, "eth returned: {}", eth);
+    suite
+        .assert_balances(&trader, 0, eth, 0)
+        .assert_balances(&basket_pair, 1100, 1000 - eth, 1000);
+}
+
+#[test]
+// Swaps btc to eth going through concentrated btc/cash pair, simulated both ways beforehand
+fn concentrated_pair_operation() {
+    let mut suite = suite::Config::new()
+        .with_actor("trader", 100, 0, 0)
+        .init()
+        .unwrap();
+
+    let trader = suite.actors[0].clone();
+    let operations = vec![
+        SwapOperation::new(suite.btc(), suite.cash()).with_pair_type(CONCENTRATED_PAIR_TYPE),
+        SwapOperation::new(suite.cash(), suite.eth()),
+    ];
+
+    let res = suite.simulate(100, operations.clone()).unwrap();
+    let eth = res.amount.u128();
+    // btc -> cash: about 3 cash per btc, less commission and spread
+    let cash = res.operations[0].return_amount.u128();
+    assert!(cash > 290 && cash < 300, "cash returned: {}", cash);
+
+    // Offers are rounded up, so offering reverse simulated amount never returns less
+    let rev_res = suite.reverse_simulate(eth, operations.clone()).unwrap();
+    assert_eq!(rev_res.operations[1].return_amount.u128(), eth);
+    assert_eq!(
+        rev_res.operations[0].return_amount,
+        rev_res.operations[1].offer_amount
+    );
+    let rev_amount = rev_res.amount.u128();
+    assert!((100..=102).contains(&rev_amount), "offer: {}", rev_amount);
+    let res = suite.simulate(rev_amount, operations.clone()).unwrap();
+    assert!(res.amount.u128() >= eth, "returned: {}", res.amount);
+
+    suite
+        .swap_native(&trader, coin(100, "btc"), operations, None, None)
+        .unwrap();
+
+    suite
+        .assert_balances(&trader, 0, eth, 0)
+        .assert_balances(&suite.router, 0, 0, 0);
+}
+
+#[test]
+fn minimum_receive_not_met() {
+    let mut suite = suite::Config::new()
+        .with_actor("trader", 1000, 0, 0)
+        .init()
+        .unwrap();
+
+    let trader = suite.actors[0].clone();
+    let operations = vec![
+        SwapOperation::new(suite.btc(), suite.cash()),
+        SwapOperation::new(suite.cash(), suite.eth()),
+    ];
+
+    let err = suite
+        .swap_native(&trader, coin(1000, "btc"), operations, 1195, None)
+        .unwrap_err();
+
+    assert_error(
+        err,
+        ContractError::MinimumReceiveAssertion {
+            minimum_receive: Uint128::new(1195),
+            received: Uint128::new(1194),
+        },
+    );
+
+    // Whole route is reverted
+    suite.assert_balances(&trader, 1000, 0, 0);
+}
+
+#[test]
+fn invalid_operations() {
+    let mut suite = suite::Config::new()
+        .with_actor("trader", 1000, 0, 0)
+        .init()
+        .unwrap();
+
+    let trader = suite.actors[0].clone();
+
+    let err = suite
+        .swap_native(&trader, coin(1000, "btc"), vec![], None, None)
+        .unwrap_err();
+    assert_error(err, ContractError::NoSwapOperations {});
+
+    let operations = vec![
+        SwapOperation::new(suite.btc(), suite.cash()),
+        SwapOperation::new(suite.eth(), suite.cash()),
+    ];
+    let err = suite
+        .swap_native(&trader, coin(1000, "btc"), operations, None, None)
+        .unwrap_err();
+    assert_error(
+        err,
+        ContractError::DisconnectedSwapOperations {
+            expected: suite.cash().to_string(),
+            actual: suite.eth().to_string(),
+        },
+    );
+}
+
+#[test]
+fn offer_asset_not_matching_route() {
+    let mut suite = suite::Config::new()
+        .with_actor("trader", 1000, 1000, 1000)
+        .init()
+        .unwrap();
+
+    let trader = suite.actors[0].clone();
+    let router = suite.router.clone();
+    let operations = vec![SwapOperation::new(suite.btc(), suite.cash())];
+
+    let err = suite
+        .app
+        .execute_contract(
+            trader.clone(),
+            router,
+            &ExecuteMsg::ExecuteSwapOperations {
+                operations: operations.clone(),
+                minimum_receive: None,
+                to: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_error(err, ContractError::InvalidFunds {});
+
+    let err = suite
+        .swap_native(&trader, coin(1000, "eth"), operations.clone(), None, None)
+        .unwrap_err();
+    assert_error(
+        err,
+        ContractError::OfferAssetMismatch {
+            expected: suite.btc().to_string(),
+            actual: suite.eth().to_string(),
+        },
+    );
+
+    let err = suite
+        .swap_cash(&trader, 1000, operations, None, None)
+        .unwrap_err();
+    assert_error(
+        err,
+        ContractError::OfferAssetMismatch {
+            expected: suite.btc().to_string(),
+            actual: suite.cash().to_string(),
+        },
+    );
+
+    suite.assert_balances(&trader, 1000, 1000, 1000);
+}
+
+#[test]
+// Tokens left on the router are not swapped by routes going through them
+fn router_balance_not_swapped() {
+    let mut suite = suite::Config::new()
+        .with_actor("trader", 1000, 0, 0)
+        .with_actor("donor", 500, 0, 300)
+        .init()
+        .unwrap();
+
+    let (trader, donor, router) = (
+        suite.actors[0].clone(),
+        suite.actors[1].clone(),
+        suite.router.clone(),
+    );
+    let operations = vec![
+        SwapOperation::new(suite.btc(), suite.cash()),
+        SwapOperation::new(suite.cash(), suite.eth()),
+    ];
+
+    suite
+        .app
+        .execute(
+            donor.clone(),
+            BankMsg::Send {
+                to_address: router.to_string(),
+                amount: vec![coin(500, "btc")],
+            }
+            .into(),
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            donor,
+            suite.cash.clone(),
+            &cw20::Cw20ExecuteMsg::Transfer {
+                recipient: router.to_string(),
+                amount: Uint128::new(300),
+            },
+            &[],
+        )
+        .unwrap();
+
+    suite
+        .swap_native(&trader, coin(1000, "btc"), operations, None, None)
+        .unwrap();
+
+    // The same return as in `native_multi_hop`, tokens sent to router directly are left there
+    suite
+        .assert_balances(&trader, 0, 1194, 0)
+        .assert_balances(&router, 500, 0, 300);
+}
+
+#[test]
+fn single_operation_is_internal() {
+    let mut suite = suite::Config::new()
+        .with_actor("trader", 1000, 0, 0)
+        .init()
+        .unwrap();
+
+    let trader = suite.actors[0].clone();
+    let router = suite.router.clone();
+    let operation = SwapOperation::new(suite.btc(), suite.cash());
+
+    let err = suite
+        .app
+        .execute_contract(
+            trader,
+            router,
+            &ExecuteMsg::ExecuteSwapOperation {
+                operation,
+                prev_balance: Uint128::zero(),
+                to: None,
+            },
+            &[coin(1000, "btc")],
+        )
+        .unwrap_err();
+    assert_error(err, ContractError::Unauthorized {});
+}
+
+#[test]
+// Simulates btc -> cash -> eth route and checks it matches actual swap
+fn simulate() {
+    let mut suite = suite::Config::new()
+        .with_actor("trader", 1000, 0, 0)
+        .init()
+        .unwrap();
+
+    let trader = suite.actors[0].clone();
+    let operations = vec![
+        SwapOperation::new(suite.btc(), suite.cash()),
+        SwapOperation::new(suite.cash(), suite.eth()),
+    ];
+
+    let res = suite.simulate(1000, operations.clone()).unwrap();
+
+    // btc -> cash: spread: 1000 * 6000 / 2000 - 2000 = 1000
+    // cash -> eth: spread: 1994 * 3000 / 3000 - 1197 = 797
+    assert_eq!(res.amount, Uint128::new(1194));
+    assert_eq!(
+        res.operations,
+        vec![
+            SwapOperationSimulation {
+                offer_amount: Uint128::new(1000),
+                return_amount: Uint128::new(1994),
+                spread_amount: Uint128::new(1000),
+                commission_amount: Uint128::new(6),
+            },
+            SwapOperationSimulation {
+                offer_amount: Uint128::new(1994),
+                return_amount: Uint128::new(1194),
+                spread_amount: Uint128::new(797),
+                commission_amount: Uint128::new(3),
+            },
+        ]
+    );
+
+    suite
+        .swap_native(&trader, coin(1000, "btc"), operations, None, None)
+        .unwrap()
+        .assert_balances(&trader, 0, 1194, 0);
+}
+
+#[test]
+// Reverse simulates btc -> cash -> eth route, and checks that offering simulated amount gives
+// expected return
+//
+// Reverse simulation results are not validated directly, as due to calculation precision it is
+// possible, reverse simulation might return range of results.
+fn reverse_simulate() {
+    let suite = suite::Config::new().init().unwrap();
+
+    let operations = vec![
+        SwapOperation::new(suite.btc(), suite.cash()),
+        SwapOperation::new(suite.cash(), suite.eth()),
+    ];
+
+    let rev_res = suite.reverse_simulate(1000, operations.clone()).unwrap();
+    assert_eq!(rev_res.operations.len(), 2);
+    assert_eq!(rev_res.operations[1].return_amount, Uint128::new(1000));
+    assert_eq!(
+        rev_res.operations[0].return_amount,
+        rev_res.operations[1].offer_amount
+    );
+    assert_eq!(rev_res.amount, rev_res.operations[0].offer_amount);
+
+    // Offers are rounded up, so the route never returns less than requested
+    let res = suite.simulate(rev_res.amount.u128(), operations).unwrap();
+    assert!(res.amount >= Uint128::new(1000), "returned: {}", res.amount);
+}
+
+#[test]
+fn simulate_invalid_operations() {
+    let suite = suite::Config::new().init().unwrap();
+
+    // Query errors are wrapped by querier, so only the end of message is compared
+    let expected_err = ContractError::NoSwapOperations {}.to_string();
+
+    let err = suite.simulate(1000, vec![]).unwrap_err();
+    assert!(err.to_string().ends_with(&expected_err), "got: {}", err);
+
+    let err = suite.reverse_simulate(1000, vec![]).unwrap_err();
+    assert!(err.to_string().ends_with(&expected_err), "got: {}", err);
+}
@@ -0,0 +1,604 @@
+use anyhow::{anyhow, Result};
+
+use cosmwasm_std::{coin, coins, to_binary, Addr, BankMsg, Coin, Decimal, Empty, Uint128};
+use cw20::{Cw20Coin, Cw20ExecuteMsg};
+use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper, Executor};
+use derivative::Derivative;
+use tfi::asset::{
+    Asset, AssetInfo, PairInfo, CONCENTRATED_PAIR_TYPE, STABLE_PAIR_TYPE, XYK_PAIR_TYPE,
+};
+use tfi::concentrated::ExecuteMsg as ConcentratedExecuteMsg;
+use tfi::factory::{
+    ExecuteCreatePair, ExecuteMsg as FactoryExecuteMsg, InstantiateMsg as FactoryInstantiateMsg,
+    PairType, QueryMsg as FactoryQueryMsg,
+};
+use tfi::pair::ExecuteMsg as PairExecuteMsg;
+use tfi::router::{
+    Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg, SimulateSwapOperationsResponse,
+    SwapOperation,
+};
+
+const TEST_RESERVE: &str = "reserve";
+const BTC: &str = "btc";
+const ETH: &str = "eth";
+
+fn mock_app() -> App {
+    // Instantiates custom account (TEST_RESERVE) with arbitrary amount
+    // of tokens to fill any custom account later needed in tests
+    AppBuilder::new_custom().build(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &Addr::unchecked(TEST_RESERVE),
+                vec![coin(1_000_000, BTC), coin(1_000_000, ETH)],
+            )
+            .unwrap();
+    })
+}
+
+fn contract_router() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    ))
+}
+
+fn contract_factory() -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new(
+            tfi_factory::contract::execute,
+            tfi_factory::contract::instantiate,
+            tfi_factory::contract::query,
+        )
+        .with_reply(tfi_factory::contract::reply),
+    )
+}
+
+fn contract_pair() -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new(
+            tfi_pair::contract::execute,
+            tfi_pair::contract::instantiate,
+            tfi_pair::contract::query,
+        )
+        .with_reply(tfi_pair::contract::reply),
+    )
+}
+
+fn contract_stable_pair() -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new(
+            tfi_stable_pair::contract::execute,
+            tfi_stable_pair::contract::instantiate,
+            tfi_stable_pair::contract::query,
+        )
+        .with_reply(tfi_stable_pair::contract::reply),
+    )
+}
+
+fn contract_concentrated_pair() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        tfi_concentrated_pair::contract::execute,
+        tfi_concentrated_pair::contract::instantiate,
+        tfi_concentrated_pair::contract::query,
+    ))
+}
+
+fn contract_cw20() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    ))
+}
+
+/// Testing environment with:
+/// * two native tokens "btc" and "eth"
+/// * single cw20 token "cash"
+/// * single tfi-factory with five pools created: btc/cash and cash/eth of `xyk` type,
+///   cash/eth and btc/eth/cash basket of `stable` type, and btc/cash of `concentrated` type
+/// * single tfi-router using the factory
+/// * number of actors which are just address initialized with some "btc", "eth" and "cash"
+///
+/// All pairs are initialized with liquidity provided by owner, so there is no direct btc/eth pair,
+/// and the only way to swap btc for eth is going through cash or the basket.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct Suite {
+    /// Application mock
+    #[derivative(Debug = "ignore")]
+    pub app: App,
+    /// Special account for performing administrative executions
+    pub owner: Addr,
+    /// General purpose actors
+    pub actors: Vec<Addr>,
+    /// cw20 cash contract address
+    pub cash: Addr,
+    /// tfi-factory contract address
+    pub factory: Addr,
+    /// tfi-router contract address
+    pub router: Addr,
+}
+
+impl Suite {
+    /// Returns btc asset info
+    pub fn btc(&self) -> AssetInfo {
+        AssetInfo::Native(BTC.to_owned())
+    }
+
+    /// Returns eth asset info
+    pub fn eth(&self) -> AssetInfo {
+        AssetInfo::Native(ETH.to_owned())
+    }
+
+    /// Returns cash asset info
+    pub fn cash(&self) -> AssetInfo {
+        AssetInfo::Token(self.cash.clone())
+    }
+
+    /// Queries factory for `xyk` pair address
+    pub fn pair(&self, asset_infos: [AssetInfo; 2]) -> Result<Addr> {
+        self.pair_of_type(asset_infos, XYK_PAIR_TYPE)
+    }
+
+    /// Queries factory for pair address of given type
+    pub fn pair_of_type(
+        &self,
+        asset_infos: impl Into<Vec<AssetInfo>>,
+        pair_type: &str,
+    ) -> Result<Addr> {
+        let res: PairInfo = self.app.wrap().query_wasm_smart(
+            self.factory.clone(),
+            &FactoryQueryMsg::Pair {
+                asset_infos: asset_infos.into(),
+                pair_type: pair_type.to_owned(),
+            },
+        )?;
+
+        Ok(res.contract_addr)
+    }
+
+    /// Executes `ExecuteSwapOperations` on router, sending native offer asset as funds
+    pub fn swap_native(
+        &mut self,
+        sender: &Addr,
+        offer: Coin,
+        operations: Vec<SwapOperation>,
+        minimum_receive: impl Into<Option<u128>>,
+        to: impl Into<Option<Addr>>,
+    ) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                sender.clone(),
+                self.router.clone(),
+                &ExecuteMsg::ExecuteSwapOperations {
+                    operations,
+                    minimum_receive: minimum_receive.into().map(Uint128::new),
+                    to: to.into().as_ref().map(ToString::to_string),
+                },
+                &[offer],
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    /// Executes `Send` on cash contract, with `Cw20HookMsg::ExecuteSwapOperations` as hook
+    pub fn swap_cash(
+        &mut self,
+        sender: &Addr,
+        cash: u128,
+        operations: Vec<SwapOperation>,
+        minimum_receive: impl Into<Option<u128>>,
+        to: impl Into<Option<Addr>>,
+    ) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                sender.clone(),
+                self.cash.clone(),
+                &Cw20ExecuteMsg::Send {
+                    contract: self.router.to_string(),
+                    amount: Uint128::new(cash),
+                    msg: to_binary(&Cw20HookMsg::ExecuteSwapOperations {
+                        operations,
+                        minimum_receive: minimum_receive.into().map(Uint128::new),
+                        to: to.into().as_ref().map(ToString::to_string),
+                    })?,
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    /// Queries router with `QueryMsg::SimulateSwapOperations`
+    pub fn simulate(
+        &self,
+        offer_amount: u128,
+        operations: Vec<SwapOperation>,
+    ) -> Result<SimulateSwapOperationsResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                self.router.clone(),
+                &QueryMsg::SimulateSwapOperations {
+                    offer_amount: Uint128::new(offer_amount),
+                    operations,
+                },
+            )
+            .map_err(|err| anyhow!(err))
+    }
+
+    /// Queries router with `QueryMsg::ReverseSimulateSwapOperations`
+    pub fn reverse_simulate(
+        &self,
+        ask_amount: u128,
+        operations: Vec<SwapOperation>,
+    ) -> Result<SimulateSwapOperationsResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                self.router.clone(),
+                &QueryMsg::ReverseSimulateSwapOperations {
+                    ask_amount: Uint128::new(ask_amount),
+                    operations,
+                },
+            )
+            .map_err(|err| anyhow!(err))
+    }
+
+    /// Asserts if balances on account are as expected
+    #[track_caller]
+    pub fn assert_balances(&self, addr: &Addr, btc: u128, eth: u128, cash: u128) -> &Self {
+        let btc_balance = self.app.wrap().query_balance(addr, BTC).unwrap();
+        assert_eq!(btc_balance, coin(btc, BTC), "Btc balance mismatch");
+
+        let eth_balance = self.app.wrap().query_balance(addr, ETH).unwrap();
+        assert_eq!(eth_balance, coin(eth, ETH), "Eth balance mismatch");
+
+        let cash_balance: cw20::BalanceResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                self.cash.clone(),
+                &cw20_base::msg::QueryMsg::Balance {
+                    address: addr.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(cash_balance.balance.u128(), cash, "Cash balance mismatch");
+
+        self
+    }
+}
+
+/// Configuration of single actor
+struct ActorConfig {
+    /// Actor address
+    addr: String,
+    /// Initial btc amount
+    btc: u128,
+    /// Initial eth amount
+    eth: u128,
+    /// Initial cash amount
+    cash: u128,
+}
+
+#[derive(Default)]
+pub struct Config {
+    /// Initial actors
+    actors: Vec<ActorConfig>,
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_actor(mut self, addr: impl Into<String>, btc: u128, eth: u128, cash: u128) -> Self {
+        self.actors.push(ActorConfig {
+            addr: addr.into(),
+            btc,
+            eth,
+            cash,
+        });
+
+        self
+    }
+
+    /// Sends native funds to account from reserve
+    fn fund(app: &mut App, addr: &Addr, btc: u128, eth: u128) -> Result<()> {
+        let amount: Vec<_> = vec![coin(btc, BTC), coin(eth, ETH)]
+            .into_iter()
+            .filter(|c| !c.amount.is_zero())
+            .collect();
+
+        if !amount.is_empty() {
+            app.execute(
+                Addr::unchecked(TEST_RESERVE),
+                BankMsg::Send {
+                    to_address: addr.to_string(),
+                    amount,
+                }
+                .into(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Creates pool of given type of `natives` and cash with factory, and provides initial
+    /// liquidity from owner
+    fn init_pair(
+        app: &mut App,
+        owner: &Addr,
+        factory: &Addr,
+        cash: &Addr,
+        pair_type: &str,
+        natives: &[(&str, u128)],
+        cash_amount: u128,
+    ) -> Result<()> {
+        let asset_infos: Vec<_> = natives
+            .iter()
+            .map(|(native, _)| AssetInfo::Native((*native).to_owned()))
+            .chain(std::iter::once(AssetInfo::Token(cash.clone())))
+            .collect();
+        let amounts = natives
+            .iter()
+            .map(|(_, amount)| *amount)
+            .chain(std::iter::once(cash_amount));
+
+        let mut create_pair = ExecuteCreatePair::new(asset_infos.clone()).with_pair_type(pair_type);
+        if pair_type == STABLE_PAIR_TYPE {
+            create_pair = create_pair.with_amp(100);
+        }
+        app.execute_contract(
+            owner.clone(),
+            factory.clone(),
+            &FactoryExecuteMsg::from(create_pair),
+            &[],
+        )
+        .map_err(|err| anyhow!(err))?;
+
+        let pair: PairInfo = app.wrap().query_wasm_smart(
+            factory.clone(),
+            &FactoryQueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type: pair_type.to_owned(),
+            },
+        )?;
+
+        app.execute_contract(
+            owner.clone(),
+            cash.clone(),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: pair.contract_addr.to_string(),
+                amount: Uint128::new(cash_amount),
+                expires: None,
+            },
+            &[],
+        )
+        .map_err(|err| anyhow!(err))?;
+
+        app.execute_contract(
+            owner.clone(),
+            pair.contract_addr,
+            &PairExecuteMsg::ProvideLiquidity {
+                assets: asset_infos
+                    .into_iter()
+                    .zip(amounts)
+                    .map(|(info, amount)| Asset {
+                        info,
+                        amount: Uint128::new(amount),
+                    })
+                    .collect(),
+                slippage_tolerance: None,
+                deadline: None,
+            },
+            &natives
+                .iter()
+                .map(|(native, amount)| coin(*amount, *native))
+                .collect::<Vec<_>>(),
+        )
+        .map_err(|err| anyhow!(err))?;
+
+        Ok(())
+    }
+
+    /// Creates `concentrated` btc/cash pair with factory at price of 3cash per btc, and opens
+    /// position of owner in ticks around it - only the part of deposit matching the price is used
+    fn init_concentrated_pair(
+        app: &mut App,
+        owner: &Addr,
+        factory: &Addr,
+        cash: &Addr,
+        btc_amount: u128,
+        cash_amount: u128,
+    ) -> Result<()> {
+        let asset_infos = vec![
+            AssetInfo::Native(BTC.to_owned()),
+            AssetInfo::Token(cash.clone()),
+        ];
+
+        let create_pair = ExecuteCreatePair::new(asset_infos.clone())
+            .with_pair_type(CONCENTRATED_PAIR_TYPE)
+            .with_initial_price(Decimal::from_ratio(3u128, 1u128));
+        app.execute_contract(
+            owner.clone(),
+            factory.clone(),
+            &FactoryExecuteMsg::from(create_pair),
+            &[],
+        )
+        .map_err(|err| anyhow!(err))?;
+
+        let pair: PairInfo = app.wrap().query_wasm_smart(
+            factory.clone(),
+            &FactoryQueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type: CONCENTRATED_PAIR_TYPE.to_owned(),
+            },
+        )?;
+
+        app.execute_contract(
+            owner.clone(),
+            cash.clone(),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: pair.contract_addr.to_string(),
+                amount: Uint128::new(cash_amount),
+                expires: None,
+            },
+            &[],
+        )
+        .map_err(|err| anyhow!(err))?;
+
+        // price of 3 is at tick 10986
+        app.execute_contract(
+            owner.clone(),
+            pair.contract_addr,
+            &ConcentratedExecuteMsg::CreatePosition {
+                lower_tick: 9000,
+                upper_tick: 13000,
+                assets: asset_infos
+                    .into_iter()
+                    .zip([btc_amount, cash_amount])
+                    .map(|(info, amount)| Asset {
+                        info,
+                        amount: Uint128::new(amount),
+                    })
+                    .collect(),
+                deadline: None,
+            },
+            &coins(btc_amount, BTC),
+        )
+        .map_err(|err| anyhow!(err))?;
+
+        Ok(())
+    }
+
+    pub fn init(self) -> Result<Suite> {
+        let mut app = mock_app();
+        let owner = Addr::unchecked("owner");
+        let cw20_id = app.store_code(contract_cw20());
+        let pair_id = app.store_code(contract_pair());
+        let stable_pair_id = app.store_code(contract_stable_pair());
+        let concentrated_pair_id = app.store_code(contract_concentrated_pair());
+        let factory_id = app.store_code(contract_factory());
+        let router_id = app.store_code(contract_router());
+
+        // Owner provides liquidity: 2000btc + 6000cash, 3000eth + 3000cash, 3000eth + 3000cash,
+        // 1000btc + 1000eth + 1000cash and up to 1000btc + 3000cash
+        Self::fund(&mut app, &owner, 4000, 7000)?;
+
+        let mut initial_balances = vec![Cw20Coin {
+            address: owner.to_string(),
+            amount: Uint128::new(16000),
+        }];
+
+        let mut actors = vec![];
+        for actor in self.actors {
+            let addr = Addr::unchecked(&actor.addr);
+            Self::fund(&mut app, &addr, actor.btc, actor.eth)?;
+            if actor.cash > 0 {
+                initial_balances.push(Cw20Coin {
+                    address: actor.addr,
+                    amount: Uint128::new(actor.cash),
+                });
+            }
+            actors.push(addr);
+        }
+
+        let cash = app
+            .instantiate_contract(
+                cw20_id,
+                owner.clone(),
+                &cw20_base::msg::InstantiateMsg {
+                    name: "Cash Money".to_owned(),
+                    symbol: "cash".to_owned(),
+                    decimals: 2,
+                    initial_balances,
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                "Cash",
+                None,
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        let factory = app
+            .instantiate_contract(
+                factory_id,
+                owner.clone(),
+                &FactoryInstantiateMsg::new(pair_id, cw20_id)
+                    .with_pair_type(PairType::new(STABLE_PAIR_TYPE, stable_pair_id))
+                    .with_pair_type(PairType::new(CONCENTRATED_PAIR_TYPE, concentrated_pair_id)),
+                &[],
+                "Factory",
+                None,
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Self::init_pair(
+            &mut app,
+            &owner,
+            &factory,
+            &cash,
+            XYK_PAIR_TYPE,
+            &[(BTC, 2000)],
+            6000,
+        )?;
+        Self::init_pair(
+            &mut app,
+            &owner,
+            &factory,
+            &cash,
+            XYK_PAIR_TYPE,
+            &[(ETH, 3000)],
+            3000,
+        )?;
+        Self::init_pair(
+            &mut app,
+            &owner,
+            &factory,
+            &cash,
+            STABLE_PAIR_TYPE,
+            &[(ETH, 3000)],
+            3000,
+        )?;
+        Self::init_pair(
+            &mut app,
+            &owner,
+            &factory,
+            &cash,
+            STABLE_PAIR_TYPE,
+            &[(BTC, 1000), (ETH, 1000)],
+            1000,
+        )?;
+        Self::init_concentrated_pair(&mut app, &owner, &factory, &cash, 1000, 3000)?;
+
+        let router = app
+            .instantiate_contract(
+                router_id,
+                owner.clone(),
+                &InstantiateMsg {
+                    tfi_factory: factory.to_string(),
+                },
+                &[],
+                "Router",
+                None,
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(Suite {
+            app,
+            owner,
+            actors,
+            cash,
+            factory,
+            router,
+        })
+    }
+}
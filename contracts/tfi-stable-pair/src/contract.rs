@@ -0,0 +1,126 @@
+use crate::error::ContractError;
+use crate::math::{compute_d, compute_offer_amount, compute_swap, MAX_AMP};
+use crate::state::AMP;
+
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+
+use cosmwasm_std::{
+    Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult, Storage, Uint128,
+};
+
+use std::convert::TryInto;
+use tfi::asset::STABLE_PAIR_TYPE;
+use tfi::pair::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use tfi_pair::curve::{Curve, Unsupported};
+
+/// Stableswap invariant with its amplification coefficient
+pub struct StableSwap {
+    pub amp: u64,
+}
+
+impl Curve for StableSwap {
+    const CONTRACT_NAME: &'static str = "crates.io:tfi-stable-pair";
+    const CONTRACT_VERSION: &'static str = env!("CARGO_PKG_VERSION");
+    const PAIR_TYPE: &'static str = STABLE_PAIR_TYPE;
+    /// Pegged assets can be pooled in baskets, not only in pairs
+    const MAX_ASSETS: usize = 4;
+
+    type Error = ContractError;
+
+    fn from_instantiate_msg(msg: &InstantiateMsg) -> Result<Self, ContractError> {
+        let amp = msg.amp.ok_or(ContractError::MissingAmp {})?;
+        if !(1..=MAX_AMP).contains(&amp) {
+            return Err(ContractError::InvalidAmp(amp, MAX_AMP));
+        }
+        Ok(StableSwap { amp })
+    }
+
+    fn load(storage: &dyn Storage) -> StdResult<Self> {
+        Ok(StableSwap {
+            amp: AMP.load(storage)?,
+        })
+    }
+
+    fn save(&self, storage: &mut dyn Storage) -> StdResult<()> {
+        AMP.save(storage, &self.amp)
+    }
+
+    fn compute_swap(
+        &self,
+        pools: &[Uint128],
+        offer_idx: usize,
+        ask_idx: usize,
+        offer_amount: Uint128,
+        commission: Decimal,
+    ) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+        Ok(compute_swap(
+            self.amp,
+            pools,
+            offer_idx,
+            ask_idx,
+            offer_amount,
+            commission,
+        )?)
+    }
+
+    fn compute_offer_amount(
+        &self,
+        pools: &[Uint128],
+        offer_idx: usize,
+        ask_idx: usize,
+        ask_amount: Uint128,
+        commission: Decimal,
+    ) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+        Ok(compute_offer_amount(
+            self.amp, pools, offer_idx, ask_idx, ask_amount, commission,
+        )?)
+    }
+
+    /// Initial share = invariant of deposits, which is their sum for balanced deposit
+    fn initial_share(&self, deposits: &[Uint128]) -> Result<Uint128, ContractError> {
+        Ok(compute_d(self.amp, deposits)?.try_into()?)
+    }
+
+    fn unsupported(operation: Unsupported) -> ContractError {
+        ContractError::NotSupported(operation)
+    }
+}
+
+/// Instantiates pair the same way as `tfi-pair` does (including liquidity token creation), and
+/// additionally stores amplification coefficient and marks the pair as `stable`
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    tfi_pair::curve::instantiate::<StableSwap>(deps, env, info, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    tfi_pair::curve::execute::<StableSwap>(deps, env, info, msg)
+}
+
+/// This just stores the result for future query
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    Ok(tfi_pair::contract::reply(deps, env, msg)?)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    tfi_pair::curve::query::<StableSwap>(deps, env, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    Ok(tfi_pair::contract::migrate_pair(deps, env, msg)?)
+}
@@ -0,0 +1,27 @@
+use cosmwasm_std::{ConversionOverflowError, OverflowError, StdError};
+use tfi_pair::curve::Unsupported;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    OverflowError(#[from] OverflowError),
+
+    #[error("{0}")]
+    ConversionOverflowError(#[from] ConversionOverflowError),
+
+    #[error("{0}")]
+    Pair(#[from] tfi_pair::error::ContractError),
+
+    #[error("Amplification coefficient is required for stable pair")]
+    MissingAmp {},
+
+    #[error("Invalid amplification coefficient: {0}, must be between 1 and {1}")]
+    InvalidAmp(u64, u64),
+
+    #[error("Operation not supported by stable pair: {0}")]
+    NotSupported(Unsupported),
+}
@@ -0,0 +1,202 @@
+use cosmwasm_std::{Decimal, StdError, StdResult, Uint128, Uint256, Uint512};
+use std::convert::TryInto;
+
+use tfi_pair::math::before_commission;
+
+/// Maximum number of Newton's method iterations. Both `D` and `y` usually converge in few steps,
+/// but `D` of heavily imbalanced pools takes up to about a hundred.
+const ITERATIONS: u8 = 255;
+/// Maximum allowed amplification coefficient
+pub const MAX_AMP: u64 = 1_000_000;
+
+/// Computes stableswap invariant `D` for given pools:
+///
+/// `A * n^n * sum(x_i) + D = A * D * n^n + D^(n+1) / (n^n * prod(x_i))`
+///
+/// using Newton's method. Products of `D` are computed with 512 bits, so pools up to
+/// `Uint128::MAX` are supported, and anything which still doesn't fit fails with overflow error.
+pub fn compute_d(amp: u64, pools: &[Uint128]) -> StdResult<Uint256> {
+    let sum = pools
+        .iter()
+        .try_fold(Uint256::zero(), |sum, pool| sum.checked_add((*pool).into()))?;
+    if sum.is_zero() {
+        return Ok(Uint256::zero());
+    }
+    if pools.iter().any(Uint128::is_zero) {
+        return Err(StdError::generic_err(
+            "Divide by zero error computing the invariant",
+        ));
+    }
+
+    let n = Uint256::from(pools.len() as u64);
+    let ann = ann(amp, pools.len())?;
+    let pools = sorted(pools);
+
+    let mut d = sum;
+    for _ in 0..ITERATIONS {
+        // d_p = D^(n+1) / (n^n * prod(x_i))
+        let d_p: Uint256 = divide_by_pools(d.into(), d, n, &pools)?.try_into()?;
+
+        let prev_d = d;
+        // D = (Ann * S + D_P * n) * D / ((Ann - 1) * D + (n + 1) * D_P)
+        let numerator = ann
+            .checked_mul(sum)?
+            .checked_add(d_p.checked_mul(n)?)?
+            .full_mul(d);
+        let denominator = (ann - Uint256::from(1u8))
+            .checked_mul(d)?
+            .checked_add((n + Uint256::from(1u8)).checked_mul(d_p)?)?;
+        d = (numerator / Uint512::from(denominator)).try_into()?;
+
+        if abs_diff(d, prev_d) <= Uint256::from(1u8) {
+            return Ok(d);
+        }
+    }
+
+    Err(StdError::generic_err(
+        "Invariant computation did not converge",
+    ))
+}
+
+/// Computes amount of the pool at `idx`, so the invariant `d` is kept with the other `pools`.
+/// Current amount of the pool at `idx` is ignored.
+pub fn compute_y(amp: u64, pools: &[Uint128], idx: usize, d: Uint256) -> StdResult<Uint256> {
+    let others: Vec<Uint128> = pools
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != idx)
+        .map(|(_, pool)| *pool)
+        .collect();
+    if others.iter().any(Uint128::is_zero) {
+        return Err(StdError::generic_err(
+            "Divide by zero error computing the swap",
+        ));
+    }
+
+    let n = Uint256::from(pools.len() as u64);
+    let ann = ann(amp, pools.len())?;
+    let sum = others
+        .iter()
+        .try_fold(Uint256::zero(), |sum, pool| sum.checked_add((*pool).into()))?;
+
+    // c = D^(n+1) / (n^n * prod(x_j) * Ann), over pools other than the computed one
+    let c = divide_by_pools(d.into(), d, n, &sorted(&others))?
+        .checked_mul(d.into())?
+        .checked_div(ann.checked_mul(n)?.into())?;
+    // b = sum(x_j) + D / Ann
+    let b = sum.checked_add(d / ann)?;
+
+    let mut y = d;
+    for _ in 0..ITERATIONS {
+        let prev_y = y;
+        // y = (y^2 + c) / (2y + b - D)
+        let denominator = y
+            .checked_mul(Uint256::from(2u8))?
+            .checked_add(b)?
+            .checked_sub(d)?;
+        y = (y.full_mul(y).checked_add(c)? / Uint512::from(denominator)).try_into()?;
+
+        if abs_diff(y, prev_y) <= Uint256::from(1u8) {
+            return Ok(y);
+        }
+    }
+
+    Err(StdError::generic_err("Swap computation did not converge"))
+}
+
+/// Computes swap of `offer_amount` of the pool at `offer_idx` for the pool at `ask_idx`, returning
+/// return amount, spread amount and commission amount
+///
+/// All assets are assumed to be pegged to each other, so spread is computed against 1:1 price.
+pub fn compute_swap(
+    amp: u64,
+    pools: &[Uint128],
+    offer_idx: usize,
+    ask_idx: usize,
+    offer_amount: Uint128,
+    commission: Decimal,
+) -> StdResult<(Uint128, Uint128, Uint128)> {
+    let d = compute_d(amp, pools)?;
+    let mut new_pools = pools.to_vec();
+    new_pools[offer_idx] = pools[offer_idx].checked_add(offer_amount)?;
+    let new_ask_pool = compute_y(amp, &new_pools, ask_idx, d)?;
+
+    // one token is kept in pool to avoid rounding in trader favour
+    let return_amount: Uint128 = Uint256::from(pools[ask_idx])
+        .checked_sub(new_ask_pool)?
+        .saturating_sub(Uint256::from(1u8))
+        .try_into()?;
+
+    let spread_amount = offer_amount.saturating_sub(return_amount);
+    let commission_amount = return_amount * commission;
+
+    // commission will be absorbed to pool
+    let return_amount = return_amount.checked_sub(commission_amount)?;
+
+    Ok((return_amount, spread_amount, commission_amount))
+}
+
+/// Computes amount of the pool at `offer_idx` required to receive `ask_amount` of the pool at
+/// `ask_idx`, returning offer amount, spread amount and commission amount
+pub fn compute_offer_amount(
+    amp: u64,
+    pools: &[Uint128],
+    offer_idx: usize,
+    ask_idx: usize,
+    ask_amount: Uint128,
+    commission: Decimal,
+) -> StdResult<(Uint128, Uint128, Uint128)> {
+    let before_commission_deduction: Uint128 =
+        before_commission(ask_amount.into(), commission)?.try_into()?;
+
+    let d = compute_d(amp, pools)?;
+    let mut new_pools = pools.to_vec();
+    new_pools[ask_idx] = pools[ask_idx].checked_sub(before_commission_deduction)?;
+    let new_offer_pool = compute_y(amp, &new_pools, offer_idx, d)?;
+
+    // one token is added to avoid rounding in trader favour
+    let offer_amount: Uint128 = (new_offer_pool.checked_sub(Uint256::from(pools[offer_idx]))?
+        + Uint256::from(1u8))
+    .try_into()?;
+
+    let spread_amount = offer_amount.saturating_sub(before_commission_deduction);
+    let commission_amount = before_commission_deduction * commission;
+
+    Ok((offer_amount, spread_amount, commission_amount))
+}
+
+/// `A * n^n` for `n` pools
+fn ann(amp: u64, n: usize) -> StdResult<Uint256> {
+    (0..n).try_fold(Uint256::from(amp), |ann, _| {
+        ann.checked_mul(Uint256::from(n as u64))
+            .map_err(StdError::from)
+    })
+}
+
+fn sorted(pools: &[Uint128]) -> Vec<Uint256> {
+    let mut pools: Vec<Uint256> = pools.iter().map(|pool| (*pool).into()).collect();
+    pools.sort();
+    pools
+}
+
+/// Computes `value * D^k / (n^k * prod(x_i))` for `k` sorted `pools`. Dividing by the smallest
+/// pools first keeps rounding error of every step negligible against the rest of the product -
+/// rounding it carelessly makes `D` of imbalanced pools oscillate instead of converging.
+fn divide_by_pools(
+    value: Uint512,
+    d: Uint256,
+    n: Uint256,
+    pools: &[Uint256],
+) -> StdResult<Uint512> {
+    pools.iter().try_fold(value, |acc, pool| {
+        Ok(acc.checked_mul(d.into())? / Uint512::from(pool.checked_mul(n)?))
+    })
+}
+
+fn abs_diff(a: Uint256, b: Uint256) -> Uint256 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
@@ -0,0 +1,292 @@
+use cosmwasm_std::{coin, to_binary, Addr, Coin, Empty, Uint128};
+use cw20::{BalanceResponse, Cw20QueryMsg};
+use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper, Executor};
+use tfi_curve_suite::SuiteConfig;
+
+use crate::error::ContractError;
+use tfi::asset::{Asset, AssetInfo, PairInfo, STABLE_PAIR_TYPE};
+use tfi::pair::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg, SimulationResponse};
+
+fn contract_pair() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    )
+    .with_reply(crate::contract::reply);
+    Box::new(contract)
+}
+
+fn contract_cw20() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    Box::new(contract)
+}
+
+fn suite_config() -> SuiteConfig {
+    SuiteConfig::new(contract_pair())
+}
+
+#[test]
+fn amp_required() {
+    let err = suite_config().init().unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::MissingAmp {}.to_string()
+    );
+
+    let err = suite_config().with_amp(0).init().unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::InvalidAmp(0, crate::math::MAX_AMP).to_string()
+    );
+}
+
+#[test]
+// Full flow: provide liquidity, swap in both directions, withdraw everything
+//
+// Swaps on balanced pool are much closer to 1:1 than constant product would give - for 1000
+// offered on 10000/10000 pool, xyk would return 909 before commission.
+fn swap() {
+    let mut suite = suite_config()
+        .with_actor("liquidity-provider", 10000, 10000)
+        .with_actor("trader", 1000, 1000)
+        .with_amp(100)
+        .init()
+        .unwrap();
+
+    let (lp, trader, pair) = (
+        suite.actors[0].clone(),
+        suite.actors[1].clone(),
+        suite.pair.clone(),
+    );
+
+    let pair_info: PairInfo = suite
+        .app
+        .wrap()
+        .query_wasm_smart(pair.clone(), &QueryMsg::Pair {})
+        .unwrap();
+    assert_eq!(pair_info.pair_type, STABLE_PAIR_TYPE);
+
+    suite.provide_liquidity(&lp, 10000, 10000).unwrap();
+
+    // initial share is the invariant, which is sum of pools for balanced pool, minus minimum
+    // liquidity locked on pair
+    suite
+        .assert_balances(&lp, 0, 0, 19000)
+        .assert_balances(&pair, 10000, 10000, 1000);
+
+    let simulation = suite.simulate_swap(1000, suite.usdc()).unwrap();
+    assert!(
+        simulation.return_amount + simulation.commission_amount > Uint128::new(990),
+        "{:?}",
+        simulation
+    );
+
+    suite.swap_usdc(&trader, 1000).unwrap();
+    let cash = 1000 + simulation.return_amount.u128();
+    suite
+        .assert_balances(&trader, 0, cash, 0)
+        .assert_balances(&pair, 11000, 11000 - cash, 1000);
+
+    // swapping back towards balance returns at least offered amount before commission
+    let simulation = suite.simulate_swap(1000, suite.cash()).unwrap();
+    assert!(
+        simulation.return_amount + simulation.commission_amount >= Uint128::new(1000),
+        "{:?}",
+        simulation
+    );
+
+    suite.swap_cash(&trader, 1000).unwrap();
+    let usdc = simulation.return_amount.u128();
+    suite.assert_balances(&trader, usdc, cash - 1000, 0);
+
+    // lp owns 19000 / 20000 of pool, the rest stays with locked liquidity
+    let (usdc_pool, cash_pool) = (11000 - usdc, 12000 - cash);
+    let (usdc_out, cash_out) = (usdc_pool * 19 / 20, cash_pool * 19 / 20);
+    suite.withdraw_liquidity(&lp, 19000).unwrap();
+    suite
+        .assert_balances(&lp, usdc_out, cash_out, 0)
+        .assert_balances(&pair, usdc_pool - usdc_out, cash_pool - cash_out, 1000);
+}
+
+tfi_curve_suite::curve_scenarios!(suite_config().with_amp(100), (10000, 10000));
+
+const BASKET: [&str; 3] = ["usdc", "usdt", "dai"];
+
+fn basket_info(idx: usize) -> AssetInfo {
+    AssetInfo::Native(BASKET[idx].to_owned())
+}
+
+fn basket_balances(app: &App, addr: &Addr) -> Vec<u128> {
+    BASKET
+        .iter()
+        .map(|denom| {
+            app.wrap()
+                .query_balance(addr, *denom)
+                .unwrap()
+                .amount
+                .u128()
+        })
+        .collect()
+}
+
+#[test]
+// Curve suite covers pairs only, basket of 3 assets is tested here: provide liquidity, swap
+// between chosen assets, withdraw everything
+fn basket() {
+    let lp = Addr::unchecked("liquidity-provider");
+    let trader = Addr::unchecked("trader");
+    let mut app = AppBuilder::new().build(|router, _, storage| {
+        let funds: Vec<Coin> = BASKET.iter().map(|denom| coin(10000, *denom)).collect();
+        router.bank.init_balance(storage, &lp, funds).unwrap();
+        router
+            .bank
+            .init_balance(storage, &trader, vec![coin(1000, BASKET[0])])
+            .unwrap();
+    });
+    let cw20_id = app.store_code(contract_cw20());
+    let pair_id = app.store_code(contract_pair());
+    let admin = Addr::unchecked("admin");
+
+    let too_many: Vec<AssetInfo> = (0..5)
+        .map(|idx| AssetInfo::Native(format!("denom{}", idx)))
+        .collect();
+    let err = app
+        .instantiate_contract(
+            pair_id,
+            admin.clone(),
+            &InstantiateMsg::new(too_many, cw20_id).with_amp(100),
+            &[],
+            "Pair",
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        tfi_pair::error::ContractError::AssetCountOutOfRange { max: 4, got: 5 }.to_string()
+    );
+
+    let asset_infos: Vec<AssetInfo> = (0..3).map(basket_info).collect();
+    let pair = app
+        .instantiate_contract(
+            pair_id,
+            admin,
+            &InstantiateMsg::new(asset_infos, cw20_id).with_amp(100),
+            &[],
+            "Pair",
+            None,
+        )
+        .unwrap();
+    let PairInfo {
+        liquidity_token: lt,
+        ..
+    } = app
+        .wrap()
+        .query_wasm_smart(pair.clone(), &QueryMsg::Pair {})
+        .unwrap();
+
+    app.execute_contract(
+        lp.clone(),
+        pair.clone(),
+        &ExecuteMsg::ProvideLiquidity {
+            assets: (0..3)
+                .map(|idx| Asset {
+                    info: basket_info(idx),
+                    amount: Uint128::new(10000),
+                })
+                .collect(),
+            slippage_tolerance: None,
+            deadline: None,
+        },
+        &BASKET
+            .iter()
+            .map(|denom| coin(10000, *denom))
+            .collect::<Vec<_>>(),
+    )
+    .unwrap();
+
+    // invariant of balanced basket is its sum, 1000 of it stays locked
+    let BalanceResponse { balance } = app
+        .wrap()
+        .query_wasm_smart(
+            lt.clone(),
+            &Cw20QueryMsg::Balance {
+                address: lp.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(balance, Uint128::new(29000));
+
+    // with more than two assets the pair can't guess which one is asked for
+    let offer_asset = Asset {
+        info: basket_info(0),
+        amount: Uint128::new(1000),
+    };
+    let swap = |ask_asset_info| ExecuteMsg::Swap {
+        offer_asset: offer_asset.clone(),
+        ask_asset_info,
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        min_return: None,
+        deadline: None,
+        to_msg: None,
+    };
+    let err = app
+        .execute_contract(
+            trader.clone(),
+            pair.clone(),
+            &swap(None),
+            &[coin(1000, BASKET[0])],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        tfi_pair::error::ContractError::MissingSwapAsset {}.to_string()
+    );
+
+    let simulation: SimulationResponse = app
+        .wrap()
+        .query_wasm_smart(
+            pair.clone(),
+            &QueryMsg::Simulation {
+                offer_asset: offer_asset.clone(),
+                ask_asset_info: Some(basket_info(2)),
+                trader: None,
+            },
+        )
+        .unwrap();
+    app.execute_contract(
+        trader.clone(),
+        pair.clone(),
+        &swap(Some(basket_info(2))),
+        &[coin(1000, BASKET[0])],
+    )
+    .unwrap();
+    let dai = simulation.return_amount.u128();
+    assert!(dai > 990 && dai < 1000, "{}", dai);
+    assert_eq!(basket_balances(&app, &trader), vec![0, 0, dai]);
+    let pools = vec![11000, 10000, 10000 - dai];
+    assert_eq!(basket_balances(&app, &pair), pools);
+
+    // lp owns 29000 / 30000 of every pool
+    app.execute_contract(
+        lp.clone(),
+        lt,
+        &cw20_base::msg::ExecuteMsg::Send {
+            contract: pair.to_string(),
+            amount: Uint128::new(29000),
+            msg: to_binary(&Cw20HookMsg::WithdrawLiquidity { deadline: None }).unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    let withdrawn: Vec<u128> = pools.iter().map(|pool| pool * 29 / 30).collect();
+    assert_eq!(basket_balances(&app, &lp), withdrawn);
+    let left: Vec<u128> = pools.iter().zip(&withdrawn).map(|(p, w)| p - w).collect();
+    assert_eq!(basket_balances(&app, &pair), left);
+}
@@ -0,0 +1,4 @@
+use cw_storage_plus::Item;
+
+/// Amplification coefficient of the stableswap invariant
+pub const AMP: Item<u64> = Item::new("amp");
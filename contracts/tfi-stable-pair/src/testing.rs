@@ -0,0 +1,209 @@
+use cosmwasm_std::{Decimal, StdError, Uint128, Uint256};
+use proptest::prelude::*;
+
+use crate::math::{compute_d, compute_offer_amount, compute_swap, compute_y, MAX_AMP};
+
+fn amp() -> impl Strategy<Value = u64> {
+    prop_oneof![1u64..=1000, Just(MAX_AMP)]
+}
+
+fn pool() -> impl Strategy<Value = u128> {
+    1_000u128..=1_000_000_000_000_000_000_000_000
+}
+
+#[test]
+fn invariant_of_balanced_pools_is_their_sum() {
+    for amp in [1, 100, MAX_AMP] {
+        let d = compute_d(amp, &[Uint128::new(1_000_000), Uint128::new(1_000_000)]).unwrap();
+        assert_eq!(d, Uint256::from(2_000_000u32));
+    }
+}
+
+#[test]
+fn invariant_of_balanced_basket_is_its_sum() {
+    for amp in [1, 100, MAX_AMP] {
+        for n in 3..=4 {
+            let d = compute_d(amp, &vec![Uint128::new(1_000_000); n]).unwrap();
+            assert_eq!(d, Uint256::from(1_000_000u32 * n as u32));
+        }
+    }
+}
+
+#[test]
+fn invariant_of_imbalanced_pools_converges() {
+    for amp in [1, 100, MAX_AMP] {
+        for (pool0, pool1) in [
+            (1u128, 1_000_000_000_000u128),
+            (1_000_000_000_000_000_000, 1_000),
+            (1, u128::MAX),
+        ] {
+            let d = compute_d(amp, &[pool0.into(), pool1.into()]).unwrap();
+            // invariant is between constant product and constant sum
+            assert!(d <= Uint256::from(pool0) + Uint256::from(pool1), "{}", d);
+            assert!(
+                d.full_mul(d) >= (Uint256::from(pool0) * Uint256::from(4u8)).full_mul(pool1),
+                "{}",
+                d
+            );
+        }
+    }
+}
+
+#[test]
+fn invariant_near_max_pools() {
+    let d = compute_d(MAX_AMP, &[Uint128::MAX, Uint128::MAX]).unwrap();
+    assert_eq!(d, Uint256::from(Uint128::MAX) * Uint256::from(2u8));
+
+    // swapping on such pools neither overflows
+    let (return_amount, _spread_amount, _commission_amount) = compute_swap(
+        MAX_AMP,
+        &[Uint128::MAX - Uint128::new(1_000_000), Uint128::MAX],
+        0,
+        1,
+        Uint128::new(1_000_000),
+        Decimal::zero(),
+    )
+    .unwrap();
+    assert!(return_amount <= Uint128::new(1_000_000));
+}
+
+#[test]
+fn overflow_is_an_error() {
+    let err = compute_y(
+        MAX_AMP,
+        &[Uint128::new(1), Uint128::zero()],
+        1,
+        Uint256::MAX,
+    )
+    .unwrap_err();
+    assert!(matches!(err, StdError::Overflow { .. }), "{}", err);
+}
+
+proptest! {
+    #[test]
+    fn invariant_grows_with_pools(
+        amp in amp(),
+        pool0 in pool(),
+        pool1 in pool(),
+        deposit in 1u128..=1_000_000_000_000_000_000,
+    ) {
+        let d = compute_d(amp, &[pool0.into(), pool1.into()]).unwrap();
+        let new_d = compute_d(amp, &[(pool0 + deposit).into(), pool1.into()]).unwrap();
+        prop_assert!(new_d >= d);
+    }
+
+    #[test]
+    fn swap_never_decreases_invariant(
+        amp in amp(),
+        offer_pool in pool(),
+        ask_pool in pool(),
+        offer_ratio in 1u64..=500,
+        commission in 0u64..=100,
+    ) {
+        let offer_amount = std::cmp::max(offer_pool * offer_ratio as u128 / 1000, 1);
+        let (return_amount, _spread_amount, _commission_amount) = compute_swap(
+            amp,
+            &[offer_pool.into(), ask_pool.into()],
+            0,
+            1,
+            offer_amount.into(),
+            Decimal::permille(commission),
+        )
+        .unwrap();
+
+        // commission stays in the pool, and return is rounded down, so the invariant never
+        // decreases
+        let d = compute_d(amp, &[offer_pool.into(), ask_pool.into()]).unwrap();
+        let new_d = compute_d(
+            amp,
+            &[(offer_pool + offer_amount).into(), (ask_pool - return_amount.u128()).into()],
+        )
+        .unwrap();
+        prop_assert!(new_d >= d);
+    }
+
+    #[test]
+    fn swap_exact_out_never_decreases_invariant(
+        amp in amp(),
+        offer_pool in pool(),
+        ask_pool in pool(),
+        ask_ratio in 1u64..=250,
+        commission in 0u64..=100,
+    ) {
+        let ask_amount = std::cmp::max(ask_pool * ask_ratio as u128 / 1000, 1);
+        let (offer_amount, _spread_amount, _commission_amount) = compute_offer_amount(
+            amp,
+            &[offer_pool.into(), ask_pool.into()],
+            0,
+            1,
+            ask_amount.into(),
+            Decimal::permille(commission),
+        )
+        .unwrap();
+
+        // offer is rounded up, so the invariant never decreases
+        let d = compute_d(amp, &[offer_pool.into(), ask_pool.into()]).unwrap();
+        let new_d = compute_d(
+            amp,
+            &[(offer_pool + offer_amount.u128()).into(), (ask_pool - ask_amount).into()],
+        )
+        .unwrap();
+        prop_assert!(new_d >= d);
+    }
+
+    #[test]
+    fn basket_swap_never_decreases_invariant(
+        amp in amp(),
+        pools in prop::collection::vec(pool(), 3..=4),
+        offer_ratio in 1u64..=500,
+        commission in 0u64..=100,
+    ) {
+        let (offer_idx, ask_idx) = (pools.len() - 1, 1);
+        let offer_amount = std::cmp::max(pools[offer_idx] * offer_ratio as u128 / 1000, 1);
+        let pools: Vec<Uint128> = pools.into_iter().map(Uint128::new).collect();
+        let (return_amount, _spread_amount, _commission_amount) = compute_swap(
+            amp,
+            &pools,
+            offer_idx,
+            ask_idx,
+            offer_amount.into(),
+            Decimal::permille(commission),
+        )
+        .unwrap();
+
+        let d = compute_d(amp, &pools).unwrap();
+        let mut new_pools = pools.clone();
+        new_pools[offer_idx] += Uint128::new(offer_amount);
+        new_pools[ask_idx] -= return_amount;
+        let new_d = compute_d(amp, &new_pools).unwrap();
+        prop_assert!(new_d >= d);
+    }
+
+    #[test]
+    fn basket_swap_exact_out_never_decreases_invariant(
+        amp in amp(),
+        pools in prop::collection::vec(pool(), 3..=4),
+        ask_ratio in 1u64..=250,
+        commission in 0u64..=100,
+    ) {
+        let (offer_idx, ask_idx) = (0, pools.len() - 1);
+        let ask_amount = std::cmp::max(pools[ask_idx] * ask_ratio as u128 / 1000, 1);
+        let pools: Vec<Uint128> = pools.into_iter().map(Uint128::new).collect();
+        let (offer_amount, _spread_amount, _commission_amount) = compute_offer_amount(
+            amp,
+            &pools,
+            offer_idx,
+            ask_idx,
+            ask_amount.into(),
+            Decimal::permille(commission),
+        )
+        .unwrap();
+
+        let d = compute_d(amp, &pools).unwrap();
+        let mut new_pools = pools.clone();
+        new_pools[offer_idx] += offer_amount;
+        new_pools[ask_idx] -= Uint128::new(ask_amount);
+        let new_d = compute_d(amp, &new_pools).unwrap();
+        prop_assert!(new_d >= d);
+    }
+}
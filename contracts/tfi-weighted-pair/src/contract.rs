@@ -0,0 +1,140 @@
+use crate::error::ContractError;
+use crate::math::{compute_offer_amount, compute_swap, validate_weights};
+use crate::state::WEIGHTS;
+
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+
+use cosmwasm_std::{
+    Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult, Storage, Uint128,
+    Uint256,
+};
+
+use std::convert::TryInto;
+use tfi::asset::WEIGHTED_PAIR_TYPE;
+use tfi::pair::{ExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse, QueryMsg};
+use tfi_pair::curve::{Curve, Unsupported};
+use tfi_pair::math::uint512_sqrt;
+
+/// Weighted constant product invariant with weights of pair assets
+pub struct WeightedCurve {
+    pub weights: [Decimal; 2],
+}
+
+impl Curve for WeightedCurve {
+    const CONTRACT_NAME: &'static str = "crates.io:tfi-weighted-pair";
+    const CONTRACT_VERSION: &'static str = env!("CARGO_PKG_VERSION");
+    const PAIR_TYPE: &'static str = WEIGHTED_PAIR_TYPE;
+    const MAX_ASSETS: usize = 2;
+
+    type Error = ContractError;
+
+    fn from_instantiate_msg(msg: &InstantiateMsg) -> Result<Self, ContractError> {
+        let weights = msg
+            .weights
+            .as_ref()
+            .ok_or(ContractError::MissingWeights {})?;
+        Ok(WeightedCurve {
+            weights: validate_weights(weights)?,
+        })
+    }
+
+    fn load(storage: &dyn Storage) -> StdResult<Self> {
+        Ok(WeightedCurve {
+            weights: WEIGHTS.load(storage)?,
+        })
+    }
+
+    fn save(&self, storage: &mut dyn Storage) -> StdResult<()> {
+        WEIGHTS.save(storage, &self.weights)
+    }
+
+    fn compute_swap(
+        &self,
+        pools: &[Uint128],
+        offer_idx: usize,
+        ask_idx: usize,
+        offer_amount: Uint128,
+        commission: Decimal,
+    ) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+        compute_swap(
+            pools[offer_idx],
+            self.weights[offer_idx],
+            pools[ask_idx],
+            self.weights[ask_idx],
+            offer_amount,
+            commission,
+        )
+    }
+
+    fn compute_offer_amount(
+        &self,
+        pools: &[Uint128],
+        offer_idx: usize,
+        ask_idx: usize,
+        ask_amount: Uint128,
+        commission: Decimal,
+    ) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+        compute_offer_amount(
+            pools[offer_idx],
+            self.weights[offer_idx],
+            pools[ask_idx],
+            self.weights[ask_idx],
+            ask_amount,
+            commission,
+        )
+    }
+
+    /// Initial share = geometric mean of deposits, the same as in `tfi-pair`. Further deposits
+    /// are pro rata, so weights don't affect the share.
+    fn initial_share(&self, deposits: &[Uint128]) -> Result<Uint128, ContractError> {
+        Ok(uint512_sqrt(Uint256::from(deposits[0]).full_mul(deposits[1]))?.try_into()?)
+    }
+
+    fn pool_response(&self, mut res: PoolResponse) -> PoolResponse {
+        res.weights = Some(self.weights.to_vec());
+        res
+    }
+
+    fn unsupported(operation: Unsupported) -> ContractError {
+        ContractError::NotSupported(operation)
+    }
+}
+
+/// Instantiates pair the same way as `tfi-pair` does (including liquidity token creation), and
+/// additionally stores asset weights and marks the pair as `weighted`
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    tfi_pair::curve::instantiate::<WeightedCurve>(deps, env, info, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    tfi_pair::curve::execute::<WeightedCurve>(deps, env, info, msg)
+}
+
+/// This just stores the result for future query
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    Ok(tfi_pair::contract::reply(deps, env, msg)?)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    tfi_pair::curve::query::<WeightedCurve>(deps, env, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    Ok(tfi_pair::contract::migrate_pair(deps, env, msg)?)
+}
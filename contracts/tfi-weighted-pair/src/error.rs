@@ -0,0 +1,33 @@
+use cosmwasm_std::{ConversionOverflowError, OverflowError, StdError};
+use tfi_pair::curve::Unsupported;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    OverflowError(#[from] OverflowError),
+
+    #[error("{0}")]
+    ConversionOverflowError(#[from] ConversionOverflowError),
+
+    #[error("{0}")]
+    Pair(#[from] tfi_pair::error::ContractError),
+
+    #[error("Asset weights are required for weighted pair")]
+    MissingWeights {},
+
+    #[error("Invalid asset weights, each has to be at least 0.01 and they have to sum up to 1")]
+    InvalidWeights {},
+
+    #[error("Swap offer cannot exceed half of the offer pool")]
+    MaxInRatio {},
+
+    #[error("Swap return cannot exceed third of the ask pool")]
+    MaxOutRatio {},
+
+    #[error("Operation not supported by weighted pair: {0}")]
+    NotSupported(Unsupported),
+}
@@ -0,0 +1,186 @@
+use cosmwasm_std::{Decimal, Decimal256, StdError, StdResult, Uint128, Uint256};
+use std::convert::{TryFrom, TryInto};
+
+use tfi_pair::math::{before_commission, decimal256};
+
+use crate::error::ContractError;
+
+/// Minimum weight of an asset (1%), it limits the ratio of weights, so also exponents used in swaps
+pub const MIN_WEIGHT: Decimal = Decimal::raw(10_000_000_000_000_000);
+/// Maximum number of binomial series terms computed for fractional powers, series used for swaps
+/// within ratio limits converge to full precision much earlier
+const POW_ITERATIONS: u32 = 128;
+/// Margin added to computed powers. It is far above rounding errors of `pow` for swaps within
+/// ratio limits, so swaps are always rounded in pool favour.
+const POW_PRECISION: Decimal256 = Decimal256::raw(1_000_000);
+
+/// Checks if weights are valid - there is one per pair asset, each is at least `MIN_WEIGHT`, and
+/// they sum up to 1
+pub fn validate_weights(weights: &[Decimal]) -> Result<[Decimal; 2], ContractError> {
+    match *weights {
+        [weight0, weight1]
+            if weight0 >= MIN_WEIGHT
+                && weight1 >= MIN_WEIGHT
+                && weight0 + weight1 == Decimal::one() =>
+        {
+            Ok([weight0, weight1])
+        }
+        _ => Err(ContractError::InvalidWeights {}),
+    }
+}
+
+/// Computes swap return, returning return amount, spread amount and commission amount. Offer
+/// cannot exceed half of the offer pool.
+pub fn compute_swap(
+    offer_pool: Uint128,
+    offer_weight: Decimal,
+    ask_pool: Uint128,
+    ask_weight: Decimal,
+    offer_amount: Uint128,
+    commission: Decimal,
+) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+    if offer_pool.is_zero() {
+        return Err(StdError::generic_err("Divide by zero error computing the swap").into());
+    }
+
+    let (offer_pool, ask_pool, offer_amount) = (
+        Uint256::from(offer_pool),
+        Uint256::from(ask_pool),
+        Uint256::from(offer_amount),
+    );
+    if offer_amount * Uint256::from(2u8) > offer_pool {
+        return Err(ContractError::MaxInRatio {});
+    }
+
+    // offer => ask
+    // ask_amount = ask_pool * (1 - (offer_pool / (offer_pool + offer_amount))^(offer_weight / ask_weight))
+    let base = Decimal256::from_ratio(offer_pool, offer_pool + offer_amount);
+    let exp = Decimal256::from_ratio(offer_weight.atomics(), ask_weight.atomics());
+    // power is rounded up, so return is rounded down
+    let pow = pow(base, exp)? + POW_PRECISION;
+    let return_amount = if pow >= Decimal256::one() {
+        Uint256::zero()
+    } else {
+        ask_pool * (Decimal256::one() - pow)
+    };
+
+    // spread is the difference to the spot price, which is weighted pools ratio
+    let spread_amount = offer_amount
+        .multiply_ratio(
+            ask_pool * Uint256::from(offer_weight.atomics()),
+            offer_pool * Uint256::from(ask_weight.atomics()),
+        )
+        .saturating_sub(return_amount);
+    let commission_amount = return_amount * decimal256(commission);
+
+    // commission will be absorbed to pool
+    let return_amount = return_amount.checked_sub(commission_amount)?;
+
+    Ok((
+        return_amount.try_into()?,
+        spread_amount.try_into()?,
+        commission_amount.try_into()?,
+    ))
+}
+
+/// Computes offer amount required to receive `ask_amount`, returning offer amount, spread amount
+/// and commission amount. Ask amount before commission deduction cannot exceed third of the ask
+/// pool.
+pub fn compute_offer_amount(
+    offer_pool: Uint128,
+    offer_weight: Decimal,
+    ask_pool: Uint128,
+    ask_weight: Decimal,
+    ask_amount: Uint128,
+    commission: Decimal,
+) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+    if offer_pool.is_zero() {
+        return Err(StdError::generic_err("Divide by zero error computing the swap").into());
+    }
+
+    let (offer_pool, ask_pool) = (Uint256::from(offer_pool), Uint256::from(ask_pool));
+    let before_commission_deduction = before_commission(ask_amount.into(), commission)?;
+    if before_commission_deduction * Uint256::from(3u8) > ask_pool {
+        return Err(ContractError::MaxOutRatio {});
+    }
+
+    // ask => offer
+    // offer_amount = offer_pool * ((ask_pool / (ask_pool - ask_amount / (1 - commission_rate)))^(ask_weight / offer_weight) - 1)
+    let base = Decimal256::from_ratio(ask_pool, ask_pool - before_commission_deduction);
+    let exp = Decimal256::from_ratio(ask_weight.atomics(), offer_weight.atomics());
+    // power is rounded up (relatively, as it can be far above 1), and one token is added to avoid
+    // rounding in trader favour
+    let pow = pow(base, exp)? * (Decimal256::one() + POW_PRECISION);
+    let offer_amount = offer_pool * (pow - Decimal256::one()) + Uint256::from(1u8);
+
+    let spread_amount = offer_amount
+        .multiply_ratio(
+            ask_pool * Uint256::from(offer_weight.atomics()),
+            offer_pool * Uint256::from(ask_weight.atomics()),
+        )
+        .saturating_sub(before_commission_deduction);
+    let commission_amount = before_commission_deduction * decimal256(commission);
+
+    Ok((
+        offer_amount.try_into()?,
+        spread_amount.try_into()?,
+        commission_amount.try_into()?,
+    ))
+}
+
+/// Computes `base^exp` for `base` between 0 and 2. Integer part of the exponent is applied by
+/// multiplication, fractional part with binomial series.
+fn pow(base: Decimal256, exp: Decimal256) -> StdResult<Decimal256> {
+    let one = Decimal256::one().atomics();
+    let whole = u32::try_from(Uint128::try_from(exp.atomics() / one)?.u128())
+        .map_err(|_| StdError::generic_err("Exponent out of range"))?;
+    let fraction = Decimal256::new(exp.atomics() % one);
+
+    let whole_pow = base.checked_pow(whole)?;
+    if fraction.is_zero() {
+        return Ok(whole_pow);
+    }
+
+    Ok(whole_pow * pow_fraction(base, fraction))
+}
+
+/// Computes `base^exp` for `exp` between 0 and 1 as binomial series:
+///
+/// `base^exp = sum((exp choose k) * (base - 1)^k)`
+///
+/// Terms of the series alternate their signs, so signs are tracked separately. It converges for
+/// `base` between 0 and 2, the closer `base` is to 1 the faster.
+fn pow_fraction(base: Decimal256, exp: Decimal256) -> Decimal256 {
+    let one = Decimal256::one();
+    let (x, x_negative) = if base >= one {
+        (base - one, false)
+    } else {
+        (one - base, true)
+    };
+
+    let mut term = one;
+    let mut sum = one;
+    let mut negative = false;
+    for k in 1..=POW_ITERATIONS {
+        let k_minus_one = Decimal256::from_ratio(k - 1, 1u8);
+        let (c, c_negative) = if exp >= k_minus_one {
+            (exp - k_minus_one, false)
+        } else {
+            (k_minus_one - exp, true)
+        };
+
+        term = term * c * x / Uint256::from(k);
+        if term.is_zero() {
+            break;
+        }
+
+        negative ^= x_negative ^ c_negative;
+        if negative {
+            sum -= term;
+        } else {
+            sum += term;
+        }
+    }
+
+    sum
+}
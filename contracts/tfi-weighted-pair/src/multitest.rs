@@ -0,0 +1,161 @@
+use cosmwasm_std::{Decimal, Empty, Uint128};
+use cw_multi_test::{Contract, ContractWrapper};
+use tfi_curve_suite::SuiteConfig;
+
+use crate::error::ContractError;
+use tfi::asset::{PairInfo, WEIGHTED_PAIR_TYPE};
+use tfi::pair::{QueryMsg, SimulationResponse};
+
+fn contract_pair() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    )
+    .with_reply(crate::contract::reply);
+    Box::new(contract)
+}
+
+fn suite_config() -> SuiteConfig {
+    SuiteConfig::new(contract_pair())
+}
+
+#[test]
+fn weights_required() {
+    let err = suite_config().init().unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::MissingWeights {}.to_string()
+    );
+
+    // weights not summing up to 1
+    let err = suite_config()
+        .with_weights(Decimal::percent(50), Decimal::percent(60))
+        .init()
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::InvalidWeights {}.to_string()
+    );
+
+    // weight below 1%
+    let err = suite_config()
+        .with_weights(Decimal::permille(5), Decimal::permille(995))
+        .init()
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::InvalidWeights {}.to_string()
+    );
+}
+
+#[test]
+// Full flow on 20/80 pool: provide liquidity, swap in both directions, withdraw everything
+//
+// Pool of 10000usdc with weight 0.2 and 40000cash with weight 0.8 prices cash 1:1 to usdc.
+fn swap() {
+    let mut suite = suite_config()
+        .with_actor("liquidity-provider", 10000, 40000)
+        .with_actor("trader", 1000, 1000)
+        .with_weights(Decimal::percent(20), Decimal::percent(80))
+        .init()
+        .unwrap();
+
+    let (lp, trader, pair) = (
+        suite.actors[0].clone(),
+        suite.actors[1].clone(),
+        suite.pair.clone(),
+    );
+
+    let pair_info: PairInfo = suite
+        .app
+        .wrap()
+        .query_wasm_smart(pair.clone(), &QueryMsg::Pair {})
+        .unwrap();
+    assert_eq!(pair_info.pair_type, WEIGHTED_PAIR_TYPE);
+
+    suite.provide_liquidity(&lp, 10000, 40000).unwrap();
+
+    // initial share is geometric mean of deposits, minus minimum liquidity locked on pair
+    suite
+        .assert_balances(&lp, 0, 0, 19000)
+        .assert_balances(&pair, 10000, 40000, 1000);
+
+    let pool = suite.pool().unwrap();
+    assert_eq!(
+        pool.weights,
+        Some(vec![Decimal::percent(20), Decimal::percent(80)])
+    );
+
+    // 40000 * (1 - (10000 / 11000)^(0.2 / 0.8)) = 941.82, commission is 0.3% of it
+    let simulation = suite.simulate_swap(1000, suite.usdc()).unwrap();
+    assert_eq!(
+        simulation,
+        SimulationResponse {
+            return_amount: Uint128::new(939),
+            spread_amount: Uint128::new(59),
+            commission_amount: Uint128::new(2),
+        }
+    );
+
+    suite.swap_usdc(&trader, 1000).unwrap();
+    suite
+        .assert_balances(&trader, 0, 1939, 0)
+        .assert_balances(&pair, 11000, 39061, 1000);
+
+    // 11000 * (1 - (39061 / 40061)^(0.8 / 0.2)) = 1057.88, commission is 0.3% of it
+    let simulation = suite.simulate_swap(1000, suite.cash()).unwrap();
+    assert_eq!(
+        simulation,
+        SimulationResponse {
+            return_amount: Uint128::new(1054),
+            spread_amount: Uint128::new(69),
+            commission_amount: Uint128::new(3),
+        }
+    );
+
+    suite.swap_cash(&trader, 1000).unwrap();
+    suite.assert_balances(&trader, 1054, 939, 0);
+
+    // lp owns 19000 / 20000 of pool, the rest stays with locked liquidity
+    let (usdc_pool, cash_pool) = (11000 - 1054, 40061);
+    let (usdc_out, cash_out) = (usdc_pool * 19 / 20, cash_pool * 19 / 20);
+    suite.withdraw_liquidity(&lp, 19000).unwrap();
+    suite
+        .assert_balances(&lp, usdc_out, cash_out, 0)
+        .assert_balances(&pair, usdc_pool - usdc_out, cash_pool - cash_out, 1000);
+}
+
+#[test]
+// Single swap can take at most half of the offer pool, and return at most third of the ask pool
+fn swap_ratio_limits() {
+    let mut suite = suite_config()
+        .with_actor("liquidity-provider", 10000, 40000)
+        .with_actor("trader", 6000, 0)
+        .with_weights(Decimal::percent(20), Decimal::percent(80))
+        .init()
+        .unwrap();
+
+    let (lp, trader) = (suite.actors[0].clone(), suite.actors[1].clone());
+    suite.provide_liquidity(&lp, 10000, 40000).unwrap();
+
+    let err = suite.swap_usdc(&trader, 5001).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::MaxInRatio {}.to_string()
+    );
+    suite.swap_usdc(&trader, 5000).unwrap();
+
+    let err = suite.simulate_reverse_swap(5000, suite.usdc()).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains(&ContractError::MaxOutRatio {}.to_string()),
+        "{}",
+        err
+    );
+}
+
+tfi_curve_suite::curve_scenarios!(
+    suite_config().with_weights(Decimal::percent(20), Decimal::percent(80)),
+    (10000, 40000)
+);
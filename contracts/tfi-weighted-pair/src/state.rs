@@ -0,0 +1,5 @@
+use cosmwasm_std::Decimal;
+use cw_storage_plus::Item;
+
+/// Weights of pair assets, in the same order as `PairInfo::asset_infos`
+pub const WEIGHTS: Item<[Decimal; 2]> = Item::new("weights");
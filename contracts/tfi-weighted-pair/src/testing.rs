@@ -0,0 +1,145 @@
+use cosmwasm_std::{Decimal, Uint512};
+use proptest::prelude::*;
+
+use crate::error::ContractError;
+use crate::math::{compute_offer_amount, compute_swap};
+
+/// Weights as ratio of small integers, so invariant can be computed exactly
+fn weights() -> impl Strategy<Value = (u32, u32)> {
+    prop::sample::select(vec![(1, 1), (1, 3), (3, 1), (2, 3), (3, 2), (1, 4), (4, 1)])
+}
+
+fn pool() -> impl Strategy<Value = u128> {
+    1_000u128..=1_000_000_000_000_000_000_000_000
+}
+
+/// Weight of `weight` out of `weight_sum` parts
+fn weight(weight: u32, weight_sum: u32) -> Decimal {
+    Decimal::from_ratio(weight, weight_sum)
+}
+
+/// Weighted invariant `offer_pool^offer_weight * ask_pool^ask_weight`, raised to power of
+/// weights denominator, so it is an integer
+fn invariant(offer_pool: u128, offer_weight: u32, ask_pool: u128, ask_weight: u32) -> Uint512 {
+    Uint512::from(offer_pool).pow(offer_weight) * Uint512::from(ask_pool).pow(ask_weight)
+}
+
+proptest! {
+    #[test]
+    fn max_in_ratio_is_half_of_offer_pool(
+        (offer_weight, ask_weight) in weights(),
+        offer_pool in pool(),
+        ask_pool in pool(),
+        excess in 1u128..=1_000,
+    ) {
+        let weight_sum = offer_weight + ask_weight;
+        let swap = |offer_amount: u128| compute_swap(
+            offer_pool.into(),
+            weight(offer_weight, weight_sum),
+            ask_pool.into(),
+            weight(ask_weight, weight_sum),
+            offer_amount.into(),
+            Decimal::zero(),
+        );
+
+        let (return_amount, _, _) = swap(offer_pool / 2).unwrap();
+        prop_assert!(return_amount.u128() < ask_pool);
+        prop_assert_eq!(
+            swap(offer_pool / 2 + excess).unwrap_err(),
+            ContractError::MaxInRatio {}
+        );
+    }
+
+    #[test]
+    fn max_out_ratio_is_third_of_ask_pool(
+        (offer_weight, ask_weight) in weights(),
+        offer_pool in pool(),
+        ask_pool in pool(),
+        excess in 1u128..=1_000,
+    ) {
+        let weight_sum = offer_weight + ask_weight;
+        let offer_amount = |ask_amount: u128| compute_offer_amount(
+            offer_pool.into(),
+            weight(offer_weight, weight_sum),
+            ask_pool.into(),
+            weight(ask_weight, weight_sum),
+            ask_amount.into(),
+            Decimal::zero(),
+        );
+
+        offer_amount(ask_pool / 3).unwrap();
+        prop_assert_eq!(
+            offer_amount(ask_pool / 3 + excess).unwrap_err(),
+            ContractError::MaxOutRatio {}
+        );
+    }
+
+    /// Powers are rounded in pool favour even for the largest swaps allowed by the max in ratio
+    #[test]
+    fn swap_up_to_max_in_ratio_keeps_invariant(
+        (offer_weight, ask_weight) in weights(),
+        offer_pool in pool(),
+        ask_pool in pool(),
+        offer_ppm in 1u128..=1_000_000,
+    ) {
+        let offer_amount = std::cmp::max(offer_pool / 2 * offer_ppm / 1_000_000, 1);
+        let weight_sum = offer_weight + ask_weight;
+        let (return_amount, _spread_amount, _commission_amount) = compute_swap(
+            offer_pool.into(),
+            weight(offer_weight, weight_sum),
+            ask_pool.into(),
+            weight(ask_weight, weight_sum),
+            offer_amount.into(),
+            Decimal::zero(),
+        )
+        .unwrap();
+
+        let k = invariant(offer_pool, offer_weight, ask_pool, ask_weight);
+        let new_k = invariant(
+            offer_pool + offer_amount,
+            offer_weight,
+            ask_pool - return_amount.u128(),
+            ask_weight,
+        );
+        prop_assert!(new_k >= k);
+    }
+
+    /// Swapping the offer computed for exact output, within both ratio limits, returns what was
+    /// asked for - short of at most the power precision margin, which swap keeps in the pool
+    #[test]
+    fn offer_amount_buys_ask_amount(
+        (offer_weight, ask_weight) in weights(),
+        offer_pool in pool(),
+        ask_pool in pool(),
+        ask_ppm in 1u128..=300_000,
+        commission in 0u64..=10,
+    ) {
+        let ask_amount = std::cmp::max(ask_pool / 3 * ask_ppm / 1_000_000, 1);
+        let weight_sum = offer_weight + ask_weight;
+        let (offer_weight, ask_weight) =
+            (weight(offer_weight, weight_sum), weight(ask_weight, weight_sum));
+        let commission = Decimal::percent(commission);
+        let (offer_amount, _spread_amount, _commission_amount) = compute_offer_amount(
+            offer_pool.into(),
+            offer_weight,
+            ask_pool.into(),
+            ask_weight,
+            ask_amount.into(),
+            commission,
+        )
+        .unwrap();
+        prop_assume!(offer_amount.u128() <= offer_pool / 2);
+
+        let (return_amount, _spread_amount, _commission_amount) = compute_swap(
+            offer_pool.into(),
+            offer_weight,
+            ask_pool.into(),
+            ask_weight,
+            offer_amount,
+            commission,
+        )
+        .unwrap();
+        let margin = ask_pool / 1_000_000_000_000 + 1;
+        prop_assert!(return_amount.u128() + margin >= ask_amount);
+    }
+}
@@ -18,7 +18,7 @@ use crate::msg::{
     AllRedeemsResponse, ExecuteMsg, InstantiateMsg, IsWhitelistedResponse, QueryMsg, RedeemInfo,
     RedeemResponse, WhitelistResponse,
 };
-use crate::state::{Redeem, REEDEMS, WHITELIST};
+use crate::state::{Redeem, EXEMPT_MINTER, REEDEMS, WHITELIST};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:trusted-token";
@@ -52,6 +52,7 @@ pub fn instantiate(
     // verify that the whitelist contract is actually tg4-compatible
     contract.list_members(&deps.querier, None, Some(1))?;
     WHITELIST.save(deps.storage, &contract)?;
+    EXEMPT_MINTER.save(deps.storage, &msg.exempt_minter)?;
 
     let event = Event::new("create_token")
         .add_attribute("name", msg.name)
@@ -61,9 +62,21 @@ pub fn instantiate(
     Ok(Response::default().add_event(event))
 }
 
+/// Checks if `addr` passes the whitelist: it is a member of the whitelist group, or the minter
+/// exempted on instantiation
+fn is_whitelisted(deps: Deps, whitelist: &Tg4Contract, addr: &Addr) -> StdResult<bool> {
+    if EXEMPT_MINTER.may_load(deps.storage)?.unwrap_or_default() {
+        let minter = TOKEN_INFO.load(deps.storage)?.mint.map(|mint| mint.minter);
+        if minter.as_ref() == Some(addr) {
+            return Ok(true);
+        }
+    }
+    Ok(whitelist.is_member(&deps.querier, addr)?.is_some())
+}
+
 pub(crate) fn verify_sender_on_whitelist(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
     let whitelist = WHITELIST.load(deps.storage)?;
-    if whitelist.is_member(&deps.querier, sender)?.is_none() {
+    if !is_whitelisted(deps, &whitelist, sender)? {
         return Err(ContractError::Unauthorized {});
     }
     Ok(())
@@ -75,15 +88,12 @@ pub(crate) fn verify_sender_and_addresses_on_whitelist(
     addresses: &[&str],
 ) -> Result<(), ContractError> {
     let whitelist = WHITELIST.load(deps.storage)?;
-    if whitelist.is_member(&deps.querier, sender)?.is_none() {
+    if !is_whitelisted(deps, &whitelist, sender)? {
         return Err(ContractError::Unauthorized {});
     }
     for address in addresses {
         let validated_address = deps.api.addr_validate(address)?;
-        if whitelist
-            .is_member(&deps.querier, &validated_address)?
-            .is_none()
-        {
+        if !is_whitelisted(deps, &whitelist, &validated_address)? {
             return Err(ContractError::Unauthorized {});
         }
     }
@@ -314,7 +324,7 @@ fn query_whitelist(deps: Deps) -> StdResult<WhitelistResponse> {
 fn query_is_whitelisted(deps: Deps, address: String) -> StdResult<IsWhitelistedResponse> {
     let address = deps.api.addr_validate(&address)?;
     let whitelist = WHITELIST.load(deps.storage)?;
-    let whitelisted = whitelist.is_member(&deps.querier, &address)?.is_some();
+    let whitelisted = is_whitelisted(deps, &whitelist, &address)?;
     Ok(IsWhitelistedResponse { whitelisted })
 }
 
@@ -551,7 +561,7 @@ mod tests {
         let err = execute_redeem(
             deps.as_mut(),
             mock_env(),
-            mock_info(&trader.to_string(), &[]),
+            mock_info(trader.as_ref(), &[]),
             Uint128::new(500),
             "redeem-code".to_owned(),
             None,
@@ -600,7 +610,7 @@ mod tests {
         let err = execute_redeem(
             deps.as_mut(),
             mock_env(),
-            mock_info(&trader.to_string(), &[]),
+            mock_info(trader.as_ref(), &[]),
             Uint128::new(500),
             "redeem-code".to_owned(),
             None,
@@ -653,7 +663,7 @@ mod tests {
         execute_redeem(
             deps.as_mut(),
             mock_env(),
-            mock_info(&trader.to_string(), &[]),
+            mock_info(trader.as_ref(), &[]),
             Uint128::new(50),
             "redeem-code".to_owned(),
             None,
@@ -664,7 +674,7 @@ mod tests {
         let err = execute_redeem(
             deps.as_mut(),
             mock_env(),
-            mock_info(&trader.to_string(), &[]),
+            mock_info(trader.as_ref(), &[]),
             Uint128::new(30),
             "redeem-code".to_owned(),
             None,
@@ -689,6 +699,7 @@ mod tests {
             mint: None,
             marketing: None,
             whitelist_group: whitelist_group.clone(),
+            exempt_minter: false,
         };
 
         let whitelist_addr = Addr::unchecked("whitelist");
@@ -16,6 +16,11 @@ pub struct InstantiateMsg {
     pub marketing: Option<InstantiateMarketingInfo>,
     /// This is the address of a tg4 compatible contract that will serve as a whitelist
     pub whitelist_group: String,
+    /// Lets the minter mint, hold, move and burn tokens without being on the whitelist. Pairs
+    /// set it for their liquidity tokens, so they don't have to be whitelisted to lock minimum
+    /// liquidity on themselves and burn withdrawn shares.
+    #[serde(default)]
+    pub exempt_minter: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -160,6 +165,7 @@ pub enum QueryMsg {
     /// Only with "marketing" extension
     /// Returns more metadata on the contract to display in the client:
     /// - description, logo, project url, etc.
+    ///
     /// Return type: MarketingInfoResponse
     MarketingInfo {},
     /// Only with "marketing" extension
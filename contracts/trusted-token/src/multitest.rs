@@ -216,6 +216,36 @@ fn mint() {
     assert_eq!(suite.total_supply().unwrap(), 500);
 }
 
+#[test]
+fn exempt_minter() {
+    let mut suite = suite::Config::new()
+        .with_minter("minter", None)
+        .with_exempt_minter()
+        .with_member("member", 0, 10)
+        .init()
+        .unwrap();
+    let (minter, member) = (suite.minter.clone().unwrap(), suite.members[0].clone());
+    let non_member = Addr::unchecked("non-member");
+
+    // minter mints to itself and to whitelisted member without being whitelisted
+    suite.mint(&minter, &minter, 1000).unwrap();
+    suite.mint(&minter, &member, 500).unwrap();
+    assert_eq!(suite.balance(&minter).unwrap(), 1000);
+    assert_eq!(suite.balance(&member).unwrap(), 500);
+
+    // member moves tokens to the minter, and the minter burns them
+    suite.transfer(&member, &minter, 200).unwrap();
+    suite.burn(&minter, 200).unwrap();
+    assert_eq!(suite.balance(&minter).unwrap(), 1000);
+    assert_eq!(suite.total_supply().unwrap(), 1300);
+
+    // exemption doesn't let tokens reach anyone else outside of whitelist
+    let err = suite.mint(&minter, &non_member, 500).unwrap_err();
+    assert_error(err, ContractError::Unauthorized {});
+    let err = suite.transfer(&minter, &non_member, 500).unwrap_err();
+    assert_error(err, ContractError::Unauthorized {});
+}
+
 #[test]
 fn increase_allowance() {
     let mut suite = suite::Config::new()
@@ -477,14 +507,14 @@ fn whitelist() {
 
     let whitelist: WhitelistResponse = app
         .wrap()
-        .query_wasm_smart(&cash.addr(), &QueryMsg::Whitelist {})
+        .query_wasm_smart(cash.addr(), &QueryMsg::Whitelist {})
         .unwrap();
     assert_eq!(whitelist.address, suite.whitelist.addr());
 
     let is_whitelisted: IsWhitelistedResponse = app
         .wrap()
         .query_wasm_smart(
-            &cash.addr(),
+            cash.addr(),
             &QueryMsg::IsWhitelisted {
                 address: member.to_string(),
             },
@@ -495,7 +525,7 @@ fn whitelist() {
     let is_whitelisted: IsWhitelistedResponse = app
         .wrap()
         .query_wasm_smart(
-            &cash.addr(),
+            cash.addr(),
             &QueryMsg::IsWhitelisted {
                 address: "non-member".to_owned(),
             },
@@ -545,7 +575,7 @@ fn redeem() {
 
     assert_event(
         &resp.events,
-        &redeem_event("redeem-code-1", &member.to_string(), 1000, "First redeem"),
+        &redeem_event("redeem-code-1", member.as_ref(), 1000, "First redeem"),
     );
     assert!(
         resp.events.iter().any(|ev| ev.ty == "wasm-redeem"),
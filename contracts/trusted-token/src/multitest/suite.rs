@@ -377,7 +377,7 @@ impl Suite {
                 &ExecuteMsg::Redeem {
                     amount: amount.into(),
                     code: code.into(),
-                    sender: sender.into().map(Into::into),
+                    sender: sender.into(),
                     memo: memo.into(),
                 },
                 &[],
@@ -432,6 +432,8 @@ pub struct Config {
     marketing: Option<InstantiateMarketingInfo>,
     /// Address allowed to ming new tokens. Not neccessary member of a whitelist.
     minter: Option<MinterResponse>,
+    /// Does minter pass the whitelist without being its member?
+    exempt_minter: bool,
 }
 
 impl Config {
@@ -458,6 +460,11 @@ impl Config {
         self
     }
 
+    pub fn with_exempt_minter(mut self) -> Self {
+        self.exempt_minter = true;
+        self
+    }
+
     pub fn init(self) -> Result<Suite> {
         let mut app = mock_app();
         let owner = Addr::unchecked("owner");
@@ -514,6 +521,7 @@ impl Config {
                     mint: self.minter,
                     marketing: self.marketing,
                     whitelist_group: whitelist.to_string(),
+                    exempt_minter: self.exempt_minter,
                 },
                 &[],
                 "Cash",
@@ -5,6 +5,8 @@ use serde::{Deserialize, Serialize};
 use tg4::Tg4Contract;
 
 pub const WHITELIST: Item<Tg4Contract> = Item::new("whitelist");
+/// Set if the minter passes the whitelist without being its member
+pub const EXEMPT_MINTER: Item<bool> = Item::new("exempt_minter");
 pub const REEDEMS: Map<&str, Redeem> = Map::new("redeems");
 
 /// Entry about redeem which had place
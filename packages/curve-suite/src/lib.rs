@@ -0,0 +1,529 @@
+//! Multitest environment shared by tests of pairs implementing `tfi_pair::curve::Curve`, together
+//! with test scenarios which don't depend on the curve. Pair crates run all the scenarios with
+//! `curve_scenarios!`.
+
+use anyhow::{anyhow, Result};
+use cosmwasm_std::{coin, coins, to_binary, Addr, BankMsg, Decimal, Empty, Uint128};
+use cw20::Cw20Coin;
+use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper, Executor};
+use derivative::Derivative;
+
+use tfi::asset::{Asset, AssetInfo, PairInfo};
+use tfi::pair::{
+    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, QueryMsg, ReverseSimulationResponse,
+    SimulationResponse,
+};
+use tfi_pair::error::ContractError;
+use tfi_pair::state::CUMULATIVE_PRICES;
+
+const TEST_RESERVE: &str = "reserve";
+const DENOM: &str = "usdc";
+
+fn mock_app() -> App {
+    // Instantiates custom account (TEST_RESERVE) with arbitrary amount
+    // of tokens to fill any custom account later needed in tests
+    AppBuilder::new_custom().build(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &Addr::unchecked(TEST_RESERVE),
+                coins(1_000_000, DENOM),
+            )
+            .unwrap();
+    })
+}
+
+fn contract_cw20() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    Box::new(contract)
+}
+
+/// Helper struct providing unified environment for curve pairs testing
+///
+/// It assumes actors:
+/// * usdc: native token
+/// * cash: cw20 token
+/// * pair: tested pair contract between usdc and cash
+/// * lt: cw20 token, pair liquidity token
+/// * actors: number of accounts with initial usdc and cash
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct Suite {
+    /// Multitest app
+    #[derivative(Debug = "ignore")]
+    pub app: App,
+    /// Cash cw20 contract address
+    pub cash: Addr,
+    /// Pair contract address
+    pub pair: Addr,
+    /// Pair liquidity token cw20 contract address
+    pub lt: Addr,
+    /// Actors addresses
+    pub actors: Vec<Addr>,
+}
+
+impl Suite {
+    /// Returns usdc asset info
+    pub fn usdc(&self) -> AssetInfo {
+        AssetInfo::Native(DENOM.to_owned())
+    }
+
+    /// Returns cash asset info
+    pub fn cash(&self) -> AssetInfo {
+        AssetInfo::Token(self.cash.clone())
+    }
+
+    /// Increases allowance for cash, and provides liquidity to pair
+    pub fn provide_liquidity(&mut self, lp: &Addr, usdc: u128, cash: u128) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                lp.clone(),
+                self.cash.clone(),
+                &cw20_base::msg::ExecuteMsg::IncreaseAllowance {
+                    spender: self.pair.to_string(),
+                    amount: Uint128::new(cash),
+                    expires: None,
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        self.app
+            .execute_contract(
+                lp.clone(),
+                self.pair.clone(),
+                &ExecuteMsg::ProvideLiquidity {
+                    assets: vec![
+                        Asset {
+                            info: self.usdc(),
+                            amount: Uint128::new(usdc),
+                        },
+                        Asset {
+                            info: self.cash(),
+                            amount: Uint128::new(cash),
+                        },
+                    ],
+                    slippage_tolerance: None,
+                    deadline: None,
+                },
+                &coins(usdc, DENOM),
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    /// Swaps usdc for cash with `ExecuteMsg::Swap`
+    pub fn swap_usdc(&mut self, trader: &Addr, usdc: u128) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                trader.clone(),
+                self.pair.clone(),
+                &ExecuteMsg::Swap {
+                    offer_asset: Asset {
+                        info: self.usdc(),
+                        amount: Uint128::new(usdc),
+                    },
+                    ask_asset_info: None,
+                    belief_price: None,
+                    max_spread: None,
+                    to: None,
+                    min_return: None,
+                    deadline: None,
+                    to_msg: None,
+                },
+                &coins(usdc, DENOM),
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    /// Swaps cash for usdc with cw20 `Send` and `Cw20HookMsg::Swap`
+    pub fn swap_cash(&mut self, trader: &Addr, cash: u128) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                trader.clone(),
+                self.cash.clone(),
+                &cw20_base::msg::ExecuteMsg::Send {
+                    contract: self.pair.to_string(),
+                    amount: Uint128::new(cash),
+                    msg: to_binary(&Cw20HookMsg::Swap {
+                        ask_asset_info: None,
+                        belief_price: None,
+                        max_spread: None,
+                        to: None,
+                        min_return: None,
+                        deadline: None,
+                        to_msg: None,
+                    })
+                    .unwrap(),
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    /// Buys exactly `cash` for at most `max_usdc` with `ExecuteMsg::SwapExactOut`
+    pub fn swap_exact_out_usdc(
+        &mut self,
+        trader: &Addr,
+        max_usdc: u128,
+        cash: u128,
+    ) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                trader.clone(),
+                self.pair.clone(),
+                &ExecuteMsg::SwapExactOut {
+                    offer_asset: Asset {
+                        info: self.usdc(),
+                        amount: Uint128::new(max_usdc),
+                    },
+                    ask_asset_info: None,
+                    ask_amount: Uint128::new(cash),
+                    to: None,
+                    deadline: None,
+                },
+                &coins(max_usdc, DENOM),
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    /// Withdraws liquidity with lt `Send` and `Cw20HookMsg::WithdrawLiquidity`
+    pub fn withdraw_liquidity(&mut self, lp: &Addr, lt: u128) -> Result<&mut Self> {
+        self.app
+            .execute_contract(
+                lp.clone(),
+                self.lt.clone(),
+                &cw20_base::msg::ExecuteMsg::Send {
+                    contract: self.pair.to_string(),
+                    amount: Uint128::new(lt),
+                    msg: to_binary(&Cw20HookMsg::WithdrawLiquidity { deadline: None }).unwrap(),
+                },
+                &[],
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(self)
+    }
+
+    pub fn pool(&self) -> Result<PoolResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.pair.clone(), &QueryMsg::Pool {})
+            .map_err(|err| anyhow!(err))
+    }
+
+    pub fn simulate_swap(&self, offer: u128, asset: AssetInfo) -> Result<SimulationResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                self.pair.clone(),
+                &QueryMsg::Simulation {
+                    offer_asset: Asset {
+                        info: asset,
+                        amount: Uint128::new(offer),
+                    },
+                    ask_asset_info: None,
+                    trader: None,
+                },
+            )
+            .map_err(|err| anyhow!(err))
+    }
+
+    pub fn simulate_reverse_swap(
+        &self,
+        ask: u128,
+        asset: AssetInfo,
+    ) -> Result<ReverseSimulationResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                self.pair.clone(),
+                &QueryMsg::ReverseSimulation {
+                    ask_asset: Asset {
+                        info: asset,
+                        amount: Uint128::new(ask),
+                    },
+                    offer_asset_info: None,
+                    trader: None,
+                },
+            )
+            .map_err(|err| anyhow!(err))
+    }
+
+    /// Asserts if balances on account are as expected
+    #[track_caller]
+    pub fn assert_balances(&self, addr: &Addr, usdc: u128, cash: u128, lt: u128) -> &Self {
+        let usdc_balance = self.app.wrap().query_balance(addr, DENOM).unwrap();
+        assert_eq!(usdc_balance, coin(usdc, DENOM), "Usdc balance mismatch");
+
+        for (token, expected, name) in [(&self.cash, cash, "Cash"), (&self.lt, lt, "Lt")] {
+            let balance: cw20::BalanceResponse = self
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    token.clone(),
+                    &cw20_base::msg::QueryMsg::Balance {
+                        address: addr.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                balance.balance.u128(),
+                expected,
+                "{} balance mismatch",
+                name
+            );
+        }
+
+        self
+    }
+}
+
+/// Builder helping construction of `Suite` helper
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct SuiteConfig {
+    /// Tested pair contract
+    #[derivative(Debug = "ignore")]
+    pair: Box<dyn Contract<Empty>>,
+    /// Initial actors: address, usdc, cash
+    actors: Vec<(String, u128, u128)>,
+    /// Amplification coefficient
+    amp: Option<u64>,
+    /// Weights of usdc and cash
+    weights: Option<[Decimal; 2]>,
+}
+
+impl SuiteConfig {
+    pub fn new(pair: Box<dyn Contract<Empty>>) -> Self {
+        Self {
+            pair,
+            actors: vec![],
+            amp: None,
+            weights: None,
+        }
+    }
+
+    pub fn with_actor(mut self, addr: &str, usdc: u128, cash: u128) -> Self {
+        self.actors.push((addr.to_owned(), usdc, cash));
+        self
+    }
+
+    pub fn with_amp(mut self, amp: u64) -> Self {
+        self.amp = Some(amp);
+        self
+    }
+
+    pub fn with_weights(mut self, usdc: Decimal, cash: Decimal) -> Self {
+        self.weights = Some([usdc, cash]);
+        self
+    }
+
+    pub fn init(self) -> Result<Suite> {
+        let mut app = mock_app();
+
+        let cw20_id = app.store_code(contract_cw20());
+        let pair_id = app.store_code(self.pair);
+
+        let admin = Addr::unchecked("admin");
+
+        let mut actors = vec![];
+        let mut initial_balances = vec![];
+        for (addr, usdc, cash) in self.actors {
+            app.execute(
+                Addr::unchecked(TEST_RESERVE),
+                BankMsg::Send {
+                    to_address: addr.clone(),
+                    amount: coins(usdc, DENOM),
+                }
+                .into(),
+            )?;
+            initial_balances.push(Cw20Coin {
+                address: addr.clone(),
+                amount: Uint128::new(cash),
+            });
+            actors.push(Addr::unchecked(addr));
+        }
+
+        let cash = app
+            .instantiate_contract(
+                cw20_id,
+                admin.clone(),
+                &cw20_base::msg::InstantiateMsg {
+                    name: "Cash Money".to_owned(),
+                    symbol: "cash".to_owned(),
+                    decimals: 6,
+                    initial_balances,
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                "Cash",
+                None,
+            )
+            .map_err(|err| anyhow!(err))?;
+
+        let instantiate_msg = InstantiateMsg::new(
+            [
+                AssetInfo::Native(DENOM.to_owned()),
+                AssetInfo::Token(cash.clone()),
+            ],
+            cw20_id,
+        );
+
+        let instantiate_msg = match self.amp {
+            Some(amp) => instantiate_msg.with_amp(amp),
+            None => instantiate_msg,
+        };
+        let instantiate_msg = match self.weights {
+            Some(weights) => instantiate_msg.with_weights(weights),
+            None => instantiate_msg,
+        };
+
+        let pair = app
+            .instantiate_contract(pair_id, admin, &instantiate_msg, &[], "Pair", None)
+            .map_err(|err| anyhow!(err))?;
+
+        let PairInfo {
+            liquidity_token: lt,
+            ..
+        } = app
+            .wrap()
+            .query_wasm_smart(pair.clone(), &QueryMsg::Pair {})
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(Suite {
+            app,
+            cash,
+            pair,
+            lt,
+            actors,
+        })
+    }
+}
+
+/// Reverse simulation results are not validated directly, as due to calculation precision it is
+/// possible, reverse simulation might return range of results. Config is expected to have curve
+/// parameters set, pools are initial usdc and cash liquidity.
+pub fn reverse_simulate(config: SuiteConfig, pools: (u128, u128)) {
+    let mut suite = config
+        .with_actor("liquidity-provider", pools.0, pools.1)
+        .init()
+        .unwrap();
+
+    let lp = suite.actors[0].clone();
+    suite.provide_liquidity(&lp, pools.0, pools.1).unwrap();
+
+    for (ask, offer) in [(suite.cash(), suite.usdc()), (suite.usdc(), suite.cash())] {
+        let rev_simulation = suite.simulate_reverse_swap(1000, ask).unwrap();
+        let simulation = suite
+            .simulate_swap(rev_simulation.offer_amount.u128(), offer)
+            .unwrap();
+
+        assert!(
+            (1000..1003).contains(&simulation.return_amount.u128()),
+            "{:?}",
+            simulation
+        );
+    }
+}
+
+/// Buying exact amount takes reverse simulated offer and refunds the rest of maximum offer.
+/// Config is expected to have curve parameters set, pools are initial usdc and cash liquidity.
+pub fn swap_exact_out(config: SuiteConfig, pools: (u128, u128)) {
+    let mut suite = config
+        .with_actor("liquidity-provider", pools.0, pools.1)
+        .with_actor("trader", 1000, 1)
+        .init()
+        .unwrap();
+
+    let (lp, trader, pair) = (
+        suite.actors[0].clone(),
+        suite.actors[1].clone(),
+        suite.pair.clone(),
+    );
+    suite.provide_liquidity(&lp, pools.0, pools.1).unwrap();
+
+    let offer = suite
+        .simulate_reverse_swap(500, suite.cash())
+        .unwrap()
+        .offer_amount;
+
+    let err = suite
+        .swap_exact_out_usdc(&trader, offer.u128() - 1, 500)
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::MaxOfferAssertion {
+            offer_amount: offer,
+            max_offer: offer - Uint128::new(1),
+        }
+        .to_string()
+    );
+
+    suite.swap_exact_out_usdc(&trader, 1000, 500).unwrap();
+    suite
+        .assert_balances(&trader, 1000 - offer.u128(), 501, 0)
+        .assert_balances(&pair, pools.0 + offer.u128(), pools.1 - 500, 1000);
+}
+
+/// Curve pairs don't accumulate constant product prices - neither swaps, nor liquidity changes
+/// store them. Config is expected to have curve parameters set, pools are initial usdc and cash
+/// liquidity.
+pub fn no_price_accumulators(config: SuiteConfig, pools: (u128, u128)) {
+    let mut suite = config
+        .with_actor("liquidity-provider", pools.0, pools.1)
+        .with_actor("trader", 1000, 0)
+        .init()
+        .unwrap();
+
+    let (lp, trader) = (suite.actors[0].clone(), suite.actors[1].clone());
+    suite
+        .provide_liquidity(&lp, pools.0, pools.1)
+        .unwrap()
+        .swap_usdc(&trader, 1000)
+        .unwrap()
+        .withdraw_liquidity(&lp, 1000)
+        .unwrap();
+
+    let prices = suite
+        .app
+        .wrap()
+        .query_wasm_raw(suite.pair.clone(), CUMULATIVE_PRICES.as_slice())
+        .unwrap();
+    assert_eq!(prices, None);
+}
+
+/// Generates a test for every curve independent scenario of this suite. `config` is an expression
+/// creating `SuiteConfig` with curve parameters set, `pools` are initial usdc and cash liquidity.
+#[macro_export]
+macro_rules! curve_scenarios {
+    ($config:expr, $pools:expr) => {
+        #[test]
+        fn reverse_simulate() {
+            $crate::reverse_simulate($config, $pools);
+        }
+
+        #[test]
+        fn swap_exact_out() {
+            $crate::swap_exact_out($config, $pools);
+        }
+
+        #[test]
+        fn no_price_accumulators() {
+            $crate::no_price_accumulators($config, $pools);
+        }
+    };
+}
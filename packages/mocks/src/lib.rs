@@ -103,7 +103,7 @@ impl WasmMockQuerier {
     pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
         match &request {
             QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => match from_binary(msg) {
-                Ok(FactoryQueryMsg::Pair { asset_infos }) => {
+                Ok(FactoryQueryMsg::Pair { asset_infos, .. }) => {
                     let key = asset_infos[0].to_string() + asset_infos[1].to_string().as_str();
                     match self.tfi_factory_querier.pairs.get(&key) {
                         Some(v) => SystemResult::Ok(ContractResult::Ok(to_binary(&v).unwrap())),
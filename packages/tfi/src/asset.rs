@@ -4,8 +4,8 @@ use std::fmt;
 
 use crate::querier::{query_balance, query_token_balance};
 use cosmwasm_std::{
-    to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, MessageInfo, QuerierWrapper, StdError,
-    StdResult, Uint128, WasmMsg,
+    to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Decimal, MessageInfo, QuerierWrapper,
+    StdError, StdResult, Uint128, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
 
@@ -45,6 +45,29 @@ impl Asset {
         }
     }
 
+    /// Sends the asset to `contract`, executing `msg` on it - cw20 tokens are sent with
+    /// `Cw20ExecuteMsg::Send`, native tokens as funds of `msg` execution
+    pub fn into_send_msg(self, contract: Addr, msg: Binary) -> StdResult<CosmosMsg> {
+        let amount = self.amount;
+
+        match &self.info {
+            AssetInfo::Token(contract_addr) => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: contract.to_string(),
+                    amount,
+                    msg,
+                })?,
+                funds: vec![],
+            })),
+            AssetInfo::Native(_) => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract.to_string(),
+                msg,
+                funds: vec![self.to_coin()?],
+            })),
+        }
+    }
+
     pub fn to_coin(&self) -> StdResult<Coin> {
         match &self.info {
             AssetInfo::Native(denom) => Ok(Coin {
@@ -146,20 +169,64 @@ impl AssetInfo {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[non_exhaustive]
 pub struct PairInfo {
-    pub asset_infos: [AssetInfo; 2],
+    /// Pooled assets, there are at least two of them
+    pub asset_infos: Vec<AssetInfo>,
     pub contract_addr: Addr,
+    /// Empty for concentrated pairs, which keep liquidity in positions instead
     pub liquidity_token: Addr,
     #[serde(default = "default_commission")]
     pub commission: Decimal,
+    /// Name of the pair type, as registered in the factory
+    #[serde(default = "default_pair_type")]
+    pub pair_type: String,
+    /// tg4 group whitelisting addresses allowed to swap, provide and withdraw liquidity, if any
+    #[serde(default)]
+    pub whitelist_group: Option<Addr>,
+    /// Address allowed to pause the pair, if any
+    #[serde(default)]
+    pub admin: Option<Addr>,
+    /// Pair operations currently paused by the admin
+    #[serde(default)]
+    pub paused: PauseState,
+}
+
+/// Pair operations which can be paused separately
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default, JsonSchema)]
+pub struct PauseState {
+    /// All kinds of swaps, including flash swaps and single sided liquidity operations
+    pub swaps: bool,
+    /// Liquidity provision
+    pub deposits: bool,
+    /// Liquidity withdrawal
+    pub withdrawals: bool,
+}
+
+impl PauseState {
+    /// Everything paused
+    pub fn all() -> Self {
+        Self {
+            swaps: true,
+            deposits: true,
+            withdrawals: true,
+        }
+    }
 }
 
 impl PairInfo {
-    pub fn new(asset_infos: [AssetInfo; 2], contract_addr: Addr, liquidity_token: Addr) -> Self {
+    pub fn new(
+        asset_infos: impl Into<Vec<AssetInfo>>,
+        contract_addr: Addr,
+        liquidity_token: Addr,
+    ) -> Self {
         Self {
-            asset_infos,
+            asset_infos: asset_infos.into(),
             contract_addr,
             liquidity_token,
             commission: default_commission(),
+            pair_type: default_pair_type(),
+            whitelist_group: None,
+            admin: None,
+            paused: PauseState::default(),
         }
     }
 
@@ -168,26 +235,51 @@ impl PairInfo {
         self
     }
 
+    pub fn with_pair_type(mut self, pair_type: impl Into<String>) -> Self {
+        self.pair_type = pair_type.into();
+        self
+    }
+
+    pub fn with_whitelist_group(mut self, whitelist_group: Addr) -> Self {
+        self.whitelist_group = Some(whitelist_group);
+        self
+    }
+
+    pub fn with_admin(mut self, admin: Addr) -> Self {
+        self.admin = Some(admin);
+        self
+    }
+
     pub fn query_pools(
         &self,
         querier: &QuerierWrapper,
         contract_addr: Addr,
-    ) -> StdResult<[Asset; 2]> {
-        let info_0 = self.asset_infos[0].clone();
-        let info_1 = self.asset_infos[1].clone();
-        Ok([
-            Asset {
-                amount: info_0.query_pool(querier, contract_addr.clone())?,
-                info: info_0,
-            },
-            Asset {
-                amount: info_1.query_pool(querier, contract_addr)?,
-                info: info_1,
-            },
-        ])
+    ) -> StdResult<Vec<Asset>> {
+        self.asset_infos
+            .iter()
+            .map(|info| {
+                Ok(Asset {
+                    amount: info.query_pool(querier, contract_addr.clone())?,
+                    info: info.clone(),
+                })
+            })
+            .collect()
     }
 }
 
 pub(crate) fn default_commission() -> Decimal {
     Decimal::permille(3)
 }
+
+/// Constant product pair type, served by `tfi-pair`
+pub const XYK_PAIR_TYPE: &str = "xyk";
+/// Stableswap pair type, served by `tfi-stable-pair`
+pub const STABLE_PAIR_TYPE: &str = "stable";
+/// Weighted constant product pair type, served by `tfi-weighted-pair`
+pub const WEIGHTED_PAIR_TYPE: &str = "weighted";
+/// Concentrated liquidity pair type, served by `tfi-concentrated-pair`
+pub const CONCENTRATED_PAIR_TYPE: &str = "concentrated";
+
+pub(crate) fn default_pair_type() -> String {
+    XYK_PAIR_TYPE.to_owned()
+}
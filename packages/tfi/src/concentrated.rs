@@ -0,0 +1,190 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::{Asset, AssetInfo};
+
+use cosmwasm_std::{Addr, Binary, Decimal, Decimal256, Uint128};
+use cw20::Cw20ReceiveMsg;
+
+/// Concentrated pairs are instantiated with `tfi::pair::InstantiateMsg`, which has to contain
+/// `initial_price` (amount of the second asset paid for the first one). `tick_spacing` is 1 by
+/// default.
+pub use crate::pair::InstantiateMsg;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    Receive(Cw20ReceiveMsg),
+    /// Opens new position of the sender providing liquidity between `lower_tick` and
+    /// `upper_tick`. Only the part of `assets` matching the current price is used, cw20 tokens
+    /// are collected with `TransferFrom`, and excess of native tokens is refunded.
+    CreatePosition {
+        lower_tick: i32,
+        upper_tick: i32,
+        /// Maximum deposit per pooled asset
+        assets: Vec<Asset>,
+        /// Block time (in seconds) after which the operation fails
+        #[serde(default)]
+        deadline: Option<u64>,
+    },
+    /// Adds liquidity to the position, deposits are collected the same way as on
+    /// `CreatePosition`. Only the position owner can execute it.
+    IncreaseLiquidity {
+        position_id: u64,
+        /// Maximum deposit per pooled asset
+        assets: Vec<Asset>,
+        /// Block time (in seconds) after which the operation fails
+        #[serde(default)]
+        deadline: Option<u64>,
+    },
+    /// Removes `liquidity` from the position, paying out its assets together with uncollected
+    /// fees. Position left without liquidity is closed. Only the position owner can execute it.
+    DecreaseLiquidity {
+        position_id: u64,
+        liquidity: Uint128,
+        /// Block time (in seconds) after which the operation fails
+        #[serde(default)]
+        deadline: Option<u64>,
+    },
+    /// Pays out fees earned by the position. Only the position owner can execute it.
+    CollectFees {
+        position_id: u64,
+    },
+    /// Transfers position ownership to `recipient`. Only the position owner can execute it.
+    TransferPosition {
+        position_id: u64,
+        recipient: String,
+    },
+    /// Swap an offer asset to the other, the same as `tfi::pair::ExecuteMsg::Swap`
+    Swap {
+        offer_asset: Asset,
+        /// Asset to be returned, the other pooled asset if not given
+        #[serde(default)]
+        ask_asset_info: Option<AssetInfo>,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+        /// Minimum amount to be returned, the swap fails if it is not met
+        #[serde(default)]
+        min_return: Option<Uint128>,
+        /// Block time (in seconds) after which the operation fails
+        #[serde(default)]
+        deadline: Option<u64>,
+        /// If set, the return is sent to `to` (or sender) contract executing this message on it -
+        /// with cw20 `Send`, or as funds for native assets
+        #[serde(default)]
+        to_msg: Option<Binary>,
+    },
+    /// Pauses or unpauses given pair operations, the same as `tfi::pair::ExecuteMsg::UpdatePause`.
+    /// Only the pair admin can execute it.
+    UpdatePause {
+        swaps: Option<bool>,
+        deposits: Option<bool>,
+        withdrawals: Option<bool>,
+    },
+    /// Updates swap commission of traders not reaching any commission tier. Only the pair admin
+    /// can execute it.
+    UpdateCommission {
+        commission: Decimal,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Sell a given amount of asset, the same as `tfi::pair::Cw20HookMsg::Swap`
+    Swap {
+        /// Asset to be returned, the other pooled asset if not given
+        #[serde(default)]
+        ask_asset_info: Option<AssetInfo>,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+        /// Minimum amount to be returned, the swap fails if it is not met
+        #[serde(default)]
+        min_return: Option<Uint128>,
+        /// Block time (in seconds) after which the operation fails
+        #[serde(default)]
+        deadline: Option<u64>,
+        /// If set, the return is sent to `to` (or sender) contract executing this message on it -
+        /// with cw20 `Send`, or as funds for native assets
+        #[serde(default)]
+        to_msg: Option<Binary>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Returns `tfi::asset::PairInfo`, the same as `tfi::pair::QueryMsg::Pair`
+    Pair {},
+    Config {},
+    /// Returns current price and liquidity
+    Pool {},
+    /// Simulates swap of `offer_asset`, returns `tfi::pair::SimulationResponse`
+    Simulation {
+        offer_asset: Asset,
+        /// Asset to be returned, the other pooled asset if not given
+        #[serde(default)]
+        ask_asset_info: Option<AssetInfo>,
+        /// Trader whose commission tier is applied, pair commission is used if not given
+        #[serde(default)]
+        trader: Option<String>,
+    },
+    /// Simulates swap returning `ask_asset`, returns `tfi::pair::ReverseSimulationResponse`
+    ReverseSimulation {
+        ask_asset: Asset,
+        /// Asset to be offered, the other pooled asset if not given
+        #[serde(default)]
+        offer_asset_info: Option<AssetInfo>,
+        /// Trader whose commission tier is applied, pair commission is used if not given
+        #[serde(default)]
+        trader: Option<String>,
+    },
+    Position {
+        position_id: u64,
+    },
+    /// Returns positions owned by `owner`, sorted by id
+    Positions {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub asset_infos: Vec<AssetInfo>,
+    pub commission: Decimal,
+    pub tick_spacing: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PoolResponse {
+    /// Amount of the second asset paid for the first one
+    pub price: Decimal256,
+    /// Square root of `price`, which is the value tracked by the pool
+    pub sqrt_price: Decimal256,
+    /// Greatest tick with price not above the current price
+    pub tick: i32,
+    /// Liquidity of positions with range containing the current price
+    pub liquidity: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PositionResponse {
+    pub position_id: u64,
+    pub owner: Addr,
+    pub lower_tick: i32,
+    pub upper_tick: i32,
+    pub liquidity: Uint128,
+    /// Assets which would be paid out if all position liquidity was removed at current price
+    pub assets: Vec<Asset>,
+    /// Fees earned by the position and not collected yet
+    pub fees: Vec<Asset>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PositionsResponse {
+    pub positions: Vec<PositionResponse>,
+}
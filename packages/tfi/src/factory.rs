@@ -1,18 +1,31 @@
-use cosmwasm_std::Decimal;
+use cosmwasm_std::{Binary, Decimal};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::asset::{default_commission, AssetInfo, PairInfo};
+use crate::asset::{default_commission, default_pair_type, AssetInfo, PairInfo};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[non_exhaustive]
 pub struct InstantiateMsg {
-    /// Pair contract code ID, which is used to
+    /// Pair contract code ID, which is used to create `xyk` pairs
     pub pair_code_id: u64,
     pub token_code_id: u64,
-    /// Default commission to be set on newly created pair, 0.003 by default
+    /// Default commission to be set on newly created `xyk` pair, 0.003 by default
     #[serde(default = "default_commission")]
     pub default_commission: Decimal,
+    /// Additional pair types which can be created by the factory
+    #[serde(default)]
+    pub pair_types: Vec<PairType>,
+    /// Address receiving protocol fees collected on newly created pairs
+    #[serde(default)]
+    pub fee_collector: Option<String>,
+    /// Part of swap commission going to `fee_collector` instead of liquidity providers on newly
+    /// created pairs, 0 by default
+    #[serde(default)]
+    pub protocol_fee_share: Decimal,
+    /// `trusted-token` code ID, used for liquidity tokens of whitelisted pairs
+    #[serde(default)]
+    pub trusted_token_code_id: Option<u64>,
 }
 
 impl InstantiateMsg {
@@ -21,6 +34,54 @@ impl InstantiateMsg {
             pair_code_id,
             token_code_id,
             default_commission: default_commission(),
+            pair_types: vec![],
+            fee_collector: None,
+            protocol_fee_share: Decimal::zero(),
+            trusted_token_code_id: None,
+        }
+    }
+
+    pub fn with_default_commission(mut self, commission: Decimal) -> Self {
+        self.default_commission = commission;
+        self
+    }
+
+    pub fn with_pair_type(mut self, pair_type: PairType) -> Self {
+        self.pair_types.push(pair_type);
+        self
+    }
+
+    pub fn with_protocol_fee(mut self, fee_collector: impl Into<String>, share: Decimal) -> Self {
+        self.fee_collector = Some(fee_collector.into());
+        self.protocol_fee_share = share;
+        self
+    }
+
+    pub fn with_trusted_token_code_id(mut self, id: u64) -> Self {
+        self.trusted_token_code_id = Some(id);
+        self
+    }
+}
+
+/// Named pair type which can be created by the factory
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[non_exhaustive]
+pub struct PairType {
+    /// Name of the type, eg. `xyk` or `stable`
+    pub name: String,
+    /// Pair contract code ID used to create pairs of this type
+    pub code_id: u64,
+    /// Default commission to be set on newly created pairs of this type, 0.003 by default
+    #[serde(default = "default_commission")]
+    pub default_commission: Decimal,
+}
+
+impl PairType {
+    pub fn new(name: impl Into<String>, code_id: u64) -> Self {
+        Self {
+            name: name.into(),
+            code_id,
+            default_commission: default_commission(),
         }
     }
 
@@ -33,20 +94,87 @@ impl InstantiateMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    /// UpdateConfig update relevant code IDs
+    /// UpdateConfig update relevant code IDs and protocol fee settings. Protocol fee settings are
+    /// applied to pairs created afterwards.
     UpdateConfig {
         owner: Option<String>,
         token_code_id: Option<u64>,
-        pair_code_id: Option<u64>,
-        default_commission: Option<Decimal>,
+        #[serde(default)]
+        fee_collector: Option<String>,
+        #[serde(default)]
+        protocol_fee_share: Option<Decimal>,
+        #[serde(default)]
+        trusted_token_code_id: Option<u64>,
     },
+    /// UpdatePairType registers new pair type, or overwrites existing one with the same name
+    UpdatePairType { pair_type: PairType },
     /// CreatePair instantiates pair contract
     CreatePair {
-        /// Asset infos
-        asset_infos: [AssetInfo; 2],
-        /// Commission on created pair. If none, default commission from factory configuration would
-        /// be used.
+        /// Type of created pair, `xyk` by default
+        #[serde(default = "default_pair_type")]
+        pair_type: String,
+        /// Asset infos - exactly two of them, except of stable pairs which can pool up to four
+        asset_infos: Vec<AssetInfo>,
+        /// Commission on created pair. If none, default commission of the pair type would be used.
         commission: Option<Decimal>,
+        /// Amplification coefficient, required by `stable` pairs
+        #[serde(default)]
+        amp: Option<u64>,
+        /// Weights of `asset_infos`, required by `weighted` pairs
+        #[serde(default)]
+        weights: Option<Vec<Decimal>>,
+        /// Initial price, required by `concentrated` pairs
+        #[serde(default)]
+        initial_price: Option<Decimal>,
+        /// Tick spacing of `concentrated` pairs, 1 by default
+        #[serde(default)]
+        tick_spacing: Option<u32>,
+        /// tg4 group whitelisting addresses allowed to trade on created pair. Its liquidity token
+        /// is created as `trusted-token` with the same whitelist.
+        #[serde(default)]
+        whitelist_group: Option<String>,
+    },
+    /// Pauses or unpauses given operations of the pair created by the factory, others are left
+    /// unchanged
+    UpdatePairPause {
+        asset_infos: Vec<AssetInfo>,
+        /// Type of the pair, `xyk` by default
+        #[serde(default = "default_pair_type")]
+        pair_type: String,
+        swaps: Option<bool>,
+        deposits: Option<bool>,
+        withdrawals: Option<bool>,
+    },
+    /// Updates commission of the pair created by the factory
+    UpdatePairCommission {
+        asset_infos: Vec<AssetInfo>,
+        /// Type of the pair, `xyk` by default
+        #[serde(default = "default_pair_type")]
+        pair_type: String,
+        commission: Decimal,
+    },
+    /// Pauses all operations of every pair created by the factory. Pause state of single pairs is
+    /// kept, and applies again after `UnpauseAll`. Pairs are updated in pages of `limit`, starting
+    /// after `start_after` pair. Pairs the factory is not admin of are reported as skipped.
+    PauseAll {
+        #[serde(default)]
+        start_after: Option<Vec<AssetInfo>>,
+        /// Type of the `start_after` pair, `xyk` by default
+        #[serde(default)]
+        start_after_pair_type: Option<String>,
+        #[serde(default)]
+        limit: Option<u32>,
+    },
+    /// Lifts the pause of `PauseAll`, leaving pairs with their own pause state. Pairs are updated
+    /// in pages of `limit`, starting after `start_after` pair.
+    UnpauseAll {
+        #[serde(default)]
+        start_after: Option<Vec<AssetInfo>>,
+        /// Type of the `start_after` pair, `xyk` by default
+        #[serde(default)]
+        start_after_pair_type: Option<String>,
+        #[serde(default)]
+        limit: Option<u32>,
     },
 }
 
@@ -56,8 +184,9 @@ pub enum ExecuteMsg {
 pub struct ExecuteUpdateConfig {
     pub owner: Option<String>,
     pub token_code_id: Option<u64>,
-    pub pair_code_id: Option<u64>,
-    pub default_commission: Option<Decimal>,
+    pub fee_collector: Option<String>,
+    pub protocol_fee_share: Option<Decimal>,
+    pub trusted_token_code_id: Option<u64>,
 }
 
 impl ExecuteUpdateConfig {
@@ -75,13 +204,18 @@ impl ExecuteUpdateConfig {
         self
     }
 
-    pub fn with_pair_code_id(mut self, id: u64) -> Self {
-        self.pair_code_id = Some(id);
+    pub fn with_fee_collector(mut self, fee_collector: impl Into<String>) -> Self {
+        self.fee_collector = Some(fee_collector.into());
         self
     }
 
-    pub fn with_default_commission(mut self, commission: Decimal) -> Self {
-        self.default_commission = Some(commission);
+    pub fn with_protocol_fee_share(mut self, share: Decimal) -> Self {
+        self.protocol_fee_share = Some(share);
+        self
+    }
+
+    pub fn with_trusted_token_code_id(mut self, id: u64) -> Self {
+        self.trusted_token_code_id = Some(id);
         self
     }
 }
@@ -91,41 +225,96 @@ impl From<ExecuteUpdateConfig> for ExecuteMsg {
         Self::UpdateConfig {
             owner: src.owner,
             token_code_id: src.token_code_id,
-            pair_code_id: src.pair_code_id,
-            default_commission: src.default_commission,
+            fee_collector: src.fee_collector,
+            protocol_fee_share: src.protocol_fee_share,
+            trusted_token_code_id: src.trusted_token_code_id,
         }
     }
 }
 
-/// Utility for creating `ExecuteMsg::UpdatePair` variant
+/// Utility for creating `ExecuteMsg::CreatePair` variant
 #[derive(Clone, Debug, PartialEq)]
 #[non_exhaustive]
 pub struct ExecuteCreatePair {
+    /// Pair type
+    pair_type: String,
     /// Asset infos
-    asset_infos: [AssetInfo; 2],
+    asset_infos: Vec<AssetInfo>,
     /// Commision on created pair
     commission: Option<Decimal>,
+    /// Amplification coefficient
+    amp: Option<u64>,
+    /// Asset weights
+    weights: Option<Vec<Decimal>>,
+    /// Initial price
+    initial_price: Option<Decimal>,
+    /// Tick spacing
+    tick_spacing: Option<u32>,
+    /// Whitelist group
+    whitelist_group: Option<String>,
 }
 
 impl ExecuteCreatePair {
-    pub fn new(asset_infos: [AssetInfo; 2]) -> Self {
+    pub fn new(asset_infos: impl Into<Vec<AssetInfo>>) -> Self {
         Self {
-            asset_infos,
+            pair_type: default_pair_type(),
+            asset_infos: asset_infos.into(),
             commission: None,
+            amp: None,
+            weights: None,
+            initial_price: None,
+            tick_spacing: None,
+            whitelist_group: None,
         }
     }
 
+    pub fn with_pair_type(mut self, pair_type: impl Into<String>) -> Self {
+        self.pair_type = pair_type.into();
+        self
+    }
+
     pub fn with_commission(mut self, commission: Decimal) -> Self {
         self.commission = Some(commission);
         self
     }
+
+    pub fn with_amp(mut self, amp: u64) -> Self {
+        self.amp = Some(amp);
+        self
+    }
+
+    pub fn with_weights(mut self, weights: impl Into<Vec<Decimal>>) -> Self {
+        self.weights = Some(weights.into());
+        self
+    }
+
+    pub fn with_initial_price(mut self, initial_price: Decimal) -> Self {
+        self.initial_price = Some(initial_price);
+        self
+    }
+
+    pub fn with_tick_spacing(mut self, tick_spacing: u32) -> Self {
+        self.tick_spacing = Some(tick_spacing);
+        self
+    }
+
+    pub fn with_whitelist_group(mut self, whitelist_group: impl Into<String>) -> Self {
+        self.whitelist_group = Some(whitelist_group.into());
+        self
+    }
 }
 
 impl From<ExecuteCreatePair> for ExecuteMsg {
     fn from(src: ExecuteCreatePair) -> Self {
         Self::CreatePair {
+            pair_type: src.pair_type,
             asset_infos: src.asset_infos,
             commission: src.commission,
+            amp: src.amp,
+            weights: src.weights,
+            initial_price: src.initial_price,
+            tick_spacing: src.tick_spacing,
+            whitelist_group: src.whitelist_group,
         }
     }
 }
@@ -135,10 +324,15 @@ impl From<ExecuteCreatePair> for ExecuteMsg {
 pub enum QueryMsg {
     Config {},
     Pair {
-        asset_infos: [AssetInfo; 2],
+        asset_infos: Vec<AssetInfo>,
+        /// Type of the pair, `xyk` by default
+        #[serde(default = "default_pair_type")]
+        pair_type: String,
     },
     Pairs {
-        start_after: Option<[AssetInfo; 2]>,
+        start_after: Option<Vec<AssetInfo>>,
+        /// Type of the `start_after` pair, `xyk` by default
+        start_after_pair_type: Option<String>,
         limit: Option<u32>,
     },
 }
@@ -147,15 +341,32 @@ pub enum QueryMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub owner: String,
-    pub pair_code_id: u64,
     pub token_code_id: u64,
-    pub default_commission: Decimal,
     pub migrate_admin: Option<String>,
+    /// All pair types registered in the factory
+    pub pair_types: Vec<PairType>,
+    /// Address receiving protocol fees on newly created pairs
+    pub fee_collector: Option<String>,
+    /// Part of swap commission going to `fee_collector` on newly created pairs
+    pub protocol_fee_share: Decimal,
+    /// `trusted-token` code ID used for liquidity tokens of whitelisted pairs
+    #[serde(default)]
+    pub trusted_token_code_id: Option<u64>,
+    /// If `PauseAll` was executed after `UnpauseAll` - pairs it didn't reach yet are not paused
+    #[serde(default)]
+    pub all_paused: bool,
 }
 
-/// We currently take no arguments for migrations
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct MigrateMsg {}
+/// Pairs stored under keys of previous versions are moved to current keys in pages of `limit`,
+/// starting after `start_after` - the raw key reported in `last_pair_key` attribute of the previous
+/// migration
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    #[serde(default)]
+    pub start_after: Option<Binary>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -1,7 +1,9 @@
 pub mod asset;
+pub mod concentrated;
 pub mod factory;
 pub mod pair;
 pub mod querier;
+pub mod router;
 pub mod token;
 
 #[cfg(test)]
@@ -3,27 +3,77 @@ use serde::{Deserialize, Serialize};
 
 use crate::asset::{default_commission, Asset, AssetInfo};
 
-use cosmwasm_std::{Decimal, Uint128};
+use cosmwasm_std::{
+    to_binary, Binary, CosmosMsg, Decimal, Decimal256, StdResult, Uint128, WasmMsg,
+};
 use cw20::Cw20ReceiveMsg;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[non_exhaustive]
 pub struct InstantiateMsg {
-    /// Asset infos
-    pub asset_infos: [AssetInfo; 2],
-    /// Token contract code id for initialization
+    /// Asset infos - exactly two of them, except of stable pairs which can pool more
+    pub asset_infos: Vec<AssetInfo>,
+    /// Token contract code id for initialization, has to be `trusted-token` if `whitelist_group`
+    /// is set
     pub token_code_id: u64,
     /// Commission to be applied on swaps, 0.3% by default
     #[serde(default = "default_commission")]
     pub commission: Decimal,
+    /// Amplification coefficient, used only by stable pairs
+    #[serde(default)]
+    pub amp: Option<u64>,
+    /// Weights of `asset_infos`, in the same order, used only by weighted pairs. They have to sum
+    /// up to 1.
+    #[serde(default)]
+    pub weights: Option<Vec<Decimal>>,
+    /// Initial pool price (amount of the second asset paid for the first one), used only by
+    /// concentrated pairs
+    #[serde(default)]
+    pub initial_price: Option<Decimal>,
+    /// Position tick bounds have to be multiples of it, used only by concentrated pairs, 1 by
+    /// default
+    #[serde(default)]
+    pub tick_spacing: Option<u32>,
+    /// Address receiving protocol fees, required if `protocol_fee_share` is not zero
+    #[serde(default)]
+    pub fee_collector: Option<String>,
+    /// Part of swap commission going to `fee_collector` instead of liquidity providers, 0 by
+    /// default
+    #[serde(default)]
+    pub protocol_fee_share: Decimal,
+    /// tg4 group whitelisting addresses allowed to swap, provide and withdraw liquidity, and to
+    /// hold liquidity tokens. Anyone is allowed if not set. The pair itself doesn't have to be a
+    /// member for its liquidity token, only for pool assets whitelisted by the same group.
+    #[serde(default)]
+    pub whitelist_group: Option<String>,
+    /// tg4 group which member points select the swap commission from `commission_tiers`
+    #[serde(default)]
+    pub commission_group: Option<String>,
+    /// Swap commission tiers, requires `commission_group`. Traders not reaching any tier pay
+    /// `commission`.
+    #[serde(default)]
+    pub commission_tiers: Vec<CommissionTier>,
+    /// Address allowed to pause pair operations, the pair cannot be paused if not set
+    #[serde(default)]
+    pub admin: Option<String>,
 }
 
 impl InstantiateMsg {
-    pub fn new(asset_infos: [AssetInfo; 2], token_code_id: u64) -> Self {
+    pub fn new(asset_infos: impl Into<Vec<AssetInfo>>, token_code_id: u64) -> Self {
         Self {
-            asset_infos,
+            asset_infos: asset_infos.into(),
             token_code_id,
             commission: default_commission(),
+            amp: None,
+            weights: None,
+            initial_price: None,
+            tick_spacing: None,
+            fee_collector: None,
+            protocol_fee_share: Decimal::zero(),
+            whitelist_group: None,
+            commission_group: None,
+            commission_tiers: vec![],
+            admin: None,
         }
     }
 
@@ -31,6 +81,69 @@ impl InstantiateMsg {
         self.commission = commission;
         self
     }
+
+    pub fn with_amp(mut self, amp: u64) -> Self {
+        self.amp = Some(amp);
+        self
+    }
+
+    pub fn with_weights(mut self, weights: impl Into<Vec<Decimal>>) -> Self {
+        self.weights = Some(weights.into());
+        self
+    }
+
+    pub fn with_initial_price(mut self, initial_price: Decimal) -> Self {
+        self.initial_price = Some(initial_price);
+        self
+    }
+
+    pub fn with_tick_spacing(mut self, tick_spacing: u32) -> Self {
+        self.tick_spacing = Some(tick_spacing);
+        self
+    }
+
+    pub fn with_protocol_fee(mut self, fee_collector: impl Into<String>, share: Decimal) -> Self {
+        self.fee_collector = Some(fee_collector.into());
+        self.protocol_fee_share = share;
+        self
+    }
+
+    pub fn with_whitelist_group(mut self, whitelist_group: impl Into<String>) -> Self {
+        self.whitelist_group = Some(whitelist_group.into());
+        self
+    }
+
+    pub fn with_commission_tiers(
+        mut self,
+        commission_group: impl Into<String>,
+        commission_tiers: Vec<CommissionTier>,
+    ) -> Self {
+        self.commission_group = Some(commission_group.into());
+        self.commission_tiers = commission_tiers;
+        self
+    }
+
+    pub fn with_admin(mut self, admin: impl Into<String>) -> Self {
+        self.admin = Some(admin.into());
+        self
+    }
+}
+
+/// Swap commission for traders with at least `min_points` in the commission group. If trader
+/// reaches multiple tiers, the one with the highest `min_points` applies.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CommissionTier {
+    pub min_points: u64,
+    pub commission: Decimal,
+}
+
+impl CommissionTier {
+    pub fn new(min_points: u64, commission: Decimal) -> Self {
+        Self {
+            min_points,
+            commission,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -39,16 +152,136 @@ pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
     /// ProvideLiquidity a user provides pool liquidity
     ProvideLiquidity {
-        assets: [Asset; 2],
+        /// One deposit per pooled asset
+        assets: Vec<Asset>,
         slippage_tolerance: Option<Decimal>,
+        /// Block time (in seconds) after which the operation fails
+        #[serde(default)]
+        deadline: Option<u64>,
     },
     /// Swap an offer asset to the other
     Swap {
         offer_asset: Asset,
+        /// Asset to be returned, required by pairs of more than two assets
+        #[serde(default)]
+        ask_asset_info: Option<AssetInfo>,
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        /// Minimum amount to be returned, the swap fails if it is not met
+        #[serde(default)]
+        min_return: Option<Uint128>,
+        /// Block time (in seconds) after which the operation fails
+        #[serde(default)]
+        deadline: Option<u64>,
+        /// If set, the return is sent to `to` (or sender) contract executing this message on it -
+        /// with cw20 `Send`, or as funds for native assets
+        #[serde(default)]
+        to_msg: Option<Binary>,
+    },
+    /// Swap at most `offer_asset` for exactly `ask_amount` of the other asset, unused part of the
+    /// offer is refunded
+    SwapExactOut {
+        offer_asset: Asset,
+        /// Asset to be returned, required by pairs of more than two assets
+        #[serde(default)]
+        ask_asset_info: Option<AssetInfo>,
+        ask_amount: Uint128,
+        to: Option<String>,
+        /// Block time (in seconds) after which the operation fails
+        #[serde(default)]
+        deadline: Option<u64>,
+    },
+    /// Swaps part of native offer asset to the other one, so both can be provided as liquidity
+    /// at pool ratio. `slippage_tolerance` limits spread of the swap.
+    ProvideSingleSided {
+        offer_asset: Asset,
+        slippage_tolerance: Option<Decimal>,
+        min_share: Option<Uint128>,
+        /// Block time (in seconds) after which the operation fails
+        #[serde(default)]
+        deadline: Option<u64>,
     },
+    /// Updates stored reserves to match actual pair balances
+    Sync {},
+    /// Sends pair balances exceeding stored reserves to `to` (or sender if not given)
+    Skim {
+        to: Option<String>,
+    },
+    /// Sends all accrued protocol fees to the fee collector
+    CollectFees {},
+    /// Sends `ask_asset` to the sender contract and executes `FlashSwapCallbackMsg` on it. Until
+    /// the callback finishes, the pair has to be repaid with any of its assets, so the constant
+    /// product after commission on repaid amounts is not lower than before the flash swap.
+    FlashSwap {
+        ask_asset: Asset,
+        /// Passed to the sender in `FlashSwapCallback`
+        msg: Binary,
+    },
+    /// Pauses or unpauses given pair operations, others are left unchanged. Only the pair admin
+    /// can execute it.
+    UpdatePause {
+        swaps: Option<bool>,
+        deposits: Option<bool>,
+        withdrawals: Option<bool>,
+    },
+    /// Updates swap commission of traders not reaching any commission tier. Only the pair admin
+    /// can execute it.
+    UpdateCommission {
+        commission: Decimal,
+    },
+    /// Places order selling native `offer_asset` for the other asset at `price` or better. The
+    /// offer is escrowed by the pair, and the order is filled against the pool when a swap moves
+    /// the pool price across `price` - immediately if it is already crossed.
+    PlaceLimitOrder {
+        offer_asset: Asset,
+        /// Minimum amount of the other asset paid for a unit of the offer asset, after commission
+        price: Decimal,
+        /// Block time (in seconds) after which the operation fails
+        #[serde(default)]
+        deadline: Option<u64>,
+    },
+    /// Cancels the order, refunding its unfilled offer. Only the order owner can execute it.
+    CancelLimitOrder {
+        order_id: u64,
+    },
+    /// Pays out to the sender returns of its filled limit orders. Fills only credit returns to
+    /// order owners, so an owner unable to receive them cannot block swaps.
+    ClaimLimitOrderProceeds {},
+}
+
+/// Payload of the callback executed on the flash swap initiator, after the borrowed asset is
+/// sent to it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FlashSwapCallback {
+    /// Asset sent to the initiator
+    pub borrowed: Asset,
+    /// Message passed with `ExecuteMsg::FlashSwap`
+    pub msg: Binary,
+}
+
+impl FlashSwapCallback {
+    /// Serializes the callback wrapped in `FlashSwapCallbackMsg`
+    pub fn into_binary(self) -> StdResult<Binary> {
+        to_binary(&FlashSwapCallbackMsg::FlashSwapCallback(self))
+    }
+
+    /// Creates a message executing the callback on `contract_addr`
+    pub fn into_cosmos_msg(self, contract_addr: impl Into<String>) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg: self.into_binary()?,
+            funds: vec![],
+        }
+        .into())
+    }
+}
+
+/// Execute message the flash swap initiator contract has to handle
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FlashSwapCallbackMsg {
+    FlashSwapCallback(FlashSwapCallback),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -56,11 +289,63 @@ pub enum ExecuteMsg {
 pub enum Cw20HookMsg {
     /// Sell a given amount of asset
     Swap {
+        /// Asset to be returned, required by pairs of more than two assets
+        #[serde(default)]
+        ask_asset_info: Option<AssetInfo>,
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        /// Minimum amount to be returned, the swap fails if it is not met
+        #[serde(default)]
+        min_return: Option<Uint128>,
+        /// Block time (in seconds) after which the operation fails
+        #[serde(default)]
+        deadline: Option<u64>,
+        /// If set, the return is sent to `to` (or sender) contract executing this message on it -
+        /// with cw20 `Send`, or as funds for native assets
+        #[serde(default)]
+        to_msg: Option<Binary>,
+    },
+    /// Buy exactly `ask_amount` of the other asset for at most sent amount, see
+    /// `ExecuteMsg::SwapExactOut`
+    SwapExactOut {
+        /// Asset to be returned, required by pairs of more than two assets
+        #[serde(default)]
+        ask_asset_info: Option<AssetInfo>,
+        ask_amount: Uint128,
+        to: Option<String>,
+        /// Block time (in seconds) after which the operation fails
+        #[serde(default)]
+        deadline: Option<u64>,
+    },
+    /// Provide liquidity with sent token only, see `ExecuteMsg::ProvideSingleSided`
+    ProvideSingleSided {
+        slippage_tolerance: Option<Decimal>,
+        min_share: Option<Uint128>,
+        /// Block time (in seconds) after which the operation fails
+        #[serde(default)]
+        deadline: Option<u64>,
+    },
+    WithdrawLiquidity {
+        /// Block time (in seconds) after which the operation fails
+        #[serde(default)]
+        deadline: Option<u64>,
+    },
+    /// Withdraw liquidity paid out in `ask_asset` only, the other asset share is swapped to it
+    WithdrawSingleSided {
+        ask_asset: AssetInfo,
+        min_return: Option<Uint128>,
+        /// Block time (in seconds) after which the operation fails
+        #[serde(default)]
+        deadline: Option<u64>,
+    },
+    /// Place limit order selling sent token, see `ExecuteMsg::PlaceLimitOrder`
+    PlaceLimitOrder {
+        price: Decimal,
+        /// Block time (in seconds) after which the operation fails
+        #[serde(default)]
+        deadline: Option<u64>,
     },
-    WithdrawLiquidity {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -68,15 +353,61 @@ pub enum Cw20HookMsg {
 pub enum QueryMsg {
     Pair {},
     Pool {},
-    Simulation { offer_asset: Asset },
-    ReverseSimulation { ask_asset: Asset },
+    Simulation {
+        offer_asset: Asset,
+        /// Asset to be returned, required by pairs of more than two assets
+        #[serde(default)]
+        ask_asset_info: Option<AssetInfo>,
+        /// Trader whose commission tier is applied, pair commission is used if not given
+        #[serde(default)]
+        trader: Option<String>,
+    },
+    ReverseSimulation {
+        ask_asset: Asset,
+        /// Asset to be offered, required by pairs of more than two assets
+        #[serde(default)]
+        offer_asset_info: Option<AssetInfo>,
+        /// Trader whose commission tier is applied, pair commission is used if not given
+        #[serde(default)]
+        trader: Option<String>,
+    },
+    /// Returns price accumulators as of current block
+    CumulativePrices {},
+    /// Returns protocol fees accrued and not collected yet
+    AccruedFees {},
+    /// Simulates withdrawal of `share` liquidity tokens paid out in `ask_asset` only
+    WithdrawSingleSidedSimulation {
+        share: Uint128,
+        ask_asset: AssetInfo,
+        /// Trader whose commission tier is applied, pair commission is used if not given
+        #[serde(default)]
+        trader: Option<String>,
+    },
+    /// Returns swap commission tiers
+    CommissionTiers {},
+    LimitOrder {
+        order_id: u64,
+    },
+    /// Returns open limit orders of `owner`, sorted by id
+    LimitOrders {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns returns of filled limit orders of `owner`, not claimed yet
+    LimitOrderProceeds {
+        owner: String,
+    },
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct PoolResponse {
-    pub assets: [Asset; 2],
+    pub assets: Vec<Asset>,
     pub total_share: Uint128,
+    /// Weights of `assets`, reported by weighted pairs only
+    #[serde(default)]
+    pub weights: Option<Vec<Decimal>>,
 }
 
 /// SimulationResponse returns swap simulation response
@@ -95,6 +426,78 @@ pub struct ReverseSimulationResponse {
     pub commission_amount: Uint128,
 }
 
-/// We currently take no arguments for migrations
+/// WithdrawSingleSidedSimulationResponse returns single sided withdrawal simulation response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WithdrawSingleSidedSimulationResponse {
+    /// Total amount paid out, including swapped share of the other asset
+    pub return_amount: Uint128,
+    /// Spread of the swap of the other asset share
+    pub spread_amount: Uint128,
+    /// Commission of the swap of the other asset share
+    pub commission_amount: Uint128,
+}
+
+/// CumulativePricesResponse returns price accumulators, which can be used to compute time
+/// weighted average price between two observations
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct MigrateMsg {}
+pub struct CumulativePricesResponse {
+    /// Current pools
+    pub assets: [Asset; 2],
+    /// Sum of prices of first asset in second asset, multiplied by seconds they were valid
+    pub price0_cumulative: Decimal256,
+    /// Sum of prices of second asset in first asset, multiplied by seconds they were valid
+    pub price1_cumulative: Decimal256,
+    /// Block time (in seconds) accumulators are computed for
+    pub timestamp: u64,
+}
+
+/// AccruedFeesResponse returns protocol fees waiting for collection
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AccruedFeesResponse {
+    /// Accrued fees, one per pair asset
+    pub fees: Vec<Asset>,
+    pub fee_collector: Option<String>,
+    pub protocol_fee_share: Decimal,
+}
+
+/// CommissionTiersResponse returns swap commission tiers, sorted by `min_points`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CommissionTiersResponse {
+    pub commission_group: Option<String>,
+    pub commission_tiers: Vec<CommissionTier>,
+}
+
+/// LimitOrderResponse returns open limit order
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LimitOrderResponse {
+    pub order_id: u64,
+    pub owner: String,
+    /// Unfilled part of the offer, still escrowed by the pair
+    pub offer_asset: Asset,
+    pub ask_asset: AssetInfo,
+    pub price: Decimal,
+    /// Part of the offer filled so far
+    pub filled_amount: Uint128,
+    /// Total of the other asset returned for filled part, claimable with
+    /// `ExecuteMsg::ClaimLimitOrderProceeds`
+    pub return_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LimitOrdersResponse {
+    pub orders: Vec<LimitOrderResponse>,
+}
+
+/// LimitOrderProceedsResponse returns unclaimed returns of filled limit orders, per pooled asset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LimitOrderProceedsResponse {
+    pub proceeds: Vec<Asset>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    /// New pair admin. Pairs created before the factory became pairs admin are migrated with the
+    /// factory address, so they can be paused by the factory owner too.
+    #[serde(default)]
+    pub admin: Option<String>,
+}
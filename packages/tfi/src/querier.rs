@@ -1,10 +1,13 @@
 use crate::asset::{Asset, AssetInfo, PairInfo};
 use crate::factory::QueryMsg as FactoryQueryMsg;
-use crate::pair::{QueryMsg as PairQueryMsg, ReverseSimulationResponse, SimulationResponse};
+use crate::pair::{
+    CumulativePricesResponse, QueryMsg as PairQueryMsg, ReverseSimulationResponse,
+    SimulationResponse,
+};
 
 use cosmwasm_std::{
-    to_binary, Addr, AllBalanceResponse, Api, BalanceResponse, BankQuery, Coin, Querier,
-    QuerierWrapper, QueryRequest, StdResult, Storage, Uint128, WasmQuery,
+    to_binary, Addr, AllBalanceResponse, BalanceResponse, BankQuery, Coin, Decimal256,
+    QuerierWrapper, QueryRequest, StdError, StdResult, Uint128, Uint256, WasmQuery,
 };
 use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
 
@@ -56,15 +59,18 @@ pub fn query_supply(querier: &QuerierWrapper, contract_addr: Addr) -> StdResult<
     Ok(token_info.total_supply)
 }
 
+/// Queries factory for pair of given assets and type
 pub fn query_pair_info(
     querier: &QuerierWrapper,
     factory_contract: Addr,
-    asset_infos: &[AssetInfo; 2],
+    asset_infos: &[AssetInfo],
+    pair_type: &str,
 ) -> StdResult<PairInfo> {
     querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
         contract_addr: factory_contract.to_string(),
         msg: to_binary(&FactoryQueryMsg::Pair {
-            asset_infos: asset_infos.clone(),
+            asset_infos: asset_infos.to_vec(),
+            pair_type: pair_type.to_owned(),
         })?,
     }))
 }
@@ -73,24 +79,71 @@ pub fn simulate(
     querier: &QuerierWrapper,
     pair_contract: Addr,
     offer_asset: &Asset,
+    ask_asset_info: &AssetInfo,
 ) -> StdResult<SimulationResponse> {
     querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
         contract_addr: pair_contract.to_string(),
         msg: to_binary(&PairQueryMsg::Simulation {
             offer_asset: offer_asset.clone(),
+            ask_asset_info: Some(ask_asset_info.clone()),
+            trader: None,
         })?,
     }))
 }
 
-pub fn reverse_simulate<S: Storage, A: Api, Q: Querier>(
+pub fn reverse_simulate(
     querier: &QuerierWrapper,
     pair_contract: Addr,
     ask_asset: &Asset,
+    offer_asset_info: &AssetInfo,
 ) -> StdResult<ReverseSimulationResponse> {
     querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
         contract_addr: pair_contract.to_string(),
         msg: to_binary(&PairQueryMsg::ReverseSimulation {
             ask_asset: ask_asset.clone(),
+            offer_asset_info: Some(offer_asset_info.clone()),
+            trader: None,
         })?,
     }))
 }
+
+pub fn query_cumulative_prices(
+    querier: &QuerierWrapper,
+    pair_contract: Addr,
+) -> StdResult<CumulativePricesResponse> {
+    querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: pair_contract.to_string(),
+        msg: to_binary(&PairQueryMsg::CumulativePrices {})?,
+    }))
+}
+
+/// Computes time weighted average prices between two `CumulativePrices` observations of the same
+/// pair. Returns average price of first asset in second asset, and of second asset in first
+/// asset.
+pub fn compute_twap(
+    start: &CumulativePricesResponse,
+    end: &CumulativePricesResponse,
+) -> StdResult<[Decimal256; 2]> {
+    if end.timestamp <= start.timestamp {
+        return Err(StdError::generic_err(
+            "End observation has to be taken after start observation",
+        ));
+    }
+
+    // accumulators wrap around on overflow, so the end one may be lower - the difference is still
+    // correct as long as they wrapped at most once between observations
+    let elapsed = Uint256::from(end.timestamp - start.timestamp);
+    Ok([
+        wrapping_sub(end.price0_cumulative, start.price0_cumulative) / elapsed,
+        wrapping_sub(end.price1_cumulative, start.price1_cumulative) / elapsed,
+    ])
+}
+
+/// Subtracts wrapping around on underflow
+fn wrapping_sub(lhs: Decimal256, rhs: Decimal256) -> Decimal256 {
+    let (lhs, rhs) = (lhs.atomics(), rhs.atomics());
+    Decimal256::new(
+        lhs.checked_sub(rhs)
+            .unwrap_or_else(|_| Uint256::MAX - rhs + lhs + Uint256::from(1u8)),
+    )
+}
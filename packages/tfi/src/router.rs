@@ -0,0 +1,145 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::{AssetInfo, XYK_PAIR_TYPE};
+
+use cosmwasm_std::Uint128;
+use cw20::Cw20ReceiveMsg;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    /// Factory used to look up pairs for swap operations
+    pub tfi_factory: String,
+}
+
+/// Single hop of a route - swaps `offer_asset_info` to `ask_asset_info` on the pair registered
+/// for those two assets in the factory, or for `pool_asset_infos` if given
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SwapOperation {
+    pub offer_asset_info: AssetInfo,
+    pub ask_asset_info: AssetInfo,
+    /// Type of the pair to swap on, `xyk` if not given
+    #[serde(default)]
+    pub pair_type: Option<String>,
+    /// All assets of the pool to swap on, for pools of more than two assets
+    #[serde(default)]
+    pub pool_asset_infos: Option<Vec<AssetInfo>>,
+}
+
+impl SwapOperation {
+    pub fn new(offer_asset_info: AssetInfo, ask_asset_info: AssetInfo) -> Self {
+        Self {
+            offer_asset_info,
+            ask_asset_info,
+            pair_type: None,
+            pool_asset_infos: None,
+        }
+    }
+
+    pub fn with_pair_type(mut self, pair_type: impl Into<String>) -> Self {
+        self.pair_type = Some(pair_type.into());
+        self
+    }
+
+    pub fn with_pool_asset_infos(mut self, pool_asset_infos: impl Into<Vec<AssetInfo>>) -> Self {
+        self.pool_asset_infos = Some(pool_asset_infos.into());
+        self
+    }
+
+    /// Type of the pair to swap on
+    pub fn pair_type(&self) -> &str {
+        self.pair_type.as_deref().unwrap_or(XYK_PAIR_TYPE)
+    }
+
+    /// Asset infos identifying the pair in the factory
+    pub fn asset_infos(&self) -> Vec<AssetInfo> {
+        self.pool_asset_infos
+            .clone()
+            .unwrap_or_else(|| vec![self.offer_asset_info.clone(), self.ask_asset_info.clone()])
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    Receive(Cw20ReceiveMsg),
+    /// Executes swap operations one by one, sending the final asset to `to` (or sender if not
+    /// given). Fails if less than `minimum_receive` of the final asset is received.
+    ExecuteSwapOperations {
+        operations: Vec<SwapOperation>,
+        minimum_receive: Option<Uint128>,
+        to: Option<String>,
+    },
+    /// Swaps router balance of the offer asset above `prev_balance`. Internal use only.
+    ExecuteSwapOperation {
+        operation: SwapOperation,
+        #[serde(default)]
+        prev_balance: Uint128,
+        to: Option<String>,
+    },
+    /// Checks if `receiver` balance grew by at least `minimum_receive`. Internal use only.
+    AssertMinimumReceive {
+        asset_info: AssetInfo,
+        prev_balance: Uint128,
+        minimum_receive: Uint128,
+        receiver: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Executes swap operations starting with the sent token
+    ExecuteSwapOperations {
+        operations: Vec<SwapOperation>,
+        minimum_receive: Option<Uint128>,
+        to: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    /// Simulates swapping `offer_amount` of the first operation offer asset through the whole
+    /// route
+    SimulateSwapOperations {
+        offer_amount: Uint128,
+        operations: Vec<SwapOperation>,
+    },
+    /// Simulates how much of the first operation offer asset is needed to receive `ask_amount`
+    /// of the last operation ask asset
+    ReverseSimulateSwapOperations {
+        ask_amount: Uint128,
+        operations: Vec<SwapOperation>,
+    },
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub tfi_factory: String,
+}
+
+/// Simulation result of single swap operation
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SwapOperationSimulation {
+    pub offer_amount: Uint128,
+    pub return_amount: Uint128,
+    pub spread_amount: Uint128,
+    pub commission_amount: Uint128,
+}
+
+/// Response for both `SimulateSwapOperations` and `ReverseSimulateSwapOperations`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateSwapOperationsResponse {
+    /// Final return amount for `SimulateSwapOperations`, and required offer amount for
+    /// `ReverseSimulateSwapOperations`
+    pub amount: Uint128,
+    /// Simulation of every operation, in route order
+    pub operations: Vec<SwapOperationSimulation>,
+}
+
+/// We currently take no arguments for migrations
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
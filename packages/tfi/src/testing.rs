@@ -1,9 +1,15 @@
-use crate::asset::{Asset, AssetInfo};
-use crate::querier::{query_all_balances, query_balance, query_supply, query_token_balance};
+use crate::asset::{Asset, AssetInfo, PairInfo};
+use crate::pair::CumulativePricesResponse;
+use crate::querier::{
+    compute_twap, query_all_balances, query_balance, query_supply, query_token_balance,
+};
 use tfi_mocks::mock_dependencies;
 
 use cosmwasm_std::testing::MOCK_CONTRACT_ADDR;
-use cosmwasm_std::{to_binary, Addr, BankMsg, Coin, CosmosMsg, Uint128, WasmMsg};
+use cosmwasm_std::{
+    from_slice, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Decimal256, StdError, Uint128,
+    Uint256, WasmMsg,
+};
 use cw20::Cw20ExecuteMsg;
 
 #[test]
@@ -92,6 +98,90 @@ fn supply_querier() {
     )
 }
 
+#[test]
+fn twap() {
+    let assets = [
+        Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: Uint128::new(100),
+        },
+        Asset {
+            info: AssetInfo::Token(Addr::unchecked("asset0000")),
+            amount: Uint128::new(200),
+        },
+    ];
+
+    let start = CumulativePricesResponse {
+        assets: assets.clone(),
+        price0_cumulative: Decimal256::from_ratio(10u8, 1u8),
+        price1_cumulative: Decimal256::from_ratio(5u8, 1u8),
+        timestamp: 100,
+    };
+
+    // 10 seconds at price 2, then 30 seconds at price 6
+    let end = CumulativePricesResponse {
+        assets,
+        price0_cumulative: Decimal256::from_ratio(10u8 + 20 + 180, 1u8),
+        price1_cumulative: Decimal256::from_ratio(5u8 + 5 + 5, 1u8),
+        timestamp: 140,
+    };
+
+    assert_eq!(
+        compute_twap(&start, &end).unwrap(),
+        [
+            Decimal256::from_ratio(200u8, 40u8),
+            Decimal256::from_ratio(10u8, 40u8)
+        ]
+    );
+
+    assert_eq!(
+        compute_twap(&end, &start).unwrap_err(),
+        StdError::generic_err("End observation has to be taken after start observation")
+    );
+    assert_eq!(
+        compute_twap(&start, &start).unwrap_err(),
+        StdError::generic_err("End observation has to be taken after start observation")
+    );
+}
+
+#[test]
+fn twap_across_accumulators_wrap() {
+    let assets = [
+        Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: Uint128::new(100),
+        },
+        Asset {
+            info: AssetInfo::Token(Addr::unchecked("asset0000")),
+            amount: Uint128::new(200),
+        },
+    ];
+
+    // 1 unit below the wrap, so 40 seconds at price 2 end up 79 units above zero
+    let start = CumulativePricesResponse {
+        assets: assets.clone(),
+        price0_cumulative: Decimal256::new(
+            Uint256::MAX - Decimal256::one().atomics() + Uint256::from(1u8),
+        ),
+        price1_cumulative: Decimal256::from_ratio(5u8, 1u8),
+        timestamp: 100,
+    };
+    let end = CumulativePricesResponse {
+        assets,
+        price0_cumulative: Decimal256::from_ratio(79u8, 1u8),
+        price1_cumulative: Decimal256::from_ratio(25u8, 1u8),
+        timestamp: 140,
+    };
+
+    assert_eq!(
+        compute_twap(&start, &end).unwrap(),
+        [
+            Decimal256::from_ratio(2u8, 1u8),
+            Decimal256::from_ratio(1u8, 2u8)
+        ]
+    );
+}
+
 #[test]
 fn test_asset_info() {
     let token_info: AssetInfo = AssetInfo::Token(Addr::unchecked("asset0000"));
@@ -195,6 +285,78 @@ fn test_asset() {
     );
 }
 
+#[test]
+fn asset_into_send_msg() {
+    let token_asset = Asset {
+        amount: Uint128::new(123123),
+        info: AssetInfo::Token(Addr::unchecked("asset0000")),
+    };
+
+    let native_token_asset = Asset {
+        amount: Uint128::new(123123),
+        info: AssetInfo::Native("uusd".to_string()),
+    };
+
+    let msg = Binary::from(b"{\"deposit\":{}}".as_ref());
+
+    assert_eq!(
+        token_asset
+            .into_send_msg(Addr::unchecked("vault"), msg.clone())
+            .unwrap(),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "vault".to_string(),
+                amount: Uint128::new(123123),
+                msg: msg.clone(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    assert_eq!(
+        native_token_asset
+            .into_send_msg(Addr::unchecked("vault"), msg.clone())
+            .unwrap(),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "vault".to_string(),
+            msg,
+            funds: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(123123),
+            }],
+        })
+    );
+}
+
+#[test]
+fn two_asset_pair_info_json_compatibility() {
+    // pair info as stored and returned when asset infos were fixed size arrays
+    let json = br#"{
+        "asset_infos": [{"native": "uusd"}, {"token": "asset0000"}],
+        "contract_addr": "pair0000",
+        "liquidity_token": "liquidity0000"
+    }"#;
+
+    let pair_info: PairInfo = from_slice(json).unwrap();
+    assert_eq!(
+        pair_info.asset_infos,
+        vec![
+            AssetInfo::Native("uusd".to_owned()),
+            AssetInfo::Token(Addr::unchecked("asset0000")),
+        ]
+    );
+    assert_eq!(
+        to_binary(&pair_info.asset_infos).unwrap(),
+        to_binary(&[
+            AssetInfo::Native("uusd".to_owned()),
+            AssetInfo::Token(Addr::unchecked("asset0000")),
+        ])
+        .unwrap()
+    );
+}
+
 // TODO: figure out compile
 
 // #[test]
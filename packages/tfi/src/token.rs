@@ -12,6 +12,13 @@ pub struct InstantiateMsg {
     pub decimals: u8,
     pub initial_balances: Vec<Cw20Coin>,
     pub mint: Option<MinterResponse>,
+    /// tg4 whitelist group, set only when instantiating `trusted-token`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub whitelist_group: Option<String>,
+    /// Lets the minter pass the whitelist of `trusted-token`, set only together with
+    /// `whitelist_group`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exempt_minter: Option<bool>,
 }
 
 impl InstantiateMsg {